@@ -1,11 +1,11 @@
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::BufReader;
 use std::path::PathBuf;
 
 use anyhow::Result;
 use causal_engine::{CausalChain, CausalEngine};
 use serde_json::Value;
-use spans_core::{span_from_json, UniversalSpan};
+use spans_core::span_from_json;
 
 pub fn run_causal_analysis(input: PathBuf) -> Result<Vec<CausalChain>> {
     let file = File::open(&input)?;
@@ -22,19 +22,3 @@ pub fn run_causal_analysis(input: PathBuf) -> Result<Vec<CausalChain>> {
     engine.ingest(spans);
     Ok(engine.infer())
 }
-
-pub fn sync_ledger(ndjson_path: PathBuf) -> Result<Vec<UniversalSpan>> {
-    let file = File::open(&ndjson_path)?;
-    let reader = BufReader::new(file);
-    let mut spans = Vec::new();
-    for line in reader.lines() {
-        let line = line?;
-        if line.trim().is_empty() {
-            continue;
-        }
-        let value: Value = serde_json::from_str(&line)?;
-        let span = span_from_json(value)?;
-        spans.push(span);
-    }
-    Ok(spans)
-}
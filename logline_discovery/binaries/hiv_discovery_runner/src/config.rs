@@ -3,11 +3,14 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use dotenvy::dotenv;
+use spans_core::schema::SchemaMode;
 
 #[derive(Debug, Clone)]
 pub struct RunnerConfig {
     pub ledger_path: PathBuf,
     pub database_url: Option<String>,
+    /// Payload schema enforcement for ingested spans (`SPAN_SCHEMA_MODE`).
+    pub schema_mode: SchemaMode,
 }
 
 impl RunnerConfig {
@@ -20,9 +23,15 @@ impl RunnerConfig {
 
         let database_url = env::var("DATABASE_URL").ok();
 
+        let schema_mode = match env::var("SPAN_SCHEMA_MODE") {
+            Ok(mode) => mode.parse()?,
+            Err(_) => SchemaMode::default(),
+        };
+
         Ok(Self {
             ledger_path,
             database_url,
+            schema_mode,
         })
     }
 }
@@ -4,9 +4,11 @@ use crate::mapping::{
     SubjectMetadata, TwinDivergenceMetadata, TwinObservationMetadata,
 };
 use anyhow::Result;
+use serde_json::Value;
 use spans_core::UniversalSpan;
 use sqlx::postgres::{PgPool, PgPoolOptions};
 use sqlx::types::Json;
+use std::collections::{HashMap, HashSet};
 use tracing::warn;
 use uuid::Uuid;
 
@@ -21,8 +23,8 @@ pub async fn init_pool(database_url: &str) -> Result<PgPool> {
 pub async fn insert_raw_span(pool: &PgPool, span: &UniversalSpan) -> Result<()> {
     sqlx::query(
         r#"
-        INSERT INTO discovery.raw_spans (span_id, flow, workflow, payload)
-        VALUES ($1, $2, $3, $4)
+        INSERT INTO discovery.raw_spans (span_id, flow, workflow, payload, content_hash)
+        VALUES ($1, $2, $3, $4, $5)
         ON CONFLICT (span_id) DO NOTHING
         "#,
     )
@@ -30,12 +32,152 @@ pub async fn insert_raw_span(pool: &PgPool, span: &UniversalSpan) -> Result<()>
     .bind(&span.flow)
     .bind(&span.workflow)
     .bind(Json(span.payload.clone()))
+    .bind(span.compute_content_hash())
     .execute(pool)
     .await?;
 
     Ok(())
 }
 
+/// What [`copy_raw_spans`] made of a batch; spans are referred to by
+/// their position in it.
+#[derive(Debug, Default)]
+pub struct RawSpanCopy {
+    /// Ids inserted, each by the first span of the batch carrying it.
+    pub inserted: HashSet<String>,
+    /// Spans whose id already has a row with another content hash, mapped
+    /// to that hash. A later span repeating an id of the batch with other
+    /// content conflicts with the first.
+    pub conflicts: HashMap<usize, String>,
+    /// Spans holding a NUL character, which Postgres text cannot store;
+    /// they were not copied.
+    pub rejected: HashSet<usize>,
+}
+
+/// Mirrors a batch of spans into `raw_spans` with a single `COPY`. As with
+/// [`insert_raw_span`], spans whose id already has a row are skipped, as
+/// are later spans repeating an id of the batch.
+pub async fn copy_raw_spans(pool: &PgPool, spans: &[UniversalSpan]) -> Result<RawSpanCopy> {
+    let mut outcome = RawSpanCopy::default();
+    let mut rows = String::new();
+    for (position, span) in spans.iter().enumerate() {
+        // JSON escapes NUL, but jsonb refuses the escape.
+        if holds_nul(&span.payload) {
+            outcome.rejected.insert(position);
+            continue;
+        }
+        let payload = span.payload.to_string();
+        let hash = span.compute_content_hash();
+        let row = csv_row(&[
+            &position.to_string(),
+            &span.id.0,
+            &span.flow,
+            &span.workflow,
+            &payload,
+            &hash,
+        ]);
+        match row {
+            Some(row) => rows.push_str(&row),
+            None => {
+                outcome.rejected.insert(position);
+            }
+        }
+    }
+    if rows.is_empty() {
+        return Ok(outcome);
+    }
+
+    // COPY cannot skip conflicting rows, so it fills a staging table that
+    // is merged with ON CONFLICT.
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        r#"
+        CREATE TEMP TABLE raw_spans_batch (
+            position BIGINT,
+            span_id TEXT,
+            flow TEXT,
+            workflow TEXT,
+            payload JSONB,
+            content_hash TEXT
+        ) ON COMMIT DROP
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+    let mut copy = tx
+        .copy_in_raw(
+            "COPY raw_spans_batch (position, span_id, flow, workflow, payload, content_hash) \
+             FROM STDIN WITH (FORMAT csv)",
+        )
+        .await?;
+    copy.send(rows.as_bytes()).await?;
+    copy.finish().await?;
+    let inserted = sqlx::query_scalar(
+        r#"
+        INSERT INTO discovery.raw_spans (span_id, flow, workflow, payload, content_hash)
+        SELECT DISTINCT ON (span_id) span_id, flow, workflow, payload, content_hash
+        FROM raw_spans_batch
+        ORDER BY span_id, position
+        ON CONFLICT (span_id) DO NOTHING
+        RETURNING span_id
+        "#,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    // After the merge every id of the batch has a row, so one join finds
+    // the spans that differ from it, whether it was there before or came
+    // from an earlier span of the batch. Rows predating content hashes
+    // cannot be compared.
+    let conflicts: Vec<(i64, String)> = sqlx::query_as(
+        r#"
+        SELECT b.position, r.content_hash
+        FROM raw_spans_batch b
+        JOIN discovery.raw_spans r ON r.span_id = b.span_id
+        WHERE r.content_hash IS NOT NULL AND r.content_hash <> b.content_hash
+        "#,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    outcome.inserted = inserted.into_iter().collect();
+    outcome.conflicts = conflicts
+        .into_iter()
+        .map(|(position, hash)| (position as usize, hash))
+        .collect();
+    Ok(outcome)
+}
+
+/// One CSV record for `COPY ... WITH (FORMAT csv)`, every field quoted, or
+/// `None` if a field holds a NUL character.
+fn csv_row(fields: &[&str]) -> Option<String> {
+    let mut row = String::new();
+    for (index, field) in fields.iter().enumerate() {
+        if field.contains('\0') {
+            return None;
+        }
+        if index > 0 {
+            row.push(',');
+        }
+        row.push('"');
+        row.push_str(&field.replace('"', "\"\""));
+        row.push('"');
+    }
+    row.push('\n');
+    Some(row)
+}
+
+fn holds_nul(value: &Value) -> bool {
+    match value {
+        Value::String(text) => text.contains('\0'),
+        Value::Array(items) => items.iter().any(holds_nul),
+        Value::Object(map) => map
+            .iter()
+            .any(|(key, value)| key.contains('\0') || holds_nul(value)),
+        _ => false,
+    }
+}
+
 pub async fn apply_mapping(pool: &PgPool, span: &UniversalSpan, kind: &SpanKind) -> Result<()> {
     match kind {
         SpanKind::Subject(meta) => insert_subject(pool, span, meta).await?,
@@ -476,3 +618,26 @@ async fn resolve_execution_id(
 
     Ok(execution_id)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn csv_rows_quote_every_field() {
+        let row = csv_row(&["0", "span::a", r#"say "hi", then"#, "two\nlines", ""]).unwrap();
+        assert_eq!(
+            row,
+            "\"0\",\"span::a\",\"say \"\"hi\"\", then\",\"two\nlines\",\"\"\n"
+        );
+        assert_eq!(csv_row(&["0", "nul\0here"]), None);
+    }
+
+    #[test]
+    fn payloads_with_nul_are_found_at_any_depth() {
+        assert!(!holds_nul(&json!({ "a": ["b", { "c": 1 }] })));
+        assert!(holds_nul(&json!({ "a": ["b", { "c": "\0" }] })));
+        assert!(holds_nul(&json!({ "a\0": 1 })));
+    }
+}
@@ -1,11 +1,195 @@
 use std::fs::OpenOptions;
-use std::io::Write;
-use std::path::Path;
+use std::io::{SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{ready, Context, Poll};
 
-use anyhow::Result;
-use serde_json::to_string;
-use spans_core::UniversalSpan;
+use anyhow::{Context as _, Result};
+use futures::Stream;
+use serde_json::{to_string, Value};
+use spans_core::{span_from_json, UniversalSpan};
+use sqlx::sqlite::{Sqlite, SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::Transaction;
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt};
+use tokio::sync::mpsc;
 
+/// Outcome of offering a span to the ledger.
+#[derive(Debug)]
+pub enum Admission {
+    /// Unseen span id; the span now carries its content hash.
+    New(UniversalSpan),
+    /// Byte-identical re-ingest of a recorded span.
+    Duplicate(UniversalSpan),
+    /// Same id as a recorded span, different content. A span whose
+    /// supplied content hash does not match its content also lands here,
+    /// with that hash as `existing_hash`.
+    Conflict {
+        span: UniversalSpan,
+        existing_hash: String,
+    },
+}
+
+/// Ledger lines indexed per transaction while the index catches up.
+const INDEX_BATCH: usize = 10_000;
+
+/// Open index pools, one per ledger path.
+static INDEXES: Mutex<Vec<(PathBuf, SqlitePool)>> = Mutex::new(Vec::new());
+
+/// The index of `path`: the content hash of every span id in the ledger,
+/// kept in SQLite beside it (`<ledger>.index.sqlite`) so that only the pool
+/// stays in memory. It also stores how many bytes of the ledger it covers,
+/// up to the end of the last complete line read.
+async fn open_index(path: &Path) -> Result<SqlitePool> {
+    let cached = |indexes: &[(PathBuf, SqlitePool)]| {
+        indexes
+            .iter()
+            .find(|(ledger, _)| ledger == path)
+            .map(|(_, pool)| pool.clone())
+    };
+    if let Some(pool) = cached(&INDEXES.lock().expect("ledger index lock poisoned")) {
+        return Ok(pool);
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = path.as_os_str().to_owned();
+    file.push(".index.sqlite");
+    let options = SqliteConnectOptions::new()
+        .filename(&file)
+        .create_if_missing(true);
+    // One connection, so lookups wait for a catch-up in progress.
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .with_context(|| format!("opening ledger index {}", PathBuf::from(&file).display()))?;
+    for statement in [
+        "CREATE TABLE IF NOT EXISTS spans (span_id TEXT PRIMARY KEY, content_hash TEXT NOT NULL)",
+        "CREATE TABLE IF NOT EXISTS coverage (id INTEGER PRIMARY KEY CHECK (id = 0), bytes INTEGER NOT NULL)",
+        "INSERT OR IGNORE INTO coverage (id, bytes) VALUES (0, 0)",
+    ] {
+        sqlx::query(statement).execute(&pool).await?;
+    }
+
+    let mut indexes = INDEXES.lock().expect("ledger index lock poisoned");
+    if let Some(pool) = cached(&indexes) {
+        return Ok(pool);
+    }
+    indexes.push((path.to_path_buf(), pool.clone()));
+    Ok(pool)
+}
+
+/// Indexes the complete lines appended to the ledger since the last
+/// catch-up; a ledger that shrank is indexed again from the start. Each
+/// line is hashed as read back, whatever hash it stores, and the first
+/// record of an id wins.
+async fn catch_up(pool: &SqlitePool, path: &Path) -> Result<()> {
+    let file_len = match tokio::fs::metadata(path).await {
+        Ok(metadata) => metadata.len(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => 0,
+        Err(err) => return Err(err.into()),
+    };
+    let covered: i64 = sqlx::query_scalar("SELECT bytes FROM coverage")
+        .fetch_one(pool)
+        .await?;
+    let mut covered = covered as u64;
+    if file_len == covered {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+    if file_len < covered {
+        sqlx::query("DELETE FROM spans").execute(&mut *tx).await?;
+        covered = 0;
+    }
+    let mut file = tokio::fs::File::open(path).await?;
+    file.seek(SeekFrom::Start(covered)).await?;
+    let mut reader = tokio::io::BufReader::new(file);
+    let mut line = String::new();
+    let mut indexed = 0;
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).await?;
+        // A line still being written is indexed once it is complete.
+        if read == 0 || !line.ends_with('\n') {
+            break;
+        }
+        covered += read as u64;
+        let Ok(span) = serde_json::from_str(&line)
+            .map_err(anyhow::Error::from)
+            .and_then(parse_span)
+        else {
+            continue;
+        };
+        sqlx::query("INSERT OR IGNORE INTO spans (span_id, content_hash) VALUES (?, ?)")
+            .bind(&span.id.0)
+            .bind(span.compute_content_hash())
+            .execute(&mut *tx)
+            .await?;
+        indexed += 1;
+        if indexed % INDEX_BATCH == 0 {
+            set_coverage(&mut tx, covered).await?;
+            tx.commit().await?;
+            tx = pool.begin().await?;
+        }
+    }
+    set_coverage(&mut tx, covered).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn set_coverage(tx: &mut Transaction<'_, Sqlite>, bytes: u64) -> Result<()> {
+    sqlx::query("UPDATE coverage SET bytes = ?")
+        .bind(bytes as i64)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Compares `span` with what the ledger already holds under its id, once
+/// the ledger's index has caught up.
+pub async fn admit(path: &Path, mut span: UniversalSpan) -> Result<Admission> {
+    if let Some(supplied) = span.rehash() {
+        return Ok(Admission::Conflict {
+            span,
+            existing_hash: supplied,
+        });
+    }
+    let hash = span.content_hash.clone().expect("rehash stores the hash");
+    let pool = open_index(path).await?;
+    catch_up(&pool, path).await?;
+    let existing: Option<String> =
+        sqlx::query_scalar("SELECT content_hash FROM spans WHERE span_id = ?")
+            .bind(&span.id.0)
+            .fetch_optional(&pool)
+            .await?;
+    Ok(match existing {
+        None => Admission::New(span),
+        Some(existing) if existing == hash => Admission::Duplicate(span),
+        Some(existing) => Admission::Conflict {
+            existing_hash: existing,
+            span,
+        },
+    })
+}
+
+/// Remembers `span` as admitted without writing it to the ledger, for
+/// spans handed to the pipeline instead, so re-ingesting it is skipped.
+pub async fn record(path: &Path, span: &UniversalSpan) -> Result<()> {
+    let pool = open_index(path).await?;
+    catch_up(&pool, path).await?;
+    sqlx::query("INSERT OR IGNORE INTO spans (span_id, content_hash) VALUES (?, ?)")
+        .bind(&span.id.0)
+        .bind(span.compute_content_hash())
+        .execute(&pool)
+        .await?;
+    Ok(())
+}
+
+/// Appends `span` with its content hash, computed afresh, stored next to
+/// the id.
 pub fn append_span(path: &Path, span: &UniversalSpan) -> Result<()> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
@@ -13,9 +197,262 @@ pub fn append_span(path: &Path, span: &UniversalSpan) -> Result<()> {
 
     let mut file = OpenOptions::new().create(true).append(true).open(path)?;
 
-    let serialized = to_string(span)?;
+    let serialized = to_string(&span.clone().with_content_hash())?;
     writeln!(file, "{}", serialized)?;
     file.flush()?;
-
     Ok(())
 }
+
+/// Spans buffered between the ledger reader task and its consumer.
+pub const STREAM_CAPACITY: usize = 1024;
+
+/// Spans of an NDJSON ledger, read line by line on a background task.
+///
+/// The reader hands spans over a bounded channel, so a slow consumer holds
+/// it back instead of letting parsed spans pile up in memory. [`offset`]
+/// is the byte offset just past the last span yielded; opening the ledger
+/// there resumes after it. A final line without a newline that does not
+/// parse is taken for a write in progress and ends the stream unread.
+///
+/// [`offset`]: SpanStream::offset
+pub struct SpanStream {
+    receiver: mpsc::Receiver<Result<(u64, UniversalSpan)>>,
+    offset: u64,
+}
+
+impl SpanStream {
+    /// Streams the spans of `path` from byte `offset`, which must be the
+    /// start of a line.
+    pub async fn open(path: impl AsRef<Path>, offset: u64, capacity: usize) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = tokio::fs::File::open(&path)
+            .await
+            .with_context(|| format!("opening ledger {}", path.display()))?;
+        file.seek(SeekFrom::Start(offset)).await?;
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        tokio::spawn(read_spans(
+            tokio::io::BufReader::new(file),
+            path,
+            offset,
+            sender,
+        ));
+        Ok(Self { receiver, offset })
+    }
+
+    /// Byte offset just past the last span yielded.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+impl Stream for SpanStream {
+    type Item = Result<UniversalSpan>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(match ready!(self.receiver.poll_recv(cx)) {
+            Some(Ok((offset, span))) => {
+                self.offset = offset;
+                Some(Ok(span))
+            }
+            Some(Err(err)) => Some(Err(err)),
+            None => None,
+        })
+    }
+}
+
+/// A ledger record as a span: lines written by [`append_span`] are read
+/// back exactly, so they hash as they did when written, and any other
+/// record goes through [`span_from_json`].
+fn parse_span(record: Value) -> Result<UniversalSpan> {
+    match serde_json::from_value(record.clone()) {
+        Ok(span) => Ok(span),
+        Err(_) => span_from_json(record),
+    }
+}
+
+async fn read_spans(
+    mut reader: tokio::io::BufReader<tokio::fs::File>,
+    path: PathBuf,
+    mut offset: u64,
+    sender: mpsc::Sender<Result<(u64, UniversalSpan)>>,
+) {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = match reader.read_line(&mut line).await {
+            Ok(0) => return,
+            Ok(read) => read,
+            Err(err) => {
+                let _ = sender.send(Err(err.into())).await;
+                return;
+            }
+        };
+        let start = offset;
+        offset += read as u64;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parsed = serde_json::from_str(&line)
+            .map_err(anyhow::Error::from)
+            .and_then(parse_span);
+        let item = match parsed {
+            Ok(span) => Ok((offset, span)),
+            Err(_) if !line.ends_with('\n') => return,
+            Err(err) => Err(err.context(format!(
+                "{} at byte {}: malformed span",
+                path.display(),
+                start
+            ))),
+        };
+        let failed = item.is_err();
+        if sender.send(item).await.is_err() || failed {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn admits_new_spans_and_flags_duplicates_and_conflicts() {
+        use futures::StreamExt;
+
+        let path =
+            std::env::temp_dir().join(format!("ledger-admit-{}.ndjson", uuid::Uuid::new_v4()));
+        let span = UniversalSpan::new(
+            "span::scan::1",
+            "scan",
+            "metric",
+            "qc",
+            Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+            json!({ "value": 1.0 }),
+        );
+
+        let Admission::New(admitted) = admit(&path, span.clone()).await.unwrap() else {
+            panic!("fresh span should be new");
+        };
+        append_span(&path, &admitted).unwrap();
+        assert!(matches!(
+            admit(&path, span.clone()).await.unwrap(),
+            Admission::Duplicate(_)
+        ));
+
+        let mut changed = span.clone();
+        changed.payload["value"] = json!(2.0);
+        match admit(&path, changed).await.unwrap() {
+            Admission::Conflict { existing_hash, .. } => {
+                assert_eq!(Some(existing_hash), admitted.content_hash);
+            }
+            other => panic!("expected a conflict, got {other:?}"),
+        }
+
+        // A hash that does not match the content is a conflict, new id or not.
+        let mut stale = admitted.clone();
+        stale.id.0 = "span::scan::9".into();
+        match admit(&path, stale).await.unwrap() {
+            Admission::Conflict { existing_hash, .. } => {
+                assert_eq!(Some(existing_hash), admitted.content_hash);
+            }
+            other => panic!("expected a conflict, got {other:?}"),
+        }
+
+        let line = std::fs::read_to_string(&path).unwrap();
+        assert!(line.starts_with(r#"{"id":"span::scan::1","content_hash":""#));
+        // Read back, the span hashes as it did when written.
+        let mut stream = SpanStream::open(&path, 0, 1).await.unwrap();
+        let read_back = stream.next().await.unwrap().unwrap();
+        assert_eq!(read_back.payload, admitted.payload);
+        assert!(matches!(
+            admit(&path, read_back).await.unwrap(),
+            Admission::Duplicate(_)
+        ));
+
+        // Lines appended by another writer are indexed once complete.
+        let mut other = span;
+        other.id.0 = "span::scan::2".into();
+        let other_line = to_string(&other.clone().with_content_hash()).unwrap();
+        let (head, tail) = other_line.split_at(20);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "{head}").unwrap();
+        assert!(matches!(
+            admit(&path, other.clone()).await.unwrap(),
+            Admission::New(_)
+        ));
+        writeln!(file, "{tail}").unwrap();
+        assert!(matches!(
+            admit(&path, other).await.unwrap(),
+            Admission::Duplicate(_)
+        ));
+
+        // A recorded span is a duplicate though the ledger never saw it.
+        let mut recorded = admitted.clone();
+        recorded.id.0 = "span::scan::3".into();
+        recorded.content_hash = None;
+        record(&path, &recorded).await.unwrap();
+        assert!(matches!(
+            admit(&path, recorded.clone()).await.unwrap(),
+            Admission::Duplicate(_)
+        ));
+
+        // The index outlives the process's pool.
+        let pool = {
+            let mut indexes = INDEXES.lock().unwrap();
+            let position = indexes.iter().position(|(ledger, _)| *ledger == path);
+            indexes.remove(position.unwrap()).1
+        };
+        pool.close().await;
+        assert!(matches!(
+            admit(&path, recorded).await.unwrap(),
+            Admission::Duplicate(_)
+        ));
+
+        let mut index = path.as_os_str().to_owned();
+        index.push(".index.sqlite");
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(index).unwrap();
+    }
+
+    #[tokio::test]
+    async fn span_stream_resumes_from_a_byte_offset() {
+        use futures::StreamExt;
+
+        let path =
+            std::env::temp_dir().join(format!("ledger-stream-{}.ndjson", uuid::Uuid::new_v4()));
+        for index in 0..3 {
+            let span = UniversalSpan::new(
+                format!("span::{index}"),
+                "scan",
+                "metric",
+                "qc",
+                Utc::now(),
+                json!({ "index": index }),
+            );
+            append_span(&path, &span).unwrap();
+        }
+        // A line still being written is left for a later read.
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, r#"{{"id":"span::3","#).unwrap();
+
+        let mut stream = SpanStream::open(&path, 0, 1).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap().id.0, "span::0");
+        let offset = stream.offset();
+        drop(stream);
+
+        let rest = SpanStream::open(&path, offset, 1).await.unwrap();
+        let ids: Vec<String> = rest.map(|span| span.unwrap().id.0).collect().await;
+        assert_eq!(ids, ["span::1", "span::2"]);
+
+        writeln!(file, "not json").unwrap();
+        let mut stream = SpanStream::open(&path, offset, 1).await.unwrap();
+        stream.next().await.unwrap().unwrap();
+        stream.next().await.unwrap().unwrap();
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("malformed span"));
+        assert!(stream.next().await.is_none());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -9,7 +9,7 @@ mod service;
 mod twin;
 mod triage;
 
-use anyhow::{self, Result};
+use anyhow::{self, Context, Result};
 use logline_common::{triage::make_plan_from_json, Error};
 use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
@@ -20,23 +20,30 @@ use std::sync::Arc;
 use std::time::{Duration as StdDuration, Instant, SystemTime};
 
 use chrono::{DateTime, Utc};
-use clap::{Parser, Subcommand};
-use commands::{run_causal_analysis, sync_ledger};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use commands::run_causal_analysis;
 use config::RunnerConfig;
-use db::{apply_mapping, init_pool, insert_raw_span};
+use db::{apply_mapping, copy_raw_spans, init_pool, insert_raw_span};
 use discovery_agent::DiscoveryAgent;
 use folding_core::{
-    ContractInstruction, FoldingContract, FoldingEngineBuilder, MicroOscillator, PhysicsLevel,
-    Ruleset,
+    ContractInstruction, EngineCheckpoint, FoldingContract, FoldingEngine, FoldingEngineBuilder,
+    MicroOscillator, PhysicsLevel, ReplicaExchange, Ruleset,
+};
+use folding_molecule::{
+    assign_from_chain_backbone, assign_from_torsions, assignment_codes, parse_fasta, read_fasta,
+    read_structure, write_structure, write_trajectory, AminoAcid, EnergyModel, ForceField,
+    PeptideChain, Residue, ResidueId, SecondaryStructureFractions, StartingConformation,
+    TrajectoryFormat,
 };
-use folding_molecule::{AminoAcid, EnergyModel, PeptideChain, Residue, ResidueId};
 use folding_runtime::FoldingAnalysis;
 use folding_time::RotationClock;
-use ledger::append_span;
+use futures::StreamExt;
+use ledger::{admit, append_span, record, Admission, SpanStream, STREAM_CAPACITY};
 use mapping::classify_span;
 use serde_json::{json, Value};
-use span_ingestor::{ingest_fold_json, ingest_json, IngestOptions};
-use spans_core::{span_from_json, UniversalSpan};
+use span_ingestor::{ingest_json, lift_file, AdapterRegistry, IngestOptions};
+use spans_core::schema::SchemaMode;
+use spans_core::{span_from_json, SpanId, UniversalSpan};
 use sqlx::postgres::PgPool;
 use tokio::time::{sleep, Duration};
 use tracing::{info, warn};
@@ -56,7 +63,8 @@ struct Cli {
 enum Command {
     /// Ingest a span payload into the ledger (NDJSON) and Postgres
     Ingest {
-        /// Path to a JSON file containing a span payload
+        /// Span payload JSON or instrument output (GROMACS .log/.xvg, OpenMM
+        /// reporter CSV, AlphaFold ranking/pLDDT JSON, metric CSV)
         path: PathBuf,
         /// Override flow if not present in payload
         #[arg(long)]
@@ -64,6 +72,10 @@ enum Command {
         /// Override workflow if not present in payload
         #[arg(long)]
         workflow: Option<String>,
+        /// Payload schema enforcement: strict, warn or off (default:
+        /// SPAN_SCHEMA_MODE, else warn)
+        #[arg(long)]
+        schema_mode: Option<SchemaMode>,
     },
     /// Analyze causal relationships within a span payload JSON file
     Causal {
@@ -106,6 +118,9 @@ enum Command {
         /// Path to NDJSON file (one span per line)
         #[arg(long)]
         path: PathBuf,
+        /// Byte offset to resume from, as printed by an earlier sync
+        #[arg(long, default_value_t = 0)]
+        from_offset: u64,
     },
     /// Execute an `.lll` folding contract and emit a report
     Fold {
@@ -115,6 +130,56 @@ enum Command {
         /// Output path for the JSON report
         #[arg(long, default_value = "tmp/folding_report.json")]
         output: PathBuf,
+        /// Starting structure (`.pdb` or `.cif`) instead of the demo chain
+        #[arg(long, conflicts_with_all = ["sequence", "fasta"])]
+        structure: Option<PathBuf>,
+        /// One-letter amino-acid sequence to build the starting chain from
+        #[arg(long, conflicts_with = "fasta")]
+        sequence: Option<String>,
+        /// FASTA file whose first record builds the starting chain
+        #[arg(long)]
+        fasta: Option<PathBuf>,
+        /// Starting conformation for `--sequence`/`--fasta` chains
+        #[arg(long, value_enum, default_value_t = ConformationArg::Extended)]
+        conformation: ConformationArg,
+        /// Chain identifier to read from the starting structure
+        #[arg(long, requires = "structure")]
+        chain: Option<String>,
+        /// Write the final folded chain to this `.pdb` or `.cif` file
+        #[arg(long)]
+        pdb_output: Option<PathBuf>,
+        /// Reference `.pdb` or `.cif` for RMSD and native contacts (default: the starting chain)
+        #[arg(long)]
+        reference: Option<PathBuf>,
+        /// Write coordinate frames to this `.pdb` (multi-model), `.xyz`, `.dcd` or `.xtc` file
+        #[arg(long)]
+        trajectory: Option<PathBuf>,
+        /// Record a trajectory frame at most every N engine steps
+        #[arg(long, default_value_t = 1, requires = "trajectory")]
+        trajectory_every: usize,
+        /// Score non-bonded terms over backbone N/CA/C/O and side-chain centroid beads
+        #[arg(long)]
+        multi_bead: bool,
+        /// Force-field table for the bead model (default: the bundled table)
+        #[arg(long, requires = "multi_bead")]
+        force_field: Option<PathBuf>,
+        #[command(flatten)]
+        checkpointing: Box<CheckpointArgs>,
+        /// Run replica exchange with this many replicas (1 = single engine)
+        #[arg(long, default_value_t = 1)]
+        replicas: usize,
+        /// Hottest rung of the replica ladder in K; the coldest is 310 K
+        #[arg(long, default_value_t = 450.0)]
+        max_temperature: f64,
+        /// Check the contract against the ruleset and budgets without running it;
+        /// the JSON dry-run report goes to --output
+        #[arg(long, conflicts_with_all = ["pdb_output", "trajectory", "checkpoint", "resume"])]
+        dry_run: bool,
+    },
+    /// Check `.lll` folding contracts without running them
+    Contract {
+        #[command(subcommand)]
+        command: ContractCommand,
     },
     /// Serve an HTTP API for dashboards and automation hooks
     Serve {
@@ -150,6 +215,59 @@ enum Command {
     },
 }
 
+/// `--conformation` values; the aliases match [`StartingConformation::by_name`].
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ConformationArg {
+    #[value(alias = "strand", alias = "beta")]
+    Extended,
+    #[value(alias = "helical", alias = "alpha")]
+    Helix,
+}
+
+impl From<ConformationArg> for StartingConformation {
+    fn from(arg: ConformationArg) -> Self {
+        match arg {
+            ConformationArg::Extended => Self::Extended,
+            ConformationArg::Helix => Self::Helix,
+        }
+    }
+}
+
+/// Resumable engine checkpoints for `fold`.
+#[derive(Args, Debug, Default)]
+struct CheckpointArgs {
+    /// Keep a resumable engine checkpoint at this path while the contract runs
+    #[arg(long)]
+    checkpoint: Option<PathBuf>,
+    /// Rewrite the checkpoint every N contract instructions
+    #[arg(long, default_value_t = 25, requires = "checkpoint")]
+    checkpoint_every: usize,
+    /// Continue an interrupted run from this checkpoint (same contract and chain)
+    #[arg(long)]
+    resume: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum ContractCommand {
+    /// Report errors and warnings in a folding contract
+    Lint {
+        /// Path to folding contract file
+        file: PathBuf,
+        /// Check residue references against this starting structure
+        #[arg(long, conflicts_with_all = ["sequence", "fasta"])]
+        structure: Option<PathBuf>,
+        /// Check residue references against this one-letter sequence
+        #[arg(long, conflicts_with = "fasta")]
+        sequence: Option<String>,
+        /// Check residue references against the first record of this FASTA file
+        #[arg(long)]
+        fasta: Option<PathBuf>,
+        /// Chain identifier to read from the starting structure
+        #[arg(long, requires = "structure")]
+        chain: Option<String>,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 enum LedgerCommand {
     /// Count entries in the ledger
@@ -182,7 +300,12 @@ async fn main() -> Result<()> {
             path,
             flow,
             workflow,
+            schema_mode,
         } => {
+            let mut cfg = cfg.clone();
+            if let Some(mode) = schema_mode {
+                cfg.schema_mode = mode;
+            }
             handle_ingest(path, flow, workflow, &cfg).await?;
         }
         Command::Watch {
@@ -203,14 +326,52 @@ async fn main() -> Result<()> {
         Command::Causal { input, output } => {
             handle_causal(input, output).await?;
         }
-        Command::SyncLedger { path } => {
-            handle_sync_ledger(path, &cfg).await?;
+        Command::SyncLedger { path, from_offset } => {
+            handle_sync_ledger(path, from_offset, &cfg).await?;
         }
         Command::Serve { address } => {
             handle_serve(address, cfg.clone()).await?;
         }
-        Command::Fold { contract, output } => {
-            handle_fold_contract(contract, output, &cfg).await?;
+        Command::Fold {
+            contract,
+            output,
+            structure,
+            sequence,
+            fasta,
+            conformation,
+            chain,
+            pdb_output,
+            reference,
+            trajectory,
+            trajectory_every,
+            multi_bead,
+            force_field,
+            checkpointing,
+            replicas,
+            max_temperature,
+            dry_run,
+        } => {
+            let options = FoldOptions {
+                structure,
+                sequence,
+                fasta,
+                conformation: conformation.into(),
+                chain,
+                pdb_output,
+                reference,
+                trajectory,
+                trajectory_every,
+                multi_bead,
+                force_field,
+                checkpointing: *checkpointing,
+                replicas,
+                max_temperature,
+                dry_run,
+            };
+            handle_fold_contract(contract, output, options, &cfg).await?;
+        }
+        Command::Contract { command } => {
+            handle_contract_command(command)?;
         }
         Command::Ledger { command } => {
             handle_ledger_command(command, &cfg).await?;
@@ -379,6 +540,8 @@ async fn run_demo() -> Result<()> {
         max_rmsd: 6.1,
         unstable: true,
         window: (span.started_at, span.finished_at),
+        helix_fraction: None,
+        strand_fraction: None,
     };
 
     let manuscript = DiscoveryAgent::orchestrate(vec![span], vec![analysis])?;
@@ -394,58 +557,161 @@ async fn ingest_path(
     cfg: &RunnerConfig,
     pool: Option<Arc<PgPool>>,
 ) -> Result<String> {
-    let raw = std::fs::read_to_string(path)?;
-    let json_payload: Value = serde_json::from_str(&raw)?;
-    let is_fold_payload = json_payload.get("protein_metadata").is_some();
-
-    let effective_flow = flow.or_else(|| {
-        if is_fold_payload {
-            Some("protein_folding".to_string())
-        } else {
-            None
-        }
-    });
-
-    let flow_value = effective_flow.unwrap_or_else(|| "unknown_flow".to_string());
-
-    let effective_workflow = workflow.or_else(|| {
-        if is_fold_payload {
-            Some("fold_pipeline".to_string())
-        } else {
-            None
-        }
-    });
-
-    let workflow_value = effective_workflow.unwrap_or_else(|| "unspecified".to_string());
-
-    let ingest_opts = IngestOptions::new(flow_value, workflow_value, Utc::now());
-    let span = if is_fold_payload {
-        ingest_fold_json(json_payload, &ingest_opts)?
-    } else {
-        ingest_json(json_payload, &ingest_opts)?
-    };
+    let raw = std::fs::read(path)?;
+    let payload = lift_file(path.file_name().and_then(|name| name.to_str()), &raw)
+        .with_context(|| format!("Cannot read {}", path.display()))?;
+    let registry = AdapterRegistry::default();
+    let adapter = registry.detect(&payload).ok_or_else(|| {
+        Error::Validation(format!(
+            "No ingest adapter recognises {} (known: {})",
+            path.display(),
+            registry.names().join(", ")
+        ))
+    })?;
+    info!(?path, adapter = adapter.name(), "ingest_adapter_selected");
+
+    let flow_value = flow
+        .or_else(|| adapter.default_flow().map(str::to_string))
+        .unwrap_or_else(|| "unknown_flow".to_string());
+    let workflow_value = workflow
+        .or_else(|| adapter.default_workflow().map(str::to_string))
+        .unwrap_or_else(|| "unspecified".to_string());
+
+    // The file's mtime rather than the clock, so re-ingesting an unchanged
+    // file reproduces the same span and is skipped as a duplicate.
+    let started_at = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_or_else(|_| Utc::now(), DateTime::<Utc>::from);
+    let ingest_opts = IngestOptions::new(flow_value, workflow_value, started_at)
+        .with_source(path.display().to_string())
+        .with_schema_mode(cfg.schema_mode);
+    let span = adapter.convert(payload, &ingest_opts)?;
 
     process_span(span, cfg, pool).await
 }
 
-async fn handle_sync_ledger(path: PathBuf, cfg: &RunnerConfig) -> Result<()> {
-    let spans = sync_ledger(path.clone())?;
+/// Spans mirrored into Postgres per `COPY` during `sync-ledger`.
+const SYNC_BATCH_SIZE: usize = 500;
+
+async fn handle_sync_ledger(path: PathBuf, from_offset: u64, cfg: &RunnerConfig) -> Result<()> {
     let pool = if let Some(db_url) = &cfg.database_url {
         Some(Arc::new(init_pool(db_url).await?))
     } else {
         None
     };
 
+    let mut spans = SpanStream::open(&path, from_offset, STREAM_CAPACITY).await?;
+    let mut batch = Vec::with_capacity(SYNC_BATCH_SIZE);
+    let mut committed = from_offset;
     let mut count = 0usize;
-    for span in spans {
-        process_span(span, cfg, pool.clone()).await?;
-        count += 1;
+    let resume_hint =
+        |offset: u64| format!("sync stopped; rerun with --from-offset {offset} to resume");
+
+    while let Some(span) = spans.next().await {
+        let span = match span {
+            Ok(span) => span,
+            Err(err) => {
+                // Keep the spans read before the bad line.
+                sync_batch(&mut batch, cfg, pool.clone())
+                    .await
+                    .with_context(|| resume_hint(committed))?;
+                return Err(err.context(resume_hint(spans.offset())));
+            }
+        };
+        batch.push(span);
+        if batch.len() == SYNC_BATCH_SIZE {
+            count += sync_batch(&mut batch, cfg, pool.clone())
+                .await
+                .with_context(|| resume_hint(committed))?;
+            committed = spans.offset();
+            info!(?path, count, offset = committed, "sync_ledger_progress");
+        }
     }
-    info!(?path, count, "sync_ledger_complete");
-    println!("Ingested {} spans", count);
+    count += sync_batch(&mut batch, cfg, pool)
+        .await
+        .with_context(|| resume_hint(committed))?;
+    committed = spans.offset();
+
+    info!(?path, count, offset = committed, "sync_ledger_complete");
+    println!("Ingested {} spans (ledger offset {})", count, committed);
     Ok(())
 }
 
+/// Processes a batch read from a ledger. With a database, spans taking
+/// the basic path are deduplicated against `raw_spans` rather than the
+/// ledger they were read from, so syncing the runner's own ledger still
+/// backfills an empty database: one `COPY` mirrors them and only the rows
+/// it inserted are mapped. Spans the triage plans for, and every span
+/// without a database, go through [`process_span`].
+async fn sync_batch(
+    batch: &mut Vec<UniversalSpan>,
+    cfg: &RunnerConfig,
+    pool: Option<Arc<PgPool>>,
+) -> Result<usize> {
+    let count = batch.len();
+    let Some(pool_arc) = pool.clone() else {
+        for span in batch.drain(..) {
+            process_span(span, cfg, None).await?;
+        }
+        return Ok(count);
+    };
+
+    let mut basic = Vec::with_capacity(count);
+    for mut span in batch.drain(..) {
+        if make_plan_from_json(&span.payload).is_ok() {
+            process_span(span, cfg, pool.clone()).await?;
+        } else if let Some(supplied) = span.rehash() {
+            warn!(span = %span.id.0, supplied = %supplied, "span_conflict_detected");
+            record_span_conflict(&span, &supplied, cfg, Some(&pool_arc)).await?;
+        } else {
+            basic.push(span);
+        }
+    }
+
+    let mut copied = copy_raw_spans(&pool_arc, &basic).await?;
+    for (position, span) in basic.into_iter().enumerate() {
+        if copied.rejected.contains(&position) {
+            warn!(span = %span.id.0, "span_rejected_nul");
+            continue;
+        }
+        if let Some(existing_hash) = copied.conflicts.remove(&position) {
+            warn!(
+                span = %span.id.0,
+                existing = %existing_hash,
+                "span_conflict_detected"
+            );
+            record_span_conflict(&span, &existing_hash, cfg, Some(&pool_arc)).await?;
+            continue;
+        }
+        // Ids already in `raw_spans`, or repeated in the batch, were skipped.
+        if !copied.inserted.remove(&span.id.0) {
+            continue;
+        }
+        let span = match admit(&cfg.ledger_path, span).await? {
+            Admission::New(span) => {
+                append_span(&cfg.ledger_path, &span)?;
+                span
+            }
+            Admission::Duplicate(span) => span,
+            Admission::Conflict {
+                span,
+                existing_hash,
+            } => {
+                warn!(
+                    span = %span.id.0,
+                    existing = %existing_hash,
+                    "span_conflict_detected"
+                );
+                record_span_conflict(&span, &existing_hash, cfg, Some(&pool_arc)).await?;
+                span
+            }
+        };
+        apply_mapping(&pool_arc, &span, &classify_span(&span)).await?;
+        persist_twin_spans(&span, cfg, pool.clone()).await?;
+    }
+    Ok(count)
+}
+
 async fn handle_serve(address: SocketAddr, cfg: RunnerConfig) -> Result<()> {
     service::serve(cfg, address).await
 }
@@ -465,9 +731,29 @@ async fn handle_causal(input: PathBuf, output: Option<PathBuf>) -> Result<()> {
     Ok(())
 }
 
+/// Optional inputs and outputs of the `fold` command beyond the contract.
+struct FoldOptions {
+    structure: Option<PathBuf>,
+    sequence: Option<String>,
+    fasta: Option<PathBuf>,
+    conformation: StartingConformation,
+    chain: Option<String>,
+    pdb_output: Option<PathBuf>,
+    reference: Option<PathBuf>,
+    trajectory: Option<PathBuf>,
+    trajectory_every: usize,
+    multi_bead: bool,
+    force_field: Option<PathBuf>,
+    checkpointing: CheckpointArgs,
+    replicas: usize,
+    max_temperature: f64,
+    dry_run: bool,
+}
+
 async fn handle_fold_contract(
     contract_path: PathBuf,
     output_path: PathBuf,
+    options: FoldOptions,
     cfg: &RunnerConfig,
 ) -> Result<()> {
     if !contract_path.exists() {
@@ -478,20 +764,117 @@ async fn handle_fold_contract(
         .into());
     }
 
-    let contract_text = fs::read_to_string(&contract_path)?;
-    let contract = parse_contract(&contract_text);
-    let mut engine = build_demo_engine();
-    let report = engine.execute_contract(&contract);
+    let (chain, scale) = load_starting_chain(&options)?;
+    let parsed = FoldingContract::parse_file(&contract_path, Some(&chain))?;
+    let origin = contract_path.display().to_string();
+    if !parsed.diagnostics.is_empty() {
+        eprint!("{}", parsed.render(&origin));
+    }
+    if parsed.has_errors() {
+        return Err(Error::Validation(format!(
+            "Contract {origin} has {} error(s); see `contract lint`",
+            parsed.errors().count()
+        ))
+        .into());
+    }
+    let contract = parsed.contract;
+    let mut builder = fold_engine_builder(chain, scale);
+    if options.multi_bead {
+        if matches!(scale, ChainScale::Toy) {
+            return Err(Error::Validation(
+                "--multi-bead needs real Cα positions (--structure, --sequence or --fasta)"
+                    .to_string(),
+            )
+            .into());
+        }
+        let field = match &options.force_field {
+            Some(path) => ForceField::from_path(path)?,
+            None => ForceField::builtin(),
+        };
+        builder = builder.with_energy_model(scale.energy_model().with_force_field(Some(field)));
+    }
+    if let Some(reference) = &options.reference {
+        builder = builder.with_reference_structure(read_structure(reference, None)?);
+    }
+    if let Some(trajectory) = &options.trajectory {
+        if TrajectoryFormat::from_path(trajectory).is_none() {
+            return Err(Error::Validation(format!(
+                "Unsupported trajectory format: {} (use .pdb, .xyz, .dcd or .xtc)",
+                trajectory.display()
+            ))
+            .into());
+        }
+        builder = builder.with_trajectory_interval(options.trajectory_every);
+    }
+    if options.dry_run {
+        return write_dry_run(&builder.build(), &contract, &contract_path, &output_path);
+    }
+    let report = if options.replicas > 1 {
+        let checkpointing = &options.checkpointing;
+        if checkpointing.checkpoint.is_some() || checkpointing.resume.is_some() {
+            return Err(Error::Validation(
+                "--checkpoint and --resume need a single engine (--replicas 1)".to_string(),
+            )
+            .into());
+        }
+        let ladder = ReplicaExchange::geometric_ladder(
+            FOLD_TEMPERATURE,
+            options.max_temperature.max(FOLD_TEMPERATURE),
+            options.replicas,
+        );
+        ReplicaExchange::new(builder, &ladder).execute_contract(&contract)
+    } else {
+        run_fold_engine(builder.build(), &contract, &options.checkpointing)?
+    };
 
     if let Some(parent) = output_path.parent() {
         fs::create_dir_all(parent)?;
     }
 
-    let summary = summarize_execution_report(&report);
-    fs::write(
-        &output_path,
-        serde_json::to_string_pretty(&summary)?,
-    )?;
+    let mut summary = summarize_execution_report(&report);
+    let checkpointing = &options.checkpointing;
+    if let Some(path) = &checkpointing.checkpoint {
+        summary["checkpoint"] = json!({
+            "path": path.display().to_string(),
+            "every": checkpointing.checkpoint_every,
+        });
+    }
+    if let Some(path) = &checkpointing.resume {
+        summary["resumed_from"] = json!(path.display().to_string());
+    }
+    if options.multi_bead {
+        summary["force_field"] = json!(options
+            .force_field
+            .as_ref()
+            .map_or_else(|| "builtin".to_string(), |path| path.display().to_string()));
+    }
+    if let Some(pdb_path) = &options.pdb_output {
+        if let Some(parent) = pdb_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_structure(pdb_path, &report.final_chain)?;
+        summary["final_structure"] = json!(pdb_path.display().to_string());
+        println!("Final structure written to {}", pdb_path.display());
+    }
+    let mut trajectory = None;
+    if let Some(path) = &options.trajectory {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let format = write_trajectory(path, &report.final_chain, &report.trajectory_frames)?;
+        summary["trajectory"] = json!({
+            "path": path.display().to_string(),
+            "format": format.as_str(),
+            "frames": report.trajectory_frames.len(),
+        });
+        println!(
+            "Trajectory ({} frames) written to {}",
+            report.trajectory_frames.len(),
+            path.display()
+        );
+        trajectory = Some((path, format));
+    }
+    fs::write(&output_path, serde_json::to_string_pretty(&summary)?)?;
 
     println!(
         "Executed folding contract {} → {}",
@@ -508,11 +891,93 @@ async fn handle_fold_contract(
         None
     };
 
-    persist_folding_report(&report, &contract_path, &output_path, cfg, pool).await?;
+    let report_span = persist_folding_report(
+        &report,
+        &contract,
+        &contract_path,
+        &output_path,
+        cfg,
+        pool.clone(),
+    )
+    .await?;
+    if let Some((path, format)) = trajectory {
+        let frames = report.trajectory_frames.len();
+        persist_trajectory_artifact(path, format, frames, &report_span, cfg, pool).await?;
+    }
 
     Ok(())
 }
 
+/// Runs `contract` on a single engine, continuing from `--resume` and
+/// rewriting `--checkpoint` every `--checkpoint-every` instructions.
+fn run_fold_engine(
+    mut engine: FoldingEngine,
+    contract: &FoldingContract,
+    checkpointing: &CheckpointArgs,
+) -> Result<folding_core::ExecutionReport> {
+    let checkpoint_path = checkpointing.checkpoint.as_deref();
+    if let Some(parent) = checkpoint_path.and_then(Path::parent) {
+        fs::create_dir_all(parent)?;
+    }
+    let every = if checkpoint_path.is_some() {
+        checkpointing.checkpoint_every
+    } else {
+        usize::MAX
+    };
+    let save = |checkpoint: &EngineCheckpoint| match checkpoint_path {
+        Some(path) => checkpoint.save(path),
+        None => Ok(()),
+    };
+    let report = match &checkpointing.resume {
+        Some(path) => {
+            let checkpoint = EngineCheckpoint::load(path)?;
+            if checkpoint.contract != *contract {
+                return Err(Error::Validation(format!(
+                    "Checkpoint {} was taken from a different contract",
+                    path.display()
+                ))
+                .into());
+            }
+            println!(
+                "Resuming from {} at instruction {}/{}",
+                path.display(),
+                checkpoint.next_instruction,
+                contract.instructions.len()
+            );
+            engine.resume_contract(checkpoint, every, save)?
+        }
+        None if checkpoint_path.is_some() => {
+            engine.execute_contract_with_checkpoints(contract, every, save)?
+        }
+        None => engine.execute_contract(contract),
+    };
+    Ok(report)
+}
+
+/// Writes the static analysis of `contract` instead of running it.
+fn write_dry_run(
+    engine: &FoldingEngine,
+    contract: &FoldingContract,
+    contract_path: &Path,
+    output_path: &Path,
+) -> Result<()> {
+    let report = engine.dry_run(contract);
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(output_path, serde_json::to_string_pretty(&report)?)?;
+    println!(
+        "Dry run of {}: {} rotation(s), {} limit violation(s), {} budget violation(s), ~{:.0} ms → {}",
+        contract_path.display(),
+        report.rotations,
+        report.rotation_limit_violations.len(),
+        report.budget_violations.len(),
+        report.estimated_wall_clock_ms,
+        output_path.display()
+    );
+    Ok(())
+}
+
 async fn handle_diagnose(cfg: &RunnerConfig) -> Result<()> {
     println!("LogLine Discovery Lab — Diagnose");
 
@@ -721,6 +1186,9 @@ fn is_supported_file(path: &Path) -> bool {
     match path.extension().and_then(|ext| ext.to_str()) {
         Some(ext) if ext.eq_ignore_ascii_case("json") => true,
         Some(ext) if ext.eq_ignore_ascii_case("ndjson") => true,
+        Some(ext) => ["log", "xvg", "csv"]
+            .iter()
+            .any(|known| ext.eq_ignore_ascii_case(known)),
         _ => false,
     }
 }
@@ -754,40 +1222,153 @@ async fn handle_ledger_command(command: LedgerCommand, cfg: &RunnerConfig) -> Re
     Ok(())
 }
 
-fn parse_contract(text: &str) -> FoldingContract {
-    let instructions: Vec<_> = text
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .collect();
-    FoldingContract::from_lines(&instructions)
+fn handle_contract_command(command: ContractCommand) -> Result<()> {
+    match command {
+        ContractCommand::Lint {
+            file,
+            structure,
+            sequence,
+            fasta,
+            chain,
+        } => {
+            // Residue ranges are only checked when a target chain is given.
+            let target = if structure.is_some() || sequence.is_some() || fasta.is_some() {
+                let options = FoldOptions {
+                    structure,
+                    sequence,
+                    fasta,
+                    conformation: StartingConformation::Extended,
+                    chain,
+                    pdb_output: None,
+                    reference: None,
+                    trajectory: None,
+                    trajectory_every: 1,
+                    multi_bead: false,
+                    force_field: None,
+                    checkpointing: CheckpointArgs::default(),
+                    replicas: 1,
+                    max_temperature: FOLD_TEMPERATURE,
+                    dry_run: false,
+                };
+                Some(load_starting_chain(&options)?.0)
+            } else {
+                None
+            };
+            let origin = file.display().to_string();
+            let parsed = FoldingContract::parse_file(&file, target.as_ref())?;
+            if !parsed.diagnostics.is_empty() {
+                println!("{}", parsed.render(&origin));
+            }
+            let errors = parsed.errors().count();
+            let warnings = parsed.warnings().count();
+            println!(
+                "{origin}: {} instruction(s), {errors} error(s), {warnings} warning(s)",
+                parsed.contract.instructions.len()
+            );
+            if errors > 0 {
+                return Err(Error::Validation(format!("Contract {origin} failed lint")).into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Coordinate scale of a starting chain, which decides the ruleset and
+/// energy model it is folded with.
+#[derive(Clone, Copy, Debug)]
+enum ChainScale {
+    /// Hand-placed toy coordinates such as [`demo_chain`].
+    Toy,
+    /// Real Cα positions with ~3.8 Å spacing.
+    AlphaCarbon,
+}
+
+impl ChainScale {
+    fn ruleset(self) -> Ruleset {
+        match self {
+            ChainScale::Toy => Ruleset::default(),
+            ChainScale::AlphaCarbon => Ruleset::alpha_carbon_trace(),
+        }
+    }
+
+    fn energy_model(self) -> EnergyModel {
+        match self {
+            ChainScale::Toy => EnergyModel::default(),
+            ChainScale::AlphaCarbon => EnergyModel::alpha_carbon_trace(),
+        }
+    }
+}
+
+/// Picks the starting chain for `fold`: a structure file, a sequence laid out
+/// with ideal geometry, or the built-in demo chain.
+fn load_starting_chain(options: &FoldOptions) -> Result<(PeptideChain, ChainScale)> {
+    if let Some(path) = &options.structure {
+        let chain = read_structure(path, options.chain.as_deref())?;
+        return Ok((chain, ChainScale::AlphaCarbon));
+    }
+
+    let record = if let Some(sequence) = &options.sequence {
+        parse_fasta(sequence)?.remove(0)
+    } else if let Some(path) = &options.fasta {
+        let records = read_fasta(path)?;
+        if records.len() > 1 {
+            warn!(
+                ?path,
+                records = records.len(),
+                "fasta_multiple_records_using_first"
+            );
+        }
+        records
+            .into_iter()
+            .next()
+            .expect("read_fasta rejects empty files")
+    } else {
+        return Ok((demo_chain(), ChainScale::Toy));
+    };
+
+    let conformation = options.conformation;
+    info!(
+        target = record.identifier(),
+        residues = record.sequence.len(),
+        ?conformation,
+        "fold_sequence_loaded"
+    );
+    Ok((record.to_chain(conformation), ChainScale::AlphaCarbon))
 }
 
-fn build_demo_engine() -> folding_core::FoldingEngine {
-    let chain = PeptideChain::new(vec![
+/// Toy chain with 1.6 Å bonds and ~115° angles. Non-bonded terms only see
+/// residues at least three apart, so it needs more than three residues for
+/// a rotation to change anything but the bonded energy.
+fn demo_chain() -> PeptideChain {
+    PeptideChain::new(vec![
         Residue::new(ResidueId(1), AminoAcid::Alanine).with_position([0.0, 0.0, 0.0]),
-        Residue::new(ResidueId(2), AminoAcid::Serine).with_position([1.6, 0.0, 0.1]),
-        Residue::new(ResidueId(3), AminoAcid::Valine).with_position([3.2, 0.5, -0.1]),
-    ]);
+        Residue::new(ResidueId(2), AminoAcid::Serine).with_position([1.6, 0.0, 0.0]),
+        Residue::new(ResidueId(3), AminoAcid::Valine).with_position([2.28, 1.45, 0.0]),
+        Residue::new(ResidueId(4), AminoAcid::Leucine).with_position([1.9, 2.37, 1.26]),
+        Residue::new(ResidueId(5), AminoAcid::Lysine).with_position([2.32, 1.77, 2.68]),
+        Residue::new(ResidueId(6), AminoAcid::Glutamate).with_position([3.88, 1.49, 2.87]),
+    ])
+}
 
+/// Bath temperature of single-engine folds and the cold rung of replica ladders (K).
+const FOLD_TEMPERATURE: f64 = 310.0;
+
+fn fold_engine_builder(chain: PeptideChain, scale: ChainScale) -> FoldingEngineBuilder {
     FoldingEngineBuilder::new()
         .with_chain(chain)
-        .with_energy_model(EnergyModel::default())
+        .with_temperature(FOLD_TEMPERATURE)
+        .with_energy_model(scale.energy_model())
         .with_oscillator(MicroOscillator::new(6.0, 0.4))
         .with_clock(RotationClock::new(5))
-        .with_ruleset(Ruleset::default())
+        .with_ruleset(scale.ruleset())
         .with_rng_seed(42)
         .with_physics_level(PhysicsLevel::Toy)
-        .build()
 }
 
 fn handle_folding_demo() -> Result<()> {
     println!("Running folding demo with synthetic contract ...");
 
-    let chain = PeptideChain::new(vec![
-        Residue::new(ResidueId(1), AminoAcid::Alanine).with_position([0.0, 0.0, 0.0]),
-        Residue::new(ResidueId(2), AminoAcid::Serine).with_position([1.6, 0.0, 0.1]),
-        Residue::new(ResidueId(3), AminoAcid::Valine).with_position([3.2, 0.5, -0.1]),
-    ]);
+    let chain = demo_chain();
 
     let energy_model = EnergyModel::default();
     let contract = FoldingContract::new(vec![
@@ -878,6 +1459,97 @@ fn summarize_execution_report(report: &folding_core::ExecutionReport) -> Value {
         .map(|rej| format!("{:?}", rej))
         .collect();
 
+    let minimizations: Vec<_> = report
+        .minimizations
+        .iter()
+        .map(|run| {
+            json!({
+                "algorithm": format!("{:?}", run.algorithm),
+                "steps": run.steps,
+                "initial_energy": run.initial_energy,
+                "final_energy": run.final_energy,
+                "rms_gradient": run.rms_gradient,
+                "converged": run.converged,
+            })
+        })
+        .collect();
+
+    let residues = report.final_chain.residues();
+    let restraints: Vec<_> = report
+        .restraints
+        .iter()
+        .map(|status| {
+            let ids: Vec<_> = status
+                .residues
+                .iter()
+                .filter_map(|index| residues.get(*index).map(|residue| residue.id.0))
+                .collect();
+            json!({
+                "kind": status.kind,
+                "residues": ids,
+                "value": status.value,
+                "target": status.target,
+                "violation": status.violation,
+                "energy": status.energy,
+            })
+        })
+        .collect();
+
+    let residue_id = |index: usize| residues.get(index).map(|residue| residue.id.0);
+    let structure_series: Vec<_> = report
+        .structure_series
+        .iter()
+        .map(|frame| {
+            let contacts: Vec<_> = frame
+                .contacts
+                .iter()
+                .filter_map(|(i, j)| Some([residue_id(*i)?, residue_id(*j)?]))
+                .collect();
+            let secondary_structure = secondary_structure_json(&frame.metrics.secondary_structure);
+            json!({
+                "step": frame.step,
+                "span_id": frame.span_id,
+                "rmsd": frame.metrics.rmsd,
+                "radius_of_gyration": frame.metrics.radius_of_gyration,
+                "end_to_end_distance": frame.metrics.end_to_end_distance,
+                "native_contact_fraction": frame.metrics.native_contact_fraction,
+                "contacts": contacts,
+                "secondary_structure": secondary_structure,
+            })
+        })
+        .collect();
+
+    let assignment = assign_from_torsions(residues);
+    let mut secondary_structure =
+        secondary_structure_json(&SecondaryStructureFractions::from_assignment(&assignment));
+    secondary_structure["assignment"] = json!(assignment_codes(&assignment));
+    secondary_structure["hydrogen_bond_assignment"] = json!(assignment_codes(
+        &assign_from_chain_backbone(&report.final_chain)
+    ));
+
+    let replica_exchange = report.replica_exchange.as_ref().map(|exchange| {
+        let swaps: Vec<_> = exchange
+            .swap_stats
+            .iter()
+            .map(|pair| {
+                json!({
+                    "lower_temperature": pair.lower_temperature,
+                    "upper_temperature": pair.upper_temperature,
+                    "accepted": pair.accepted,
+                    "rejected": pair.rejected,
+                    "acceptance_rate": pair.acceptance_rate(),
+                })
+            })
+            .collect();
+        json!({
+            "temperatures": exchange.temperatures,
+            "final_energies": exchange.final_energies,
+            "best_replica": exchange.best_replica,
+            "exchange_rounds": exchange.exchange_rounds,
+            "swaps": swaps,
+        })
+    });
+
     json!({
         "final_energy": {
             "potential": report.final_energy.total_potential,
@@ -894,17 +1566,50 @@ fn summarize_execution_report(report: &folding_core::ExecutionReport) -> Value {
         "rejections": rejections,
         "trajectory_spans": trajectory,
         "physics_spans": physics_spans,
+        "minimizations": minimizations,
+        "restraints": restraints,
+        "structure_series": structure_series,
+        "secondary_structure": secondary_structure,
+        "replica_exchange": replica_exchange,
         "domains": report.domains.len(),
         "chaperones": report.chaperone_requirements.len(),
         "modifications": report.modifications.len(),
     })
 }
 
+fn secondary_structure_json(fractions: &SecondaryStructureFractions) -> Value {
+    json!({
+        "helix": fractions.helix,
+        "strand": fractions.strand,
+        "turn": fractions.turn,
+        "coil": fractions.coil,
+    })
+}
+
 async fn process_span(
     span: UniversalSpan,
     cfg: &RunnerConfig,
     pool: Option<Arc<PgPool>>,
 ) -> Result<String> {
+    let span = match admit(&cfg.ledger_path, span).await? {
+        Admission::New(span) => span,
+        Admission::Duplicate(span) => {
+            info!(span = %span.id.0, "span_duplicate_skipped");
+            return Ok(span.id.0);
+        }
+        Admission::Conflict {
+            span,
+            existing_hash,
+        } => {
+            warn!(
+                span = %span.id.0,
+                existing = %existing_hash,
+                "span_conflict_detected"
+            );
+            record_span_conflict(&span, &existing_hash, cfg, pool.as_deref()).await?;
+            return Ok(span.id.0);
+        }
+    };
     let span_kind = classify_span(&span);
 
     // 🧠 INTELLIGENT TRIAGE: Decide which analyses to run based on configurable rules
@@ -919,6 +1624,8 @@ async fn process_span(
             // 🚀 EXECUTE PIPELINE: Run the planned stages
             let orchestrator = pipeline::PipelineOrchestrator::new()?;
             orchestrator.orchestrate_span(&span).await?;
+            // Not written to the ledger, only remembered as ingested.
+            record(&cfg.ledger_path, &span).await?;
         }
         Err(err) => {
             warn!(
@@ -941,7 +1648,17 @@ async fn process_span(
         }
     }
 
-    let extra_spans = twin::handle_twin_observation(&span).await?;
+    persist_twin_spans(&span, cfg, pool).await?;
+    Ok(span.id.0.clone())
+}
+
+/// Writes the twin divergence spans `span` gives rise to.
+async fn persist_twin_spans(
+    span: &UniversalSpan,
+    cfg: &RunnerConfig,
+    pool: Option<Arc<PgPool>>,
+) -> Result<()> {
+    let extra_spans = twin::handle_twin_observation(span).await?;
 
     for extra in extra_spans {
         let extra_kind = classify_span(&extra);
@@ -956,7 +1673,47 @@ async fn process_span(
         info!(span = %extra.id.0, "twin_divergence_persisted");
     }
 
-    Ok(span.id.0.clone())
+    Ok(())
+}
+
+/// Records that `span` reused a ledger id with different content, as a
+/// `span_conflict` event carrying both hashes and the rejected span.
+async fn record_span_conflict(
+    span: &UniversalSpan,
+    existing_hash: &str,
+    cfg: &RunnerConfig,
+    pool: Option<&PgPool>,
+) -> Result<()> {
+    let incoming_hash = span.content_hash.clone().unwrap_or_default();
+    let event = UniversalSpan::new(
+        format!(
+            "span::conflict::{}::{}",
+            span.id.0,
+            &incoming_hash[..12.min(incoming_hash.len())]
+        ),
+        "span_conflict",
+        "span_conflict",
+        span.workflow.clone(),
+        span.started_at,
+        json!({
+            "span_id": span.id.0,
+            "existing_content_hash": existing_hash,
+            "incoming_content_hash": incoming_hash,
+            "incoming": span,
+        }),
+    )
+    .with_parent(span.id.clone());
+
+    // Re-ingesting the same conflicting span must not repeat the event.
+    let Admission::New(event) = admit(&cfg.ledger_path, event).await? else {
+        return Ok(());
+    };
+    append_span(&cfg.ledger_path, &event)?;
+    if let Some(pool) = pool {
+        insert_raw_span(pool, &event).await?;
+    }
+    info!(span = %event.id.0, "span_conflict_recorded");
+    Ok(())
 }
 
 async fn emit_cycle_metrics(
@@ -986,14 +1743,16 @@ async fn emit_cycle_metrics(
 
 async fn persist_folding_report(
     report: &folding_core::ExecutionReport,
+    contract: &FoldingContract,
     contract_path: &Path,
     output_path: &Path,
     cfg: &RunnerConfig,
     pool: Option<Arc<PgPool>>,
-) -> Result<()> {
+) -> Result<String> {
     let span_id = format!("span::folding_report::{}", Uuid::new_v4());
     let payload = json!({
         "contract_path": contract_path.display().to_string(),
+        "contract": contract,
         "output_path": output_path.display().to_string(),
         "final_energy": {
             "potential": report.final_energy.total_potential,
@@ -1038,5 +1797,44 @@ async fn persist_folding_report(
 
     let log = SpanLog::new(&cfg.ledger_path)?;
     log.append_contract(&contract)?;
-    Ok(())
+    Ok(span_id)
+}
+
+/// Records a written trajectory as an `artifact_manifest` span under the
+/// folding report, so the file can be found from the run.
+async fn persist_trajectory_artifact(
+    path: &Path,
+    format: TrajectoryFormat,
+    frames: usize,
+    report_span: &str,
+    cfg: &RunnerConfig,
+    pool: Option<Arc<PgPool>>,
+) -> Result<()> {
+    let checksum = format!("{:x}", md5::compute(fs::read(path)?));
+    let storage_path = path.display().to_string();
+    let mut payload = json!({
+        "metadata": {
+            "execution_span": report_span,
+            "artifact_kind": format!("trajectory_{}", format.as_str()),
+            "storage_path": storage_path,
+            "checksum": checksum,
+        },
+        "format": format.as_str(),
+        "frames": frames,
+    });
+    if format == TrajectoryFormat::Xtc {
+        payload["file_xtc"] = json!(storage_path);
+    }
+
+    let span = UniversalSpan::new(
+        format!("span::artifact::trajectory::{}", Uuid::new_v4()),
+        "folding trajectory",
+        "artifact_manifest",
+        "fold_contract",
+        Utc::now(),
+        payload,
+    )
+    .with_parent(SpanId::new(report_span));
+
+    process_span(span, cfg, pool).await.map(|_| ())
 }
@@ -213,11 +213,14 @@ impl ManuscriptContext {
             .collect();
 
         json!({
+            "protein": self.analysis.protein,
             "mean_energy": self.analysis.mean_energy,
             "max_rmsd": self.analysis.max_rmsd,
             "unstable": self.analysis.unstable,
             "stability": stability,
             "energy_trajectory": energy_series,
+            "helix_fraction": self.analysis.helix_fraction,
+            "strand_fraction": self.analysis.strand_fraction,
         })
     }
 }
@@ -326,6 +329,13 @@ async fn fetch_folding_analysis(
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
+    let secondary_structure = |class: &str| {
+        summary
+            .pointer(&format!("/secondary_structure/{class}"))
+            .or_else(|| summary.get(format!("{class}_fraction")))
+            .and_then(|v| v.as_f64())
+    };
+
     let protein = summary
         .get("protein")
         .and_then(|v| v.as_str())
@@ -339,6 +349,8 @@ async fn fetch_folding_analysis(
         max_rmsd,
         unstable,
         window: (execution.started_at, execution.finished_at),
+        helix_fraction: secondary_structure("helix"),
+        strand_fraction: secondary_structure("strand"),
     })
 }
 
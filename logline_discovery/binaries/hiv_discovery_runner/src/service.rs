@@ -2,46 +2,58 @@ use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
+use axum::body::Bytes;
 use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
-use axum::routing::get;
+use axum::routing::{get, post};
 use axum::{Json, Router};
 use causal_engine::{CausalChain, CausalEngine};
 use chrono::{DateTime, Utc};
+use futures::StreamExt;
 use serde::Serialize;
 use serde_json::Value;
+use span_ingestor::check_schema;
+use spans_core::otlp::{
+    self, ExportTracePartialSuccess, ExportTraceServiceRequest, ExportTraceServiceResponse,
+    OtlpEncoding,
+};
+use spans_core::schema::SchemaMode;
 use spans_core::UniversalSpan;
 use tokio::net::TcpListener;
-use tokio::sync::RwLock;
 use tracing::{info, warn};
 
-use crate::commands::sync_ledger;
 use crate::config::RunnerConfig;
+use crate::ledger::{admit, append_span, Admission, SpanStream, STREAM_CAPACITY};
 use crate::mapping::{self, SpanKind};
 
 #[derive(Clone)]
 struct AppState {
-    ledger: Arc<LedgerCache>,
+    ledger: Arc<Ledger>,
+    schema_mode: SchemaMode,
 }
 
 pub async fn serve(cfg: RunnerConfig, address: SocketAddr) -> Result<()> {
-    let ledger = Arc::new(LedgerCache::new(cfg.ledger_path.clone()));
-    let state = AppState { ledger };
+    let ledger = Arc::new(Ledger::new(cfg.ledger_path.clone()));
+    let state = AppState {
+        ledger,
+        schema_mode: cfg.schema_mode,
+    };
 
     let app = Router::new()
         .route("/health", get(health))
         .route("/executions", get(list_executions))
         .route("/executions/:execution_id/spans", get(execution_spans))
         .route("/executions/:execution_id/causal", get(execution_causal))
+        .route("/executions/:execution_id/otlp", get(execution_otlp))
         .route("/executions/:execution_id/twin", get(execution_twin))
         .route("/executions/:execution_id/twin-observations", get(execution_twin_observations))
         .route("/executions/:execution_id/twin-divergences", get(execution_twin_divergences))
         .route("/twin-observations", get(all_twin_observations))
         .route("/twin-divergences", get(all_twin_divergences))
+        .route("/v1/traces", post(receive_traces))
         .with_state(state);
 
     let listener = TcpListener::bind(address).await?;
@@ -57,15 +69,11 @@ async fn health() -> impl IntoResponse {
 async fn list_executions(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<ExecutionSummary>>, AppError> {
-    let spans = state.ledger.spans().await.map_err(AppError::from)?;
-    let mut executions = Vec::new();
-
-    for span in spans {
-        if matches!(span.flow.as_str(), "execution_run") {
-            let summary = build_execution_summary(&span);
-            executions.push(summary);
-        }
-    }
+    let spans = state
+        .ledger
+        .scan(|span| matches!(span.flow.as_str(), "execution_run"))
+        .await?;
+    let mut executions: Vec<_> = spans.iter().map(build_execution_summary).collect();
 
     executions.sort_by(|a, b| b.started_at.cmp(&a.started_at));
     Ok(Json(executions))
@@ -75,8 +83,10 @@ async fn execution_spans(
     State(state): State<AppState>,
     Path(execution_id): Path<String>,
 ) -> Result<Json<Vec<UniversalSpan>>, AppError> {
-    let spans = state.ledger.spans().await.map_err(AppError::from)?;
-    let matching = spans_for_execution(&spans, &execution_id);
+    let matching = state
+        .ledger
+        .scan(|span| belongs_to_execution(span, &execution_id))
+        .await?;
     if matching.is_empty() {
         return Err(AppError::not_found(format!(
             "execution span {} not found",
@@ -90,8 +100,10 @@ async fn execution_causal(
     State(state): State<AppState>,
     Path(execution_id): Path<String>,
 ) -> Result<Json<Vec<CausalChain>>, AppError> {
-    let spans = state.ledger.spans().await.map_err(AppError::from)?;
-    let matching = spans_for_execution(&spans, &execution_id);
+    let matching = state
+        .ledger
+        .scan(|span| belongs_to_execution(span, &execution_id))
+        .await?;
     if matching.is_empty() {
         return Err(AppError::not_found(format!(
             "execution span {} not found",
@@ -105,13 +117,94 @@ async fn execution_causal(
     Ok(Json(chains))
 }
 
+async fn execution_otlp(
+    State(state): State<AppState>,
+    Path(execution_id): Path<String>,
+) -> Result<Json<ExportTraceServiceRequest>, AppError> {
+    let matching = state
+        .ledger
+        .scan(|span| belongs_to_execution(span, &execution_id))
+        .await?;
+    if matching.is_empty() {
+        return Err(AppError::not_found(format!(
+            "execution span {} not found",
+            execution_id
+        )));
+    }
+    let request = otlp::to_otlp(&matching).map_err(anyhow::Error::from)?;
+    Ok(Json(request))
+}
+
+/// OTLP/HTTP trace receiver. Spans are appended to the ledger, where the
+/// causal and twin endpoints pick them up; the response uses the request's
+/// encoding. Spans failing a strict payload schema check are rejected as a
+/// partial success.
+async fn receive_traces(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, AppError> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("");
+    let encoding = OtlpEncoding::from_content_type(content_type).ok_or_else(|| {
+        AppError::UnsupportedMediaType(format!(
+            "expected application/x-protobuf or application/json, got `{}`",
+            content_type
+        ))
+    })?;
+    let request: ExportTraceServiceRequest = encoding
+        .decode(&body)
+        .map_err(|err| AppError::BadRequest(err.to_string()))?;
+    let spans = otlp::from_otlp(&request).map_err(|err| AppError::BadRequest(err.to_string()))?;
+
+    let mut accepted = Vec::with_capacity(spans.len());
+    let mut rejected = Vec::new();
+    for span in spans {
+        match check_schema(span, state.schema_mode) {
+            Ok(span) => accepted.push(span),
+            Err(err) => rejected.push(err.to_string()),
+        }
+    }
+
+    let count = accepted.len();
+    rejected.extend(state.ledger.append(accepted).await?);
+    info!(spans = count, rejected = rejected.len(), encoding = ?encoding, "otlp_traces_received");
+
+    let response = ExportTraceServiceResponse {
+        partial_success: (!rejected.is_empty()).then(|| ExportTracePartialSuccess {
+            rejected_spans: rejected.len() as i64,
+            error_message: rejected.join("\n"),
+        }),
+    };
+    let body = encoding.encode(&response);
+    Ok(([(header::CONTENT_TYPE, encoding.content_type())], body).into_response())
+}
+
 async fn execution_twin(
     State(state): State<AppState>,
     Path(execution_id): Path<String>,
 ) -> Result<Json<TwinSummary>, AppError> {
-    let spans = state.ledger.spans().await.map_err(AppError::from)?;
-    let matching = spans_for_execution(&spans, &execution_id);
-    if matching.is_empty() {
+    let spans = state
+        .ledger
+        .scan(|span| {
+            belongs_to_execution(span, &execution_id)
+                || match mapping::classify_span(span) {
+                    SpanKind::TwinObservation(meta) => {
+                        span_links_execution(span, &execution_id, meta.execution_span.as_ref())
+                    }
+                    SpanKind::TwinDivergence(meta) => {
+                        span_links_execution(span, &execution_id, meta.execution_span.as_ref())
+                    }
+                    _ => false,
+                }
+        })
+        .await?;
+    if !spans
+        .iter()
+        .any(|span| belongs_to_execution(span, &execution_id))
+    {
         return Err(AppError::not_found(format!(
             "execution span {} not found",
             execution_id
@@ -131,28 +224,29 @@ async fn execution_twin(
 async fn all_twin_observations(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<TwinObservationSummary>>, AppError> {
-    let spans = state.ledger.spans().await.map_err(AppError::from)?;
+    let spans = state
+        .ledger
+        .scan(|span| matches!(span.flow.as_str(), "twin_observation"))
+        .await?;
     let mut observations = Vec::new();
 
     for span in &spans {
-        if matches!(span.flow.as_str(), "twin_observation") {
-            if let SpanKind::TwinObservation(meta) = mapping::classify_span(span) {
-                let metric_count = meta
-                    .metrics
-                    .as_object()
-                    .map(|m| m.len())
-                    .or_else(|| meta.metrics.as_array().map(|arr| arr.len()))
-                    .unwrap_or(0);
-
-                observations.push(TwinObservationSummary {
-                    span_id: span.id.0.clone(),
-                    cycle_id: meta.cycle_id.clone(),
-                    side: meta.side.clone(),
-                    recorded_at: meta.recorded_at,
-                    metric_count,
-                    metrics: meta.metrics.clone(),
-                });
-            }
+        if let SpanKind::TwinObservation(meta) = mapping::classify_span(span) {
+            let metric_count = meta
+                .metrics
+                .as_object()
+                .map(|m| m.len())
+                .or_else(|| meta.metrics.as_array().map(|arr| arr.len()))
+                .unwrap_or(0);
+
+            observations.push(TwinObservationSummary {
+                span_id: span.id.0.clone(),
+                cycle_id: meta.cycle_id.clone(),
+                side: meta.side.clone(),
+                recorded_at: meta.recorded_at,
+                metric_count,
+                metrics: meta.metrics.clone(),
+            });
         }
     }
 
@@ -163,25 +257,26 @@ async fn all_twin_observations(
 async fn all_twin_divergences(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<TwinDivergenceSummary>>, AppError> {
-    let spans = state.ledger.spans().await.map_err(AppError::from)?;
+    let spans = state
+        .ledger
+        .scan(|span| matches!(span.flow.as_str(), "twin_divergence"))
+        .await?;
     let mut divergences = Vec::new();
 
     for span in &spans {
-        if matches!(span.flow.as_str(), "twin_divergence") {
-            if let SpanKind::TwinDivergence(meta) = mapping::classify_span(span) {
-                divergences.push(TwinDivergenceSummary {
-                    span_id: span.id.0.clone(),
-                    cycle_id: meta.cycle_id.clone(),
-                    metric: meta.metric.clone(),
-                    severity: meta.severity.clone(),
-                    absolute_delta: meta.absolute_delta,
-                    percent_delta: meta.percent_delta,
-                    detected_at: meta.detected_at,
-                    physical_span: meta.physical_span.clone(),
-                    digital_span: meta.digital_span.clone(),
-                    payload: meta.payload.clone(),
-                });
-            }
+        if let SpanKind::TwinDivergence(meta) = mapping::classify_span(span) {
+            divergences.push(TwinDivergenceSummary {
+                span_id: span.id.0.clone(),
+                cycle_id: meta.cycle_id.clone(),
+                metric: meta.metric.clone(),
+                severity: meta.severity.clone(),
+                absolute_delta: meta.absolute_delta,
+                percent_delta: meta.percent_delta,
+                detected_at: meta.detected_at,
+                physical_span: meta.physical_span.clone(),
+                digital_span: meta.digital_span.clone(),
+                payload: meta.payload.clone(),
+            });
         }
     }
 
@@ -193,8 +288,10 @@ async fn execution_twin_observations(
     State(state): State<AppState>,
     Path(execution_id): Path<String>,
 ) -> Result<Json<Vec<TwinObservationSummary>>, AppError> {
-    let spans = state.ledger.spans().await.map_err(AppError::from)?;
-    let matching = spans_for_execution(&spans, &execution_id);
+    let matching = state
+        .ledger
+        .scan(|span| belongs_to_execution(span, &execution_id))
+        .await?;
     if matching.is_empty() {
         return Err(AppError::not_found(format!(
             "execution span {} not found",
@@ -237,8 +334,10 @@ async fn execution_twin_divergences(
     State(state): State<AppState>,
     Path(execution_id): Path<String>,
 ) -> Result<Json<Vec<TwinDivergenceSummary>>, AppError> {
-    let spans = state.ledger.spans().await.map_err(AppError::from)?;
-    let matching = spans_for_execution(&spans, &execution_id);
+    let matching = state
+        .ledger
+        .scan(|span| belongs_to_execution(span, &execution_id))
+        .await?;
     if matching.is_empty() {
         return Err(AppError::not_found(format!(
             "execution span {} not found",
@@ -305,78 +404,42 @@ fn build_execution_summary(span: &UniversalSpan) -> ExecutionSummary {
     }
 }
 
-fn spans_for_execution(spans: &[UniversalSpan], execution_id: &str) -> Vec<UniversalSpan> {
-    spans
-        .iter()
-        .filter_map(|span| {
-            if span.id.0 == execution_id {
-                return Some(span.clone());
-            }
+fn belongs_to_execution(span: &UniversalSpan, execution_id: &str) -> bool {
+    if span.id.0 == execution_id {
+        return true;
+    }
 
-            if span
-                .causal
-                .parent_id
-                .as_ref()
-                .map(|parent| parent.0.as_str() == execution_id)
-                .unwrap_or(false)
-            {
-                return Some(span.clone());
-            }
+    if span
+        .causal
+        .parent_id
+        .as_ref()
+        .map(|parent| parent.0.as_str() == execution_id)
+        .unwrap_or(false)
+    {
+        return true;
+    }
 
-            if span
-                .causal
-                .related_ids
-                .iter()
-                .any(|related| related.0 == execution_id)
-            {
-                return Some(span.clone());
-            }
+    if span
+        .causal
+        .related_ids
+        .iter()
+        .any(|related| related.0 == execution_id)
+    {
+        return true;
+    }
 
-            match mapping::classify_span(span) {
-                SpanKind::Analysis(meta) => meta
-                    .execution_span
-                    .as_deref()
-                    .filter(|span_id| *span_id == execution_id)
-                    .map(|_| span.clone()),
-                SpanKind::Metric(meta) => meta
-                    .execution_span
-                    .as_deref()
-                    .filter(|span_id| *span_id == execution_id)
-                    .map(|_| span.clone()),
-                SpanKind::Frame(meta) => meta
-                    .execution_span
-                    .as_deref()
-                    .filter(|span_id| *span_id == execution_id)
-                    .map(|_| span.clone()),
-                SpanKind::Acquisition(meta) => meta
-                    .execution_span
-                    .as_deref()
-                    .filter(|span_id| *span_id == execution_id)
-                    .map(|_| span.clone()),
-                SpanKind::Review(meta) => meta
-                    .execution_span
-                    .as_deref()
-                    .filter(|span_id| *span_id == execution_id)
-                    .map(|_| span.clone()),
-                SpanKind::Manuscript(meta) => meta
-                    .execution_span
-                    .as_deref()
-                    .filter(|span_id| *span_id == execution_id)
-                    .map(|_| span.clone()),
-                SpanKind::Artifact(meta) => meta
-                    .execution_span
-                    .as_deref()
-                    .filter(|span_id| *span_id == execution_id)
-                    .map(|_| span.clone()),
-                SpanKind::Execution(meta) => meta
-                    .protocol_span
-                    .as_deref()
-                    .filter(|span_id| *span_id == execution_id)
-                    .map(|_| span.clone()),
-                _ => None,
-            }
-        })
-        .collect()
+    let linked = match mapping::classify_span(span) {
+        SpanKind::Analysis(meta) => meta.execution_span,
+        SpanKind::Metric(meta) => meta.execution_span,
+        SpanKind::Frame(meta) => meta.execution_span,
+        SpanKind::Acquisition(meta) => meta.execution_span,
+        SpanKind::Review(meta) => meta.execution_span,
+        SpanKind::Manuscript(meta) => meta.execution_span,
+        SpanKind::Artifact(meta) => meta.execution_span,
+        SpanKind::Execution(meta) => meta.protocol_span,
+        _ => None,
+    };
+    linked.as_deref() == Some(execution_id)
 }
 
 fn build_twin_summary(spans: &[UniversalSpan], execution_id: &str) -> Option<TwinSummary> {
@@ -606,65 +669,73 @@ impl CyclePresence {
     }
 }
 
-struct LedgerState {
-    modified: Option<SystemTime>,
-    spans: Vec<UniversalSpan>,
-}
-
-struct LedgerCache {
+/// The ledger as seen by the handlers. Nothing is held between requests:
+/// each query streams the file and keeps only the spans it asks for, so
+/// memory follows the size of the answer rather than of the ledger.
+struct Ledger {
     path: PathBuf,
-    state: RwLock<LedgerState>,
 }
 
-impl LedgerCache {
+impl Ledger {
     fn new(path: PathBuf) -> Self {
-        Self {
-            path,
-            state: RwLock::new(LedgerState {
-                modified: None,
-                spans: Vec::new(),
-            }),
-        }
+        Self { path }
     }
 
-    async fn spans(&self) -> Result<Vec<UniversalSpan>> {
+    /// Streams the ledger and collects the spans matching `keep`. A ledger
+    /// that does not exist yet is empty.
+    async fn scan(
+        &self,
+        mut keep: impl FnMut(&UniversalSpan) -> bool,
+    ) -> Result<Vec<UniversalSpan>> {
         if !tokio::fs::try_exists(&self.path).await? {
-            let state = self.state.read().await;
-            return Ok(state.spans.clone());
+            return Ok(Vec::new());
         }
 
-        let metadata = tokio::fs::metadata(&self.path).await?;
-        let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
-
-        {
-            let state = self.state.read().await;
-            if state
-                .modified
-                .map(|cached| cached == modified)
-                .unwrap_or(false)
-            {
-                return Ok(state.spans.clone());
+        let mut stream = SpanStream::open(&self.path, 0, STREAM_CAPACITY).await?;
+        let mut spans = Vec::new();
+        while let Some(span) = stream.next().await {
+            let span = span?;
+            if keep(&span) {
+                spans.push(span);
             }
         }
+        Ok(spans)
+    }
 
-        let path = self.path.clone();
-        let spans = tokio::task::spawn_blocking(move || sync_ledger(path))
-            .await
-            .map_err(|err| anyhow::anyhow!(err.to_string()))??;
-
-        {
-            let mut state = self.state.write().await;
-            state.modified = Some(modified);
-            state.spans = spans.clone();
+    /// Appends the spans not yet in the ledger. Byte-identical repeats are
+    /// dropped; a span reusing an id with different content is not written
+    /// and comes back as a rejection message.
+    async fn append(&self, spans: Vec<UniversalSpan>) -> Result<Vec<String>> {
+        let mut conflicts = Vec::new();
+        for span in spans {
+            match admit(&self.path, span).await? {
+                Admission::New(span) => append_span(&self.path, &span)?,
+                Admission::Duplicate(_) => {}
+                Admission::Conflict {
+                    span,
+                    existing_hash,
+                } => {
+                    warn!(
+                        span = %span.id.0,
+                        existing = %existing_hash,
+                        "span_conflict_detected"
+                    );
+                    conflicts.push(format!(
+                        "span {} conflicts with the recorded content {}",
+                        span.id.0, existing_hash
+                    ));
+                }
+            }
         }
-
-        Ok(spans)
+        Ok(conflicts)
     }
 }
 
 #[derive(Debug)]
 enum AppError {
     NotFound(String),
+    BadRequest(String),
+    UnsupportedMediaType(String),
     Internal(anyhow::Error),
 }
 
@@ -688,6 +759,16 @@ impl IntoResponse for AppError {
                 Json(serde_json::json!({ "error": message })),
             )
                 .into_response(),
+            AppError::BadRequest(message) => (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": message })),
+            )
+                .into_response(),
+            AppError::UnsupportedMediaType(message) => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                Json(serde_json::json!({ "error": message })),
+            )
+                .into_response(),
             AppError::Internal(err) => {
                 warn!(error = %err, "runner_service_error");
                 (
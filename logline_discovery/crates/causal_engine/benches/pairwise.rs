@@ -0,0 +1,67 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use folding_molecule::{AminoAcid, CellList, EnergyModel, PeptideChain, StartingConformation};
+
+const SIZES: [usize; 3] = [100, 500, 2_000];
+
+fn helical_chain(len: usize) -> PeptideChain {
+    let cycle = [
+        AminoAcid::Alanine,
+        AminoAcid::Lysine,
+        AminoAcid::Serine,
+        AminoAcid::Glutamate,
+        AminoAcid::Leucine,
+    ];
+    let sequence: Vec<AminoAcid> = (0..len).map(|i| cycle[i % cycle.len()]).collect();
+    PeptideChain::from_sequence(&sequence, StartingConformation::Helix)
+}
+
+fn bench_energy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("energy_summary");
+    for len in SIZES {
+        let chain = helical_chain(len);
+        let cutoff = EnergyModel::alpha_carbon_trace();
+        let all_pairs = EnergyModel::alpha_carbon_trace().with_nonbonded_cutoff(None);
+        group.bench_with_input(BenchmarkId::new("cell_list", len), &chain, |b, chain| {
+            b.iter(|| black_box(cutoff.energy_summary(chain)))
+        });
+        group.bench_with_input(BenchmarkId::new("all_pairs", len), &chain, |b, chain| {
+            b.iter(|| black_box(all_pairs.energy_summary(chain)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_gradient(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gradient");
+    for len in SIZES {
+        let chain = helical_chain(len);
+        let model = EnergyModel::alpha_carbon_trace();
+        group.bench_with_input(BenchmarkId::from_parameter(len), &chain, |b, chain| {
+            b.iter(|| black_box(model.gradient(chain)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_cell_list(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cell_list");
+    for len in SIZES {
+        let positions = helical_chain(len).positions();
+        group.bench_with_input(BenchmarkId::new("build_pairs", len), &positions, |b, p| {
+            b.iter(|| black_box(CellList::new(p, 12.0).pairs(3)))
+        });
+        group.bench_with_input(BenchmarkId::new("refresh_one", len), &positions, |b, p| {
+            let mut cells = CellList::new(p, 12.0);
+            let mut moved = p.clone();
+            let middle = len / 2;
+            b.iter(|| {
+                moved[middle][0] += 0.5;
+                black_box(cells.refresh(&moved))
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_energy, bench_gradient, bench_cell_list);
+criterion_main!(benches);
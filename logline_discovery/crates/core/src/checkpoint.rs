@@ -0,0 +1,87 @@
+//! On-disk checkpoints of a running contract.
+//!
+//! An [`EngineCheckpoint`] holds everything the engine mutates while it
+//! executes a contract: the chain, trajectory and commit stack, the random
+//! stream, the step and schedule position, Metropolis counts, physics-span
+//! records and the outcomes collected so far. Configuration (ruleset,
+//! oscillator, base energy model) is not stored; an engine built with the
+//! same [`crate::FoldingEngineBuilder`] settings resumes bit-for-bit through
+//! [`crate::FoldingEngine::resume_contract`].
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::folding_parser::FoldingContract;
+use crate::folding_runtime::{EngineState, RunLog};
+
+/// Layout version written into every checkpoint.
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum CheckpointError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("malformed checkpoint: {0}")]
+    Format(#[from] serde_json::Error),
+    #[error("checkpoint version {found} is not supported (expected {CHECKPOINT_VERSION})")]
+    UnsupportedVersion { found: u32 },
+    #[error("checkpoint chain differs from the engine's chain at residue index {index}")]
+    ChainMismatch { index: usize },
+}
+
+/// Full engine state between two instructions of `contract`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EngineCheckpoint {
+    pub version: u32,
+    pub contract: FoldingContract,
+    /// Index of the first instruction still to run.
+    pub next_instruction: usize,
+    pub(crate) run: RunLog,
+    pub(crate) state: EngineState,
+}
+
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: u32,
+}
+
+impl EngineCheckpoint {
+    pub(crate) fn new(
+        contract: FoldingContract,
+        next_instruction: usize,
+        run: RunLog,
+        state: EngineState,
+    ) -> Self {
+        Self {
+            version: CHECKPOINT_VERSION,
+            contract,
+            next_instruction,
+            run,
+            state,
+        }
+    }
+
+    /// Writes the checkpoint as JSON. The file is written beside `path` and
+    /// renamed over it, so a crash mid-write keeps the previous checkpoint.
+    pub fn save(&self, path: &Path) -> Result<(), CheckpointError> {
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        fs::write(&partial, serde_json::to_vec(self)?)?;
+        fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, CheckpointError> {
+        let bytes = fs::read(path)?;
+        let probe: VersionProbe = serde_json::from_slice(&bytes)?;
+        if probe.version != CHECKPOINT_VERSION {
+            return Err(CheckpointError::UnsupportedVersion {
+                found: probe.version,
+            });
+        }
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+}
@@ -0,0 +1,184 @@
+//! Errors and warnings produced while parsing `.lll` folding contracts,
+//! with rustc-style rendering for the `contract lint` command.
+
+use std::fmt::Write as _;
+use std::ops::Range;
+
+use crate::folding_parser::FoldingContract;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Included file the diagnostic points into; `None` for the contract
+    /// being parsed.
+    pub file: Option<String>,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column (in characters) where `span` starts.
+    pub column: usize,
+    /// Byte range of the offending text within the line.
+    pub span: Range<usize>,
+    pub help: Option<String>,
+    /// The offending source line.
+    pub source_line: String,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(
+        severity: Severity,
+        message: impl Into<String>,
+        line_number: usize,
+        line: &str,
+        span: Range<usize>,
+    ) -> Self {
+        let start = span.start.min(line.len());
+        Self {
+            severity,
+            message: message.into(),
+            file: None,
+            line: line_number,
+            column: line[..start].chars().count() + 1,
+            span,
+            help: None,
+            source_line: line.to_string(),
+        }
+    }
+
+    pub(crate) fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub(crate) fn with_file(mut self, file: Option<String>) -> Self {
+        self.file = file;
+        self
+    }
+
+    /// Renders the diagnostic the way rustc prints errors. `origin` names
+    /// the contract unless the diagnostic points into an included file.
+    pub fn render(&self, origin: &str) -> String {
+        let text = self.source_line.as_str();
+        let origin = self.file.as_deref().unwrap_or(origin);
+        let gutter = self.line.to_string().len();
+        let pad = " ".repeat(gutter);
+        let start = self.span.start.min(text.len());
+        let end = self.span.end.clamp(start, text.len());
+        let offset = text[..start].chars().count();
+        let width = text[start..end].chars().count().max(1);
+
+        let mut out = String::new();
+        let _ = writeln!(out, "{}: {}", self.severity.label(), self.message);
+        let _ = writeln!(out, "{pad}--> {origin}:{}:{}", self.line, self.column);
+        let _ = writeln!(out, "{pad} |");
+        let _ = writeln!(out, "{} | {text}", self.line);
+        let _ = write!(out, "{pad} | {}{}", " ".repeat(offset), "^".repeat(width));
+        if let Some(help) = &self.help {
+            let _ = write!(out, " help: {help}");
+        }
+        out.push('\n');
+        out
+    }
+}
+
+/// Contract built from the lines that parsed, plus everything found wrong.
+#[derive(Clone, Debug, Default)]
+pub struct ParsedContract {
+    pub contract: FoldingContract,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ParsedContract {
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Every diagnostic in source order, separated by blank lines.
+    pub fn render(&self, origin: &str) -> String {
+        self.diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.render(origin))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Candidate within edit distance two of `word`, preferring the closest.
+pub(crate) fn closest<'a>(word: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(word, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 2 && *distance < word.len())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    let mut previous: Vec<usize> = (0..=right.len()).collect();
+    for (i, l) in left.chars().enumerate() {
+        let mut current = vec![i + 1; right.len() + 1];
+        for (j, r) in right.iter().enumerate() {
+            let substitution = previous[j] + usize::from(l != *r);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[right.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggests_nearby_directive() {
+        let directives = ["rotate", "commit", "rollback"];
+        assert_eq!(closest("rotat", &directives), Some("rotate"));
+        assert_eq!(closest("comit", &directives), Some("commit"));
+        assert_eq!(closest("fold", &directives), None);
+    }
+
+    #[test]
+    fn renders_rustc_style() {
+        let diagnostic = Diagnostic::new(
+            Severity::Error,
+            "unknown directive `rotat`",
+            2,
+            "rotat residue=3",
+            0..5,
+        )
+        .with_help("did you mean `rotate`?");
+        assert_eq!(
+            diagnostic.render("fold.lll"),
+            "error: unknown directive `rotat`\n --> fold.lll:2:1\n  |\n2 | rotat residue=3\n  | ^^^^^ help: did you mean `rotate`?\n"
+        );
+    }
+}
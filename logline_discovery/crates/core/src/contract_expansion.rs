@@ -0,0 +1,831 @@
+//! Expands the control-flow layer of `.lll` contracts into plain directive
+//! lines before they reach the instruction parser:
+//!
+//! ```text
+//! let base = 10
+//! repeat 3 {
+//!     rotate residue=base angle=15
+//! }
+//! for r in base..base+4 step 2 {
+//!     rotate residue=r angle=(r-base)*5
+//! }
+//! define_domain tail base+2..base+5
+//! include "shared/relax.lll"
+//! ```
+//!
+//! Argument values that mention a bound variable or an arithmetic operator
+//! are evaluated; anything else passes through untouched, so units such as
+//! `28deg` and literal ranges such as `2-4` keep working. A bare word that
+//! is only a name (a domain, alias or chaperone) is never replaced, even if
+//! a variable of that name is in scope; `residue=r` passes a variable. `a..b` with
+//! expressions on either side becomes the range `A-B`. Every expanded line
+//! remembers which source line (and file) it came from so diagnostics still
+//! point at what the author wrote.
+
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::contract_diagnostics::{Diagnostic, Severity};
+
+/// Keywords handled here rather than by the instruction parser.
+pub(crate) const CONTROL_KEYWORDS: &[&str] = &["let", "repeat", "for", "include"];
+
+/// Upper bound on expanded lines, so a typo like `repeat 1e9` fails fast.
+const MAX_EXPANDED_LINES: usize = 100_000;
+
+/// One directive line after expansion.
+#[derive(Clone, Debug)]
+pub(crate) struct ExpandedLine {
+    /// Directive text with comments stripped and arguments substituted.
+    pub text: String,
+    /// 1-based line in the file it came from.
+    pub line: usize,
+    /// That line as written.
+    pub source: String,
+    /// Included file the line came from; `None` for the root contract.
+    pub file: Option<String>,
+    /// `(expanded, source)` byte ranges of each word.
+    pieces: Vec<(Range<usize>, Range<usize>)>,
+}
+
+impl ExpandedLine {
+    /// Maps a byte range in `text` back onto `source`. Words that were
+    /// substituted map to the whole word as written.
+    pub fn source_span(&self, span: Range<usize>) -> Range<usize> {
+        let mut mapped: Option<Range<usize>> = None;
+        for (expanded, source) in &self.pieces {
+            if expanded.end <= span.start || expanded.start >= span.end.max(span.start + 1) {
+                continue;
+            }
+            let piece = if expanded.len() == source.len() {
+                let start = span.start.max(expanded.start) - expanded.start + source.start;
+                let end = span.end.min(expanded.end) - expanded.start + source.start;
+                start..end.max(start)
+            } else {
+                source.clone()
+            };
+            mapped = Some(match mapped {
+                Some(range) => range.start.min(piece.start)..range.end.max(piece.end),
+                None => piece,
+            });
+        }
+        mapped.unwrap_or(span)
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Expansion {
+    pub lines: Vec<ExpandedLine>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Expands `source`. `path` is where it was read from: includes resolve
+/// relative to its directory (the working directory otherwise) and a file
+/// may not include itself.
+pub(crate) fn expand(source: &str, path: Option<&Path>) -> Expansion {
+    let file = SourceFile {
+        label: None,
+        dir: path
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .unwrap_or_default(),
+        lines: source.lines().map(str::to_string).collect(),
+    };
+    let mut expander = Expander {
+        expansion: Expansion::default(),
+        scopes: vec![HashMap::new()],
+        include_stack: path
+            .and_then(|path| path.canonicalize().ok())
+            .into_iter()
+            .collect(),
+        truncated: false,
+    };
+    expander.expand_file(&file);
+    expander.expansion
+}
+
+struct SourceFile {
+    label: Option<String>,
+    dir: PathBuf,
+    lines: Vec<String>,
+}
+
+impl SourceFile {
+    /// Comment-free part of line `index` and its byte range in the line.
+    fn content(&self, index: usize) -> (&str, Range<usize>) {
+        let raw = self.lines[index].as_str();
+        let without_comment = strip_comment(raw);
+        let start = without_comment.len() - without_comment.trim_start().len();
+        let trimmed = without_comment.trim();
+        (trimmed, start..start + trimmed.len())
+    }
+}
+
+enum Node {
+    Line(usize),
+    /// `repeat`/`for` header line and the nodes inside its braces.
+    Block {
+        header: usize,
+        body: Vec<Node>,
+    },
+}
+
+/// Iterations of a `repeat`/`for` block; iteration `i` binds `variable`
+/// to `start + i·step`.
+struct Loop {
+    variable: Option<String>,
+    start: f64,
+    step: f64,
+    count: usize,
+}
+
+struct Expander {
+    expansion: Expansion,
+    scopes: Vec<HashMap<String, f64>>,
+    include_stack: Vec<PathBuf>,
+    truncated: bool,
+}
+
+impl Expander {
+    fn expand_file(&mut self, file: &SourceFile) {
+        let nodes = self.build_tree(file);
+        self.expand_nodes(file, &nodes);
+    }
+
+    /// Groups lines into blocks, reporting unbalanced braces.
+    fn build_tree(&mut self, file: &SourceFile) -> Vec<Node> {
+        let mut stack: Vec<(usize, Vec<Node>)> = Vec::new();
+        let mut root = Vec::new();
+        for index in 0..file.lines.len() {
+            let (content, span) = file.content(index);
+            let keyword = content.split_whitespace().next().unwrap_or("");
+            if content == "}" {
+                match stack.pop() {
+                    Some((header, body)) => {
+                        let block = Node::Block { header, body };
+                        match stack.last_mut() {
+                            Some((_, parent)) => parent.push(block),
+                            None => root.push(block),
+                        }
+                    }
+                    None => self.error(file, index, span, "unmatched `}`", None),
+                }
+            } else if matches!(keyword, "repeat" | "for") {
+                if content.ends_with('{') {
+                    stack.push((index, Vec::new()));
+                } else {
+                    let help =
+                        format!("open the block with `{{` at the end of the `{keyword}` line");
+                    self.error(
+                        file,
+                        index,
+                        span,
+                        format!("expected `{{` after `{keyword}`"),
+                        Some(help),
+                    );
+                }
+            } else {
+                match stack.last_mut() {
+                    Some((_, body)) => body.push(Node::Line(index)),
+                    None => root.push(Node::Line(index)),
+                }
+            }
+        }
+        for (header, _) in stack {
+            let (_, span) = file.content(header);
+            let help = "close it with a line holding only `}`".to_string();
+            self.error(file, header, span, "unclosed block", Some(help));
+        }
+        root
+    }
+
+    fn expand_nodes(&mut self, file: &SourceFile, nodes: &[Node]) {
+        for node in nodes {
+            if self.truncated {
+                return;
+            }
+            match node {
+                Node::Line(index) => self.expand_line(file, *index),
+                Node::Block { header, body } => self.expand_block(file, *header, body),
+            }
+        }
+    }
+
+    fn expand_block(&mut self, file: &SourceFile, header: usize, body: &[Node]) {
+        let (content, span) = file.content(header);
+        let content = content.trim_end_matches('{').trim_end();
+        let iterations = match content.split_once(char::is_whitespace) {
+            Some(("repeat", count)) => self.repeat_count(count),
+            Some(("for", rest)) => self.for_values(rest),
+            _ => Err("missing loop bounds".to_string()),
+        };
+        let iterations = match iterations {
+            Ok(iterations) => iterations,
+            Err(message) => {
+                let help = if content.starts_with("for") {
+                    "expected `for <name> in <start>..<end> [step <n>] {`"
+                } else {
+                    "expected `repeat <count> {`"
+                };
+                self.error(file, header, span, message, Some(help.to_string()));
+                return;
+            }
+        };
+        for iteration in 0..iterations.count {
+            let mut scope = HashMap::new();
+            if let Some(name) = &iterations.variable {
+                scope.insert(
+                    name.clone(),
+                    iterations.start + iteration as f64 * iterations.step,
+                );
+            }
+            self.scopes.push(scope);
+            self.expand_nodes(file, body);
+            self.scopes.pop();
+            if self.truncated {
+                let help = format!("contracts expand to at most {MAX_EXPANDED_LINES} lines");
+                self.error(
+                    file,
+                    header,
+                    span,
+                    "loop expands to too many lines",
+                    Some(help),
+                );
+                return;
+            }
+        }
+    }
+
+    /// Iterations of `repeat <count>`.
+    fn repeat_count(&self, count: &str) -> Result<Loop, String> {
+        let count = self.evaluate(count)?;
+        if count < 0.0 || count.fract() != 0.0 {
+            return Err(format!("repeat count must be a whole number, got {count}"));
+        }
+        if count > MAX_EXPANDED_LINES as f64 {
+            return Err(format!("loop runs {count} times"));
+        }
+        Ok(Loop {
+            variable: None,
+            start: 0.0,
+            step: 0.0,
+            count: count as usize,
+        })
+    }
+
+    /// Loop variable and its values for `<name> in <start>..[=]<end> [step <n>]`.
+    fn for_values(&self, rest: &str) -> Result<Loop, String> {
+        let mut parts = rest.splitn(3, char::is_whitespace);
+        let name = parts.next().unwrap_or("");
+        if !is_identifier(name) {
+            return Err(format!("`{name}` is not a valid loop variable"));
+        }
+        if parts.next() != Some("in") {
+            return Err("expected `in` after the loop variable".to_string());
+        }
+        let bounds = parts.next().unwrap_or("");
+        let (range, step) = match bounds.split_once(" step ") {
+            Some((range, step)) => (range, self.evaluate(step)?),
+            None => (bounds, 1.0),
+        };
+        if step <= 0.0 {
+            return Err(format!("loop step must be positive, got {step}"));
+        }
+        let (start, end, inclusive) = if let Some((start, end)) = range.split_once("..=") {
+            (start, end, true)
+        } else if let Some((start, end)) = range.split_once("..") {
+            (start, end, false)
+        } else {
+            return Err(format!(
+                "expected a `start..end` range, got `{}`",
+                range.trim()
+            ));
+        };
+        let (start, end) = (self.evaluate(start)?, self.evaluate(end)?);
+        let span = (end - start) / step;
+        let count = if inclusive {
+            (span + 1e-9).floor() + 1.0
+        } else {
+            (span - 1e-9).ceil()
+        };
+        if count > MAX_EXPANDED_LINES as f64 {
+            return Err(format!("loop runs {count} times"));
+        }
+        Ok(Loop {
+            variable: Some(name.to_string()),
+            start,
+            step,
+            count: count.max(0.0) as usize,
+        })
+    }
+
+    fn expand_line(&mut self, file: &SourceFile, index: usize) {
+        let (content, span) = file.content(index);
+        if content.is_empty() {
+            return;
+        }
+        match content.split_once(char::is_whitespace) {
+            Some(("let", binding)) => self.bind(file, index, span, binding),
+            Some(("include", target)) => self.include(file, index, span, target),
+            _ => self.substitute_line(file, index, span),
+        }
+    }
+
+    fn bind(&mut self, file: &SourceFile, index: usize, span: Range<usize>, binding: &str) {
+        let result = match binding.split_once('=') {
+            Some((name, value)) if is_identifier(name.trim()) => self
+                .evaluate(value)
+                .map(|value| (name.trim().to_string(), value)),
+            _ => Err("malformed `let` binding".to_string()),
+        };
+        match result {
+            Ok((name, value)) => {
+                self.scopes
+                    .last_mut()
+                    .expect("expander always has a scope")
+                    .insert(name, value);
+            }
+            Err(message) => {
+                let help = "expected `let <name> = <expression>`".to_string();
+                self.error(file, index, span, message, Some(help));
+            }
+        }
+    }
+
+    fn include(&mut self, file: &SourceFile, index: usize, span: Range<usize>, target: &str) {
+        let target = target.trim().trim_matches(|c| c == '"' || c == '\'');
+        let path = file.dir.join(target);
+        let canonical = match path.canonicalize() {
+            Ok(canonical) => canonical,
+            Err(err) => {
+                let message = format!("cannot include `{target}`: {err}");
+                self.error(file, index, span, message, None);
+                return;
+            }
+        };
+        if self.include_stack.contains(&canonical) {
+            let message = format!("`{target}` is already being included");
+            let help = "includes may not form a cycle".to_string();
+            self.error(file, index, span, message, Some(help));
+            return;
+        }
+        let text = match fs::read_to_string(&canonical) {
+            Ok(text) => text,
+            Err(err) => {
+                let message = format!("cannot include `{target}`: {err}");
+                self.error(file, index, span, message, None);
+                return;
+            }
+        };
+        let included = SourceFile {
+            label: Some(path.display().to_string()),
+            dir: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            lines: text.lines().map(str::to_string).collect(),
+        };
+        self.include_stack.push(canonical);
+        self.expand_file(&included);
+        self.include_stack.pop();
+    }
+
+    /// Evaluates the arguments of a directive line and records where each
+    /// word came from.
+    fn substitute_line(&mut self, file: &SourceFile, index: usize, span: Range<usize>) {
+        if self.expansion.lines.len() >= MAX_EXPANDED_LINES {
+            self.truncated = true;
+            return;
+        }
+        let content = &file.lines[index][span.clone()];
+        let mut text = String::new();
+        let mut pieces = Vec::new();
+        for (position, (word, range)) in words(content).into_iter().enumerate() {
+            let word = if position == 0 {
+                word
+            } else {
+                match self.substitute_word(&word) {
+                    Ok(word) => word,
+                    Err(message) => {
+                        let word_span = range.start + span.start..range.end + span.start;
+                        self.error(file, index, word_span, message, None);
+                        return;
+                    }
+                }
+            };
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            let expanded = text.len()..text.len() + word.len();
+            text.push_str(&word);
+            pieces.push((expanded, range.start + span.start..range.end + span.start));
+        }
+        self.expansion.lines.push(ExpandedLine {
+            text,
+            line: index + 1,
+            source: file.lines[index].clone(),
+            file: file.label.clone(),
+            pieces,
+        });
+    }
+
+    /// Substitutes the value of a bare or `key=value` word.
+    fn substitute_word(&self, word: &str) -> Result<String, String> {
+        let body = word.trim_end_matches([',', ';']);
+        let punctuation = &word[body.len()..];
+        let (prefix, value) = match body.find(['=', ':']) {
+            Some(at) if is_identifier(&body[..at]) => body.split_at(at + 1),
+            _ => ("", body),
+        };
+        if prefix.is_empty() && is_identifier(value) {
+            return Ok(word.to_string());
+        }
+        let value = match value.split_once("..") {
+            Some((start, end)) if self.is_expression(start) || self.is_expression(end) => {
+                let start = self.substitute_value(start)?;
+                let end = self.substitute_value(end)?;
+                format!("{start}-{end}")
+            }
+            _ => self.substitute_value(value)?,
+        };
+        Ok(format!("{prefix}{value}{punctuation}"))
+    }
+
+    fn substitute_value(&self, value: &str) -> Result<String, String> {
+        if self.is_expression(value) {
+            self.evaluate(value).map(format_number)
+        } else {
+            Ok(value.to_string())
+        }
+    }
+
+    /// Whether `value` is meant as arithmetic: it lexes as an expression and
+    /// mentions a variable in scope or an operator other than a sign.
+    fn is_expression(&self, value: &str) -> bool {
+        lex(value).is_some_and(|tokens| {
+            tokens.iter().any(|token| match token {
+                Token::Identifier(name) => self.lookup(name).is_some(),
+                Token::Operator(op) => *op != '-',
+                Token::Open | Token::Close => true,
+                Token::Number(_) => false,
+            })
+        })
+    }
+
+    fn evaluate(&self, text: &str) -> Result<f64, String> {
+        let tokens = lex(text.trim())
+            .ok_or_else(|| format!("`{}` is not an arithmetic expression", text.trim()))?;
+        let mut parser = ExpressionParser {
+            tokens: &tokens,
+            position: 0,
+            expander: self,
+        };
+        let value = parser.expression()?;
+        match tokens.get(parser.position) {
+            None => Ok(value),
+            Some(_) => Err(format!("unexpected input in `{}`", text.trim())),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<f64> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    }
+
+    fn error(
+        &mut self,
+        file: &SourceFile,
+        index: usize,
+        span: Range<usize>,
+        message: impl Into<String>,
+        help: Option<String>,
+    ) {
+        let mut diagnostic = Diagnostic::new(
+            Severity::Error,
+            message,
+            index + 1,
+            &file.lines[index],
+            span,
+        )
+        .with_file(file.label.clone());
+        if let Some(help) = help {
+            diagnostic = diagnostic.with_help(help);
+        }
+        self.expansion.diagnostics.push(diagnostic);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Operator(char),
+    Open,
+    Close,
+}
+
+/// Splits `text` into expression tokens; `None` if it holds anything else.
+fn lex(text: &str) -> Option<Vec<Token>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        if ch.is_whitespace() {
+            i += 1;
+        } else if ch.is_ascii_digit() || ch == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let literal: String = chars[start..i].iter().collect();
+            tokens.push(Token::Number(literal.parse().ok()?));
+        } else if ch.is_alphabetic() || ch == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Identifier(chars[start..i].iter().collect()));
+        } else {
+            tokens.push(match ch {
+                '+' | '-' | '*' | '/' | '%' => Token::Operator(ch),
+                '(' => Token::Open,
+                ')' => Token::Close,
+                _ => return None,
+            });
+            i += 1;
+        }
+    }
+    (!tokens.is_empty()).then_some(tokens)
+}
+
+/// Recursive-descent evaluator over `+ - * / %`, unary signs and parentheses.
+struct ExpressionParser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    expander: &'a Expander,
+}
+
+impl ExpressionParser<'_> {
+    fn expression(&mut self) -> Result<f64, String> {
+        let mut value = self.term()?;
+        while let Some(Token::Operator(op @ ('+' | '-'))) = self.tokens.get(self.position) {
+            self.position += 1;
+            let rhs = self.term()?;
+            value = if *op == '+' { value + rhs } else { value - rhs };
+        }
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<f64, String> {
+        let mut value = self.unary()?;
+        while let Some(Token::Operator(op @ ('*' | '/' | '%'))) = self.tokens.get(self.position) {
+            self.position += 1;
+            let rhs = self.unary()?;
+            if *op != '*' && rhs == 0.0 {
+                return Err("division by zero".to_string());
+            }
+            value = match op {
+                '*' => value * rhs,
+                '/' => value / rhs,
+                _ => value % rhs,
+            };
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<f64, String> {
+        match self.tokens.get(self.position) {
+            Some(Token::Operator('-')) => {
+                self.position += 1;
+                Ok(-self.unary()?)
+            }
+            Some(Token::Operator('+')) => {
+                self.position += 1;
+                self.unary()
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<f64, String> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        match token {
+            Some(Token::Number(value)) => Ok(*value),
+            Some(Token::Identifier(name)) => self
+                .expander
+                .lookup(name)
+                .ok_or_else(|| format!("unknown variable `{name}`")),
+            Some(Token::Open) => {
+                let value = self.expression()?;
+                match self.tokens.get(self.position) {
+                    Some(Token::Close) => {
+                        self.position += 1;
+                        Ok(value)
+                    }
+                    _ => Err("missing `)`".to_string()),
+                }
+            }
+            Some(Token::Close) => Err("unexpected `)`".to_string()),
+            Some(Token::Operator(op)) => Err(format!("unexpected `{op}`")),
+            None => Err("incomplete expression".to_string()),
+        }
+    }
+}
+
+/// `line` up to its first `#` outside quotes.
+pub(crate) fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (offset, ch) in line.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if ch == '\\' => escaped = true,
+            Some(open) if ch == open => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == '#' => return &line[..offset],
+            None => {}
+        }
+    }
+    line
+}
+
+/// Whitespace-separated words with their byte ranges; quoted text, including
+/// escaped quotes, stays in one word.
+fn words(line: &str) -> Vec<(String, Range<usize>)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut quote = None;
+    let mut escaped = false;
+    for (offset, ch) in line.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if ch == '\\' => escaped = true,
+            Some(open) if ch == open => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => {
+                quote = Some(ch);
+                start.get_or_insert(offset);
+            }
+            None if ch.is_whitespace() => {
+                if let Some(begin) = start.take() {
+                    words.push((line[begin..offset].to_string(), begin..offset));
+                }
+            }
+            None => {
+                start.get_or_insert(offset);
+            }
+        }
+    }
+    if let Some(begin) = start {
+        words.push((line[begin..].to_string(), begin..line.len()));
+    }
+    words
+}
+
+fn is_identifier(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|ch| ch.is_alphanumeric() || ch == '_')
+}
+
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{value}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(expansion: &Expansion) -> Vec<&str> {
+        expansion
+            .lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn expands_loops_and_arithmetic() {
+        let source = "let base = 10\n\
+                      repeat 2 {\n\
+                      \x20   rotate residue=base angle=-5deg\n\
+                      }\n\
+                      for r in base..=base+4 step 2 {\n\
+                      \x20   rotate residue=r angle=(r-base)*2.5\n\
+                      }\n\
+                      define_domain tail base+2..base+5, 2-4\n";
+        let expansion = expand(source, None);
+        assert!(
+            expansion.diagnostics.is_empty(),
+            "{:?}",
+            expansion.diagnostics
+        );
+        assert_eq!(
+            texts(&expansion),
+            [
+                "rotate residue=10 angle=-5deg",
+                "rotate residue=10 angle=-5deg",
+                "rotate residue=10 angle=0",
+                "rotate residue=12 angle=5",
+                "rotate residue=14 angle=10",
+                "define_domain tail 12-15, 2-4",
+            ]
+        );
+        let line = &expansion.lines[3];
+        assert_eq!(line.line, 6);
+        // `angle=(r-base)*2.5` maps back to the word as written.
+        let angle = line.text.find("angle").unwrap()..line.text.len();
+        assert_eq!(&line.source[line.source_span(angle)], "angle=(r-base)*2.5");
+        let residue = line.text.find("residue").unwrap()..line.text.find("=12").unwrap();
+        assert_eq!(&line.source[line.source_span(residue)], "residue=r");
+    }
+
+    #[test]
+    fn reports_structural_and_expression_errors() {
+        let source = "repeat 2\n\
+                      rotate residue=n+1 angle=10\n\
+                      for i in 0..3 {\n\
+                      rotate residue=i angle=1/0\n";
+        let expansion = expand(source, None);
+        let messages: Vec<(usize, &str)> = expansion
+            .diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                (1, "expected `{` after `repeat`"),
+                (3, "unclosed block"),
+                (2, "unknown variable `n`"),
+            ]
+        );
+        assert!(expansion.lines.is_empty());
+    }
+
+    #[test]
+    fn huge_repeat_counts_fail_fast_and_names_stay_names() {
+        let expansion = expand("repeat 100000000000 {\n}\n", None);
+        assert_eq!(expansion.diagnostics.len(), 1);
+        assert_eq!(
+            expansion.diagnostics[0].message,
+            "loop runs 100000000000 times"
+        );
+
+        let source = "for core in 1..3 {\n\
+                      \x20   span_alias core\n\
+                      \x20   move_domain core dz=core*0.1\n\
+                      \x20   define_domain hinge core..core+2\n\
+                      }\n";
+        let expansion = expand(source, None);
+        assert!(expansion.diagnostics.is_empty());
+        assert_eq!(
+            texts(&expansion)[..3],
+            [
+                "span_alias core",
+                "move_domain core dz=0.1",
+                "define_domain hinge 1-3",
+            ]
+        );
+    }
+
+    #[test]
+    fn includes_resolve_relative_to_the_contract() {
+        let dir = std::env::temp_dir().join(format!("lll-include-{}", std::process::id()));
+        fs::create_dir_all(dir.join("shared")).unwrap();
+        fs::write(dir.join("shared/relax.lll"), "minimize steps=k\n").unwrap();
+        fs::write(dir.join("loop.lll"), "include loop.lll\n").unwrap();
+        let main = dir.join("main.lll");
+        fs::write(
+            &main,
+            "let k = 5\ninclude \"shared/relax.lll\"\ninclude loop.lll\n",
+        )
+        .unwrap();
+
+        let expansion = expand(&fs::read_to_string(&main).unwrap(), Some(&main));
+        assert_eq!(texts(&expansion), ["minimize steps=5"]);
+        let included = expansion.lines[0].file.as_deref().unwrap();
+        assert!(included.ends_with("relax.lll"));
+        assert_eq!(expansion.diagnostics.len(), 1);
+        assert_eq!(
+            expansion.diagnostics[0].message,
+            "`loop.lll` is already being included"
+        );
+        assert!(expansion.diagnostics[0]
+            .file
+            .as_deref()
+            .unwrap()
+            .ends_with("loop.lll"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
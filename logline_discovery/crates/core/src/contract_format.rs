@@ -0,0 +1,429 @@
+//! Canonical `.lll` formatting for folding contracts.
+//!
+//! Every instruction is written as one directive line with its canonical
+//! spelling, keyed arguments and shortest round-tripping numbers, so
+//! `FoldingContract::parse(&contract.to_string(), None).contract == contract`.
+//! Free text (aliases, chaperones, modifications) is quoted when it is not a
+//! plain word, with backslash escapes for quotes, backslashes and line
+//! breaks; the parser reads quoted text literally, so `#`, separators and
+//! keywords such as `for` and `at` survive the round trip. Empty aliases,
+//! modifications and paths are the exception: the parser drops them.
+
+use std::fmt;
+
+use crate::folding_parser::{ContractInstruction, FoldingContract, PhysicsLevel, PhysicsSpanMode};
+use crate::schedule::TemperatureSchedule;
+
+/// Words that mark the next argument in free-text directives.
+const KEYWORDS: [&str; 4] = ["for", "span", "at", "on"];
+
+const AXES: [(&str, [f64; 3]); 3] = [
+    ("x", [1.0, 0.0, 0.0]),
+    ("y", [0.0, 1.0, 0.0]),
+    ("z", [0.0, 0.0, 1.0]),
+];
+
+impl fmt::Display for FoldingContract {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for instruction in &self.instructions {
+            writeln!(f, "{instruction}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ContractInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractInstruction::Rotate {
+                residue,
+                angle_degrees,
+                duration_ms,
+            } => write!(
+                f,
+                "rotate residue={} angle={angle_degrees} duration={duration_ms}",
+                residue.0
+            ),
+            ContractInstruction::ClashCheck => f.write_str("clash_check"),
+            ContractInstruction::Commit => f.write_str("commit"),
+            ContractInstruction::Rollback => f.write_str("rollback"),
+            ContractInstruction::GhostMode(on) => {
+                write!(f, "ghost {}", if *on { "on" } else { "off" })
+            }
+            ContractInstruction::SpanAlias(alias) => write!(f, "span_alias {}", quoted(alias)),
+            ContractInstruction::DefineDomain { name, start, end } => {
+                f.write_str("define_domain ")?;
+                if let Some(name) = name {
+                    write!(f, "{} ", quoted(name))?;
+                }
+                write!(f, "{}-{}", start.0, end.0)
+            }
+            ContractInstruction::RequireChaperone { chaperone, span } => {
+                write!(f, "require_chaperone {}", quoted(chaperone))?;
+                if let Some(span) = span {
+                    write!(f, " for {}", quoted(span))?;
+                }
+                Ok(())
+            }
+            ContractInstruction::AddModification {
+                modification,
+                residue,
+            } => write!(
+                f,
+                "add_modification {} at {}",
+                quoted(modification),
+                residue.0
+            ),
+            ContractInstruction::MoveDomain {
+                domain,
+                angle_degrees,
+                axis,
+                translation,
+            } => {
+                write!(f, "move_domain {} angle={angle_degrees}", quoted(domain))?;
+                match AXES.iter().find(|(_, unit)| unit == axis) {
+                    Some(("z", _)) => {}
+                    Some((name, _)) => write!(f, " axis={name}")?,
+                    None => write!(f, " ax={} ay={} az={}", axis[0], axis[1], axis[2])?,
+                }
+                for (key, value) in ["dx", "dy", "dz"].iter().zip(translation) {
+                    if *value != 0.0 {
+                        write!(f, " {key}={value}")?;
+                    }
+                }
+                Ok(())
+            }
+            ContractInstruction::SetPhysicsLevel(level) => {
+                let level = match level {
+                    PhysicsLevel::Toy => "toy",
+                    PhysicsLevel::Coarse => "coarse",
+                    PhysicsLevel::Gb => "gb",
+                    PhysicsLevel::Full => "full",
+                };
+                write!(f, "set_physics_level {level}")
+            }
+            ContractInstruction::SetSpanPhysics(mode) => {
+                let mode = match mode {
+                    PhysicsSpanMode::Toy => "toy",
+                    PhysicsSpanMode::Physics => "physics",
+                };
+                write!(f, "physics_span {mode}")
+            }
+            ContractInstruction::SetSchedule(schedule) => {
+                write!(f, "set_schedule {}", ScheduleArguments(schedule))
+            }
+            ContractInstruction::Minimize { steps, tolerance } => {
+                write!(f, "minimize steps={steps} tolerance={tolerance}")
+            }
+            ContractInstruction::RestrainDistance {
+                left,
+                right,
+                target,
+                force_constant,
+            } => write!(
+                f,
+                "restrain_distance a={} b={} target={target} k={force_constant}",
+                left.0, right.0
+            ),
+            ContractInstruction::RestrainDihedral {
+                residues: [a, b, c, d],
+                target_degrees,
+                force_constant,
+            } => write!(
+                f,
+                "restrain_dihedral a={} b={} c={} d={} target={target_degrees} k={force_constant}",
+                a.0, b.0, c.0, d.0
+            ),
+            ContractInstruction::TargetRmsd {
+                path,
+                target,
+                force_constant,
+            } => write!(
+                f,
+                "target_rmsd {} target={target} k={force_constant}",
+                quoted(path)
+            ),
+        }
+    }
+}
+
+struct ScheduleArguments<'a>(&'a TemperatureSchedule);
+
+impl fmt::Display for ScheduleArguments<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            TemperatureSchedule::Constant => f.write_str("constant"),
+            TemperatureSchedule::Linear { start, end, steps } => {
+                write!(f, "linear start={start} end={end} steps={steps}")
+            }
+            TemperatureSchedule::Exponential { start, end, factor } => {
+                write!(f, "exponential start={start} end={end} factor={factor}")
+            }
+            TemperatureSchedule::Cyclic { high, low, period } => {
+                write!(f, "cyclic high={high} low={low} period={period}")
+            }
+            TemperatureSchedule::Adaptive {
+                target_acceptance,
+                min,
+                max,
+                window,
+            } => {
+                write!(f, "adaptive target={target_acceptance} min={min}")?;
+                // An unbounded ceiling is the parser default.
+                if *max != f64::MAX {
+                    write!(f, " max={max}")?;
+                }
+                write!(f, " window={window}")
+            }
+        }
+    }
+}
+
+/// `text` as one `.lll` token: bare when it is a plain word, otherwise
+/// quoted and escaped so separators, comments and keywords stay literal.
+fn quoted(text: &str) -> String {
+    let plain = text
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && text
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || matches!(ch, '_' | '.'))
+        && !KEYWORDS
+            .iter()
+            .any(|keyword| text.eq_ignore_ascii_case(keyword));
+    if plain {
+        return text.to_string();
+    }
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            _ => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use folding_molecule::ResidueId;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+    use std::ops::Range;
+
+    const NAMES: &[&str] = &["core", "hinge", "n_term", "Tail2", "loop.a", "loop-3"];
+    const PATHS: &[&str] = &["ref.pdb", "structures/1abc.cif", "native model.pdb"];
+    const TEXT: &[&str] = &[
+        "GroEL",
+        "Hsp70",
+        "GroEL+GroES",
+        "heat shock 90",
+        "phosphorylation",
+        "N-glycan",
+        "tilt alpha",
+        "scan(1..4)",
+        "for",
+        "At",
+        "heat # shock",
+        "say \"hi\"",
+        "it's",
+        "a\\b",
+        "two\nlines",
+        "k=v",
+    ];
+
+    /// Listed samples mixed with arbitrary non-empty text.
+    fn text(samples: &'static [&'static str]) -> impl Strategy<Value = String> {
+        prop_oneof![
+            select(samples).prop_map(String::from),
+            "[ -~\n]{1,16}",
+            "\\PC{1,8}",
+        ]
+    }
+
+    fn real(range: Range<f64>) -> impl Strategy<Value = f64> {
+        // Mix round values with full-precision ones.
+        prop_oneof![range.clone().prop_map(f64::round), range]
+    }
+
+    fn residue() -> impl Strategy<Value = ResidueId> {
+        (0..500usize).prop_map(ResidueId)
+    }
+
+    fn schedule() -> impl Strategy<Value = TemperatureSchedule> {
+        prop_oneof![
+            Just(TemperatureSchedule::Constant),
+            (real(200.0..600.0), real(200.0..600.0), 0..500usize)
+                .prop_map(|(start, end, steps)| TemperatureSchedule::Linear { start, end, steps }),
+            (real(300.0..600.0), real(100.0..300.0), 0.5..1.0f64).prop_map(
+                |(start, end, factor)| TemperatureSchedule::Exponential { start, end, factor }
+            ),
+            (real(400.0..600.0), real(200.0..400.0), 1..100usize)
+                .prop_map(|(high, low, period)| TemperatureSchedule::Cyclic { high, low, period }),
+            (
+                0.0..1.0f64,
+                real(1.0..300.0),
+                prop_oneof![Just(f64::MAX), real(300.0..900.0)],
+                1..50usize,
+            )
+                .prop_map(|(target_acceptance, min, max, window)| {
+                    TemperatureSchedule::Adaptive {
+                        target_acceptance,
+                        min,
+                        max,
+                        window,
+                    }
+                }),
+        ]
+    }
+
+    fn instruction() -> impl Strategy<Value = ContractInstruction> {
+        prop_oneof![
+            (residue(), real(-180.0..180.0), 0..1000u64).prop_map(
+                |(residue, angle_degrees, duration_ms)| ContractInstruction::Rotate {
+                    residue,
+                    angle_degrees,
+                    duration_ms,
+                }
+            ),
+            Just(ContractInstruction::ClashCheck),
+            Just(ContractInstruction::Commit),
+            Just(ContractInstruction::Rollback),
+            any::<bool>().prop_map(ContractInstruction::GhostMode),
+            text(TEXT).prop_map(ContractInstruction::SpanAlias),
+            (proptest::option::of(text(NAMES)), 0..400usize, 0..50usize).prop_map(
+                |(name, start, length)| ContractInstruction::DefineDomain {
+                    name,
+                    start: ResidueId(start),
+                    end: ResidueId(start + length),
+                }
+            ),
+            (text(TEXT), proptest::option::of(text(NAMES))).prop_map(|(chaperone, span)| {
+                ContractInstruction::RequireChaperone { chaperone, span }
+            }),
+            (text(TEXT), residue()).prop_map(|(modification, residue)| {
+                ContractInstruction::AddModification {
+                    modification,
+                    residue,
+                }
+            }),
+            (
+                text(NAMES),
+                real(-90.0..90.0),
+                prop_oneof![
+                    select(AXES.map(|(_, unit)| unit).to_vec()),
+                    [real(-1.0..1.0), real(-1.0..1.0), real(-1.0..1.0)],
+                ],
+                [
+                    real(-2.0..2.0).boxed(),
+                    prop_oneof![Just(0.0), real(-2.0..2.0)].boxed(),
+                    real(-2.0..2.0).boxed(),
+                ],
+            )
+                .prop_map(|(domain, angle_degrees, axis, translation)| {
+                    ContractInstruction::MoveDomain {
+                        domain,
+                        angle_degrees,
+                        axis,
+                        translation,
+                    }
+                }),
+            select(vec![
+                PhysicsLevel::Toy,
+                PhysicsLevel::Coarse,
+                PhysicsLevel::Gb,
+                PhysicsLevel::Full,
+            ])
+            .prop_map(ContractInstruction::SetPhysicsLevel),
+            select(vec![PhysicsSpanMode::Toy, PhysicsSpanMode::Physics])
+                .prop_map(ContractInstruction::SetSpanPhysics),
+            schedule().prop_map(ContractInstruction::SetSchedule),
+            (0..1000usize, 1e-6..1.0f64)
+                .prop_map(|(steps, tolerance)| ContractInstruction::Minimize { steps, tolerance }),
+            (residue(), residue(), real(0.0..20.0), real(0.0..50.0)).prop_map(
+                |(left, right, target, force_constant)| ContractInstruction::RestrainDistance {
+                    left,
+                    right,
+                    target,
+                    force_constant,
+                }
+            ),
+            (
+                [residue(), residue(), residue(), residue()],
+                real(-180.0..180.0),
+                real(0.0..100.0),
+            )
+                .prop_map(|(residues, target_degrees, force_constant)| {
+                    ContractInstruction::RestrainDihedral {
+                        residues,
+                        target_degrees,
+                        force_constant,
+                    }
+                }),
+            (text(PATHS), real(0.0..5.0), real(0.0..50.0)).prop_map(
+                |(path, target, force_constant)| ContractInstruction::TargetRmsd {
+                    path,
+                    target,
+                    force_constant,
+                }
+            ),
+        ]
+    }
+
+    #[test]
+    fn formats_canonical_lines() {
+        let contract = FoldingContract::new(vec![
+            ContractInstruction::SpanAlias("tilt alpha".into()),
+            ContractInstruction::Rotate {
+                residue: ResidueId(2),
+                angle_degrees: 28.0,
+                duration_ms: 6,
+            },
+            ContractInstruction::MoveDomain {
+                domain: "hinge".into(),
+                angle_degrees: 12.5,
+                axis: [1.0, 0.0, 0.0],
+                translation: [0.0, 0.0, 0.2],
+            },
+            ContractInstruction::RequireChaperone {
+                chaperone: "GroEL+GroES".into(),
+                span: Some("core".into()),
+            },
+        ]);
+        assert_eq!(
+            contract.to_string(),
+            "span_alias \"tilt alpha\"\n\
+             rotate residue=2 angle=28 duration=6\n\
+             move_domain hinge angle=12.5 axis=x dz=0.2\n\
+             require_chaperone \"GroEL+GroES\" for core\n"
+        );
+    }
+
+    proptest! {
+        /// Formatting then parsing any contract yields the same contract,
+        /// as does a JSON round trip.
+        #[test]
+        fn parse_format_round_trip(
+            contract in proptest::collection::vec(instruction(), 0..25)
+                .prop_map(FoldingContract::new)
+        ) {
+            let text = contract.to_string();
+
+            let parsed = FoldingContract::parse(&text, None);
+            // Random sequences may still draw warnings, e.g. for undefined domains.
+            prop_assert!(!parsed.has_errors(), "{}", parsed.render("generated.lll"));
+            prop_assert_eq!(&parsed.contract, &contract, "{}", text);
+
+            let json = serde_json::to_string(&contract).unwrap();
+            let decoded: FoldingContract = serde_json::from_str(&json).unwrap();
+            prop_assert_eq!(decoded, contract, "{}", json);
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Static analysis of a contract against an engine's ruleset and clock,
+//! without touching the chain.
+//!
+//! Every rotation is assumed to be accepted, so budget figures are an upper
+//! bound on what the real run consumes; Metropolis rejections, clashes and
+//! physics backends are not simulated. Wall-clock time is paced by the
+//! engine's `RotationClock`.
+
+use std::time::Duration;
+
+use folding_time::{EntropyMeter, Trajectory};
+use serde::Serialize;
+
+use crate::folding_parser::{ContractInstruction, FoldingContract};
+use crate::folding_ruleset::RuleViolation;
+use crate::folding_runtime::FoldingEngine;
+use crate::rotation_solver::RotationCommand;
+use crate::validation::ValidationEvent;
+
+/// Violation the dry run expects at instruction `instruction` (0-based).
+#[derive(Clone, Debug, Serialize)]
+pub struct ProjectedViolation {
+    pub instruction: usize,
+    pub violation: RuleViolation,
+}
+
+/// Entropy and information the contract would consume.
+#[derive(Clone, Debug, Serialize)]
+pub struct BudgetProjection {
+    pub entropy_budget: Option<f64>,
+    pub information_budget: Option<f64>,
+    pub projected_entropy: f64,
+    pub projected_information: f64,
+    /// Budget left at the end; `None` when unbounded.
+    pub remaining_entropy: Option<f64>,
+    pub remaining_information: Option<f64>,
+    /// First instruction the budget check would reject.
+    pub exhausted_at: Option<usize>,
+}
+
+/// `ghost on` region with rotations but no `commit` before `ghost off`.
+#[derive(Clone, Debug, Serialize)]
+pub struct GhostRegion {
+    pub start: usize,
+    /// `ghost off` instruction, or `None` if the contract ends in ghost mode.
+    pub end: Option<usize>,
+    pub rotations: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DryRunReport {
+    pub instructions: usize,
+    pub rotations: usize,
+    pub ghost_rotations: usize,
+    pub rotation_limit_violations: Vec<ProjectedViolation>,
+    pub budget_violations: Vec<ProjectedViolation>,
+    pub budget: BudgetProjection,
+    /// `rollback`s with no `commit` left to return to.
+    pub unmatched_rollbacks: Vec<usize>,
+    /// `commit`s never consumed by a `rollback`.
+    pub unmatched_commits: Vec<usize>,
+    pub uncommitted_ghost_regions: Vec<GhostRegion>,
+    pub estimated_wall_clock_ms: f64,
+}
+
+impl DryRunReport {
+    /// Whether the real run is expected to reject instructions.
+    pub fn has_violations(&self) -> bool {
+        !self.rotation_limit_violations.is_empty() || !self.budget_violations.is_empty()
+    }
+}
+
+impl FoldingEngine {
+    /// Checks `contract` the way [`Self::execute_contract`] would run it,
+    /// leaving the engine untouched.
+    pub fn dry_run(&self, contract: &FoldingContract) -> DryRunReport {
+        let ruleset = self.validator().ruleset();
+        let mut trajectory = self.trajectory().clone();
+        let mut checkpoints: Vec<(usize, Trajectory)> = Vec::new();
+        let mut ghost: Option<GhostRegion> = None;
+        let mut ghost_committed = false;
+        let mut wall_clock = Duration::ZERO;
+        let mut report = DryRunReport {
+            instructions: contract.instructions.len(),
+            rotations: 0,
+            ghost_rotations: 0,
+            rotation_limit_violations: Vec::new(),
+            budget_violations: Vec::new(),
+            budget: BudgetProjection {
+                entropy_budget: ruleset.entropy_budget,
+                information_budget: ruleset.information_budget,
+                projected_entropy: 0.0,
+                projected_information: 0.0,
+                remaining_entropy: None,
+                remaining_information: None,
+                exhausted_at: None,
+            },
+            unmatched_rollbacks: Vec::new(),
+            unmatched_commits: Vec::new(),
+            uncommitted_ghost_regions: Vec::new(),
+            estimated_wall_clock_ms: 0.0,
+        };
+
+        for (index, instruction) in contract.instructions.iter().enumerate() {
+            match instruction {
+                ContractInstruction::Rotate {
+                    residue,
+                    angle_degrees,
+                    duration_ms,
+                } => {
+                    report.rotations += 1;
+                    if let Err(violation) =
+                        self.validator()
+                            .validate_rotation(*residue, *angle_degrees, self.chain())
+                    {
+                        report.rotation_limit_violations.push(ProjectedViolation {
+                            instruction: index,
+                            violation,
+                        });
+                        continue;
+                    }
+                    let outcome = self.solver().solve(RotationCommand {
+                        residue: *residue,
+                        angle_degrees: *angle_degrees,
+                        duration: Duration::from_millis((*duration_ms).max(1)),
+                        label: None,
+                    });
+                    // The clock paces rotations to at least one tick each.
+                    wall_clock += outcome
+                        .span_record
+                        .duration
+                        .max(self.solver().clock().tick_duration());
+                    if let Some(region) = ghost.as_mut() {
+                        region.rotations += 1;
+                        report.ghost_rotations += 1;
+                        continue;
+                    }
+                    match self
+                        .validator()
+                        .validate_span(&outcome.span_record, &trajectory)
+                    {
+                        ValidationEvent::Accepted => trajectory.push(outcome.span_record),
+                        ValidationEvent::Rejected(violation) => {
+                            report.budget.exhausted_at.get_or_insert(index);
+                            report.budget_violations.push(ProjectedViolation {
+                                instruction: index,
+                                violation,
+                            });
+                        }
+                    }
+                }
+                ContractInstruction::Commit => {
+                    checkpoints.push((index, trajectory.clone()));
+                    ghost_committed |= ghost.is_some();
+                }
+                ContractInstruction::Rollback => match checkpoints.pop() {
+                    Some((_, snapshot)) => trajectory = snapshot,
+                    None => {
+                        report.unmatched_rollbacks.push(index);
+                        trajectory.pop_last();
+                    }
+                },
+                ContractInstruction::GhostMode(true) if ghost.is_none() => {
+                    ghost = Some(GhostRegion {
+                        start: index,
+                        end: None,
+                        rotations: 0,
+                    });
+                    ghost_committed = false;
+                }
+                ContractInstruction::GhostMode(false) => {
+                    if let Some(mut region) = ghost.take() {
+                        region.end = Some(index);
+                        if region.rotations > 0 && !ghost_committed {
+                            report.uncommitted_ghost_regions.push(region);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(region) = ghost.filter(|region| region.rotations > 0 && !ghost_committed) {
+            report.uncommitted_ghost_regions.push(region);
+        }
+        report.unmatched_commits = checkpoints.into_iter().map(|(index, _)| index).collect();
+
+        let meter = EntropyMeter::new(
+            ruleset.entropy_budget.unwrap_or(f64::INFINITY),
+            ruleset.information_budget.unwrap_or(f64::INFINITY),
+        );
+        let consumed = self.trajectory();
+        report.budget.projected_entropy = trajectory.total_entropy() - consumed.total_entropy();
+        report.budget.projected_information =
+            trajectory.total_information() - consumed.total_information();
+        report.budget.remaining_entropy = ruleset
+            .entropy_budget
+            .map(|_| meter.remaining_entropy(&trajectory));
+        report.budget.remaining_information = ruleset
+            .information_budget
+            .map(|_| meter.remaining_information(&trajectory));
+        report.estimated_wall_clock_ms = wall_clock.as_secs_f64() * 1000.0;
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::folding_ruleset::Ruleset;
+    use crate::folding_runtime::FoldingEngineBuilder;
+    use crate::micro_oscillator::MicroOscillator;
+    use folding_molecule::{AminoAcid, PeptideChain, StartingConformation};
+
+    fn engine(ruleset: Ruleset) -> FoldingEngine {
+        FoldingEngineBuilder::new()
+            .with_chain(PeptideChain::from_sequence(
+                &[AminoAcid::Alanine; 8],
+                StartingConformation::Extended,
+            ))
+            .with_ruleset(ruleset)
+            .with_oscillator(MicroOscillator::new(0.0, 0.0))
+            .with_rng_seed(3)
+            .build()
+    }
+
+    #[test]
+    fn reports_limits_budgets_and_unbalanced_checkpoints() {
+        // Each rotation of θ degrees consumes 0.01·|θ| entropy.
+        let engine = engine(
+            Ruleset::alpha_carbon_trace()
+                .with_rotation_limit(45.0)
+                .with_entropy_budget(1.0),
+        );
+        let source = "rollback\n\
+                      rotate residue=2 angle=40 duration=5\n\
+                      commit\n\
+                      rotate residue=3 angle=90\n\
+                      rotate residue=3 angle=40 duration=7\n\
+                      rotate residue=4 angle=30\n\
+                      ghost on\n\
+                      rotate residue=5 angle=20 duration=3\n\
+                      ghost off\n\
+                      rollback\n\
+                      commit\n";
+        let contract = FoldingContract::parse(source, None).contract;
+        let before = engine.chain().positions();
+        let report = engine.dry_run(&contract);
+
+        assert_eq!(report.rotations, 5);
+        assert_eq!(report.ghost_rotations, 1);
+        assert_eq!(report.rotation_limit_violations.len(), 1);
+        assert_eq!(report.rotation_limit_violations[0].instruction, 3);
+        // 0.4 + 0.4 fits, the 0.3 after it does not.
+        assert_eq!(report.budget.exhausted_at, Some(5));
+        assert!(matches!(
+            report.budget_violations[0].violation,
+            RuleViolation::EntropyBudgetExceeded { .. }
+        ));
+        assert_eq!(report.unmatched_rollbacks, [0]);
+        assert_eq!(report.unmatched_commits, [10]);
+        assert_eq!(report.uncommitted_ghost_regions.len(), 1);
+        assert_eq!(report.uncommitted_ghost_regions[0].start, 6);
+        assert_eq!(report.uncommitted_ghost_regions[0].end, Some(8));
+        // The rollback returns to the first commit: only the first span counts.
+        assert!((report.budget.projected_entropy - 0.4).abs() < 1e-9);
+        assert!((report.budget.remaining_entropy.unwrap() - 0.6).abs() < 1e-9);
+        assert_eq!(report.budget.remaining_information, None);
+        assert!((report.estimated_wall_clock_ms - 16.0).abs() < 1e-9);
+        assert!(report.has_violations());
+        assert_eq!(engine.chain().positions(), before);
+        assert!(engine.trajectory().spans().is_empty());
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["budget"]["exhausted_at"], 5);
+    }
+}
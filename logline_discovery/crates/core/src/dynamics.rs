@@ -0,0 +1,221 @@
+//! Native coarse-grained Langevin dynamics for physics spans.
+//!
+//! Each residue is a single bead moving on the [`EnergyModel`] surface. The
+//! integrator uses the BAOAB splitting of Langevin dynamics, which reduces to
+//! plain velocity Verlet when the friction is zero. Units are Å, ps, amu and
+//! kcal/mol.
+
+use std::time::Duration;
+
+use folding_molecule::{kabsch_rmsd, radius_of_gyration, EnergyModel, PeptideChain};
+use rand::Rng;
+
+use crate::folding_parser::PhysicsLevel;
+use crate::physics_bridge::PhysicsSpanMetrics;
+
+/// kcal·mol⁻¹·K⁻¹
+const BOLTZMANN: f64 = 0.0019872041;
+/// Converts kcal·mol⁻¹·Å⁻¹·amu⁻¹ to Å·ps⁻².
+const ACCELERATION_UNIT: f64 = 418.4;
+/// Average residue mass used for every bead (amu).
+const RESIDUE_MASS: f64 = 110.0;
+/// Integrator steps granted per millisecond of contract span duration.
+const STEPS_PER_MS: usize = 100;
+const MAX_STEPS_PER_SPAN: usize = 10_000;
+
+#[derive(Clone, Debug)]
+pub struct LangevinIntegrator {
+    pub timestep_ps: f64,
+    /// Collision frequency γ (ps⁻¹); zero gives microcanonical velocity Verlet.
+    pub friction_per_ps: f64,
+    /// Mass of an unmodified bead; modifications add their own mass on top.
+    pub bead_mass: f64,
+}
+
+impl LangevinIntegrator {
+    pub fn new(timestep_ps: f64, friction_per_ps: f64) -> Self {
+        Self {
+            timestep_ps,
+            friction_per_ps,
+            bead_mass: RESIDUE_MASS,
+        }
+    }
+
+    /// Integrator settings for a physics level; `Toy` has no dynamics.
+    pub fn for_level(level: PhysicsLevel) -> Option<Self> {
+        match level {
+            PhysicsLevel::Toy => None,
+            PhysicsLevel::Coarse => Some(Self::new(0.010, 1.0)),
+            // Implicit solvent: stronger coupling stands in for solvent drag.
+            PhysicsLevel::Gb => Some(Self::new(0.005, 5.0)),
+            PhysicsLevel::Full => Some(Self::new(0.002, 1.0)),
+        }
+    }
+
+    /// Number of integration steps covering a span of `duration`.
+    pub fn steps_for(&self, duration: Duration) -> usize {
+        let millis = duration.as_millis().max(1) as usize;
+        millis.saturating_mul(STEPS_PER_MS).min(MAX_STEPS_PER_SPAN)
+    }
+
+    /// Propagates `chain` for `steps` steps with the bath at `temperature`.
+    /// Velocities are drawn from the Maxwell–Boltzmann distribution at the
+    /// start of every call, so each span is thermalised at the engine's
+    /// current (possibly scheduled) temperature.
+    pub fn run<R: Rng>(
+        &self,
+        model: &EnergyModel,
+        chain: &mut PeptideChain,
+        temperature: f64,
+        steps: usize,
+        rng: &mut R,
+    ) -> PhysicsSpanMetrics {
+        let reference = chain.positions();
+        let mut positions = reference.clone();
+        let masses: Vec<f64> = chain
+            .residues()
+            .iter()
+            .map(|residue| residue.mass(self.bead_mass))
+            .collect();
+        let kt = BOLTZMANN * temperature.max(0.0);
+        let thermal_speeds: Vec<f64> = masses
+            .iter()
+            .map(|mass| (kt * ACCELERATION_UNIT / mass).sqrt())
+            .collect();
+        let mut velocities: Vec<[f64; 3]> = thermal_speeds
+            .iter()
+            .map(|speed| gaussian_vector(rng, *speed))
+            .collect();
+
+        let dt = self.timestep_ps;
+        let damping = (-self.friction_per_ps * dt).exp();
+        let noise_scale = (1.0 - damping * damping).sqrt();
+        let mut accelerations = bead_accelerations(model, chain, &masses);
+        for _ in 0..steps {
+            kick(&mut velocities, &accelerations, 0.5 * dt);
+            drift(&mut positions, &velocities, 0.5 * dt);
+            for (velocity, speed) in velocities.iter_mut().zip(&thermal_speeds) {
+                let random = gaussian_vector(rng, speed * noise_scale);
+                for axis in 0..3 {
+                    velocity[axis] = damping * velocity[axis] + random[axis];
+                }
+            }
+            drift(&mut positions, &velocities, 0.5 * dt);
+            chain.set_positions(&positions);
+            accelerations = bead_accelerations(model, chain, &masses);
+            kick(&mut velocities, &accelerations, 0.5 * dt);
+        }
+
+        let kinetic_energy = kinetic_energy(&velocities, &masses);
+        let degrees_of_freedom = (3 * velocities.len()).max(1) as f64;
+        PhysicsSpanMetrics {
+            rmsd: kabsch_rmsd(&reference, &positions),
+            radius_of_gyration: radius_of_gyration(&positions),
+            potential_energy: model.total_energy(chain),
+            kinetic_energy,
+            temperature: 2.0 * kinetic_energy / (degrees_of_freedom * BOLTZMANN),
+            simulation_time_ps: steps as f64 * dt,
+            trajectory_path: None,
+        }
+    }
+}
+
+fn bead_accelerations(model: &EnergyModel, chain: &PeptideChain, masses: &[f64]) -> Vec<[f64; 3]> {
+    model
+        .gradient(chain)
+        .forces()
+        .into_iter()
+        .zip(masses)
+        .map(|(f, mass)| {
+            let scale = ACCELERATION_UNIT / mass;
+            [f[0] * scale, f[1] * scale, f[2] * scale]
+        })
+        .collect()
+}
+
+fn kinetic_energy(velocities: &[[f64; 3]], masses: &[f64]) -> f64 {
+    velocities
+        .iter()
+        .zip(masses)
+        .map(|(v, mass)| 0.5 * mass * (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]))
+        .sum::<f64>()
+        / ACCELERATION_UNIT
+}
+
+fn kick(velocities: &mut [[f64; 3]], accelerations: &[[f64; 3]], dt: f64) {
+    for (v, a) in velocities.iter_mut().zip(accelerations) {
+        for axis in 0..3 {
+            v[axis] += a[axis] * dt;
+        }
+    }
+}
+
+fn drift(positions: &mut [[f64; 3]], velocities: &[[f64; 3]], dt: f64) {
+    for (x, v) in positions.iter_mut().zip(velocities) {
+        for axis in 0..3 {
+            x[axis] += v[axis] * dt;
+        }
+    }
+}
+
+/// Box–Muller normal deviates with standard deviation `sigma`.
+fn gaussian_vector<R: Rng>(rng: &mut R, sigma: f64) -> [f64; 3] {
+    let mut sample = || {
+        let u1: f64 = rng.gen_range(f64::MIN_POSITIVE..1.0);
+        let u2: f64 = rng.gen_range(0.0..1.0);
+        (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos() * sigma
+    };
+    [sample(), sample(), sample()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use folding_molecule::{AminoAcid, StartingConformation};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn helix() -> PeptideChain {
+        let sequence = [AminoAcid::Alanine; 12];
+        PeptideChain::from_sequence(&sequence, StartingConformation::Helix)
+    }
+
+    #[test]
+    fn thermostat_holds_bath_temperature() {
+        let model = EnergyModel::alpha_carbon_trace();
+        let integrator = LangevinIntegrator::for_level(PhysicsLevel::Coarse).expect("coarse");
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut chain = helix();
+        let samples: Vec<f64> = (0..200)
+            .map(|_| {
+                integrator
+                    .run(&model, &mut chain, 300.0, 20, &mut rng)
+                    .temperature
+            })
+            .collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        assert!(
+            (mean - 300.0).abs() < 30.0,
+            "mean kinetic temperature {mean}"
+        );
+    }
+
+    #[test]
+    fn frictionless_integration_conserves_energy() {
+        let model = EnergyModel::alpha_carbon_trace();
+        let integrator = LangevinIntegrator::new(0.002, 0.0);
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut chain = helix();
+        let first = integrator.run(&model, &mut chain, 50.0, 1, &mut rng);
+        let mut replay = StdRng::seed_from_u64(3);
+        let mut chain = helix();
+        let last = integrator.run(&model, &mut chain, 50.0, 500, &mut replay);
+        let drift = (last.potential_energy + last.kinetic_energy)
+            - (first.potential_energy + first.kinetic_energy);
+        assert!(
+            drift.abs() < 0.05 * first.kinetic_energy,
+            "energy drift {drift}"
+        );
+        assert!((last.simulation_time_ps - 1.0).abs() < 1e-9);
+        assert!(last.rmsd > 0.0);
+    }
+}
@@ -1,9 +1,91 @@
-use std::collections::HashMap;
-
-use folding_molecule::ResidueId;
-
-/// Core instruction set for `.lll` folding contracts.
-#[derive(Debug, Clone)]
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::Path;
+use std::{fs, io};
+
+use folding_molecule::{Modification, PeptideChain, ResidueId};
+use serde::{Deserialize, Serialize};
+
+use crate::contract_diagnostics::{closest, Diagnostic, ParsedContract, Severity};
+use crate::contract_expansion::{expand, strip_comment, Expansion, CONTROL_KEYWORDS};
+use crate::schedule::TemperatureSchedule;
+
+/// Iteration cap used when `minimize` omits `steps=`.
+const DEFAULT_MINIMIZE_STEPS: usize = 200;
+/// RMS gradient threshold (kcal·mol⁻¹·Å⁻¹) used when `minimize` omits `tolerance=`.
+const DEFAULT_MINIMIZE_TOLERANCE: f64 = 0.01;
+/// Cooling factor used when `set_schedule exponential` omits `factor=`.
+const DEFAULT_COOLING_FACTOR: f64 = 0.95;
+/// Acceptance rate targeted when `set_schedule adaptive` omits `target=`.
+const DEFAULT_TARGET_ACCEPTANCE: f64 = 0.4;
+/// Metropolis decisions between adaptive adjustments when `window=` is omitted.
+const DEFAULT_ADAPTIVE_WINDOW: usize = 10;
+/// Force constant (kcal·mol⁻¹·Å⁻²) used when `restrain_distance` omits `k=`.
+const DEFAULT_DISTANCE_RESTRAINT_K: f64 = 10.0;
+/// Force constant (kcal·mol⁻¹·rad⁻²) used when `restrain_dihedral` omits `k=`.
+const DEFAULT_DIHEDRAL_RESTRAINT_K: f64 = 50.0;
+/// Force constant (kcal·mol⁻¹·Å⁻²) used when `target_rmsd` omits `k=`.
+const DEFAULT_RMSD_RESTRAINT_K: f64 = 10.0;
+
+/// Every directive with its accepted spellings and a usage line for
+/// diagnostics. Keep in sync with the match in `parse_line`.
+const DIRECTIVES: &[(&[&str], &str)] = &[
+    (
+        &["rotate"],
+        "rotate residue=<id> angle=<deg> [duration=<ms>]",
+    ),
+    (&["clash_check", "clash"], "clash_check"),
+    (&["commit"], "commit"),
+    (&["rollback", "revert"], "rollback"),
+    (&["ghost"], "ghost on|off"),
+    (&["span_alias", "alias", "label"], "span_alias <name>"),
+    (
+        &["define_domain", "domain"],
+        "define_domain [name] <start>-<end>",
+    ),
+    (
+        &["require_chaperone", "chaperone"],
+        "require_chaperone <chaperone> [for <domain>]",
+    ),
+    (
+        &["add_modification", "modification", "modify"],
+        "add_modification <modification> at <residue>",
+    ),
+    (
+        &["move_domain", "domain_move"],
+        "move_domain <domain> [angle=<deg>] [axis=x|y|z | ax= ay= az=] [dx=<Å>] [dy=<Å>] [dz=<Å>]",
+    ),
+    (
+        &["set_physics_level", "physics_level"],
+        "set_physics_level toy|coarse|gb|full",
+    ),
+    (&["physics_span", "set_span_physics"], "physics_span on|off"),
+    (
+        &["set_schedule", "schedule", "anneal"],
+        "set_schedule constant|linear|exponential|cyclic|adaptive [key=value ...]",
+    ),
+    (
+        &["minimize", "minimise", "relax"],
+        "minimize [steps=<n>] [tolerance=<kcal/mol/Å>]",
+    ),
+    (
+        &["restrain_distance", "distance_restraint", "noe"],
+        "restrain_distance <a> <b> <target Å> [k=<kcal/mol/Å²>]",
+    ),
+    (
+        &["restrain_dihedral", "dihedral_restraint"],
+        "restrain_dihedral <a> <b> <c> <d> <target deg> [k=<kcal/mol/rad²>]",
+    ),
+    (
+        &["target_rmsd", "target_structure"],
+        "target_rmsd <pdb> [target=<Å>] [k=<kcal/mol/Å²>]",
+    ),
+];
+
+/// Core instruction set for `.lll` folding contracts. `Display` writes the
+/// canonical `.lll` line (see `contract_format`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ContractInstruction {
     Rotate {
         residue: ResidueId,
@@ -28,11 +110,45 @@ pub enum ContractInstruction {
         modification: String,
         residue: ResidueId,
     },
+    /// Rigid-body move of a defined domain: rotation about `axis` through
+    /// the domain centroid, then a translation (Å).
+    MoveDomain {
+        domain: String,
+        angle_degrees: f64,
+        axis: [f64; 3],
+        translation: [f64; 3],
+    },
     SetPhysicsLevel(PhysicsLevel),
     SetSpanPhysics(PhysicsSpanMode),
+    SetSchedule(TemperatureSchedule),
+    Minimize {
+        steps: usize,
+        tolerance: f64,
+    },
+    /// Harmonic restraint on the distance (Å) between two residues.
+    RestrainDistance {
+        left: ResidueId,
+        right: ResidueId,
+        target: f64,
+        force_constant: f64,
+    },
+    /// Harmonic restraint on the dihedral through four residues.
+    RestrainDihedral {
+        residues: [ResidueId; 4],
+        target_degrees: f64,
+        force_constant: f64,
+    },
+    /// Flat-bottom restraint keeping the RMSD (Å) to a reference structure
+    /// at or below `target`. Residues are matched by number.
+    TargetRmsd {
+        path: String,
+        target: f64,
+        force_constant: f64,
+    },
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PhysicsLevel {
     Toy,
     Coarse,
@@ -40,14 +156,15 @@ pub enum PhysicsLevel {
     Full,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PhysicsSpanMode {
     Toy,
     Physics,
 }
 
 /// Parsed folding contract ready for execution.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct FoldingContract {
     pub instructions: Vec<ContractInstruction>,
 }
@@ -57,6 +174,8 @@ impl FoldingContract {
         Self { instructions }
     }
 
+    /// Lenient parse: lines that do not parse are skipped. Use
+    /// [`Self::parse`] to find out what was dropped and why.
     pub fn from_lines(lines: &[&str]) -> Self {
         let instructions = lines
             .iter()
@@ -65,10 +184,221 @@ impl FoldingContract {
             .collect();
         Self { instructions }
     }
+
+    /// Parses `source`, reporting unknown or malformed directives and, when
+    /// `chain` is given, residues the chain does not have. `let`, `repeat`,
+    /// `for` and `include` are expanded first (includes resolve against the
+    /// working directory). Lines with errors are left out of the returned
+    /// contract.
+    pub fn parse(source: &str, chain: Option<&PeptideChain>) -> ParsedContract {
+        Self::parse_expansion(expand(source, None), None, chain)
+    }
+
+    /// Like [`Self::parse`], with includes and `target_rmsd` structures
+    /// resolved next to the file that names them.
+    pub fn parse_file(path: &Path, chain: Option<&PeptideChain>) -> io::Result<ParsedContract> {
+        let source = fs::read_to_string(path)?;
+        Ok(Self::parse_expansion(
+            expand(&source, Some(path)),
+            path.parent(),
+            chain,
+        ))
+    }
+
+    fn parse_expansion(
+        expansion: Expansion,
+        dir: Option<&Path>,
+        chain: Option<&PeptideChain>,
+    ) -> ParsedContract {
+        let mut parsed = ParsedContract {
+            diagnostics: expansion.diagnostics,
+            ..ParsedContract::default()
+        };
+        let mut checker = ContractChecker::default();
+        // Loop bodies repeat their diagnostics once per iteration.
+        let mut reported: HashSet<Diagnostic> = parsed.diagnostics.iter().cloned().collect();
+        for line in &expansion.lines {
+            let tokens = tokenize_spanned(&line.text);
+            let Some((command, command_span)) = tokens.first().cloned() else {
+                continue;
+            };
+            let diagnostic = |severity: Severity, message: String, span: Range<usize>| {
+                Diagnostic::new(
+                    severity,
+                    message,
+                    line.line,
+                    &line.source,
+                    line.source_span(span),
+                )
+                .with_file(line.file.clone())
+            };
+            let mut report = |diagnostic: Diagnostic| {
+                if reported.insert(diagnostic.clone()) {
+                    parsed.diagnostics.push(diagnostic);
+                }
+            };
+            let error =
+                |message: String, span: Range<usize>| diagnostic(Severity::Error, message, span);
+            let command_lower = command.to_lowercase();
+            let Some(usage) = directive_usage(&command_lower) else {
+                let spellings: Vec<&str> = DIRECTIVES
+                    .iter()
+                    .flat_map(|(names, _)| names.iter().copied())
+                    .chain(CONTROL_KEYWORDS.iter().copied())
+                    .collect();
+                let mut diagnostic = error(format!("unknown directive `{command}`"), command_span);
+                if let Some(suggestion) = closest(&command_lower, &spellings) {
+                    diagnostic = diagnostic.with_help(format!("did you mean `{suggestion}`?"));
+                }
+                report(diagnostic);
+                continue;
+            };
+            let arguments_span = tokens.last().map_or(command_span.clone(), |(_, span)| {
+                command_span.start..span.end
+            });
+            let Some(mut instructions) = parse_line(&line.text) else {
+                report(
+                    error(
+                        format!("malformed `{command_lower}` directive"),
+                        arguments_span,
+                    )
+                    .with_help(format!("expected `{usage}`")),
+                );
+                continue;
+            };
+
+            let line_dir = line
+                .file
+                .as_deref()
+                .map(Path::new)
+                .and_then(Path::parent)
+                .or(dir);
+            let mut line_ok = true;
+            for instruction in &mut instructions {
+                if let (ContractInstruction::TargetRmsd { path, .. }, Some(line_dir)) =
+                    (&mut *instruction, line_dir)
+                {
+                    if Path::new(path.as_str()).is_relative() {
+                        *path = line_dir.join(path.as_str()).display().to_string();
+                    }
+                }
+                if let Some(chain) = chain {
+                    for residue in referenced_residues(instruction) {
+                        if chain.index_of(residue).is_none() {
+                            let span = residue_token_span(&tokens[1..], residue.0)
+                                .unwrap_or_else(|| arguments_span.clone());
+                            report(
+                                error(
+                                    format!("residue {} is not in the target chain", residue.0),
+                                    span,
+                                )
+                                .with_help(chain_range_help(chain)),
+                            );
+                            line_ok = false;
+                        }
+                    }
+                }
+                if let Some(warning) = checker.check(instruction) {
+                    report(diagnostic(
+                        Severity::Warning,
+                        warning,
+                        arguments_span.clone(),
+                    ));
+                }
+            }
+            if line_ok {
+                parsed.contract.instructions.extend(instructions);
+            }
+        }
+        parsed
+    }
+}
+
+/// Contract-level state behind the parse warnings.
+#[derive(Default)]
+struct ContractChecker {
+    domains: HashSet<String>,
+    checkpoints: usize,
+}
+
+impl ContractChecker {
+    fn check(&mut self, instruction: &ContractInstruction) -> Option<String> {
+        match instruction {
+            ContractInstruction::Commit => {
+                self.checkpoints += 1;
+                None
+            }
+            ContractInstruction::Rollback => {
+                if self.checkpoints == 0 {
+                    Some("`rollback` without a preceding `commit` only drops the last span".into())
+                } else {
+                    self.checkpoints -= 1;
+                    None
+                }
+            }
+            ContractInstruction::DefineDomain {
+                name: Some(name), ..
+            } => (!self.domains.insert(name.clone()))
+                .then(|| format!("domain `{name}` is defined more than once")),
+            ContractInstruction::MoveDomain { domain, .. }
+            | ContractInstruction::RequireChaperone {
+                span: Some(domain), ..
+            } => (!self.domains.contains(domain))
+                .then(|| format!("domain `{domain}` is not defined before this line")),
+            ContractInstruction::AddModification { modification, .. } => {
+                Modification::from_name(modification).is_none().then(|| {
+                    format!(
+                        "modification `{modification}` has no physical model; it is only recorded"
+                    )
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn directive_usage(command: &str) -> Option<&'static str> {
+    DIRECTIVES
+        .iter()
+        .find(|(names, _)| names.contains(&command))
+        .map(|(_, usage)| *usage)
+}
+
+fn referenced_residues(instruction: &ContractInstruction) -> Vec<ResidueId> {
+    match instruction {
+        ContractInstruction::Rotate { residue, .. }
+        | ContractInstruction::AddModification { residue, .. } => vec![*residue],
+        ContractInstruction::DefineDomain { start, end, .. } => vec![*start, *end],
+        ContractInstruction::RestrainDistance { left, right, .. } => vec![*left, *right],
+        ContractInstruction::RestrainDihedral { residues, .. } => residues.to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+/// Span of the argument token naming residue `id`, if one does.
+fn residue_token_span(tokens: &[(String, Range<usize>)], id: usize) -> Option<Range<usize>> {
+    tokens.iter().find_map(|(token, span)| {
+        let value = split_key_value(token).map_or(token.clone(), |(_, value)| value);
+        let names_residue = parse_residue(&value) == Some(id)
+            || (is_range_token(&value)
+                && value
+                    .split(['-', ','])
+                    .any(|part| parse_residue(part) == Some(id)));
+        names_residue.then(|| span.clone())
+    })
+}
+
+fn chain_range_help(chain: &PeptideChain) -> String {
+    match (chain.residues().first(), chain.residues().last()) {
+        (Some(first), Some(last)) => {
+            format!("the chain has residues {} to {}", first.id.0, last.id.0)
+        }
+        _ => "the chain is empty".to_string(),
+    }
 }
 
 fn parse_line(raw_line: &str) -> Option<Vec<ContractInstruction>> {
-    let line_without_comments = raw_line.split('#').next().map(str::trim).unwrap_or("");
+    let line_without_comments = strip_comment(raw_line).trim();
 
     if line_without_comments.is_empty() {
         return None;
@@ -79,12 +409,13 @@ fn parse_line(raw_line: &str) -> Option<Vec<ContractInstruction>> {
         return None;
     }
 
-    let command = tokens.remove(0).to_lowercase();
+    let command = tokens.remove(0).text.to_lowercase();
+    let words: Vec<String> = tokens.iter().map(|token| token.text.clone()).collect();
     let mut instructions = Vec::new();
 
     match command.as_str() {
         "rotate" => {
-            if let Some(instr) = parse_rotate(tokens) {
+            if let Some(instr) = parse_rotate(words) {
                 instructions.push(instr);
             }
         }
@@ -92,16 +423,15 @@ fn parse_line(raw_line: &str) -> Option<Vec<ContractInstruction>> {
         "commit" => instructions.push(ContractInstruction::Commit),
         "rollback" | "revert" => instructions.push(ContractInstruction::Rollback),
         "ghost" => {
-            if let Some(state) = tokens.first() {
+            if let Some(state) = words.first() {
                 let flag = matches!(state.as_str().to_lowercase().as_str(), "on" | "true" | "1");
                 instructions.push(ContractInstruction::GhostMode(flag));
             }
         }
         "span_alias" | "alias" | "label" => {
-            let alias = tokens.join(" ");
-            let alias = alias.trim_matches(|c| c == '"' || c == '\'');
+            let alias = words.join(" ");
             if !alias.is_empty() {
-                instructions.push(ContractInstruction::SpanAlias(alias.to_owned()));
+                instructions.push(ContractInstruction::SpanAlias(alias));
             }
         }
         "define_domain" | "domain" => {
@@ -119,13 +449,43 @@ fn parse_line(raw_line: &str) -> Option<Vec<ContractInstruction>> {
                 instructions.push(instr);
             }
         }
+        "move_domain" | "domain_move" => {
+            if let Some(instr) = parse_move_domain(tokens) {
+                instructions.push(instr);
+            }
+        }
         "set_physics_level" | "physics_level" => {
-            if let Some(instr) = parse_set_physics_level(tokens) {
+            if let Some(instr) = parse_set_physics_level(words) {
                 instructions.push(instr);
             }
         }
         "physics_span" | "set_span_physics" => {
-            if let Some(instr) = parse_set_span_physics(tokens) {
+            if let Some(instr) = parse_set_span_physics(words) {
+                instructions.push(instr);
+            }
+        }
+        "set_schedule" | "schedule" | "anneal" => {
+            if let Some(instr) = parse_set_schedule(words) {
+                instructions.push(instr);
+            }
+        }
+        "minimize" | "minimise" | "relax" => {
+            if let Some(instr) = parse_minimize(words) {
+                instructions.push(instr);
+            }
+        }
+        "restrain_distance" | "distance_restraint" | "noe" => {
+            if let Some(instr) = parse_restrain_distance(tokens) {
+                instructions.push(instr);
+            }
+        }
+        "restrain_dihedral" | "dihedral_restraint" => {
+            if let Some(instr) = parse_restrain_dihedral(tokens) {
+                instructions.push(instr);
+            }
+        }
+        "target_rmsd" | "target_structure" => {
+            if let Some(instr) = parse_target_rmsd(tokens) {
                 instructions.push(instr);
             }
         }
@@ -160,21 +520,21 @@ fn parse_rotate(tokens: Vec<String>) -> Option<ContractInstruction> {
     })
 }
 
-fn parse_define_domain(tokens: Vec<String>) -> Option<ContractInstruction> {
+fn parse_define_domain(tokens: Vec<Token>) -> Option<ContractInstruction> {
     if tokens.is_empty() {
         return None;
     }
 
     let mut name: Option<String> = None;
     let range_value = if tokens.len() == 1 {
-        tokens[0].clone()
+        tokens[0].text.clone()
     } else {
         let first = &tokens[0];
-        if is_range_token(first) {
-            first.clone()
+        if !first.quoted && is_range_token(&first.text) {
+            first.text.clone()
         } else {
-            name = Some(first.clone());
-            tokens.get(1).cloned().unwrap_or_else(|| first.clone())
+            name = Some(first.text.clone());
+            tokens[1].text.clone()
         }
     };
 
@@ -187,7 +547,7 @@ fn parse_define_domain(tokens: Vec<String>) -> Option<ContractInstruction> {
     })
 }
 
-fn parse_require_chaperone(tokens: Vec<String>) -> Option<ContractInstruction> {
+fn parse_require_chaperone(tokens: Vec<Token>) -> Option<ContractInstruction> {
     if tokens.is_empty() {
         return None;
     }
@@ -196,15 +556,14 @@ fn parse_require_chaperone(tokens: Vec<String>) -> Option<ContractInstruction> {
     let mut in_span = false;
 
     for token in tokens {
-        let lower = token.to_lowercase();
-        if lower == "for" || lower == "span" {
+        if token.is_keyword(&["for", "span"]) {
             in_span = true;
             continue;
         }
         if in_span {
-            span_parts.push(token);
+            span_parts.push(token.text);
         } else {
-            chaperone_parts.push(token);
+            chaperone_parts.push(token.text);
         }
     }
 
@@ -221,7 +580,7 @@ fn parse_require_chaperone(tokens: Vec<String>) -> Option<ContractInstruction> {
     Some(ContractInstruction::RequireChaperone { chaperone, span })
 }
 
-fn parse_add_modification(tokens: Vec<String>) -> Option<ContractInstruction> {
+fn parse_add_modification(tokens: Vec<Token>) -> Option<ContractInstruction> {
     if tokens.is_empty() {
         return None;
     }
@@ -230,12 +589,11 @@ fn parse_add_modification(tokens: Vec<String>) -> Option<ContractInstruction> {
     let mut residue_token: Option<String> = None;
     let mut iter = tokens.into_iter();
     while let Some(token) = iter.next() {
-        let lower = token.to_lowercase();
-        if lower == "at" || lower == "on" {
-            residue_token = iter.next();
+        if token.is_keyword(&["at", "on"]) {
+            residue_token = iter.next().map(|token| token.text);
             break;
         } else {
-            modification_parts.push(token);
+            modification_parts.push(token.text);
         }
     }
     let modification = modification_parts.join(" ");
@@ -251,6 +609,61 @@ fn parse_add_modification(tokens: Vec<String>) -> Option<ContractInstruction> {
     })
 }
 
+/// `move_domain <name> angle=10 axis=x dx=0.5 dy=0 dz=0`; the axis defaults
+/// to z. Other axes are given by components: `ax=1 ay=1 az=0`.
+fn parse_move_domain(tokens: Vec<Token>) -> Option<ContractInstruction> {
+    let mut domain = None;
+    let mut angle_degrees = 0.0;
+    let mut axis = [0.0, 0.0, 1.0];
+    let mut components: Option<[f64; 3]> = None;
+    let mut translation = [0.0; 3];
+    for token in tokens {
+        let Some((key, value)) = token.key_value() else {
+            if domain.is_some() {
+                return None;
+            }
+            domain = Some(token.text);
+            continue;
+        };
+        match key.as_str() {
+            "domain" | "name" => domain = Some(value),
+            "angle" | "theta" | "deg" => angle_degrees = parse_angle(&value)?,
+            "axis" => {
+                axis = match value.to_lowercase().as_str() {
+                    "x" => [1.0, 0.0, 0.0],
+                    "y" => [0.0, 1.0, 0.0],
+                    "z" => [0.0, 0.0, 1.0],
+                    _ => return None,
+                }
+            }
+            "ax" | "ay" | "az" => {
+                let component = components.get_or_insert([0.0; 3]);
+                component[axis_component(&key)] = value.parse::<f64>().ok()?;
+            }
+            "dx" | "dy" | "dz" => {
+                translation[axis_component(&key)] = value.parse::<f64>().ok()?;
+            }
+            _ => return None,
+        }
+    }
+
+    Some(ContractInstruction::MoveDomain {
+        domain: domain?,
+        angle_degrees,
+        axis: components.unwrap_or(axis),
+        translation,
+    })
+}
+
+/// 0, 1 or 2 for keys ending in `x`, `y` or `z`.
+fn axis_component(key: &str) -> usize {
+    match key.chars().last() {
+        Some('x') => 0,
+        Some('y') => 1,
+        _ => 2,
+    }
+}
+
 fn parse_set_physics_level(tokens: Vec<String>) -> Option<ContractInstruction> {
     if tokens.is_empty() {
         return None;
@@ -295,6 +708,204 @@ fn parse_set_span_physics(tokens: Vec<String>) -> Option<ContractInstruction> {
     Some(ContractInstruction::SetSpanPhysics(mode))
 }
 
+fn parse_minimize(tokens: Vec<String>) -> Option<ContractInstruction> {
+    let mut steps = DEFAULT_MINIMIZE_STEPS;
+    let mut tolerance = DEFAULT_MINIMIZE_TOLERANCE;
+    let mut positional = 0;
+    for token in tokens {
+        let (key, value) = match split_key_value(&token) {
+            Some((key, value)) => (key, value),
+            None => {
+                positional += 1;
+                let key = if positional == 1 {
+                    "steps"
+                } else {
+                    "tolerance"
+                };
+                (key.to_string(), token)
+            }
+        };
+        match key.as_str() {
+            "steps" | "max_steps" | "iterations" => steps = value.parse::<usize>().ok()?,
+            "tolerance" | "tol" => tolerance = value.parse::<f64>().ok()?,
+            _ => return None,
+        }
+    }
+    if !(tolerance.is_finite() && tolerance > 0.0) {
+        return None;
+    }
+
+    Some(ContractInstruction::Minimize { steps, tolerance })
+}
+
+/// `restrain_distance 3 17 5.5 k=10`, or keyed as `a= b= target= k=`.
+fn parse_restrain_distance(tokens: Vec<Token>) -> Option<ContractInstruction> {
+    let arguments = restraint_arguments(tokens, &["a", "b", "target", "k"])?;
+    let target = arguments.get("target")?.parse::<f64>().ok()?;
+    if !(target.is_finite() && target >= 0.0) {
+        return None;
+    }
+    Some(ContractInstruction::RestrainDistance {
+        left: ResidueId(parse_residue(arguments.get("a")?)?),
+        right: ResidueId(parse_residue(arguments.get("b")?)?),
+        target,
+        force_constant: force_constant(&arguments, DEFAULT_DISTANCE_RESTRAINT_K)?,
+    })
+}
+
+/// `restrain_dihedral 4 5 6 7 -60 k=50`, or keyed as `a= b= c= d= target= k=`.
+fn parse_restrain_dihedral(tokens: Vec<Token>) -> Option<ContractInstruction> {
+    let arguments = restraint_arguments(tokens, &["a", "b", "c", "d", "target", "k"])?;
+    let target_degrees = parse_angle(arguments.get("target")?)?;
+    if !target_degrees.is_finite() {
+        return None;
+    }
+    let residue = |key: &str| arguments.get(key).and_then(|value| parse_residue(value));
+    Some(ContractInstruction::RestrainDihedral {
+        residues: [
+            ResidueId(residue("a")?),
+            ResidueId(residue("b")?),
+            ResidueId(residue("c")?),
+            ResidueId(residue("d")?),
+        ],
+        target_degrees,
+        force_constant: force_constant(&arguments, DEFAULT_DIHEDRAL_RESTRAINT_K)?,
+    })
+}
+
+/// `target_rmsd reference.pdb target=1.5 k=10`; `target` defaults to 0 Å.
+fn parse_target_rmsd(tokens: Vec<Token>) -> Option<ContractInstruction> {
+    let arguments = restraint_arguments(tokens, &["path", "target", "k"])?;
+    let path = arguments.get("path").filter(|path| !path.is_empty())?;
+    let target = match arguments.get("target") {
+        Some(value) => value.parse::<f64>().ok()?,
+        None => 0.0,
+    };
+    if !(target.is_finite() && target >= 0.0) {
+        return None;
+    }
+    Some(ContractInstruction::TargetRmsd {
+        path: path.clone(),
+        target,
+        force_constant: force_constant(&arguments, DEFAULT_RMSD_RESTRAINT_K)?,
+    })
+}
+
+/// Restraint arguments by key. Bare tokens fill `keys` in order; `force=`
+/// and `force_constant=` are accepted for `k=`. Unknown keys and surplus
+/// bare tokens fail the line.
+fn restraint_arguments(tokens: Vec<Token>, keys: &[&str]) -> Option<HashMap<String, String>> {
+    let mut arguments = HashMap::new();
+    let mut positional = keys.iter();
+    for token in tokens {
+        let (key, value) = match token.key_value() {
+            Some((key, value)) => match key.as_str() {
+                "force" | "force_constant" => ("k".to_string(), value),
+                _ if keys.contains(&key.as_str()) => (key, value),
+                _ => return None,
+            },
+            None => {
+                let key = positional.find(|key| !arguments.contains_key(**key))?;
+                (key.to_string(), token.text)
+            }
+        };
+        arguments.insert(key, value);
+    }
+    Some(arguments)
+}
+
+fn force_constant(arguments: &HashMap<String, String>, default: f64) -> Option<f64> {
+    let value = match arguments.get("k") {
+        Some(value) => value.parse::<f64>().ok()?,
+        None => default,
+    };
+    (value.is_finite() && value >= 0.0).then_some(value)
+}
+
+/// `set_schedule <kind> key=value ...`, e.g.
+/// `set_schedule cyclic high=450 low=300 period=20`.
+fn parse_set_schedule(tokens: Vec<String>) -> Option<ContractInstruction> {
+    let mut kind = None;
+    let mut params: HashMap<String, String> = HashMap::new();
+    for token in tokens {
+        match split_key_value(&token) {
+            Some((key, value)) if key == "kind" || key == "type" => kind = Some(value),
+            Some((key, value)) => {
+                params.insert(key, value);
+            }
+            None if kind.is_none() => kind = Some(token),
+            None => return None,
+        }
+    }
+    let number = |keys: &[&str]| -> Option<Option<f64>> {
+        match keys.iter().find_map(|key| params.get(*key)) {
+            Some(value) => value
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .map(Some),
+            None => Some(None),
+        }
+    };
+    let count = |keys: &[&str]| -> Option<Option<usize>> {
+        match keys.iter().find_map(|key| params.get(*key)) {
+            Some(value) => value.parse::<usize>().ok().map(Some),
+            None => Some(None),
+        }
+    };
+    let temperature = |value: f64| (value > 0.0).then_some(value);
+
+    let schedule = match kind?.to_lowercase().as_str() {
+        "constant" | "fixed" => TemperatureSchedule::Constant,
+        "linear" => TemperatureSchedule::Linear {
+            start: temperature(number(&["start", "from"])??)?,
+            end: temperature(number(&["end", "to"])??)?,
+            steps: count(&["steps"])??,
+        },
+        "exponential" | "geometric" => {
+            let factor = number(&["factor", "rate", "alpha"])?.unwrap_or(DEFAULT_COOLING_FACTOR);
+            if factor <= 0.0 {
+                return None;
+            }
+            TemperatureSchedule::Exponential {
+                start: temperature(number(&["start", "from"])??)?,
+                end: temperature(number(&["end", "to"])??)?,
+                factor,
+            }
+        }
+        "cyclic" | "sawtooth" => {
+            let period = count(&["period", "steps"])??;
+            if period == 0 {
+                return None;
+            }
+            TemperatureSchedule::Cyclic {
+                high: temperature(number(&["high", "max"])??)?,
+                low: temperature(number(&["low", "min"])??)?,
+                period,
+            }
+        }
+        "adaptive" => {
+            let target_acceptance =
+                number(&["target", "acceptance"])?.unwrap_or(DEFAULT_TARGET_ACCEPTANCE);
+            let min = temperature(number(&["min", "low"])?.unwrap_or(1.0))?;
+            let max = number(&["max", "high"])?.unwrap_or(f64::MAX);
+            let window = count(&["window"])?.unwrap_or(DEFAULT_ADAPTIVE_WINDOW);
+            if !(0.0..1.0).contains(&target_acceptance) || min > max || window == 0 {
+                return None;
+            }
+            TemperatureSchedule::Adaptive {
+                target_acceptance,
+                min,
+                max,
+                window,
+            }
+        }
+        _ => return None,
+    };
+
+    Some(ContractInstruction::SetSchedule(schedule))
+}
+
 fn parse_range(token: &str) -> Option<(usize, usize)> {
     let cleaned = token.trim_matches(|c: char| c == '[' || c == ']' || c.is_whitespace());
     let mut parts = cleaned.split(['-', ',']);
@@ -404,17 +1015,71 @@ fn parse_rotate_keyed(tokens: Vec<String>) -> Option<(usize, f64, u64)> {
     Some((residue, angle, duration))
 }
 
-fn tokenize(input: &str) -> Vec<String> {
+/// A directive argument. Quoted arguments are literal text: they are never
+/// keywords such as `for` or `at`, nor `key=value` pairs.
+struct Token {
+    text: String,
+    quoted: bool,
+}
+
+impl Token {
+    fn is_keyword(&self, keywords: &[&str]) -> bool {
+        !self.quoted && keywords.contains(&self.text.to_lowercase().as_str())
+    }
+
+    fn key_value(&self) -> Option<(String, String)> {
+        if self.quoted {
+            None
+        } else {
+            split_key_value(&self.text)
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Vec<Token> {
+    tokenize_spanned(input)
+        .into_iter()
+        .map(|(text, span)| Token {
+            quoted: input[span].starts_with(['"', '\'']),
+            text,
+        })
+        .collect()
+}
+
+/// Tokens with their byte ranges in `input`. Inside quotes a backslash
+/// escapes the next character; `\n`, `\r` and `\t` stand for control
+/// characters.
+fn tokenize_spanned(input: &str) -> Vec<(String, Range<usize>)> {
     let mut tokens = Vec::new();
     let mut current = String::new();
+    let mut start = 0;
     let mut in_quotes = false;
+    let mut escaped = false;
     let mut quote_char = '\0';
 
-    for ch in input.chars() {
+    let flush = |tokens: &mut Vec<(String, Range<usize>)>,
+                 current: &mut String,
+                 start: usize,
+                 end: usize| {
+        if !current.is_empty() {
+            tokens.push((std::mem::take(current), start..end));
+        }
+    };
+
+    for (offset, ch) in input.char_indices() {
         if in_quotes {
-            if ch == quote_char {
-                tokens.push(current.clone());
-                current.clear();
+            if escaped {
+                current.push(match ch {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    other => other,
+                });
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == quote_char {
+                tokens.push((std::mem::take(&mut current), start..offset + 1));
                 in_quotes = false;
             } else {
                 current.push(ch);
@@ -424,39 +1089,23 @@ fn tokenize(input: &str) -> Vec<String> {
 
         match ch {
             '"' | '\'' => {
-                if !current.is_empty() {
-                    tokens.push(current.clone());
-                    current.clear();
-                }
+                flush(&mut tokens, &mut current, start, offset);
                 in_quotes = true;
                 quote_char = ch;
+                start = offset;
             }
-            '{' | '}' | '(' | ')' => {
-                if !current.is_empty() {
-                    tokens.push(current.clone());
-                    current.clear();
-                }
-            }
-            ',' | ';' => {
-                if !current.is_empty() {
-                    tokens.push(current.clone());
-                    current.clear();
-                }
-            }
-            c if c.is_whitespace() => {
-                if !current.is_empty() {
-                    tokens.push(current.clone());
-                    current.clear();
+            '{' | '}' | '(' | ')' | ',' | ';' => flush(&mut tokens, &mut current, start, offset),
+            c if c.is_whitespace() => flush(&mut tokens, &mut current, start, offset),
+            _ => {
+                if current.is_empty() {
+                    start = offset;
                 }
+                current.push(ch);
             }
-            _ => current.push(ch),
         }
     }
 
-    if !current.is_empty() {
-        tokens.push(current);
-    }
-
+    flush(&mut tokens, &mut current, start, input.len());
     tokens
 }
 
@@ -588,9 +1237,10 @@ mod tests {
             "add_modification phosphorylation at S50",
             "set_physics_level GB",
             "physics_span on",
+            "move_domain helixA angle=8 axis=x dz=-0.5",
         ];
         let contract = FoldingContract::from_lines(&lines);
-        assert_eq!(contract.instructions.len(), 5);
+        assert_eq!(contract.instructions.len(), 6);
 
         match &contract.instructions[0] {
             ContractInstruction::DefineDomain { name, start, end } => {
@@ -633,5 +1283,227 @@ mod tests {
             }
             other => panic!("unexpected instruction: {other:?}"),
         }
+
+        match &contract.instructions[5] {
+            ContractInstruction::MoveDomain {
+                domain,
+                angle_degrees,
+                axis,
+                translation,
+            } => {
+                assert_eq!(domain, "helixA");
+                assert!((angle_degrees - 8.0).abs() < 1e-9);
+                assert_eq!(*axis, [1.0, 0.0, 0.0]);
+                assert_eq!(*translation, [0.0, 0.0, -0.5]);
+            }
+            other => panic!("unexpected instruction: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_set_schedule_directive() {
+        let lines = [
+            "set_schedule linear start=400 end=300 steps=20",
+            "set_schedule exponential start=450 end=300",
+            "set_schedule cyclic high=450 low=300 period=12",
+            "set_schedule adaptive target=0.3 min=250 max=600",
+            "set_schedule constant",
+            "set_schedule cyclic high=450 low=300 period=0",
+            "set_schedule adaptive target=1.5",
+            "set_schedule linear start=400",
+        ];
+        let contract = FoldingContract::from_lines(&lines);
+        let schedules: Vec<TemperatureSchedule> = contract
+            .instructions
+            .into_iter()
+            .map(|instruction| match instruction {
+                ContractInstruction::SetSchedule(schedule) => schedule,
+                other => panic!("unexpected instruction: {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            schedules,
+            vec![
+                TemperatureSchedule::Linear {
+                    start: 400.0,
+                    end: 300.0,
+                    steps: 20
+                },
+                TemperatureSchedule::Exponential {
+                    start: 450.0,
+                    end: 300.0,
+                    factor: DEFAULT_COOLING_FACTOR
+                },
+                TemperatureSchedule::Cyclic {
+                    high: 450.0,
+                    low: 300.0,
+                    period: 12
+                },
+                TemperatureSchedule::Adaptive {
+                    target_acceptance: 0.3,
+                    min: 250.0,
+                    max: 600.0,
+                    window: DEFAULT_ADAPTIVE_WINDOW
+                },
+                TemperatureSchedule::Constant,
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_reports_errors_and_warnings() {
+        use folding_molecule::{AminoAcid, StartingConformation};
+
+        let chain =
+            PeptideChain::from_sequence(&[AminoAcid::Alanine; 6], StartingConformation::Helix);
+        let source = "rotat residue=3 angle=10\n\
+                      rotate residue=9 angle=10  # past the end\n\
+                      minimize steps=ten\n\
+                      define_domain core 2-4\n\
+                      rollback\n\
+                      move_domain tail dz=0.2\n\
+                      rotate residue=2 angle=5\n\
+                      add_modification sumoylation at 3\n";
+        let parsed = FoldingContract::parse(source, Some(&chain));
+
+        let errors: Vec<&Diagnostic> = parsed.errors().collect();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0].message, "unknown directive `rotat`");
+        assert_eq!(errors[0].help.as_deref(), Some("did you mean `rotate`?"));
+        assert_eq!(
+            (errors[0].line, errors[0].column, errors[0].span.clone()),
+            (1, 1, 0..5)
+        );
+        assert_eq!(errors[1].message, "residue 9 is not in the target chain");
+        assert_eq!((errors[1].line, errors[1].column), (2, 8));
+        assert!(errors[2].message.contains("malformed `minimize`"));
+
+        let warnings: Vec<&Diagnostic> = parsed.warnings().collect();
+        assert_eq!(warnings.len(), 3);
+        assert_eq!(warnings[0].line, 5);
+        assert!(warnings[1].message.contains("`tail` is not defined"));
+        assert!(warnings[2]
+            .message
+            .contains("`sumoylation` has no physical model"));
+
+        // define_domain, rollback, move_domain, rotate and add_modification survive.
+        assert_eq!(parsed.contract.instructions.len(), 5);
+        assert!(!FoldingContract::parse("commit\nrotate 2 10\n", Some(&chain)).has_errors());
+    }
+
+    #[test]
+    fn parse_expands_loops_with_source_positions() {
+        use folding_molecule::{AminoAcid, StartingConformation};
+
+        let chain =
+            PeptideChain::from_sequence(&[AminoAcid::Glycine; 6], StartingConformation::Extended);
+        let source = "let angle = 12\n\
+                      for r in 2..9 step 2 {\n\
+                      \x20   rotate residue=r angle=angle*r\n\
+                      \x20   move_domain hinge dz=0.1\n\
+                      }\n";
+        let parsed = FoldingContract::parse(source, Some(&chain));
+
+        // r = 2, 4, 6 rotate; r = 8 is past the chain and is reported once.
+        let rotations: Vec<(usize, f64)> = parsed
+            .contract
+            .instructions
+            .iter()
+            .filter_map(|instruction| match instruction {
+                ContractInstruction::Rotate {
+                    residue,
+                    angle_degrees,
+                    ..
+                } => Some((residue.0, *angle_degrees)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(rotations, [(2, 24.0), (4, 48.0), (6, 72.0)]);
+
+        let errors: Vec<&Diagnostic> = parsed.errors().collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "residue 8 is not in the target chain");
+        assert_eq!((errors[0].line, errors[0].column), (3, 12));
+        assert_eq!(&errors[0].source_line[errors[0].span.clone()], "residue=r");
+        assert_eq!(parsed.warnings().count(), 1);
+    }
+
+    #[test]
+    fn parses_minimize_directive() {
+        let lines = [
+            "minimize steps=500 tolerance=1e-3",
+            "minimize 50",
+            "minimize",
+            "minimize steps=10 tolerance=-1",
+        ];
+        let contract = FoldingContract::from_lines(&lines);
+        assert_eq!(contract.instructions.len(), 3);
+        match contract.instructions[0] {
+            ContractInstruction::Minimize { steps, tolerance } => {
+                assert_eq!(steps, 500);
+                assert!((tolerance - 1e-3).abs() < 1e-12);
+            }
+            ref other => panic!("unexpected instruction: {other:?}"),
+        }
+        assert!(matches!(
+            contract.instructions[1],
+            ContractInstruction::Minimize { steps: 50, .. }
+        ));
+        assert!(matches!(
+            contract.instructions[2],
+            ContractInstruction::Minimize {
+                steps: DEFAULT_MINIMIZE_STEPS,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parses_restraint_directives() {
+        let lines = [
+            "restrain_distance 3 17 5.5 k=2",
+            "noe a=4 b=9 target=3",
+            "restrain_dihedral 4 5 6 7 -60",
+            "target_rmsd \"ref/native.pdb\" target=1.5",
+            "restrain_distance 3 17",
+            "restrain_distance 3 17 -1",
+            "target_rmsd ref.pdb k=-1",
+        ];
+        let contract = FoldingContract::from_lines(&lines);
+        assert_eq!(
+            contract.instructions,
+            [
+                ContractInstruction::RestrainDistance {
+                    left: ResidueId(3),
+                    right: ResidueId(17),
+                    target: 5.5,
+                    force_constant: 2.0,
+                },
+                ContractInstruction::RestrainDistance {
+                    left: ResidueId(4),
+                    right: ResidueId(9),
+                    target: 3.0,
+                    force_constant: DEFAULT_DISTANCE_RESTRAINT_K,
+                },
+                ContractInstruction::RestrainDihedral {
+                    residues: [ResidueId(4), ResidueId(5), ResidueId(6), ResidueId(7)],
+                    target_degrees: -60.0,
+                    force_constant: DEFAULT_DIHEDRAL_RESTRAINT_K,
+                },
+                ContractInstruction::TargetRmsd {
+                    path: "ref/native.pdb".into(),
+                    target: 1.5,
+                    force_constant: DEFAULT_RMSD_RESTRAINT_K,
+                },
+            ]
+        );
+
+        let chain = PeptideChain::from_sequence(
+            &[folding_molecule::AminoAcid::Alanine; 10],
+            folding_molecule::StartingConformation::Extended,
+        );
+        let parsed = FoldingContract::parse("restrain_dihedral 1 2 3 12 180\n", Some(&chain));
+        assert_eq!(parsed.errors().count(), 1);
+        assert!(parsed.errors().next().unwrap().message.contains("12"));
     }
 }
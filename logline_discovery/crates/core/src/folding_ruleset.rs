@@ -1,5 +1,6 @@
-use folding_molecule::{BondConstraintSet, PeptideChain, ResidueId};
+use folding_molecule::{BondConstraintSet, CellList, PeptideChain, ResidueId};
 use folding_time::trajectory::SpanRecord;
+use serde::{Deserialize, Serialize};
 
 /// Validates spans against chemical and informational constraints.
 #[derive(Debug, Clone)]
@@ -27,7 +28,8 @@ impl Default for Ruleset {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum RuleViolation {
     RotationLimitExceeded {
         residue: ResidueId,
@@ -66,9 +68,38 @@ pub enum RuleViolation {
         min: f64,
         max: f64,
     },
+    DomainLocked {
+        residue: ResidueId,
+        domain: String,
+    },
+    UnknownDomain {
+        name: String,
+    },
+    UnknownResidue {
+        residue: ResidueId,
+    },
+    ReferenceStructureUnavailable {
+        path: String,
+        reason: String,
+    },
+    /// `set_schedule` in a replica-exchange run, where every rung keeps its
+    /// ladder temperature.
+    ScheduleUnderReplicaExchange,
 }
 
 impl Ruleset {
+    /// Limits suited to Cα traces read from real structures, where
+    /// consecutive Cα atoms sit ~3.8 Å apart (~2.9 Å across a cis peptide)
+    /// and virtual Cα angles span roughly 80°–150°.
+    pub fn alpha_carbon_trace() -> Self {
+        Self {
+            min_distance_angstrom: Some(3.0),
+            bond_distance_range: Some((2.7, 4.2)),
+            bond_angle_range: Some((1.35, 2.7)),
+            ..Self::default()
+        }
+    }
+
     pub fn with_rotation_limit(mut self, limit: f64) -> Self {
         self.max_rotation_degrees = limit;
         self
@@ -148,19 +179,17 @@ impl Ruleset {
     }
 
     pub fn check_structure(&self, chain: &PeptideChain) -> Result<(), RuleViolation> {
-        if let Some(min_distance) = self.min_distance_angstrom {
+        if let Some(min_distance) = self.min_distance_angstrom.filter(|d| *d > 0.0) {
             let residues = chain.residues();
-            for (i, left) in residues.iter().enumerate() {
-                for right in residues.iter().skip(i + 1) {
-                    let distance = distance(left.position(), right.position());
-                    if distance < min_distance {
-                        return Err(RuleViolation::StructuralClash {
-                            residue_a: left.id,
-                            residue_b: right.id,
-                            distance,
-                        });
-                    }
-                }
+            // Pairs come back in (left, right) order, so the first clash is the
+            // same one a full double loop would report.
+            let cells = CellList::from_chain(chain, min_distance);
+            if let Some(pair) = cells.pairs(1).first() {
+                return Err(RuleViolation::StructuralClash {
+                    residue_a: residues[pair.left].id,
+                    residue_b: residues[pair.right].id,
+                    distance: pair.distance,
+                });
             }
         }
 
@@ -1,40 +1,88 @@
+use std::path::Path;
 use std::time::Duration;
 
-use folding_molecule::{EnergyModel, PeptideChain, ResidueId};
+use folding_molecule::{
+    contact_map, read_structure, AggregationShield, EnergyModel, ImplicitSolvent,
+    MinimizationReport, Minimizer, Modification, PeptideChain, ResidueId, Restraint,
+    RestraintStatus, StructureMetrics, StructureReference, TrajectoryFrame,
+};
+use folding_time::trajectory::SpanRecord;
 use folding_time::{RotationClock, Trajectory};
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 
+use crate::checkpoint::{CheckpointError, EngineCheckpoint};
+use crate::dynamics::LangevinIntegrator;
 use crate::folding_parser::{ContractInstruction, FoldingContract, PhysicsLevel, PhysicsSpanMode};
 use crate::folding_ruleset::{RuleViolation, Ruleset};
 use crate::micro_oscillator::MicroOscillator;
 use crate::physics_bridge::{self, PhysicsRequest, PhysicsSpanMetrics};
 use crate::protein_state::{EnergyState, ProteinSnapshot, ProteinState};
+use crate::replica_exchange::ReplicaExchangeReport;
 use crate::rotation_solver::{RotationCommand, RotationOutcome, RotationSolver};
+use crate::schedule::TemperatureSchedule;
 use crate::validation::{ValidationEvent, Validator};
 
-#[derive(Clone, Debug)]
-pub enum TemperatureSchedule {
-    Constant,
-    Linear { start: f64, end: f64, steps: usize },
+/// Penalty (kcal/mol) per shielded hydrophobic contact. Chaperonins
+/// enclose their client, so they suppress collapse more than holdases.
+fn chaperone_strength(chaperone: &str) -> f64 {
+    match chaperone.trim().to_lowercase().as_str() {
+        "groel" | "groes" | "hsp60" | "tric" | "cct" => 1.5,
+        "hsp70" | "dnak" | "bip" | "hsc70" => 1.0,
+        "hsp90" | "htpg" => 0.8,
+        _ => 0.5,
+    }
 }
 
-impl TemperatureSchedule {
-    pub fn temperature_for_step(&self, step: usize, initial: f64) -> f64 {
-        match self {
-            TemperatureSchedule::Constant => initial,
-            TemperatureSchedule::Linear { start, end, steps } => {
-                if *steps == 0 {
-                    *end
-                } else {
-                    let ratio = (step.min(*steps) as f64) / (*steps as f64);
-                    start + (end - start) * ratio
-                }
-            }
+/// Rotates `positions` by `angle` (radians) about `axis` through their
+/// centroid (Rodrigues), then shifts them by `translation`.
+fn rigid_move(positions: &mut [[f64; 3]], axis: [f64; 3], angle: f64, translation: [f64; 3]) {
+    if positions.is_empty() {
+        return;
+    }
+    let length = (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt();
+    let k = if length > 0.0 {
+        [axis[0] / length, axis[1] / length, axis[2] / length]
+    } else {
+        [0.0, 0.0, 1.0]
+    };
+    let n = positions.len() as f64;
+    let mut center = [0.0; 3];
+    for p in positions.iter() {
+        for axis in 0..3 {
+            center[axis] += p[axis] / n;
         }
     }
+    let (sin, cos) = angle.sin_cos();
+    for p in positions.iter_mut() {
+        let v = [p[0] - center[0], p[1] - center[1], p[2] - center[2]];
+        let cross = [
+            k[1] * v[2] - k[2] * v[1],
+            k[2] * v[0] - k[0] * v[2],
+            k[0] * v[1] - k[1] * v[0],
+        ];
+        let dot = k[0] * v[0] + k[1] * v[1] + k[2] * v[2];
+        for axis in 0..3 {
+            p[axis] = center[axis]
+                + v[axis] * cos
+                + cross[axis] * sin
+                + k[axis] * dot * (1.0 - cos)
+                + translation[axis];
+        }
+    }
+}
+
+fn unit_step_scale() -> f64 {
+    1.0
+}
+
+/// Metropolis acceptance probability `min(1, e^exponent)`.
+pub(crate) fn boltzmann_acceptance(exponent: f64) -> f64 {
+    exponent.clamp(-700.0, 50.0).exp().min(1.0)
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct MetropolisStats {
     pub accepted: usize,
     pub rejected: usize,
@@ -74,18 +122,42 @@ pub struct FoldingEngine {
     ghost_trajectory: Trajectory,
     temperature: f64,
     boltzmann_constant: f64,
-    rng: StdRng,
+    /// ChaCha12, the generator behind `StdRng`, held by name so checkpoints
+    /// can serialize its stream position.
+    rng: ChaCha12Rng,
     temperature_schedule: Option<TemperatureSchedule>,
     initial_temperature: f64,
     step_index: usize,
+    /// Step at which the current schedule was installed.
+    schedule_origin: usize,
     metropolis_stats: MetropolisStats,
+    /// Metropolis counts at the last adaptive adjustment.
+    adaptive_mark: MetropolisStats,
+    /// Fraction of each `rotate` angle applied; only the adaptive schedule
+    /// moves it away from 1.
+    step_scale: f64,
     domains: Vec<DomainDefinition>,
     chaperone_requirements: Vec<ChaperoneRequirement>,
     modifications: Vec<PostTranslationalModification>,
+    /// Aggregation shields the energy model carried before any chaperone.
+    base_shield_count: usize,
+    /// Restraints the energy model carried before any contract added one.
+    base_restraint_count: usize,
     physics_level: PhysicsLevel,
+    /// Set when the engine, not the caller, enabled GB/SA for `PhysicsLevel::Gb`.
+    auto_solvent: bool,
     span_physics_mode: PhysicsSpanMode,
     physics_spans: Vec<String>,
     physics_span_metrics: Vec<PhysicsSpanRecord>,
+    /// Structure the caller asked metrics to be measured against.
+    reference_structure: Option<PeptideChain>,
+    /// What this run's metrics are measured against: `reference_structure`
+    /// when given, the starting chain otherwise.
+    structure_reference: StructureReference,
+    structure_frames: Vec<StructureFrame>,
+    /// Record coordinates at most every this many steps; `None` records none.
+    trajectory_interval: Option<usize>,
+    trajectory_frames: Vec<TrajectoryFrame>,
 }
 
 #[cfg(test)]
@@ -93,25 +165,516 @@ mod tests {
     use super::*;
 
     #[test]
-    fn constant_schedule_returns_initial() {
-        let schedule = TemperatureSchedule::Constant;
-        assert_eq!(schedule.temperature_for_step(10, 300.0), 300.0);
+    fn set_schedule_instruction_drives_temperature() {
+        use folding_molecule::{AminoAcid, StartingConformation};
+
+        let sequence = [AminoAcid::Alanine; 8];
+        let chain = PeptideChain::from_sequence(&sequence, StartingConformation::Extended);
+        let mut engine = FoldingEngineBuilder::new()
+            .with_chain(chain)
+            .with_energy_model(EnergyModel::alpha_carbon_trace())
+            .with_ruleset(Ruleset::alpha_carbon_trace())
+            .with_oscillator(MicroOscillator::new(0.0, 0.0))
+            .with_rng_seed(9)
+            .build();
+        let contract = FoldingContract::from_lines(&[
+            "rotate residue=3 angle=5",
+            "set_schedule exponential start=600 end=300 factor=0.5",
+            "rotate residue=4 angle=5",
+            "rotate residue=5 angle=5",
+        ]);
+        engine.execute_contract(&contract);
+        // Installed at step 1: steps 1 and 2 map to schedule steps 0 and 1.
+        assert_eq!(engine.temperature(), 300.0);
+        assert_eq!(engine.schedule_origin, 1);
+    }
+
+    #[test]
+    fn adaptive_schedule_shrinks_rotation_steps() {
+        use folding_molecule::{AminoAcid, StartingConformation};
+
+        let sequence = [AminoAcid::Alanine; 8];
+        let chain = PeptideChain::from_sequence(&sequence, StartingConformation::Extended);
+        let mut engine = FoldingEngineBuilder::new()
+            .with_chain(chain)
+            .with_energy_model(EnergyModel::alpha_carbon_trace())
+            .with_ruleset(Ruleset::alpha_carbon_trace())
+            .with_oscillator(MicroOscillator::new(0.0, 0.0))
+            .with_rng_seed(9)
+            .build();
+        engine.set_temperature_schedule(TemperatureSchedule::Adaptive {
+            target_acceptance: 0.5,
+            min: 200.0,
+            max: 400.0,
+            window: 2,
+        });
+        engine.metropolis_stats.record_reject();
+        engine.metropolis_stats.record_reject();
+
+        let mut run = RunLog::default();
+        engine.execute_instruction(
+            &ContractInstruction::Rotate {
+                residue: ResidueId(3),
+                angle_degrees: 11.0,
+                duration_ms: 1,
+            },
+            &mut run,
+        );
+        assert!((engine.step_scale() - 1.0 / 1.1).abs() < 1e-12);
+        assert!(run.rejections.is_empty(), "{:?}", run.rejections);
+        assert!((run.applied_rotations[0].applied_angle - 10.0).abs() < 1e-9);
+
+        engine.set_temperature_schedule(TemperatureSchedule::Constant);
+        assert_eq!(engine.step_scale(), 1.0);
+    }
+
+    #[test]
+    fn modifications_do_not_outlive_their_run() {
+        use folding_molecule::{AminoAcid, StartingConformation};
+
+        let sequence = [AminoAcid::Serine, AminoAcid::Alanine, AminoAcid::Lysine];
+        let chain = PeptideChain::from_sequence(&sequence, StartingConformation::Extended);
+        let mut engine = FoldingEngineBuilder::new()
+            .with_chain(chain)
+            .with_energy_model(EnergyModel::alpha_carbon_trace())
+            .with_ruleset(Ruleset::alpha_carbon_trace())
+            .build();
+        let modified = engine.execute_contract(&FoldingContract::from_lines(&[
+            "add_modification phosphorylation at S1",
+            "require_chaperone GroEL for missing",
+        ]));
+        assert!(modified.final_chain.residues()[0].modification.is_some());
+        assert_eq!(modified.modifications.len(), 1);
+        assert!(modified.chaperone_requirements.is_empty());
+
+        let plain = engine.execute_contract(&FoldingContract::from_lines(&[]));
+        assert!(plain.final_chain.residues()[0].modification.is_none());
+        assert!(plain.modifications.is_empty());
     }
 
     #[test]
-    fn linear_schedule_interpolates() {
-        let schedule = TemperatureSchedule::Linear {
-            start: 400.0,
-            end: 300.0,
-            steps: 10,
+    fn domain_modification_and_chaperone_directives_change_physics() {
+        use folding_molecule::{AminoAcid, StartingConformation};
+
+        let sequence = [
+            AminoAcid::Serine,
+            AminoAcid::Leucine,
+            AminoAcid::Isoleucine,
+            AminoAcid::Valine,
+            AminoAcid::Leucine,
+            AminoAcid::Alanine,
+            AminoAcid::Phenylalanine,
+            AminoAcid::Glycine,
+            AminoAcid::Lysine,
+            AminoAcid::Serine,
+        ];
+        let run = |lines: &[&str]| {
+            let chain = PeptideChain::from_sequence(&sequence, StartingConformation::Helix);
+            let mut engine = FoldingEngineBuilder::new()
+                .with_chain(chain)
+                .with_energy_model(EnergyModel::alpha_carbon_trace())
+                .with_ruleset(Ruleset::alpha_carbon_trace())
+                .with_oscillator(MicroOscillator::new(0.0, 0.0))
+                .with_temperature(1.0e6)
+                .with_rng_seed(3)
+                .build();
+            engine.execute_contract(&FoldingContract::from_lines(lines))
         };
-        assert!((schedule.temperature_for_step(0, 400.0) - 400.0).abs() < 1e-6);
-        assert!((schedule.temperature_for_step(5, 400.0) - 350.0).abs() < 1e-6);
-        assert!((schedule.temperature_for_step(10, 400.0) - 300.0).abs() < 1e-6);
-        assert!((schedule.temperature_for_step(20, 400.0) - 300.0).abs() < 1e-6);
+        let baseline = run(&[]).final_energy.total_potential;
+
+        let modified = run(&["add_modification phosphorylation at S1"]);
+        assert!(modified.rejections.is_empty());
+        assert!(modified.final_chain.residues()[0].modification.is_some());
+        assert_ne!(modified.final_energy.total_potential, baseline);
+        let unknown = run(&["add_modification sumoylation at K9"]);
+        assert!(unknown.rejections.is_empty());
+        assert_eq!(unknown.modifications.len(), 1);
+        assert_eq!(unknown.final_energy.total_potential, baseline);
+
+        let shielded = run(&["define_domain core 2-7", "require_chaperone GroEL for core"]);
+        assert!(shielded.rejections.is_empty());
+        assert!(shielded.final_energy.total_potential > baseline);
+
+        let report = run(&[
+            "define_domain core 2-7",
+            "rotate residue=5 angle=10",
+            "rotate residue=3 angle=0.5",
+            "move_domain core angle=1 axis=x dz=0.05",
+        ]);
+        assert!(matches!(
+            &report.rejections[..],
+            [RuleViolation::DomainLocked { domain, .. }] if domain == "core"
+        ));
+        assert_eq!(report.applied_rotations.len(), 2);
+        assert_eq!(
+            report.applied_rotations[1].span_record.id.as_str(),
+            "domain-core"
+        );
+        let unknown = run(&["move_domain missing dz=0.1"]);
+        assert!(matches!(
+            unknown.rejections[0],
+            RuleViolation::UnknownDomain { .. }
+        ));
+    }
+
+    #[test]
+    fn minimize_instruction_relaxes_chain() {
+        use folding_molecule::{AminoAcid, Residue};
+
+        let chain = PeptideChain::new(vec![
+            Residue::new(ResidueId(1), AminoAcid::Alanine).with_position([0.0, 0.0, 0.0]),
+            Residue::new(ResidueId(2), AminoAcid::Serine).with_position([1.6, 0.0, 0.1]),
+            Residue::new(ResidueId(3), AminoAcid::Valine).with_position([3.2, 0.5, -0.1]),
+        ]);
+        let mut engine = FoldingEngineBuilder::new()
+            .with_chain(chain)
+            .with_rng_seed(7)
+            .build();
+        let before = engine.state.energy_state().total_potential;
+        let contract = FoldingContract::from_lines(&["minimize steps=100 tolerance=0.01"]);
+        let report = engine.execute_contract(&contract);
+
+        assert!(report.rejections.is_empty(), "{:?}", report.rejections);
+        assert_eq!(report.minimizations.len(), 1);
+        let relaxation = &report.minimizations[0];
+        assert!(relaxation.final_energy < before);
+        assert!((report.final_energy.total_potential - relaxation.final_energy).abs() < 1e-9);
+        assert_eq!(report.trajectory.spans()[0].id.as_str(), "minimize");
+    }
+
+    #[test]
+    fn restraints_steer_minimization_and_report_violations() {
+        use folding_molecule::{write_structure, AminoAcid, StartingConformation};
+
+        let sequence = [AminoAcid::Alanine; 8];
+        let run = |lines: &[&str]| {
+            let chain = PeptideChain::from_sequence(&sequence, StartingConformation::Helix);
+            let mut engine = FoldingEngineBuilder::new()
+                .with_chain(chain)
+                .with_energy_model(EnergyModel::alpha_carbon_trace())
+                .with_ruleset(Ruleset::alpha_carbon_trace())
+                .with_rng_seed(11)
+                .build();
+            let report = engine.execute_contract(&FoldingContract::from_lines(lines));
+            // Restraints do not carry over into the next contract.
+            let next = engine.execute_contract(&FoldingContract::default());
+            assert!(next.restraints.is_empty());
+            report
+        };
+
+        let before = run(&["restrain_distance 1 8 6.0 k=20"]);
+        assert!(before.rejections.is_empty(), "{:?}", before.rejections);
+        let refined = run(&[
+            "restrain_distance 1 8 6.0 k=20",
+            "restrain_dihedral 2 3 4 5 60",
+            "minimize steps=300",
+        ]);
+        assert_eq!(refined.restraints.len(), 2);
+        assert_eq!(refined.restraints[0].kind, "distance");
+        assert_eq!(refined.restraints[1].residues, [1, 2, 3, 4]);
+        assert!(refined.restraints[0].violation < before.restraints[0].violation);
+        assert!(refined.restraints[0].energy > 0.0);
+
+        let dir = std::env::temp_dir().join(format!("restraint-target-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("extended.pdb");
+        let extended = PeptideChain::from_sequence(&sequence, StartingConformation::Extended);
+        write_structure(&path, &extended).unwrap();
+        let target = format!("target_rmsd \"{}\" target=0.5", path.display());
+        let targeted = run(&[&target, "minimize steps=200"]);
+        let initial = run(&[&target]);
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(targeted.rejections.is_empty(), "{:?}", targeted.rejections);
+        assert_eq!(targeted.restraints[0].kind, "rmsd");
+        assert_eq!(targeted.restraints[0].residues.len(), sequence.len());
+        assert!(targeted.restraints[0].value < initial.restraints[0].value);
+
+        let missing = run(&["restrain_distance 1 42 5.0", "target_rmsd missing.pdb"]);
+        assert!(matches!(
+            missing.rejections[..],
+            [
+                RuleViolation::UnknownResidue {
+                    residue: ResidueId(42)
+                },
+                RuleViolation::ReferenceStructureUnavailable { .. }
+            ]
+        ));
+        assert!(missing.restraints.is_empty());
+    }
+
+    #[test]
+    fn structure_series_tracks_committed_steps() {
+        use folding_molecule::{AminoAcid, StartingConformation};
+
+        let sequence = [AminoAcid::Alanine; 10];
+        let helix = PeptideChain::from_sequence(&sequence, StartingConformation::Helix);
+        let builder = FoldingEngineBuilder::new()
+            .with_chain(helix.clone())
+            .with_energy_model(EnergyModel::alpha_carbon_trace())
+            .with_ruleset(Ruleset::alpha_carbon_trace())
+            .with_oscillator(MicroOscillator::new(0.0, 0.0))
+            .with_temperature(1.0e6)
+            .with_rng_seed(9);
+        let contract = FoldingContract::from_lines(&[
+            "commit",
+            "rotate residue=5 angle=20",
+            "ghost on",
+            "rotate residue=6 angle=20",
+            "ghost off",
+            "rollback",
+            "minimize steps=20",
+        ]);
+        let report = builder.clone().build().execute_contract(&contract);
+
+        assert!(report.rejections.is_empty(), "{:?}", report.rejections);
+        let labels: Vec<_> = report
+            .structure_series
+            .iter()
+            .map(|frame| frame.span_id.as_str())
+            .collect();
+        assert_eq!(labels, ["start", "residue-5", "rollback", "minimize"]);
+        let [start, rotated, restored, _] = &report.structure_series[..] else {
+            unreachable!();
+        };
+        assert_eq!(start.step, 0);
+        assert!(start.metrics.rmsd < 1e-6);
+        assert!(rotated.metrics.rmsd > 0.1);
+        assert!(rotated.metrics.native_contact_fraction < 1.0);
+        assert!(restored.metrics.rmsd < 1e-6);
+        assert!(!start.contacts.is_empty());
+        assert_eq!(restored.contacts, start.contacts);
+        assert!((start.metrics.secondary_structure.helix - 1.0).abs() < 1e-9);
+
+        // Against an explicit reference the start is already far away.
+        let extended = PeptideChain::from_sequence(&sequence, StartingConformation::Extended);
+        let report = builder
+            .with_reference_structure(extended)
+            .build()
+            .execute_contract(&FoldingContract::default());
+        let start = &report.structure_series[0].metrics;
+        assert!(start.rmsd > 1.0);
+        assert!(start.end_to_end_distance < 20.0);
+    }
+
+    #[test]
+    fn trajectory_frames_follow_the_interval() {
+        use folding_molecule::{AminoAcid, StartingConformation};
+
+        let chain =
+            PeptideChain::from_sequence(&[AminoAcid::Alanine; 8], StartingConformation::Helix);
+        let builder = FoldingEngineBuilder::new()
+            .with_chain(chain)
+            .with_energy_model(EnergyModel::alpha_carbon_trace())
+            .with_ruleset(Ruleset::alpha_carbon_trace())
+            .with_oscillator(MicroOscillator::new(0.0, 0.0))
+            .with_temperature(1.0e6)
+            .with_rng_seed(18);
+        let contract = FoldingContract::from_lines(&[
+            "rotate residue=3 angle=10",
+            "rotate residue=4 angle=10",
+            "rotate residue=5 angle=10",
+            "rotate residue=6 angle=10",
+        ]);
+        let frames = |builder: FoldingEngineBuilder| {
+            let report = builder.build().execute_contract(&contract);
+            assert!(report.rejections.is_empty(), "{:?}", report.rejections);
+            let labels: Vec<_> = report
+                .trajectory_frames
+                .iter()
+                .map(|frame| (frame.step, frame.label.clone()))
+                .collect();
+            if let Some(last) = report.trajectory_frames.last() {
+                assert_eq!(last.positions, report.final_chain.positions());
+            }
+            // Contact maps are recorded on every step, frames or not.
+            assert!(report
+                .structure_series
+                .iter()
+                .all(|frame| !frame.contacts.is_empty()));
+            labels
+        };
+
+        assert!(frames(builder.clone()).is_empty());
+        assert_eq!(
+            frames(builder.clone().with_trajectory_interval(2)),
+            [
+                (0, "start".to_string()),
+                (2, "residue-4".to_string()),
+                (4, "residue-6".to_string()),
+            ]
+        );
+        assert_eq!(
+            frames(builder.with_trajectory_interval(3)),
+            [
+                (0, "start".to_string()),
+                (3, "residue-5".to_string()),
+                (4, "final".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn resumed_checkpoints_match_an_uninterrupted_run() {
+        use folding_molecule::{AminoAcid, StartingConformation};
+
+        let helix =
+            |amino_acid| PeptideChain::from_sequence(&[amino_acid; 8], StartingConformation::Helix);
+        let builder = FoldingEngineBuilder::new()
+            .with_chain(helix(AminoAcid::Alanine))
+            .with_energy_model(EnergyModel::alpha_carbon_trace())
+            .with_ruleset(Ruleset::alpha_carbon_trace())
+            .with_oscillator(MicroOscillator::new(0.0, 0.0))
+            .with_temperature(300.0)
+            .with_rng_seed(5)
+            .with_trajectory_interval(2);
+        let contract = FoldingContract::from_lines(&[
+            "set_physics_level coarse",
+            "physics_span on",
+            "rotate residue=4 angle=-5 duration=2",
+            "physics_span off",
+            "set_schedule cyclic high=320 low=280 period=4",
+            "restrain_distance 2 7 target=8.5",
+            "rotate residue=3 angle=8",
+            "commit",
+            "rotate residue=5 angle=-10",
+            "rotate residue=6 angle=12",
+            "rollback",
+            "rotate residue=4 angle=10",
+            "minimize steps=20",
+            "rotate residue=6 angle=-12",
+            "rotate residue=2 angle=9",
+        ]);
+        let fingerprint = |report: &ExecutionReport| {
+            let json = serde_json::to_string(&(
+                &report.applied_rotations,
+                &report.rejections,
+                &report.minimizations,
+                &report.final_chain,
+                &report.trajectory,
+                &report.metropolis_stats,
+                &report.physics_span_metrics,
+                &report.structure_series,
+                &report.trajectory_frames,
+            ))
+            .unwrap();
+            (json, report.final_energy.total_potential.to_bits())
+        };
+        let expected = builder.clone().build().execute_contract(&contract);
+        let stats = &expected.metropolis_stats;
+        assert!(stats.accepted > 0 && stats.rejected > 0, "{stats:?}");
+        assert_eq!(expected.physics_span_metrics.len(), 1);
+
+        let mut saved = Vec::new();
+        let checkpointed = builder
+            .clone()
+            .build()
+            .execute_contract_with_checkpoints(&contract, 4, |checkpoint| {
+                saved.push(checkpoint.clone());
+                Ok::<_, CheckpointError>(())
+            })
+            .unwrap();
+        assert_eq!(fingerprint(&checkpointed), fingerprint(&expected));
+        let resume_points: Vec<_> = saved.iter().map(|c| c.next_instruction).collect();
+        assert_eq!(resume_points, [4, 8, 12]);
+
+        let path = std::env::temp_dir().join(format!("checkpoint-{}.json", std::process::id()));
+        for checkpoint in &saved {
+            checkpoint.save(&path).unwrap();
+            let restored = EngineCheckpoint::load(&path).unwrap();
+            let resumed = builder
+                .clone()
+                .build()
+                .resume_contract(restored, 4, |_| Ok::<_, CheckpointError>(()))
+                .unwrap();
+            assert_eq!(fingerprint(&resumed), fingerprint(&expected));
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        let other = FoldingEngineBuilder::new()
+            .with_chain(helix(AminoAcid::Glycine))
+            .build()
+            .resume_contract(saved[0].clone(), 4, |_| Ok::<_, CheckpointError>(()));
+        assert!(matches!(
+            other,
+            Err(CheckpointError::ChainMismatch { index: 0 })
+        ));
+    }
+
+    #[test]
+    fn physics_spans_run_native_dynamics_without_backend() {
+        use folding_molecule::{AminoAcid, StartingConformation};
+
+        let sequence = [AminoAcid::Alanine; 8];
+        let chain = PeptideChain::from_sequence(&sequence, StartingConformation::Helix);
+        let mut engine = FoldingEngineBuilder::new()
+            .with_chain(chain)
+            .with_energy_model(EnergyModel::alpha_carbon_trace())
+            .with_ruleset(Ruleset::alpha_carbon_trace())
+            .with_oscillator(MicroOscillator::new(0.0, 0.0))
+            .with_temperature(300.0)
+            .with_rng_seed(5)
+            .build();
+        let contract = FoldingContract::from_lines(&[
+            "set_physics_level coarse",
+            "physics_span on",
+            "rotate residue=4 angle=-5 duration=2",
+        ]);
+        let report = engine.execute_contract(&contract);
+
+        assert_eq!(
+            report.physics_span_metrics.len(),
+            1,
+            "{:?}",
+            report.rejections
+        );
+        let metrics = &report.physics_span_metrics[0].metrics;
+        assert!((metrics.simulation_time_ps - 2.0).abs() < 1e-9);
+        assert!(metrics.kinetic_energy > 0.0);
+        assert!(metrics.radius_of_gyration > 0.0);
+        assert_eq!(report.physics_spans, vec!["residue-4".to_string()]);
+    }
+
+    #[test]
+    fn gb_level_enables_implicit_solvent() {
+        use folding_molecule::{AminoAcid, EnvironmentPreset, StartingConformation};
+
+        let sequence = [
+            AminoAcid::Alanine,
+            AminoAcid::Valine,
+            AminoAcid::Glycine,
+            AminoAcid::Isoleucine,
+            AminoAcid::Glycine,
+            AminoAcid::Alanine,
+            AminoAcid::Lysine,
+            AminoAcid::Glutamate,
+        ];
+        let energy_in = |environment: EnvironmentPreset| {
+            let chain = PeptideChain::from_sequence(&sequence, StartingConformation::Helix);
+            let mut engine = FoldingEngineBuilder::new()
+                .with_chain(chain)
+                .with_energy_model(EnergyModel::alpha_carbon_trace().with_environment(environment))
+                .with_ruleset(Ruleset::alpha_carbon_trace())
+                .with_physics_level(PhysicsLevel::Gb)
+                .with_rng_seed(1)
+                .build();
+            assert!(engine.state.energy_model.implicit_solvent.is_some());
+            let gb = engine
+                .state
+                .energy_model
+                .energy_summary(&engine.state.chain);
+            engine.execute_contract(&FoldingContract::from_lines(&["set_physics_level coarse"]));
+            assert!(engine.state.energy_model.implicit_solvent.is_none());
+            gb
+        };
+        let aqueous = energy_in(EnvironmentPreset::aqueous());
+        let membrane = energy_in(EnvironmentPreset::membrane());
+        assert!(aqueous.nonpolar_solvation > 0.0);
+        assert_eq!(membrane.nonpolar_solvation, 0.0);
+        assert!(aqueous.polar_solvation < membrane.polar_solvation);
     }
 }
 
+#[derive(Clone)]
 pub struct FoldingEngineBuilder {
     chain: Option<PeptideChain>,
     energy_model: Option<EnergyModel>,
@@ -122,13 +685,17 @@ pub struct FoldingEngineBuilder {
     rng_seed: Option<u64>,
     temperature_schedule: Option<TemperatureSchedule>,
     physics_level: Option<PhysicsLevel>,
+    reference_structure: Option<PeptideChain>,
+    trajectory_interval: Option<usize>,
 }
 
 pub struct ExecutionReport {
     pub applied_rotations: Vec<RotationOutcome>,
     pub ghost_rotations: Vec<RotationOutcome>,
     pub rejections: Vec<RuleViolation>,
+    pub minimizations: Vec<MinimizationReport>,
     pub final_energy: EnergyState,
+    pub final_chain: PeptideChain,
     pub trajectory: Trajectory,
     pub metropolis_stats: MetropolisStats,
     pub domains: Vec<DomainDefinition>,
@@ -137,33 +704,111 @@ pub struct ExecutionReport {
     pub physics_level: PhysicsLevel,
     pub physics_spans: Vec<String>,
     pub physics_span_metrics: Vec<PhysicsSpanRecord>,
+    /// Ladder and swap statistics when the report comes from [`crate::ReplicaExchange`].
+    pub replica_exchange: Option<ReplicaExchangeReport>,
+    /// Every restraint active at the end of the run, measured on `final_chain`.
+    pub restraints: Vec<RestraintStatus>,
+    /// Structural metrics of the starting chain and after every committed
+    /// step and checkpoint restore, in execution order.
+    pub structure_series: Vec<StructureFrame>,
+    /// Coordinate frames when the engine was built with
+    /// [`FoldingEngineBuilder::with_trajectory_interval`]; the last frame is
+    /// always the final chain.
+    pub trajectory_frames: Vec<TrajectoryFrame>,
 }
 
-#[derive(Clone, Debug)]
+/// Outcomes collected while a contract executes.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct RunLog {
+    pub applied_rotations: Vec<RotationOutcome>,
+    pub ghost_rotations: Vec<RotationOutcome>,
+    pub rejections: Vec<RuleViolation>,
+    pub minimizations: Vec<MinimizationReport>,
+}
+
+/// Everything a contract run mutates; the remaining engine fields are fixed
+/// by the builder. Stored in an [`EngineCheckpoint`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct EngineState {
+    chain: PeptideChain,
+    trajectory: Trajectory,
+    aggregation_shields: Vec<AggregationShield>,
+    restraints: Vec<Restraint>,
+    implicit_solvent: Option<ImplicitSolvent>,
+    checkpoints: Vec<ProteinSnapshot>,
+    ghost_mode: bool,
+    pending_alias: Option<String>,
+    ghost_trajectory: Trajectory,
+    temperature: f64,
+    rng: ChaCha12Rng,
+    temperature_schedule: Option<TemperatureSchedule>,
+    initial_temperature: f64,
+    step_index: usize,
+    schedule_origin: usize,
+    metropolis_stats: MetropolisStats,
+    adaptive_mark: MetropolisStats,
+    #[serde(default = "unit_step_scale")]
+    step_scale: f64,
+    domains: Vec<DomainDefinition>,
+    chaperone_requirements: Vec<ChaperoneRequirement>,
+    modifications: Vec<PostTranslationalModification>,
+    physics_level: PhysicsLevel,
+    auto_solvent: bool,
+    span_physics_mode: PhysicsSpanMode,
+    physics_spans: Vec<String>,
+    physics_span_metrics: Vec<PhysicsSpanRecord>,
+    structure_reference: StructureReference,
+    structure_frames: Vec<StructureFrame>,
+    trajectory_frames: Vec<TrajectoryFrame>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DomainDefinition {
     pub name: Option<String>,
     pub start: ResidueId,
     pub end: ResidueId,
 }
 
-#[derive(Clone, Debug)]
+impl DomainDefinition {
+    /// Name, or the residue range for anonymous domains.
+    pub fn label(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("{}-{}", self.start.0, self.end.0))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ChaperoneRequirement {
     pub chaperone: String,
     pub span: Option<String>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PostTranslationalModification {
     pub modification: String,
     pub residue: ResidueId,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PhysicsSpanRecord {
     pub span_id: String,
     pub metrics: PhysicsSpanMetrics,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StructureFrame {
+    /// Engine step the frame was taken at; 0 is the starting chain.
+    pub step: usize,
+    /// Span committed at this step, `start`, or `rollback`.
+    pub span_id: String,
+    pub metrics: StructureMetrics,
+    /// Contact map as the chain-index pairs in contact (see
+    /// [`contact_map`]) rather than a dense matrix.
+    #[serde(default)]
+    pub contacts: Vec<(usize, usize)>,
+}
+
 impl Default for FoldingEngineBuilder {
     fn default() -> Self {
         Self::new()
@@ -182,6 +827,8 @@ impl FoldingEngineBuilder {
             rng_seed: None,
             temperature_schedule: None,
             physics_level: None,
+            reference_structure: None,
+            trajectory_interval: None,
         }
     }
 
@@ -230,6 +877,35 @@ impl FoldingEngineBuilder {
         self
     }
 
+    /// Measures RMSD and native contacts against `structure` (residues
+    /// matched by number) instead of the starting chain.
+    pub fn with_reference_structure(mut self, structure: PeptideChain) -> Self {
+        self.reference_structure = Some(structure);
+        self
+    }
+
+    /// Records the chain's coordinates at the start, after committed steps
+    /// at least `steps` apart, and at the end of each run.
+    pub fn with_trajectory_interval(mut self, steps: usize) -> Self {
+        self.trajectory_interval = Some(steps.max(1));
+        self
+    }
+
+    /// Engine for rung `index` of a replica ladder: fixed at `temperature`,
+    /// with its own seed derived from the builder's.
+    pub(crate) fn build_replica(&self, index: usize, temperature: f64) -> FoldingEngine {
+        let mut builder = self
+            .clone()
+            .with_temperature(temperature)
+            .with_temperature_schedule(TemperatureSchedule::Constant);
+        builder.rng_seed = self.rng_seed.map(|seed| seed.wrapping_add(index as u64));
+        builder.build()
+    }
+
+    pub(crate) fn rng_seed(&self) -> Option<u64> {
+        self.rng_seed
+    }
+
     pub fn build(self) -> FoldingEngine {
         let chain = self.chain.expect("chain not provided");
         let energy_model = self.energy_model.unwrap_or_default();
@@ -240,18 +916,24 @@ impl FoldingEngineBuilder {
         let ruleset = self.ruleset.unwrap_or_default();
         let mut temperature = self.temperature.unwrap_or(310.0);
         let temperature_schedule = self.temperature_schedule.clone();
-        if let Some(TemperatureSchedule::Linear { start, .. }) = &temperature_schedule {
-            temperature = *start;
+        if let Some(start) = temperature_schedule
+            .as_ref()
+            .and_then(TemperatureSchedule::start_temperature)
+        {
+            temperature = start;
         }
         let rng = match self.rng_seed {
-            Some(seed) => StdRng::seed_from_u64(seed),
-            None => StdRng::from_entropy(),
+            Some(seed) => ChaCha12Rng::seed_from_u64(seed),
+            None => ChaCha12Rng::from_entropy(),
         };
+        let base_shield_count = energy_model.aggregation_shields.len();
+        let base_restraint_count = energy_model.restraints.len();
+        let structure_reference = StructureReference::new(&chain);
         let state = ProteinState::new(chain, energy_model);
         let solver = RotationSolver::new(oscillator, clock);
         let validator = Validator::new(ruleset);
         let physics_level = self.physics_level.unwrap_or(PhysicsLevel::Toy);
-        FoldingEngine {
+        let mut engine = FoldingEngine {
             state,
             solver,
             validator,
@@ -265,95 +947,386 @@ impl FoldingEngineBuilder {
             temperature_schedule,
             initial_temperature: temperature,
             step_index: 0,
+            schedule_origin: 0,
             metropolis_stats: MetropolisStats::default(),
+            adaptive_mark: MetropolisStats::default(),
+            step_scale: 1.0,
             domains: Vec::new(),
             chaperone_requirements: Vec::new(),
             modifications: Vec::new(),
+            base_shield_count,
+            base_restraint_count,
             physics_level,
+            auto_solvent: false,
             span_physics_mode: PhysicsSpanMode::Toy,
             physics_spans: Vec::new(),
             physics_span_metrics: Vec::new(),
-        }
+            reference_structure: self.reference_structure,
+            structure_reference,
+            structure_frames: Vec::new(),
+            trajectory_interval: self.trajectory_interval,
+            trajectory_frames: Vec::new(),
+        };
+        engine.set_physics_level(physics_level);
+        engine
     }
 }
 
 impl FoldingEngine {
     pub fn execute_contract(&mut self, contract: &FoldingContract) -> ExecutionReport {
-        let mut applied_rotations = Vec::new();
-        let mut ghost_rotations = Vec::new();
-        let mut rejections = Vec::new();
+        let mut run = RunLog::default();
+        self.begin_run();
+        for instruction in &contract.instructions {
+            self.execute_instruction(instruction, &mut run);
+        }
+        self.finish_run(run)
+    }
+
+    /// Runs `contract` like [`Self::execute_contract`], handing
+    /// `on_checkpoint` the engine state after every `every` instructions so
+    /// a killed run can continue with [`Self::resume_contract`].
+    pub fn execute_contract_with_checkpoints<E>(
+        &mut self,
+        contract: &FoldingContract,
+        every: usize,
+        on_checkpoint: impl FnMut(&EngineCheckpoint) -> Result<(), E>,
+    ) -> Result<ExecutionReport, E> {
+        self.begin_run();
+        self.run_instructions(contract, 0, RunLog::default(), every, on_checkpoint)
+    }
+
+    /// Restores `checkpoint` and runs the rest of its contract, still
+    /// checkpointing every `every` instructions. An engine built with the
+    /// same settings reports exactly what the uninterrupted run would have.
+    pub fn resume_contract<E: From<CheckpointError>>(
+        &mut self,
+        checkpoint: EngineCheckpoint,
+        every: usize,
+        on_checkpoint: impl FnMut(&EngineCheckpoint) -> Result<(), E>,
+    ) -> Result<ExecutionReport, E> {
+        let EngineCheckpoint {
+            contract,
+            next_instruction,
+            run,
+            state,
+            ..
+        } = checkpoint;
+        self.restore_state(state)?;
+        self.run_instructions(&contract, next_instruction, run, every, on_checkpoint)
+    }
+
+    fn run_instructions<E>(
+        &mut self,
+        contract: &FoldingContract,
+        start: usize,
+        mut run: RunLog,
+        every: usize,
+        mut on_checkpoint: impl FnMut(&EngineCheckpoint) -> Result<(), E>,
+    ) -> Result<ExecutionReport, E> {
+        let every = every.max(1);
+        let total = contract.instructions.len();
+        for (index, instruction) in contract.instructions.iter().enumerate().skip(start) {
+            self.execute_instruction(instruction, &mut run);
+            let next = index + 1;
+            if next < total && next.is_multiple_of(every) {
+                let state = self.capture_state();
+                on_checkpoint(&EngineCheckpoint::new(
+                    contract.clone(),
+                    next,
+                    run.clone(),
+                    state,
+                ))?;
+            }
+        }
+        Ok(self.finish_run(run))
+    }
+
+    fn capture_state(&self) -> EngineState {
+        let model = &self.state.energy_model;
+        EngineState {
+            chain: self.state.chain.clone(),
+            trajectory: self.state.trajectory.clone(),
+            aggregation_shields: model.aggregation_shields.clone(),
+            restraints: model.restraints.clone(),
+            implicit_solvent: model.implicit_solvent,
+            checkpoints: self.checkpoints.clone(),
+            ghost_mode: self.ghost_mode,
+            pending_alias: self.pending_alias.clone(),
+            ghost_trajectory: self.ghost_trajectory.clone(),
+            temperature: self.temperature,
+            rng: self.rng.clone(),
+            temperature_schedule: self.temperature_schedule.clone(),
+            initial_temperature: self.initial_temperature,
+            step_index: self.step_index,
+            schedule_origin: self.schedule_origin,
+            metropolis_stats: self.metropolis_stats.clone(),
+            adaptive_mark: self.adaptive_mark.clone(),
+            step_scale: self.step_scale,
+            domains: self.domains.clone(),
+            chaperone_requirements: self.chaperone_requirements.clone(),
+            modifications: self.modifications.clone(),
+            physics_level: self.physics_level,
+            auto_solvent: self.auto_solvent,
+            span_physics_mode: self.span_physics_mode,
+            physics_spans: self.physics_spans.clone(),
+            physics_span_metrics: self.physics_span_metrics.clone(),
+            structure_reference: self.structure_reference.clone(),
+            structure_frames: self.structure_frames.clone(),
+            trajectory_frames: self.trajectory_frames.clone(),
+        }
+    }
+
+    /// Replaces the run state with `state`, refusing a chain whose sequence
+    /// differs from the one the engine was built with.
+    fn restore_state(&mut self, state: EngineState) -> Result<(), CheckpointError> {
+        let current = self.state.chain.residues();
+        let restored = state.chain.residues();
+        let mismatch = current
+            .iter()
+            .zip(restored)
+            .position(|(current, restored)| current.amino_acid != restored.amino_acid);
+        if let Some(index) = mismatch {
+            return Err(CheckpointError::ChainMismatch { index });
+        }
+        if current.len() != restored.len() {
+            let index = current.len().min(restored.len());
+            return Err(CheckpointError::ChainMismatch { index });
+        }
+
+        let EngineState {
+            chain,
+            trajectory,
+            aggregation_shields,
+            restraints,
+            implicit_solvent,
+            checkpoints,
+            ghost_mode,
+            pending_alias,
+            ghost_trajectory,
+            temperature,
+            rng,
+            temperature_schedule,
+            initial_temperature,
+            step_index,
+            schedule_origin,
+            metropolis_stats,
+            adaptive_mark,
+            step_scale,
+            domains,
+            chaperone_requirements,
+            modifications,
+            physics_level,
+            auto_solvent,
+            span_physics_mode,
+            physics_spans,
+            physics_span_metrics,
+            structure_reference,
+            structure_frames,
+            trajectory_frames,
+        } = state;
+        self.state.chain = chain;
+        self.state.trajectory = trajectory;
+        self.state.energy_model.aggregation_shields = aggregation_shields;
+        self.state.energy_model.restraints = restraints;
+        self.state.energy_model.implicit_solvent = implicit_solvent;
+        self.checkpoints = checkpoints;
+        self.ghost_mode = ghost_mode;
+        self.pending_alias = pending_alias;
+        self.ghost_trajectory = ghost_trajectory;
+        self.temperature = temperature;
+        self.rng = rng;
+        self.temperature_schedule = temperature_schedule;
+        self.initial_temperature = initial_temperature;
+        self.step_index = step_index;
+        self.schedule_origin = schedule_origin;
+        self.metropolis_stats = metropolis_stats;
+        self.adaptive_mark = adaptive_mark;
+        self.step_scale = step_scale;
+        self.domains = domains;
+        self.chaperone_requirements = chaperone_requirements;
+        self.modifications = modifications;
+        self.physics_level = physics_level;
+        self.auto_solvent = auto_solvent;
+        self.span_physics_mode = span_physics_mode;
+        self.physics_spans = physics_spans;
+        self.physics_span_metrics = physics_span_metrics;
+        self.structure_reference = structure_reference;
+        self.structure_frames = structure_frames;
+        self.trajectory_frames = trajectory_frames;
+        Ok(())
+    }
+
+    /// Clears the per-contract bookkeeping before a run.
+    pub(crate) fn begin_run(&mut self) {
         self.step_index = 0;
+        self.schedule_origin = 0;
         self.metropolis_stats = MetropolisStats::default();
+        self.adaptive_mark = MetropolisStats::default();
+        self.step_scale = 1.0;
         self.domains.clear();
         self.chaperone_requirements.clear();
-        self.modifications.clear();
+        for entry in self.modifications.drain(..) {
+            if let Some(residue) = self.state.chain.residue_mut(entry.residue) {
+                residue.modification = None;
+            }
+        }
+        self.state
+            .energy_model
+            .aggregation_shields
+            .truncate(self.base_shield_count);
+        self.state
+            .energy_model
+            .restraints
+            .truncate(self.base_restraint_count);
         self.physics_spans.clear();
         self.physics_span_metrics.clear();
-        for instruction in &contract.instructions {
-            match instruction {
-                ContractInstruction::Rotate {
-                    residue,
-                    angle_degrees,
-                    duration_ms,
-                } => match self.execute_rotation(*residue, *angle_degrees, *duration_ms) {
-                    Ok(outcome) => {
-                        if outcome.ghost {
-                            ghost_rotations.push(outcome);
-                        } else {
-                            applied_rotations.push(outcome);
-                        }
-                    }
-                    Err(err) => rejections.push(err),
-                },
-                ContractInstruction::ClashCheck => {
-                    if let Err(err) = self.validator.validate_structure(&self.state.chain) {
-                        rejections.push(err);
+        let chain = &self.state.chain;
+        self.structure_reference = self
+            .reference_structure
+            .as_ref()
+            .and_then(|structure| StructureReference::matching(chain, structure))
+            .unwrap_or_else(|| StructureReference::new(chain));
+        self.structure_frames.clear();
+        self.trajectory_frames.clear();
+        self.record_structure("start");
+    }
+
+    pub(crate) fn execute_instruction(
+        &mut self,
+        instruction: &ContractInstruction,
+        run: &mut RunLog,
+    ) {
+        match instruction {
+            ContractInstruction::Rotate {
+                residue,
+                angle_degrees,
+                duration_ms,
+            } => match self.execute_rotation(*residue, *angle_degrees, *duration_ms) {
+                Ok(outcome) => {
+                    if outcome.ghost {
+                        run.ghost_rotations.push(outcome);
+                    } else {
+                        run.applied_rotations.push(outcome);
                     }
                 }
-                ContractInstruction::Commit => self.commit(),
-                ContractInstruction::Rollback => self.rollback(),
-                ContractInstruction::GhostMode(enabled) => self.set_ghost_mode(*enabled),
-                ContractInstruction::SpanAlias(alias) => {
-                    self.pending_alias = Some(alias.clone());
-                }
-                ContractInstruction::DefineDomain { name, start, end } => {
-                    self.domains.push(DomainDefinition {
-                        name: name.clone(),
-                        start: *start,
-                        end: *end,
-                    });
+                Err(err) => run.rejections.push(err),
+            },
+            ContractInstruction::ClashCheck => {
+                if let Err(err) = self.validator.validate_structure(&self.state.chain) {
+                    run.rejections.push(err);
                 }
-                ContractInstruction::RequireChaperone { chaperone, span } => {
-                    self.chaperone_requirements.push(ChaperoneRequirement {
-                        chaperone: chaperone.clone(),
-                        span: span.clone(),
-                    });
+            }
+            ContractInstruction::Commit => self.commit(),
+            ContractInstruction::Rollback => self.rollback(),
+            ContractInstruction::GhostMode(enabled) => self.set_ghost_mode(*enabled),
+            ContractInstruction::SpanAlias(alias) => {
+                self.pending_alias = Some(alias.clone());
+            }
+            ContractInstruction::DefineDomain { name, start, end } => {
+                self.domains.push(DomainDefinition {
+                    name: name.clone(),
+                    start: *start,
+                    end: *end,
+                });
+            }
+            ContractInstruction::RequireChaperone { chaperone, span } => {
+                let requirement = ChaperoneRequirement {
+                    chaperone: chaperone.clone(),
+                    span: span.clone(),
+                };
+                match self.require_chaperone(&requirement) {
+                    Ok(()) => self.chaperone_requirements.push(requirement),
+                    Err(err) => run.rejections.push(err),
                 }
-                ContractInstruction::AddModification {
-                    modification,
-                    residue,
-                } => {
-                    self.modifications.push(PostTranslationalModification {
-                        modification: modification.clone(),
-                        residue: *residue,
-                    });
+            }
+            ContractInstruction::AddModification {
+                modification,
+                residue,
+            } => {
+                let entry = PostTranslationalModification {
+                    modification: modification.clone(),
+                    residue: *residue,
+                };
+                match self.add_modification(&entry) {
+                    Ok(()) => self.modifications.push(entry),
+                    Err(err) => run.rejections.push(err),
                 }
-                ContractInstruction::SetPhysicsLevel(level) => {
-                    self.physics_level = *level;
+            }
+            ContractInstruction::MoveDomain {
+                domain,
+                angle_degrees,
+                axis,
+                translation,
+            } => match self.execute_domain_move(domain, *angle_degrees, *axis, *translation) {
+                Ok(outcome) => {
+                    if outcome.ghost {
+                        run.ghost_rotations.push(outcome);
+                    } else {
+                        run.applied_rotations.push(outcome);
+                    }
                 }
-                ContractInstruction::SetSpanPhysics(mode) => {
-                    self.span_physics_mode = *mode;
+                Err(err) => run.rejections.push(err),
+            },
+            ContractInstruction::SetPhysicsLevel(level) => self.set_physics_level(*level),
+            ContractInstruction::SetSchedule(schedule) => {
+                self.set_temperature_schedule(schedule.clone());
+            }
+            ContractInstruction::SetSpanPhysics(mode) => {
+                self.span_physics_mode = *mode;
+            }
+            ContractInstruction::Minimize { steps, tolerance } => {
+                match self.execute_minimization(*steps, *tolerance) {
+                    Ok(report) => run.minimizations.push(report),
+                    Err(err) => run.rejections.push(err),
                 }
             }
+            ContractInstruction::RestrainDistance {
+                left,
+                right,
+                target,
+                force_constant,
+            } => {
+                let restraint = self.residue_index(*left).and_then(|left| {
+                    let right = self.residue_index(*right)?;
+                    Ok(Restraint::distance(left, right, *target, *force_constant))
+                });
+                self.add_restraint(restraint, run);
+            }
+            ContractInstruction::RestrainDihedral {
+                residues,
+                target_degrees,
+                force_constant,
+            } => {
+                let restraint = residues
+                    .iter()
+                    .map(|residue| self.residue_index(*residue))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(|indices| {
+                        let indices = [indices[0], indices[1], indices[2], indices[3]];
+                        Restraint::dihedral(indices, *target_degrees, *force_constant)
+                    });
+                self.add_restraint(restraint, run);
+            }
+            ContractInstruction::TargetRmsd {
+                path,
+                target,
+                force_constant,
+            } => {
+                let restraint = self.reference_restraint(path, *target, *force_constant);
+                self.add_restraint(restraint, run);
+            }
         }
-        let final_energy = self.state.energy_state();
-        let trajectory = self.state.trajectory().clone();
+    }
+
+    pub(crate) fn finish_run(&self, run: RunLog) -> ExecutionReport {
         ExecutionReport {
-            applied_rotations,
-            ghost_rotations,
-            rejections,
-            final_energy,
-            trajectory,
+            applied_rotations: run.applied_rotations,
+            ghost_rotations: run.ghost_rotations,
+            rejections: run.rejections,
+            minimizations: run.minimizations,
+            final_energy: self.state.energy_state(),
+            final_chain: self.state.chain.clone(),
+            trajectory: self.state.trajectory().clone(),
             metropolis_stats: self.metropolis_stats.clone(),
             domains: self.domains.clone(),
             chaperone_requirements: self.chaperone_requirements.clone(),
@@ -361,6 +1334,91 @@ impl FoldingEngine {
             physics_level: self.physics_level,
             physics_spans: self.physics_spans.clone(),
             physics_span_metrics: self.physics_span_metrics.clone(),
+            replica_exchange: None,
+            restraints: self
+                .state
+                .energy_model
+                .restraint_statuses(&self.state.chain),
+            structure_series: self.structure_frames.clone(),
+            trajectory_frames: self.final_trajectory_frames(),
+        }
+    }
+
+    fn final_trajectory_frames(&self) -> Vec<TrajectoryFrame> {
+        let mut frames = self.trajectory_frames.clone();
+        let positions = self.state.chain.positions();
+        if self.trajectory_interval.is_some()
+            && frames.last().is_none_or(|last| last.positions != positions)
+        {
+            frames.push(TrajectoryFrame {
+                step: self.step_index,
+                label: "final".to_string(),
+                positions,
+            });
+        }
+        frames
+    }
+
+    pub fn temperature(&self) -> f64 {
+        self.temperature
+    }
+
+    /// Fraction of each `rotate` angle currently applied.
+    pub fn step_scale(&self) -> f64 {
+        self.step_scale
+    }
+
+    pub fn potential_energy(&self) -> f64 {
+        self.state.energy_model.total_energy(&self.state.chain)
+    }
+
+    /// 1/(k_B·T) at the current temperature.
+    pub(crate) fn beta(&self) -> f64 {
+        1.0 / (self.boltzmann_constant * self.temperature.max(1.0))
+    }
+
+    pub(crate) fn roll(&mut self) -> f64 {
+        self.rng.gen_range(0.0..1.0)
+    }
+
+    /// Trades conformations (chain, trajectory and checkpoints) with `other`;
+    /// each engine keeps its own temperature and random stream.
+    pub(crate) fn exchange_conformation(&mut self, other: &mut FoldingEngine) {
+        std::mem::swap(&mut self.state.chain, &mut other.state.chain);
+        std::mem::swap(&mut self.state.trajectory, &mut other.state.trajectory);
+        std::mem::swap(&mut self.checkpoints, &mut other.checkpoints);
+        std::mem::swap(&mut self.structure_frames, &mut other.structure_frames);
+        std::mem::swap(&mut self.trajectory_frames, &mut other.trajectory_frames);
+    }
+
+    /// Installs `schedule` from the current step on. Schedules with a start
+    /// temperature jump to it; `Constant` and `Adaptive` continue from the
+    /// current temperature. Rotation steps return to their full angle.
+    pub fn set_temperature_schedule(&mut self, schedule: TemperatureSchedule) {
+        if let Some(start) = schedule.start_temperature() {
+            self.temperature = start;
+        }
+        self.initial_temperature = self.temperature;
+        self.schedule_origin = self.step_index;
+        self.adaptive_mark = self.metropolis_stats.clone();
+        self.step_scale = 1.0;
+        self.temperature_schedule = Some(schedule);
+    }
+
+    /// Switches the physics level. `Gb` turns on the native GB/SA solvent
+    /// unless the energy model already carries one; leaving `Gb` removes it
+    /// again only if it was added here.
+    pub fn set_physics_level(&mut self, level: PhysicsLevel) {
+        self.physics_level = level;
+        let model = &mut self.state.energy_model;
+        if level == PhysicsLevel::Gb {
+            if model.implicit_solvent.is_none() {
+                model.implicit_solvent = Some(ImplicitSolvent::default());
+                self.auto_solvent = true;
+            }
+        } else if self.auto_solvent {
+            model.implicit_solvent = None;
+            self.auto_solvent = false;
         }
     }
 
@@ -371,10 +1429,17 @@ impl FoldingEngine {
         duration_ms: u64,
     ) -> Result<RotationOutcome, RuleViolation> {
         self.apply_temperature_schedule();
+        let angle_degrees = angle_degrees * self.step_scale;
+        if let Some(domain) = self.locking_domain(residue) {
+            return Err(RuleViolation::DomainLocked {
+                residue,
+                domain: domain.label(),
+            });
+        }
         self.validator
             .validate_rotation(residue, angle_degrees, &self.state.chain)?;
         let alias = self.pending_alias.take();
-        let baseline_energy = self.state.energy_model.total_energy(&self.state.chain);
+        let baseline_energy = self.state.potential_energy();
         let command = RotationCommand {
             residue,
             angle_degrees,
@@ -382,6 +1447,7 @@ impl FoldingEngine {
             label: alias.clone(),
         };
         let mut physics_applied = false;
+        let mut native_dynamics = None;
         let mut outcome = if self.span_physics_mode == PhysicsSpanMode::Physics {
             if let Some(physics_outcome) = physics_bridge::run_physics_step(PhysicsRequest {
                 chain: &self.state.chain,
@@ -392,12 +1458,14 @@ impl FoldingEngine {
                 physics_applied = true;
                 physics_outcome
             } else {
+                // No external backend: relax the rotated chain natively below.
+                native_dynamics = LangevinIntegrator::for_level(self.physics_level);
                 self.solver.solve(command.clone())
             }
         } else {
             self.solver.solve(command.clone())
         };
-        let pending_metrics = if physics_applied {
+        let mut pending_metrics = if physics_applied {
             outcome.physics_metrics.clone()
         } else {
             None
@@ -421,6 +1489,19 @@ impl FoldingEngine {
 
         let snapshot = self.state.snapshot();
         self.state.apply_rotation(residue, outcome.applied_angle);
+        if let Some(integrator) = native_dynamics {
+            let steps = integrator.steps_for(command.duration);
+            let metrics = integrator.run(
+                &self.state.energy_model,
+                &mut self.state.chain,
+                self.temperature,
+                steps,
+                &mut self.rng,
+            );
+            outcome.physics_metrics = Some(metrics.clone());
+            pending_metrics = Some(metrics);
+            physics_applied = true;
+        }
         if let Err(err) = self.validator.validate_structure(&self.state.chain) {
             self.state.restore(snapshot);
             self.pending_alias = alias;
@@ -428,29 +1509,20 @@ impl FoldingEngine {
             return Err(err);
         }
 
-        let new_energy = self.state.energy_model.total_energy(&self.state.chain);
+        let new_energy = self.state.potential_energy();
         let delta_energy = new_energy - baseline_energy;
         let projected_entropy =
             self.state.trajectory().total_entropy() + outcome.span_record.delta_entropy;
         let projected_gibbs = new_energy - self.temperature * projected_entropy;
         outcome.span_record.delta_energy = delta_energy;
         outcome.span_record.gibbs_energy = projected_gibbs;
-        if delta_energy > 0.0 {
-            let beta = 1.0 / (self.boltzmann_constant * self.temperature.max(1.0));
-            let exponent = (-delta_energy * beta).clamp(-700.0, 50.0);
-            let acceptance = exponent.exp().min(1.0);
-            let roll: f64 = self.rng.gen_range(0.0..1.0);
-            if roll >= acceptance {
-                self.state.restore(snapshot);
-                self.pending_alias = alias;
-                self.metropolis_stats.record_reject();
-                self.increment_step();
-                return Err(RuleViolation::MetropolisRejected { delta_energy });
-            }
+        if !self.metropolis_accept(delta_energy) {
+            self.state.restore(snapshot);
+            self.pending_alias = alias;
+            self.increment_step();
+            return Err(RuleViolation::MetropolisRejected { delta_energy });
         }
 
-        self.metropolis_stats.record_accept();
-
         let trajectory = self.state.trajectory_mut();
         trajectory.push(outcome.span_record.clone());
         if physics_applied {
@@ -462,9 +1534,244 @@ impl FoldingEngine {
             }
         }
         self.increment_step();
+        self.record_structure(outcome.span_record.id.as_str());
         Ok(outcome)
     }
 
+    /// Metropolis test on `delta_energy`, recorded in the run statistics.
+    fn metropolis_accept(&mut self, delta_energy: f64) -> bool {
+        let accepted =
+            delta_energy <= 0.0 || self.roll() < boltzmann_acceptance(-delta_energy * self.beta());
+        if accepted {
+            self.metropolis_stats.record_accept();
+        } else {
+            self.metropolis_stats.record_reject();
+        }
+        accepted
+    }
+
+    /// Chain indices covered by `domain`, if both ends exist.
+    fn domain_range(&self, domain: &DomainDefinition) -> Option<(usize, usize)> {
+        let start = self.state.chain.index_of(domain.start)?;
+        let end = self.state.chain.index_of(domain.end)?;
+        Some((start.min(end), start.max(end)))
+    }
+
+    fn residue_index(&self, residue: ResidueId) -> Result<usize, RuleViolation> {
+        self.state
+            .chain
+            .index_of(residue)
+            .ok_or(RuleViolation::UnknownResidue { residue })
+    }
+
+    fn add_restraint(&mut self, restraint: Result<Restraint, RuleViolation>, run: &mut RunLog) {
+        match restraint {
+            Ok(restraint) => self.state.energy_model.restraints.push(restraint),
+            Err(err) => run.rejections.push(err),
+        }
+    }
+
+    /// RMSD restraint toward the structure at `path`, over the residues it
+    /// shares with the chain by number.
+    fn reference_restraint(
+        &self,
+        path: &str,
+        target: f64,
+        force_constant: f64,
+    ) -> Result<Restraint, RuleViolation> {
+        let unavailable = |reason: String| RuleViolation::ReferenceStructureUnavailable {
+            path: path.to_string(),
+            reason,
+        };
+        let reference =
+            read_structure(Path::new(path), None).map_err(|err| unavailable(err.to_string()))?;
+        let (residues, positions): (Vec<usize>, Vec<[f64; 3]>) = reference
+            .residues()
+            .iter()
+            .filter_map(|residue| {
+                let index = self.state.chain.index_of(residue.id)?;
+                Some((index, residue.position()))
+            })
+            .unzip();
+        if residues.is_empty() {
+            return Err(unavailable(
+                "no residue numbers in common with the chain".into(),
+            ));
+        }
+        Ok(Restraint::Rmsd {
+            residues,
+            reference: positions,
+            target,
+            force_constant,
+        })
+    }
+
+    fn find_domain(&self, name: &str) -> Result<(usize, usize), RuleViolation> {
+        self.domains
+            .iter()
+            .rev()
+            .find(|domain| domain.name.as_deref() == Some(name))
+            .and_then(|domain| self.domain_range(domain))
+            .ok_or_else(|| RuleViolation::UnknownDomain {
+                name: name.to_string(),
+            })
+    }
+
+    /// Domain that a torsion rotation at `residue` would bend. Rotating at
+    /// residue `i` swings everything after `i` about the `(i-1, i)` bond, so
+    /// a domain keeps its shape unless it holds residues both before `i-1`
+    /// and after `i`; rotations at its hinges stay allowed.
+    fn locking_domain(&self, residue: ResidueId) -> Option<&DomainDefinition> {
+        let pivot = self.state.chain.index_of(residue)?;
+        self.domains.iter().find(|domain| {
+            self.domain_range(domain)
+                .is_some_and(|(start, end)| start + 2 <= pivot && pivot < end)
+        })
+    }
+
+    /// Rotates a domain rigidly about its centroid and translates it, then
+    /// applies the same structure and Metropolis checks as a rotation.
+    fn execute_domain_move(
+        &mut self,
+        name: &str,
+        angle_degrees: f64,
+        axis: [f64; 3],
+        translation: [f64; 3],
+    ) -> Result<RotationOutcome, RuleViolation> {
+        self.apply_temperature_schedule();
+        let (start, end) = self.find_domain(name)?;
+        let alias = self.pending_alias.take();
+        let mut moved = self.state.chain.clone();
+        let mut positions = moved.positions();
+        rigid_move(
+            &mut positions[start..=end],
+            axis,
+            angle_degrees.to_radians(),
+            translation,
+        );
+        moved.set_positions(&positions);
+
+        let baseline_energy = self.state.energy_model.total_energy(&self.state.chain);
+        let new_energy = self.state.energy_model.total_energy(&moved);
+        let delta_energy = new_energy - baseline_energy;
+        let mut span = SpanRecord::new(
+            alias.clone().unwrap_or_else(|| format!("domain-{name}")),
+            0.0,
+            0.0,
+            Duration::from_millis(1),
+        );
+        span.delta_theta = angle_degrees;
+        span.delta_energy = delta_energy;
+        self.increment_step();
+        let mut outcome = RotationOutcome {
+            applied_angle: angle_degrees,
+            span_record: span,
+            ghost: false,
+            physics_metrics: None,
+        };
+        if self.ghost_mode {
+            outcome.ghost = true;
+            self.ghost_trajectory.push(outcome.span_record.clone());
+            return Ok(outcome);
+        }
+        if let Err(err) = self.validator.validate_structure(&moved) {
+            self.pending_alias = alias;
+            return Err(err);
+        }
+        if !self.metropolis_accept(delta_energy) {
+            self.pending_alias = alias;
+            return Err(RuleViolation::MetropolisRejected { delta_energy });
+        }
+
+        self.state.chain = moved;
+        outcome.span_record.gibbs_energy =
+            new_energy - self.temperature * self.state.trajectory().total_entropy();
+        self.state
+            .trajectory_mut()
+            .push(outcome.span_record.clone());
+        self.record_structure(outcome.span_record.id.as_str());
+        Ok(outcome)
+    }
+
+    /// Marks the residue as modified so its charge, LJ bead and mass change.
+    /// Names without a physical model are recorded with no effect; the
+    /// contract parser warns about them.
+    fn add_modification(
+        &mut self,
+        entry: &PostTranslationalModification,
+    ) -> Result<(), RuleViolation> {
+        let residue =
+            self.state
+                .chain
+                .residue_mut(entry.residue)
+                .ok_or(RuleViolation::UnknownResidue {
+                    residue: entry.residue,
+                })?;
+        if let Some(modification) = Modification::from_name(&entry.modification) {
+            residue.modification = Some(modification);
+        }
+        Ok(())
+    }
+
+    /// Shields the chaperone's client (a named domain, or the whole chain)
+    /// against hydrophobic collapse.
+    fn require_chaperone(
+        &mut self,
+        requirement: &ChaperoneRequirement,
+    ) -> Result<(), RuleViolation> {
+        let (start, end) = match &requirement.span {
+            Some(name) => self.find_domain(name)?,
+            None => (0, self.state.chain.len().saturating_sub(1)),
+        };
+        self.state
+            .energy_model
+            .aggregation_shields
+            .push(AggregationShield {
+                start,
+                end,
+                strength: chaperone_strength(&requirement.chaperone),
+            });
+        Ok(())
+    }
+
+    /// Relaxes the current chain with L-BFGS and records the relaxation as a
+    /// span carrying the energy change. In ghost mode the relaxed geometry is
+    /// discarded; a relaxed structure that breaks the ruleset is rejected.
+    fn execute_minimization(
+        &mut self,
+        steps: usize,
+        tolerance: f64,
+    ) -> Result<MinimizationReport, RuleViolation> {
+        let alias = self.pending_alias.take();
+        let mut relaxed = self.state.chain.clone();
+        let report =
+            Minimizer::new(steps, tolerance).minimize(&self.state.energy_model, &mut relaxed);
+        let mut span = SpanRecord::new(
+            alias.clone().unwrap_or_else(|| "minimize".to_string()),
+            0.0,
+            0.0,
+            Duration::from_millis(report.steps.max(1) as u64),
+        );
+        span.delta_energy = report.delta_energy();
+        self.increment_step();
+        if self.ghost_mode {
+            self.ghost_trajectory.push(span);
+            return Ok(report);
+        }
+        if let Err(err) = self.validator.validate_structure(&relaxed) {
+            self.pending_alias = alias;
+            return Err(err);
+        }
+
+        self.state.chain = relaxed;
+        span.gibbs_energy =
+            report.final_energy - self.temperature * self.state.trajectory().total_entropy();
+        let span_id = span.id.as_str().to_string();
+        self.state.trajectory_mut().push(span);
+        self.record_structure(&span_id);
+        Ok(report)
+    }
+
     fn commit(&mut self) {
         self.checkpoints.push(self.state.snapshot());
     }
@@ -472,6 +1779,7 @@ impl FoldingEngine {
     fn rollback(&mut self) {
         if let Some(snapshot) = self.checkpoints.pop() {
             self.state.restore(snapshot);
+            self.record_structure("rollback");
         } else {
             let _ = self.state.trajectory_mut().pop_last();
         }
@@ -488,14 +1796,62 @@ impl FoldingEngine {
         self.state.trajectory()
     }
 
+    pub fn chain(&self) -> &PeptideChain {
+        &self.state.chain
+    }
+
+    pub(crate) fn validator(&self) -> &Validator {
+        &self.validator
+    }
+
+    pub(crate) fn solver(&self) -> &RotationSolver {
+        &self.solver
+    }
+
     fn apply_temperature_schedule(&mut self) {
-        if let Some(schedule) = &self.temperature_schedule {
-            let new_temp = schedule.temperature_for_step(self.step_index, self.initial_temperature);
-            self.temperature = new_temp;
+        let Some(schedule) = &self.temperature_schedule else {
+            return;
+        };
+        if let TemperatureSchedule::Adaptive { window, .. } = schedule {
+            let window_stats = MetropolisStats {
+                accepted: self.metropolis_stats.accepted - self.adaptive_mark.accepted,
+                rejected: self.metropolis_stats.rejected - self.adaptive_mark.rejected,
+            };
+            if window_stats.total() >= (*window).max(1) {
+                let rate = window_stats.acceptance_rate();
+                self.temperature = schedule.adapt(self.temperature, rate);
+                self.step_scale = schedule.adapt_step_scale(self.step_scale, rate);
+                self.adaptive_mark = self.metropolis_stats.clone();
+            }
+        } else {
+            let step = self.step_index - self.schedule_origin;
+            self.temperature = schedule.temperature_for_step(step, self.initial_temperature);
         }
     }
 
     fn increment_step(&mut self) {
         self.step_index = self.step_index.saturating_add(1);
     }
+
+    fn record_structure(&mut self, span_id: &str) {
+        let due = self.trajectory_interval.is_some_and(|interval| {
+            self.trajectory_frames
+                .last()
+                .is_none_or(|last| self.step_index >= last.step + interval)
+        });
+        let positions = self.state.chain.positions();
+        self.structure_frames.push(StructureFrame {
+            step: self.step_index,
+            span_id: span_id.to_string(),
+            metrics: self.structure_reference.measure(&self.state.chain),
+            contacts: contact_map(&positions),
+        });
+        if due {
+            self.trajectory_frames.push(TrajectoryFrame {
+                step: self.step_index,
+                label: span_id.to_string(),
+                positions,
+            });
+        }
+    }
 }
@@ -1,20 +1,34 @@
+pub mod checkpoint;
+pub mod contract_diagnostics;
+mod contract_expansion;
+mod contract_format;
+pub mod dry_run;
+pub mod dynamics;
 pub mod folding_parser;
 pub mod folding_ruleset;
 pub mod folding_runtime;
 pub mod micro_oscillator;
 pub mod physics_bridge;
 pub mod protein_state;
+pub mod replica_exchange;
 pub mod rotation_solver;
+pub mod schedule;
 pub mod validation;
 
+pub use checkpoint::{CheckpointError, EngineCheckpoint, CHECKPOINT_VERSION};
+pub use contract_diagnostics::{Diagnostic, ParsedContract, Severity};
+pub use dry_run::{BudgetProjection, DryRunReport, GhostRegion, ProjectedViolation};
+pub use dynamics::LangevinIntegrator;
 pub use folding_parser::{ContractInstruction, FoldingContract, PhysicsLevel, PhysicsSpanMode};
 pub use folding_ruleset::{RuleViolation, Ruleset};
 pub use folding_runtime::{
     ChaperoneRequirement, DomainDefinition, ExecutionReport, FoldingEngine, FoldingEngineBuilder,
-    MetropolisStats, PhysicsSpanRecord, PostTranslationalModification, TemperatureSchedule,
+    MetropolisStats, PhysicsSpanRecord, PostTranslationalModification, StructureFrame,
 };
 pub use micro_oscillator::MicroOscillator;
 pub use physics_bridge::{PhysicsRequest, PhysicsSpanMetrics};
 pub use protein_state::{EnergyState, ProteinState};
+pub use replica_exchange::{ReplicaExchange, ReplicaExchangeReport, SwapStats};
 pub use rotation_solver::{RotationCommand, RotationOutcome, RotationSolver};
+pub use schedule::TemperatureSchedule;
 pub use validation::{ValidationEvent, Validator};
@@ -1,6 +1,7 @@
 use crate::folding_parser::PhysicsLevel;
 use crate::rotation_solver::{RotationCommand, RotationOutcome};
 use folding_molecule::PeptideChain;
+use serde::{Deserialize, Serialize};
 
 /// Request passed to the physics backend bridge.
 pub struct PhysicsRequest<'a> {
@@ -11,7 +12,7 @@ pub struct PhysicsRequest<'a> {
 }
 
 /// Diagnostics captured when a physics backend services a span.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PhysicsSpanMetrics {
     pub rmsd: f64,
     pub radius_of_gyration: f64,
@@ -23,7 +24,8 @@ pub struct PhysicsSpanMetrics {
 }
 
 /// Attempt to execute a physics-backed step. Returns `None` when the OpenMM
-/// bridge is not available or the request cannot be satisfied.
+/// bridge is not available or the request cannot be satisfied; the engine
+/// then falls back to the native [`crate::dynamics::LangevinIntegrator`].
 pub fn run_physics_step(request: PhysicsRequest<'_>) -> Option<RotationOutcome> {
     #[cfg(feature = "openmm")]
     {
@@ -1,5 +1,6 @@
-use folding_molecule::{EnergyModel, PeptideChain, ResidueId};
+use folding_molecule::{CellList, EnergyModel, PeptideChain, ResidueId};
 use folding_time::Trajectory;
+use serde::{Deserialize, Serialize};
 
 /// Runtime representation of the protein being folded.
 #[derive(Debug)]
@@ -7,10 +8,12 @@ pub struct ProteinState {
     pub chain: PeptideChain,
     pub trajectory: Trajectory,
     pub energy_model: EnergyModel,
+    /// Cell list carried between [`Self::potential_energy`] calls.
+    cells: Option<CellList>,
 }
 
 /// Snapshot used for commit/rollback operations.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProteinSnapshot {
     pub chain: PeptideChain,
     pub trajectory: Trajectory,
@@ -29,6 +32,7 @@ impl ProteinState {
             chain,
             trajectory: Trajectory::new(),
             energy_model,
+            cells: None,
         }
     }
 
@@ -54,21 +58,28 @@ impl ProteinState {
         }
     }
 
+    /// Total potential energy of the chain. The cell list from the previous
+    /// call is refreshed in place, re-binning only residues that moved; it is
+    /// rebuilt when the cutoff or chain length has changed.
+    pub fn potential_energy(&mut self) -> f64 {
+        let positions = self.chain.positions();
+        let cutoff = self.energy_model.nonbonded_cutoff.unwrap_or(f64::INFINITY);
+        let cells = match &mut self.cells {
+            Some(cells) if cells.cutoff() == cutoff && cells.len() == positions.len() => {
+                cells.refresh(&positions);
+                cells
+            }
+            cells => cells.insert(CellList::new(&positions, cutoff)),
+        };
+        self.energy_model
+            .energy_summary_with(&self.chain, cells)
+            .total()
+    }
+
+    /// Rotates the torsion at `residue` and rebuilds the downstream chain
+    /// around the bond axis (see [`PeptideChain::rotate_torsion`]).
     pub fn apply_rotation(&mut self, residue: ResidueId, delta_angle: f64) {
-        if let Some(residue_entry) = self.chain.residue_mut(residue) {
-            residue_entry.phi += delta_angle;
-            let current_position = residue_entry.position();
-            let radius = (current_position[0].powi(2) + current_position[1].powi(2))
-                .sqrt()
-                .max(1.0);
-            let angle_rad = residue_entry.phi.to_radians();
-            let new_position = [
-                radius * angle_rad.cos(),
-                radius * angle_rad.sin(),
-                current_position[2],
-            ];
-            residue_entry.set_position(new_position);
-        }
+        self.chain.rotate_torsion(residue, delta_angle);
     }
 
     pub fn snapshot(&self) -> ProteinSnapshot {
@@ -83,3 +94,24 @@ impl ProteinState {
         self.trajectory = snapshot.trajectory;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use folding_molecule::{AminoAcid, StartingConformation};
+
+    #[test]
+    fn potential_energy_tracks_rotations_through_the_cell_list() {
+        let sequence = [AminoAcid::Lysine, AminoAcid::Alanine, AminoAcid::Glutamate];
+        let sequence: Vec<AminoAcid> = sequence.iter().cycle().take(40).copied().collect();
+        let chain = PeptideChain::from_sequence(&sequence, StartingConformation::Helix);
+        let mut state = ProteinState::new(chain, EnergyModel::alpha_carbon_trace());
+
+        let before = state.potential_energy();
+        assert_eq!(before, state.energy_model.total_energy(&state.chain));
+        state.apply_rotation(ResidueId(20), 35.0);
+        let after = state.potential_energy();
+        assert_ne!(after, before);
+        assert!((after - state.energy_model.total_energy(&state.chain)).abs() < 1e-9);
+    }
+}
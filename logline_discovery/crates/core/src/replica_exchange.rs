@@ -0,0 +1,357 @@
+//! Replica-exchange (parallel tempering) driver.
+//!
+//! One [`FoldingEngine`] runs per rung of a temperature ladder. The contract
+//! is executed in blocks of rotations on every replica in parallel; between
+//! blocks, neighbouring rungs try to trade conformations with the Metropolis
+//! criterion `min(1, exp((β_i − β_j)(E_i − E_j)))`. Hot replicas cross
+//! barriers and hand good conformations down to the cold end of the ladder.
+//! Every rung keeps its ladder temperature, so `set_schedule` instructions
+//! are not run and are reported as rejections instead.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::folding_parser::{ContractInstruction, FoldingContract};
+use crate::folding_ruleset::RuleViolation;
+use crate::folding_runtime::{
+    boltzmann_acceptance, ExecutionReport, FoldingEngine, FoldingEngineBuilder, MetropolisStats,
+    RunLog,
+};
+
+/// Rotations every replica executes between two rounds of swap attempts.
+const DEFAULT_EXCHANGE_INTERVAL: usize = 10;
+/// Offsets the swap stream from the per-replica seeds.
+const SWAP_SEED_OFFSET: u64 = 0x5eed_5a5a;
+
+/// Swap attempts between two neighbouring rungs of the ladder.
+#[derive(Clone, Debug)]
+pub struct SwapStats {
+    pub lower_temperature: f64,
+    pub upper_temperature: f64,
+    pub accepted: usize,
+    pub rejected: usize,
+}
+
+impl SwapStats {
+    pub fn total(&self) -> usize {
+        self.accepted + self.rejected
+    }
+
+    pub fn acceptance_rate(&self) -> f64 {
+        let total = self.total();
+        if total == 0 {
+            0.0
+        } else {
+            self.accepted as f64 / total as f64
+        }
+    }
+}
+
+/// Ladder-wide results attached to the combined [`ExecutionReport`].
+#[derive(Clone, Debug)]
+pub struct ReplicaExchangeReport {
+    /// Rung temperatures in ascending order.
+    pub temperatures: Vec<f64>,
+    /// Final potential energy per rung.
+    pub final_energies: Vec<f64>,
+    pub metropolis_stats: Vec<MetropolisStats>,
+    /// `swap_stats[i]` covers rungs `i` and `i + 1`.
+    pub swap_stats: Vec<SwapStats>,
+    pub exchange_rounds: usize,
+    /// Rung whose report was returned as the combined report.
+    pub best_replica: usize,
+}
+
+pub struct ReplicaExchange {
+    replicas: Vec<FoldingEngine>,
+    exchange_interval: usize,
+    rng: StdRng,
+}
+
+impl ReplicaExchange {
+    /// Builds one engine per temperature from `builder`. Replica seeds are
+    /// derived from the builder's seed, so a seeded run is reproducible
+    /// regardless of the rayon thread count.
+    pub fn new(builder: FoldingEngineBuilder, temperatures: &[f64]) -> Self {
+        assert!(
+            !temperatures.is_empty(),
+            "replica exchange needs at least one temperature"
+        );
+        let mut ladder = temperatures.to_vec();
+        ladder.sort_by(f64::total_cmp);
+        let replicas = ladder
+            .iter()
+            .enumerate()
+            .map(|(index, temperature)| builder.build_replica(index, *temperature))
+            .collect();
+        let rng = match builder.rng_seed() {
+            Some(seed) => StdRng::seed_from_u64(seed ^ SWAP_SEED_OFFSET),
+            None => StdRng::from_entropy(),
+        };
+        Self {
+            replicas,
+            exchange_interval: DEFAULT_EXCHANGE_INTERVAL,
+            rng,
+        }
+    }
+
+    /// `count` temperatures spaced geometrically from `min` to `max`, which
+    /// keeps swap acceptance roughly even along the ladder.
+    pub fn geometric_ladder(min: f64, max: f64, count: usize) -> Vec<f64> {
+        match count {
+            0 => Vec::new(),
+            1 => vec![min],
+            _ => {
+                let ratio = (max / min).powf(1.0 / (count - 1) as f64);
+                (0..count).map(|i| min * ratio.powi(i as i32)).collect()
+            }
+        }
+    }
+
+    pub fn with_exchange_interval(mut self, rotations: usize) -> Self {
+        self.exchange_interval = rotations.max(1);
+        self
+    }
+
+    pub fn replicas(&self) -> &[FoldingEngine] {
+        &self.replicas
+    }
+
+    pub fn temperatures(&self) -> Vec<f64> {
+        self.replicas
+            .iter()
+            .map(FoldingEngine::temperature)
+            .collect()
+    }
+
+    /// Runs `contract` on every replica with swap rounds between blocks and
+    /// returns the report of the lowest-energy replica.
+    pub fn execute_contract(&mut self, contract: &FoldingContract) -> ExecutionReport {
+        let temperatures = self.temperatures();
+        let mut swap_stats: Vec<SwapStats> = temperatures
+            .windows(2)
+            .map(|pair| SwapStats {
+                lower_temperature: pair[0],
+                upper_temperature: pair[1],
+                accepted: 0,
+                rejected: 0,
+            })
+            .collect();
+        let mut runs: Vec<RunLog> = self.replicas.iter().map(|_| RunLog::default()).collect();
+        for replica in &mut self.replicas {
+            replica.begin_run();
+        }
+
+        let mut exchange_rounds = 0;
+        for block in self.blocks(&contract.instructions) {
+            self.replicas
+                .par_iter_mut()
+                .zip(runs.par_iter_mut())
+                .for_each(|(replica, run)| {
+                    for instruction in block {
+                        // Each rung holds its ladder temperature.
+                        if matches!(instruction, ContractInstruction::SetSchedule(_)) {
+                            run.rejections
+                                .push(RuleViolation::ScheduleUnderReplicaExchange);
+                        } else {
+                            replica.execute_instruction(instruction, run);
+                        }
+                    }
+                });
+            if block
+                .iter()
+                .any(|instruction| matches!(instruction, ContractInstruction::Rotate { .. }))
+            {
+                self.attempt_swaps(exchange_rounds % 2, &mut swap_stats);
+                exchange_rounds += 1;
+            }
+        }
+
+        let mut reports: Vec<ExecutionReport> = self
+            .replicas
+            .iter()
+            .zip(runs)
+            .map(|(replica, run)| replica.finish_run(run))
+            .collect();
+        let final_energies: Vec<f64> = reports
+            .iter()
+            .map(|report| report.final_energy.total_potential)
+            .collect();
+        let best_replica = final_energies
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(index, _)| index)
+            .unwrap_or(0);
+        let metropolis_stats = reports
+            .iter()
+            .map(|report| report.metropolis_stats.clone())
+            .collect();
+        let mut combined = reports.swap_remove(best_replica);
+        combined.replica_exchange = Some(ReplicaExchangeReport {
+            temperatures,
+            final_energies,
+            metropolis_stats,
+            swap_stats,
+            exchange_rounds,
+            best_replica,
+        });
+        combined
+    }
+
+    /// Splits the contract after every `exchange_interval` rotations.
+    fn blocks<'a>(
+        &self,
+        instructions: &'a [ContractInstruction],
+    ) -> Vec<&'a [ContractInstruction]> {
+        let mut blocks = Vec::new();
+        let mut start = 0;
+        let mut rotations = 0;
+        for (index, instruction) in instructions.iter().enumerate() {
+            if matches!(instruction, ContractInstruction::Rotate { .. }) {
+                rotations += 1;
+                if rotations == self.exchange_interval {
+                    blocks.push(&instructions[start..=index]);
+                    start = index + 1;
+                    rotations = 0;
+                }
+            }
+        }
+        if start < instructions.len() {
+            blocks.push(&instructions[start..]);
+        }
+        blocks
+    }
+
+    /// Tries to swap rung pairs `(offset, offset + 1)`, `(offset + 2, …)`;
+    /// alternating the offset between rounds lets every pair exchange.
+    fn attempt_swaps(&mut self, offset: usize, swap_stats: &mut [SwapStats]) {
+        for lower in (offset..self.replicas.len().saturating_sub(1)).step_by(2) {
+            let (cold, hot) = self.replicas.split_at_mut(lower + 1);
+            let (cold, hot) = (&mut cold[lower], &mut hot[0]);
+            let exponent =
+                (cold.beta() - hot.beta()) * (cold.potential_energy() - hot.potential_energy());
+            let stats = &mut swap_stats[lower];
+            if self.rng.gen_range(0.0..1.0) < boltzmann_acceptance(exponent) {
+                cold.exchange_conformation(hot);
+                stats.accepted += 1;
+            } else {
+                stats.rejected += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::folding_ruleset::Ruleset;
+    use crate::micro_oscillator::MicroOscillator;
+    use folding_molecule::{AminoAcid, EnergyModel, PeptideChain, StartingConformation};
+
+    fn builder() -> FoldingEngineBuilder {
+        let sequence = [
+            AminoAcid::Alanine,
+            AminoAcid::Leucine,
+            AminoAcid::Lysine,
+            AminoAcid::Glutamate,
+            AminoAcid::Glycine,
+            AminoAcid::Valine,
+            AminoAcid::Serine,
+            AminoAcid::Alanine,
+        ];
+        FoldingEngineBuilder::new()
+            .with_chain(PeptideChain::from_sequence(
+                &sequence,
+                StartingConformation::Extended,
+            ))
+            .with_energy_model(EnergyModel::alpha_carbon_trace())
+            .with_ruleset(Ruleset::alpha_carbon_trace())
+            .with_oscillator(MicroOscillator::new(0.0, 0.0))
+            .with_rng_seed(21)
+    }
+
+    fn contract() -> FoldingContract {
+        let lines: Vec<String> = (0..40)
+            .map(|i| {
+                let residue = 2 + i % 5;
+                let angle = if i % 3 == 0 { -25.0 } else { 20.0 };
+                format!("rotate residue={residue} angle={angle} duration=1")
+            })
+            .collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        FoldingContract::from_lines(&lines)
+    }
+
+    #[test]
+    fn geometric_ladder_spans_range() {
+        let ladder = ReplicaExchange::geometric_ladder(300.0, 600.0, 4);
+        assert_eq!(ladder.len(), 4);
+        assert!((ladder[0] - 300.0).abs() < 1e-9);
+        assert!((ladder[3] - 600.0).abs() < 1e-9);
+        assert!((ladder[1] / ladder[0] - ladder[2] / ladder[1]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn exchange_reports_lowest_energy_replica() {
+        let ladder = ReplicaExchange::geometric_ladder(280.0, 900.0, 4);
+        let mut exchange = ReplicaExchange::new(builder(), &ladder).with_exchange_interval(5);
+        let report = exchange.execute_contract(&contract());
+
+        let summary = report.replica_exchange.as_ref().expect("replica summary");
+        assert_eq!(summary.temperatures.len(), 4);
+        assert_eq!(summary.swap_stats.len(), 3);
+        assert_eq!(summary.exchange_rounds, 8);
+        let attempts: usize = summary.swap_stats.iter().map(SwapStats::total).sum();
+        assert_eq!(attempts, 12);
+        let lowest = summary
+            .final_energies
+            .iter()
+            .copied()
+            .fold(f64::INFINITY, f64::min);
+        assert_eq!(report.final_energy.total_potential, lowest);
+        assert_eq!(
+            summary.final_energies[summary.best_replica],
+            report.final_energy.total_potential
+        );
+        assert!(summary
+            .metropolis_stats
+            .iter()
+            .all(|stats| stats.total() == 40));
+
+        assert!(summary.swap_stats.iter().any(|stats| stats.accepted > 0));
+        // Same seed, same ladder: identical outcome.
+        let mut replay = ReplicaExchange::new(builder(), &ladder).with_exchange_interval(5);
+        let again = replay.execute_contract(&contract());
+        let again = again.replica_exchange.expect("replica summary");
+        assert_eq!(again.final_energies, summary.final_energies);
+        assert_eq!(
+            again
+                .swap_stats
+                .iter()
+                .map(|s| s.accepted)
+                .collect::<Vec<_>>(),
+            summary
+                .swap_stats
+                .iter()
+                .map(|s| s.accepted)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn set_schedule_is_rejected_on_every_rung() {
+        let ladder = ReplicaExchange::geometric_ladder(300.0, 600.0, 2);
+        let mut exchange = ReplicaExchange::new(builder(), &ladder);
+        let report = exchange.execute_contract(&FoldingContract::from_lines(&[
+            "set_schedule linear start=900 end=300 steps=10",
+            "rotate residue=3 angle=10",
+        ]));
+
+        assert!(matches!(
+            report.rejections.as_slice(),
+            [RuleViolation::ScheduleUnderReplicaExchange]
+        ));
+        assert_eq!(exchange.temperatures(), ladder);
+    }
+}
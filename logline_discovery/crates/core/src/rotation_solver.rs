@@ -3,6 +3,7 @@ use std::time::Duration;
 use folding_molecule::ResidueId;
 use folding_time::trajectory::SpanRecord;
 use folding_time::RotationClock;
+use serde::{Deserialize, Serialize};
 
 use crate::micro_oscillator::MicroOscillator;
 use crate::physics_bridge::PhysicsSpanMetrics;
@@ -17,7 +18,7 @@ pub struct RotationCommand {
 }
 
 /// Result from executing a rotation step.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RotationOutcome {
     pub applied_angle: f64,
     pub span_record: SpanRecord,
@@ -36,6 +37,10 @@ impl RotationSolver {
         Self { oscillator, clock }
     }
 
+    pub fn clock(&self) -> &RotationClock {
+        &self.clock
+    }
+
     pub fn solve(&self, command: RotationCommand) -> RotationOutcome {
         let oscillation = self.oscillator.sample(command.duration);
         let applied_angle = command.angle_degrees + oscillation;
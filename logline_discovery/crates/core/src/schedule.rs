@@ -0,0 +1,198 @@
+//! Temperature schedules shared by contract parsing and the engine.
+
+use serde::{Deserialize, Serialize};
+
+/// Multiplicative temperature and step-scale change per adaptive adjustment.
+const ADAPTIVE_GAIN: f64 = 1.1;
+/// Smallest fraction of a `rotate` angle the adaptive schedule shrinks
+/// steps to.
+pub const MIN_STEP_SCALE: f64 = 0.1;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemperatureSchedule {
+    Constant,
+    Linear {
+        start: f64,
+        end: f64,
+        steps: usize,
+    },
+    /// Geometric cooling `start·factor^step`, held at `end` once reached.
+    Exponential {
+        start: f64,
+        end: f64,
+        factor: f64,
+    },
+    /// Sawtooth: cools linearly from `high` to `low` over `period` steps,
+    /// then reheats to `high`.
+    Cyclic {
+        high: f64,
+        low: f64,
+        period: usize,
+    },
+    /// Every `window` Metropolis decisions, heats and shortens rotation steps
+    /// when acceptance fell below `target_acceptance`, and cools and lengthens
+    /// them when it rose above. The temperature stays within `[min, max]`;
+    /// `rotate` angles are scaled by between [`MIN_STEP_SCALE`] and 1.
+    Adaptive {
+        target_acceptance: f64,
+        min: f64,
+        max: f64,
+        window: usize,
+    },
+}
+
+impl TemperatureSchedule {
+    /// Temperature at `step`; `Constant` and `Adaptive` keep `initial`
+    /// (adaptive moves happen in [`Self::adapt`]).
+    pub fn temperature_for_step(&self, step: usize, initial: f64) -> f64 {
+        match self {
+            TemperatureSchedule::Constant | TemperatureSchedule::Adaptive { .. } => initial,
+            TemperatureSchedule::Linear { start, end, steps } => {
+                if *steps == 0 {
+                    *end
+                } else {
+                    let ratio = (step.min(*steps) as f64) / (*steps as f64);
+                    start + (end - start) * ratio
+                }
+            }
+            TemperatureSchedule::Exponential { start, end, factor } => {
+                let exponent = step.min(i32::MAX as usize) as i32;
+                let temperature = start * factor.powi(exponent);
+                if *factor < 1.0 {
+                    temperature.max(*end)
+                } else {
+                    temperature.min(*end)
+                }
+            }
+            TemperatureSchedule::Cyclic { high, low, period } => {
+                if *period == 0 {
+                    *low
+                } else {
+                    let ratio = (step % period) as f64 / *period as f64;
+                    high + (low - high) * ratio
+                }
+            }
+        }
+    }
+
+    /// Next adaptive temperature given the acceptance rate of the last window.
+    pub fn adapt(&self, current: f64, acceptance_rate: f64) -> f64 {
+        match self {
+            TemperatureSchedule::Adaptive {
+                target_acceptance,
+                min,
+                max,
+                ..
+            } => {
+                let next = if acceptance_rate < *target_acceptance {
+                    current * ADAPTIVE_GAIN
+                } else if acceptance_rate > *target_acceptance {
+                    current / ADAPTIVE_GAIN
+                } else {
+                    current
+                };
+                next.clamp(*min, *max)
+            }
+            _ => current,
+        }
+    }
+
+    /// Next rotation step scale given the acceptance rate of the last window.
+    /// Only `Adaptive` rescales steps; every other schedule returns `current`.
+    pub fn adapt_step_scale(&self, current: f64, acceptance_rate: f64) -> f64 {
+        match self {
+            TemperatureSchedule::Adaptive {
+                target_acceptance, ..
+            } => {
+                let next = if acceptance_rate < *target_acceptance {
+                    current / ADAPTIVE_GAIN
+                } else if acceptance_rate > *target_acceptance {
+                    current * ADAPTIVE_GAIN
+                } else {
+                    current
+                };
+                next.clamp(MIN_STEP_SCALE, 1.0)
+            }
+            _ => current,
+        }
+    }
+
+    /// Temperature the schedule starts from, if it prescribes one.
+    pub fn start_temperature(&self) -> Option<f64> {
+        match self {
+            TemperatureSchedule::Linear { start, .. }
+            | TemperatureSchedule::Exponential { start, .. } => Some(*start),
+            TemperatureSchedule::Cyclic { high, .. } => Some(*high),
+            TemperatureSchedule::Constant | TemperatureSchedule::Adaptive { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_schedule_returns_initial() {
+        let schedule = TemperatureSchedule::Constant;
+        assert_eq!(schedule.temperature_for_step(10, 300.0), 300.0);
+    }
+
+    #[test]
+    fn linear_schedule_interpolates() {
+        let schedule = TemperatureSchedule::Linear {
+            start: 400.0,
+            end: 300.0,
+            steps: 10,
+        };
+        assert!((schedule.temperature_for_step(0, 400.0) - 400.0).abs() < 1e-6);
+        assert!((schedule.temperature_for_step(5, 400.0) - 350.0).abs() < 1e-6);
+        assert!((schedule.temperature_for_step(10, 400.0) - 300.0).abs() < 1e-6);
+        assert!((schedule.temperature_for_step(20, 400.0) - 300.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn exponential_and_cyclic_schedules() {
+        let exponential = TemperatureSchedule::Exponential {
+            start: 400.0,
+            end: 300.0,
+            factor: 0.9,
+        };
+        assert!((exponential.temperature_for_step(1, 0.0) - 360.0).abs() < 1e-9);
+        assert!((exponential.temperature_for_step(2, 0.0) - 324.0).abs() < 1e-9);
+        assert_eq!(exponential.temperature_for_step(50, 0.0), 300.0);
+
+        let cyclic = TemperatureSchedule::Cyclic {
+            high: 500.0,
+            low: 300.0,
+            period: 4,
+        };
+        let temperatures: Vec<f64> = (0..6)
+            .map(|step| cyclic.temperature_for_step(step, 0.0))
+            .collect();
+        assert_eq!(temperatures, vec![500.0, 450.0, 400.0, 350.0, 500.0, 450.0]);
+    }
+
+    #[test]
+    fn adaptive_schedule_tracks_target_acceptance() {
+        let schedule = TemperatureSchedule::Adaptive {
+            target_acceptance: 0.5,
+            min: 250.0,
+            max: 400.0,
+            window: 10,
+        };
+        assert!((schedule.adapt(300.0, 0.2) - 330.0).abs() < 1e-9);
+        assert!(schedule.adapt(300.0, 0.9) < 300.0);
+        assert_eq!(schedule.adapt(390.0, 0.0), 400.0);
+        assert_eq!(schedule.start_temperature(), None);
+
+        assert!((schedule.adapt_step_scale(1.0, 0.2) - 1.0 / 1.1).abs() < 1e-9);
+        assert_eq!(schedule.adapt_step_scale(1.0, 0.9), 1.0);
+        assert_eq!(schedule.adapt_step_scale(0.105, 0.0), MIN_STEP_SCALE);
+        assert_eq!(
+            TemperatureSchedule::Constant.adapt_step_scale(0.5, 0.0),
+            0.5
+        );
+    }
+}
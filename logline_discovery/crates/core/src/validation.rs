@@ -21,6 +21,10 @@ impl Validator {
         Self { ruleset }
     }
 
+    pub fn ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
     pub fn validate_rotation(
         &self,
         residue: ResidueId,
@@ -42,13 +42,21 @@ impl DiscoveryAgent {
         builder.add_section(overview);
 
         if let Some(analysis) = folding.first() {
+            let mut content = format!(
+                "Mean potential energy {:.2} kcal/mol, max RMSD {:.2} Å, unstable={}",
+                analysis.mean_energy, analysis.max_rmsd, analysis.unstable
+            );
+            if let Some(helix) = analysis.helix_fraction {
+                content.push_str(&format!(
+                    "; {} retained {:.0}% helicity",
+                    analysis.protein,
+                    helix * 100.0
+                ));
+            }
             let folding_section = Section {
                 id: "folding".to_string(),
                 title: "Folding Analysis".to_string(),
-                content: format!(
-                    "Mean potential energy {:.2} kcal/mol, max RMSD {:.2} Å, unstable={}",
-                    analysis.mean_energy, analysis.max_rmsd, analysis.unstable
-                ),
+                content,
                 subsections: vec![],
                 figure_refs: vec![],
                 citation_refs: vec![],
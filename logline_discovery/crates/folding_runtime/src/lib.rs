@@ -27,6 +27,12 @@ pub struct FoldingAnalysis {
     pub max_rmsd: f64,
     pub unstable: bool,
     pub window: (DateTime<Utc>, Option<DateTime<Utc>>),
+    /// Final helix and strand content in `[0, 1]`, when the span reports a
+    /// secondary-structure assignment.
+    #[serde(default)]
+    pub helix_fraction: Option<f64>,
+    #[serde(default)]
+    pub strand_fraction: Option<f64>,
 }
 
 impl FoldingAnalysis {
@@ -52,6 +58,8 @@ impl FoldingAnalysis {
             max_rmsd,
             unstable: max_rmsd > RMSD_UNSTABLE_THRESHOLD,
             window: (span.started_at, span.finished_at),
+            helix_fraction: pick_secondary_structure(&span.payload, "helix"),
+            strand_fraction: pick_secondary_structure(&span.payload, "strand"),
         })
     }
 
@@ -155,6 +163,18 @@ fn extract_frames(span: &UniversalSpan) -> Vec<FoldingFrame> {
     frames
 }
 
+fn pick_secondary_structure(payload: &Value, class: &str) -> Option<f64> {
+    pick_scalar(
+        payload,
+        &[
+            &format!("secondary_structure.{class}"),
+            &format!("results.secondary_structure.{class}"),
+            &format!("analysis.secondary_structure.{class}"),
+            &format!("{class}_fraction"),
+        ],
+    )
+}
+
 fn pick_series(payload: &Value, paths: &[&str]) -> Vec<f64> {
     for raw_path in paths {
         if let Some(values) = resolve_array(payload, raw_path) {
@@ -256,6 +276,7 @@ mod tests {
         assert!((analysis.mean_energy - (-120.0 - 118.0 - 122.0) / 3.0).abs() < 1e-6);
         assert_eq!(analysis.max_rmsd, 3.0);
         assert!(!analysis.unstable);
+        assert_eq!(analysis.helix_fraction, None);
     }
 
     #[test]
@@ -272,4 +293,28 @@ mod tests {
         assert_eq!(analysis.max_rmsd, 6.0);
         assert!(analysis.unstable);
     }
+
+    #[test]
+    fn secondary_structure_content() {
+        let span = span_from_value(json!({
+            "protein": "gp41 HR1",
+            "secondary_structure": {"assignment": "CHHHHT", "helix": 0.78, "strand": 0.0},
+            "results": {"final_rmsd_angstrom": 1.2}
+        }));
+
+        let analysis = FoldingAnalysis::from_span(&span).unwrap();
+        assert_eq!(analysis.helix_fraction, Some(0.78));
+        assert_eq!(analysis.strand_fraction, Some(0.0));
+
+        let legacy: FoldingAnalysis = serde_json::from_value(json!({
+            "span_id": "span::test",
+            "protein": "gp41",
+            "mean_energy": -1.0,
+            "max_rmsd": 1.0,
+            "unstable": false,
+            "window": ["2023-11-14T22:13:20Z", null]
+        }))
+        .unwrap();
+        assert_eq!(legacy.helix_fraction, None);
+    }
 }
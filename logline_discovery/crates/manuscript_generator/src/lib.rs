@@ -335,6 +335,18 @@ impl ManuscriptBuilder {
             }
         }
 
+        if let Some(helix) = analysis_data.get("helix_fraction").and_then(|v| v.as_f64()) {
+            let protein = analysis_data
+                .get("protein")
+                .and_then(|v| v.as_str())
+                .unwrap_or("The structure");
+            content.push_str(&format!("{} retained {:.0}% helicity", protein, helix * 100.0));
+            if let Some(strand) = analysis_data.get("strand_fraction").and_then(|v| v.as_f64()) {
+                content.push_str(&format!(" and {:.0}% strand content", strand * 100.0));
+            }
+            content.push_str(".\n\n");
+        }
+
         if let Some(stability) = analysis_data.get("stability").and_then(|v| v.as_str()) {
             content.push_str(&format!("Overall stability classified as {}.\n\n", stability));
         }
@@ -531,6 +543,19 @@ mod tests {
         assert_eq!(builder.manuscript.figures.len(), 1);
     }
 
+    #[test]
+    fn results_report_helicity() {
+        let mut builder = ManuscriptBuilder::new("Test".to_string(), "exec_001".to_string());
+        let analysis = serde_json::json!({
+            "protein": "gp41 HR1",
+            "helix_fraction": 0.78,
+            "strand_fraction": 0.05,
+        });
+        builder.add_results_section(&analysis, &Value::Null);
+        let results = &builder.manuscript.sections[0].content;
+        assert!(results.contains("gp41 HR1 retained 78% helicity and 5% strand content."));
+    }
+
     #[test]
     fn markdown_render_contains_title() {
         let manuscript = ManuscriptBuilder::new("Test".to_string(), "exec_001".to_string())
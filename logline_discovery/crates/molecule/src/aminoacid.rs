@@ -1,9 +1,12 @@
+use serde::{Deserialize, Serialize};
+
 /// Identifier for a residue in a peptide chain.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct ResidueId(pub usize);
 
 /// Simplified representation of amino acids; extend as needed.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AminoAcid {
     Alanine,
     Cysteine,
@@ -54,6 +57,59 @@ impl AminoAcid {
         }
     }
 
+    /// Parses a three-letter residue name as found in PDB/mmCIF files,
+    /// including common protonation-state and modified-residue aliases.
+    pub fn from_three_letter(code: &str) -> Option<Self> {
+        match code.trim().to_ascii_uppercase().as_str() {
+            "ALA" => Some(Self::Alanine),
+            "CYS" | "CYX" | "CYM" => Some(Self::Cysteine),
+            "ASP" | "ASH" => Some(Self::Aspartate),
+            "GLU" | "GLH" => Some(Self::Glutamate),
+            "PHE" => Some(Self::Phenylalanine),
+            "GLY" => Some(Self::Glycine),
+            "HIS" | "HID" | "HIE" | "HIP" | "HSD" | "HSE" | "HSP" => Some(Self::Histidine),
+            "ILE" => Some(Self::Isoleucine),
+            "LYS" | "LYN" => Some(Self::Lysine),
+            "LEU" => Some(Self::Leucine),
+            "MET" | "MSE" => Some(Self::Methionine),
+            "ASN" => Some(Self::Asparagine),
+            "PRO" => Some(Self::Proline),
+            "GLN" => Some(Self::Glutamine),
+            "ARG" => Some(Self::Arginine),
+            "SER" => Some(Self::Serine),
+            "THR" => Some(Self::Threonine),
+            "VAL" => Some(Self::Valine),
+            "TRP" => Some(Self::Tryptophan),
+            "TYR" => Some(Self::Tyrosine),
+            _ => None,
+        }
+    }
+
+    pub fn three_letter(self) -> &'static str {
+        match self {
+            Self::Alanine => "ALA",
+            Self::Cysteine => "CYS",
+            Self::Aspartate => "ASP",
+            Self::Glutamate => "GLU",
+            Self::Phenylalanine => "PHE",
+            Self::Glycine => "GLY",
+            Self::Histidine => "HIS",
+            Self::Isoleucine => "ILE",
+            Self::Lysine => "LYS",
+            Self::Leucine => "LEU",
+            Self::Methionine => "MET",
+            Self::Asparagine => "ASN",
+            Self::Proline => "PRO",
+            Self::Glutamine => "GLN",
+            Self::Arginine => "ARG",
+            Self::Serine => "SER",
+            Self::Threonine => "THR",
+            Self::Valine => "VAL",
+            Self::Tryptophan => "TRP",
+            Self::Tyrosine => "TYR",
+        }
+    }
+
     pub fn partial_charge(self) -> f64 {
         match self {
             Self::Aspartate | Self::Glutamate => -1.0,
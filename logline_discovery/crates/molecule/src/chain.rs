@@ -1,17 +1,24 @@
+use serde::{Deserialize, Serialize};
+
 use crate::aminoacid::{AminoAcid, ResidueId};
+use crate::fasta::StartingConformation;
+use crate::geometry::{self, BackboneAtoms, BackboneTorsions, InternalCoordinates};
+use crate::parameters::Modification;
 
 /// Residue entry in a peptide chain with simplified spatial metadata.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Residue {
     pub id: ResidueId,
     pub amino_acid: AminoAcid,
     pub phi: f64,
     pub psi: f64,
+    pub omega: f64,
+    pub modification: Option<Modification>,
     position: [f64; 3],
 }
 
 /// Ordered peptide chain used by the folding engine.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct PeptideChain {
     residues: Vec<Residue>,
 }
@@ -21,6 +28,22 @@ impl PeptideChain {
         Self { residues }
     }
 
+    /// Builds a chain numbered from 1 with ideal backbone geometry for the
+    /// requested conformation; residue positions are the Cα atoms.
+    pub fn from_sequence(sequence: &[AminoAcid], conformation: StartingConformation) -> Self {
+        let (phi, psi) = conformation.torsions();
+        let residues = sequence
+            .iter()
+            .enumerate()
+            .map(|(index, amino_acid)| {
+                Residue::new(ResidueId(index + 1), *amino_acid).with_torsions(phi, psi, 180.0)
+            })
+            .collect();
+        let mut chain = Self::new(residues);
+        chain.rebuild_from_torsions();
+        chain
+    }
+
     pub fn len(&self) -> usize {
         self.residues.len()
     }
@@ -36,6 +59,77 @@ impl PeptideChain {
     pub fn residue_mut(&mut self, id: ResidueId) -> Option<&mut Residue> {
         self.residues.iter_mut().find(|res| res.id == id)
     }
+
+    pub fn positions(&self) -> Vec<[f64; 3]> {
+        self.residues.iter().map(Residue::position).collect()
+    }
+
+    /// Assigns positions in residue order; extra entries are ignored.
+    pub fn set_positions(&mut self, positions: &[[f64; 3]]) {
+        for (residue, position) in self.residues.iter_mut().zip(positions) {
+            residue.position = *position;
+        }
+    }
+
+    pub fn index_of(&self, id: ResidueId) -> Option<usize> {
+        self.residues.iter().position(|res| res.id == id)
+    }
+
+    /// Rotates the torsion at `id` by `delta_degrees`.
+    ///
+    /// The residue's `phi` is updated and every residue after it swings
+    /// rigidly around the virtual bond joining it to its predecessor, so bond
+    /// lengths and angles along the trace are preserved. Returns `false` when
+    /// the residue does not exist.
+    pub fn rotate_torsion(&mut self, id: ResidueId, delta_degrees: f64) -> bool {
+        let Some(index) = self.index_of(id) else {
+            return false;
+        };
+        self.residues[index].phi += delta_degrees;
+
+        let mut internal = InternalCoordinates::from_positions(&self.positions());
+        if internal.rotate_bond(index, delta_degrees.to_radians()) {
+            self.set_positions(&internal.to_positions());
+        }
+        true
+    }
+
+    /// Shifts each residue's `phi` by how much the Cα virtual torsion it
+    /// drives in [`Self::rotate_torsion`] changed since the chain was at
+    /// `previous`, keeping the torsions in step with a Cartesian move.
+    pub fn sync_torsions(&mut self, previous: &[[f64; 3]]) {
+        let before = InternalCoordinates::from_positions(previous);
+        let after = InternalCoordinates::from_positions(&self.positions());
+        for (index, residue) in self.residues.iter_mut().enumerate() {
+            if let (Some(from), Some(to)) = (before.torsion(index), after.torsion(index)) {
+                let delta = (to - from).to_degrees();
+                residue.phi += delta - 360.0 * (delta / 360.0).round();
+            }
+        }
+    }
+
+    /// Ideal N/Cα/C backbone built from each residue's phi/psi/omega.
+    pub fn backbone(&self) -> Vec<BackboneAtoms> {
+        let torsions: Vec<BackboneTorsions> = self
+            .residues
+            .iter()
+            .map(|res| BackboneTorsions {
+                phi: res.phi,
+                psi: res.psi,
+                omega: res.omega,
+            })
+            .collect();
+        geometry::build_backbone(&torsions)
+    }
+
+    /// Replaces every residue position with the Cα of the ideal backbone
+    /// built from the current phi/psi/omega torsions.
+    pub fn rebuild_from_torsions(&mut self) {
+        let backbone = self.backbone();
+        for (residue, atoms) in self.residues.iter_mut().zip(backbone) {
+            residue.position = atoms.ca;
+        }
+    }
 }
 
 impl Residue {
@@ -45,10 +139,33 @@ impl Residue {
             amino_acid,
             phi: 0.0,
             psi: 0.0,
+            omega: 180.0,
+            modification: None,
             position: [0.0, 0.0, 0.0],
         }
     }
 
+    pub fn with_torsions(mut self, phi: f64, psi: f64, omega: f64) -> Self {
+        self.phi = phi;
+        self.psi = psi;
+        self.omega = omega;
+        self
+    }
+
+    pub fn with_modification(mut self, modification: Option<Modification>) -> Self {
+        self.modification = modification;
+        self
+    }
+
+    pub fn charge(&self) -> f64 {
+        crate::parameters::residue_charge(self.amino_acid, self.modification)
+    }
+
+    /// Bead mass (amu) on top of `base`, the unmodified residue mass.
+    pub fn mass(&self, base: f64) -> f64 {
+        base + self.modification.map_or(0.0, Modification::mass_delta)
+    }
+
     pub fn with_position(mut self, position: [f64; 3]) -> Self {
         self.position = position;
         self
@@ -62,3 +179,76 @@ impl Residue {
         self.position = position;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+        ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn rotate_torsion_swings_downstream_residues() {
+        let mut chain = PeptideChain::new(vec![
+            Residue::new(ResidueId(1), AminoAcid::Alanine).with_position([0.0, 0.0, 0.0]),
+            Residue::new(ResidueId(2), AminoAcid::Serine).with_position([1.6, 0.0, 0.1]),
+            Residue::new(ResidueId(3), AminoAcid::Valine).with_position([3.2, 0.5, -0.1]),
+            Residue::new(ResidueId(4), AminoAcid::Glycine).with_position([4.0, 1.9, 0.3]),
+        ]);
+        let before: Vec<[f64; 3]> = chain.residues().iter().map(Residue::position).collect();
+
+        assert!(chain.rotate_torsion(ResidueId(2), 90.0));
+        let after: Vec<[f64; 3]> = chain.residues().iter().map(Residue::position).collect();
+
+        assert!((chain.residues()[1].phi - 90.0).abs() < 1e-9);
+        assert!(distance(before[0], after[0]) < 1e-9);
+        assert!(distance(before[1], after[1]) < 1e-9);
+        assert!(distance(before[2], after[2]) > 0.1);
+        assert!(distance(before[3], after[3]) > 0.1);
+        for (i, j) in [(1, 2), (2, 3), (1, 3)] {
+            assert!((distance(before[i], before[j]) - distance(after[i], after[j])).abs() < 1e-9);
+        }
+        assert!(!chain.rotate_torsion(ResidueId(99), 10.0));
+    }
+
+    #[test]
+    fn sync_torsions_tracks_cartesian_moves() {
+        let mut chain = PeptideChain::new(vec![
+            Residue::new(ResidueId(1), AminoAcid::Alanine).with_position([0.0, 0.0, 0.0]),
+            Residue::new(ResidueId(2), AminoAcid::Serine).with_position([1.6, 0.0, 0.1]),
+            Residue::new(ResidueId(3), AminoAcid::Valine).with_position([3.2, 0.5, -0.1]),
+            Residue::new(ResidueId(4), AminoAcid::Glycine).with_position([4.0, 1.9, 0.3]),
+        ]);
+        let mut rotated = chain.clone();
+        rotated.rotate_torsion(ResidueId(3), -40.0);
+
+        let previous = chain.positions();
+        chain.set_positions(&rotated.positions());
+        chain.sync_torsions(&previous);
+        for (synced, expected) in chain.residues().iter().zip(rotated.residues()) {
+            assert!((synced.phi - expected.phi).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn rebuild_from_torsions_places_alpha_carbons() {
+        let mut chain = PeptideChain::new(
+            (0..5)
+                .map(|i| {
+                    Residue::new(ResidueId(i), AminoAcid::Alanine)
+                        .with_torsions(-57.0, -47.0, 180.0)
+                })
+                .collect(),
+        );
+        chain.rebuild_from_torsions();
+        let residues = chain.residues();
+        for window in residues.windows(2) {
+            let ca_ca = distance(window[0].position(), window[1].position());
+            assert!((ca_ca - 3.8).abs() < 0.05);
+        }
+        // An α-helix rises ~1.5 Å per residue, so i→i+3 is much closer than
+        // the extended ~10 Å.
+        assert!(distance(residues[0].position(), residues[3].position()) < 6.0);
+    }
+}
@@ -1,9 +1,35 @@
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
 use crate::{
     aminoacid::ResidueId,
-    chain::PeptideChain,
+    chain::{PeptideChain, Residue},
+    force_field::{BeadParameters, ForceField},
+    implicit_solvent::ImplicitSolvent,
+    neighbor_list::{CellList, NeighborPair},
     parameters::{self, ResidueClass},
+    residue_template::BeadModel,
+    restraints::{Restraint, RestraintStatus},
 };
 
+/// Smallest sequence separation at which non-bonded terms apply; 1-2 and 1-3
+/// pairs are covered by the bond and angle terms instead.
+const NONBONDED_MIN_SEPARATION: usize = 3;
+/// Default non-bonded cutoff (Å); Lennard-Jones is below 0.1% of ε beyond it.
+const DEFAULT_NONBONDED_CUTOFF: f64 = 12.0;
+/// Width (Å) of the band below the cutoff over which Lennard-Jones and
+/// Coulomb terms are switched smoothly to zero.
+const NONBONDED_SWITCH_WIDTH: f64 = 2.0;
+/// Pair lists shorter than this are evaluated on the calling thread.
+const PARALLEL_MIN_PAIRS: usize = 4096;
+/// Midpoint (Å) and width of the switch counting a hydrophobic contact.
+const CONTACT_MIDPOINT: f64 = 6.5;
+const CONTACT_WIDTH: f64 = 0.5;
+/// Donor–acceptor distance (Å) at the bottom of the bead hydrogen-bond well,
+/// and the distance beyond which the term is dropped.
+const BEAD_HBOND_DISTANCE: f64 = 2.9;
+const BEAD_HBOND_RANGE: f64 = 4.5;
+
 /// Snapshot of energy for a residue during simulation.
 #[derive(Clone, Debug)]
 pub struct EnergySample {
@@ -21,6 +47,14 @@ pub struct EnergySummary {
     pub van_der_waals: f64,
     pub electrostatic: f64,
     pub hydrogen_bond: f64,
+    /// Generalized Born polar solvation; zero without implicit solvent.
+    pub polar_solvation: f64,
+    /// Surface-area nonpolar solvation; zero without implicit solvent.
+    pub nonpolar_solvation: f64,
+    /// Penalty on hydrophobic contacts inside [`AggregationShield`] ranges.
+    pub aggregation: f64,
+    /// Harmonic [`Restraint`] terms; not multiplied by the scaling factor.
+    pub restraint: f64,
 }
 
 impl EnergySummary {
@@ -31,6 +65,83 @@ impl EnergySummary {
             + self.van_der_waals
             + self.electrostatic
             + self.hydrogen_bond
+            + self.polar_solvation
+            + self.nonpolar_solvation
+            + self.aggregation
+            + self.restraint
+    }
+}
+
+/// Per-residue gradient (dE/dx, kcal·mol⁻¹·Å⁻¹) of every energy term,
+/// indexed like [`PeptideChain::residues`].
+#[derive(Clone, Debug, Default)]
+pub struct EnergyGradient {
+    pub bond: Vec<[f64; 3]>,
+    pub angle: Vec<[f64; 3]>,
+    pub dihedral: Vec<[f64; 3]>,
+    pub van_der_waals: Vec<[f64; 3]>,
+    pub electrostatic: Vec<[f64; 3]>,
+    pub hydrogen_bond: Vec<[f64; 3]>,
+    pub polar_solvation: Vec<[f64; 3]>,
+    pub nonpolar_solvation: Vec<[f64; 3]>,
+    pub aggregation: Vec<[f64; 3]>,
+    pub restraint: Vec<[f64; 3]>,
+}
+
+impl EnergyGradient {
+    pub fn zeros(len: usize) -> Self {
+        let zeros = vec![[0.0; 3]; len];
+        Self {
+            bond: zeros.clone(),
+            angle: zeros.clone(),
+            dihedral: zeros.clone(),
+            van_der_waals: zeros.clone(),
+            electrostatic: zeros.clone(),
+            hydrogen_bond: zeros.clone(),
+            polar_solvation: zeros.clone(),
+            nonpolar_solvation: zeros.clone(),
+            aggregation: zeros.clone(),
+            restraint: zeros,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bond.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bond.is_empty()
+    }
+
+    pub fn total(&self) -> Vec<[f64; 3]> {
+        (0..self.len())
+            .map(|index| {
+                let mut sum = [0.0; 3];
+                for term in [
+                    &self.bond,
+                    &self.angle,
+                    &self.dihedral,
+                    &self.van_der_waals,
+                    &self.electrostatic,
+                    &self.hydrogen_bond,
+                    &self.polar_solvation,
+                    &self.nonpolar_solvation,
+                    &self.aggregation,
+                    &self.restraint,
+                ] {
+                    add_scaled(&mut sum, term[index], 1.0);
+                }
+                sum
+            })
+            .collect()
+    }
+
+    /// Forces are the negative total gradient.
+    pub fn forces(&self) -> Vec<[f64; 3]> {
+        self.total()
+            .into_iter()
+            .map(|g| [-g[0], -g[1], -g[2]])
+            .collect()
     }
 }
 
@@ -50,6 +161,8 @@ pub struct EnvironmentPreset {
     pub dielectric: f64,
     pub hydrogen_strength: f64,
     pub default_temperature: f64,
+    /// Nonpolar solvation cost per exposed area (kcal·mol⁻¹·Å⁻²).
+    pub surface_tension: f64,
 }
 
 impl EnvironmentPreset {
@@ -59,6 +172,7 @@ impl EnvironmentPreset {
             dielectric: 78.5,
             hydrogen_strength: 0.6,
             default_temperature: 310.0,
+            surface_tension: 0.005,
         }
     }
 
@@ -68,6 +182,7 @@ impl EnvironmentPreset {
             dielectric: 1.0,
             hydrogen_strength: 0.0,
             default_temperature: 298.0,
+            surface_tension: 0.0,
         }
     }
 
@@ -77,6 +192,8 @@ impl EnvironmentPreset {
             dielectric: 10.0,
             hydrogen_strength: 0.4,
             default_temperature: 305.0,
+            // Exposed apolar surface costs nothing inside the lipid slab.
+            surface_tension: 0.0,
         }
     }
 
@@ -90,6 +207,17 @@ impl EnvironmentPreset {
     }
 }
 
+/// Penalises hydrophobic contacts between residues `start..=end` (chain
+/// indices), the way a holdase chaperone keeps an exposed segment from
+/// collapsing or aggregating. Each contact costs `strength` through a
+/// sigmoid switch centred at 6.5 Å.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AggregationShield {
+    pub start: usize,
+    pub end: usize,
+    pub strength: f64,
+}
+
 /// Simplified dynamic energy model for the folding simulation.
 #[derive(Clone, Debug)]
 pub struct EnergyModel {
@@ -102,6 +230,20 @@ pub struct EnergyModel {
     pub dielectric: f64,
     pub hydrogen_strength: f64,
     pub environment: EnvironmentPreset,
+    /// Overrides the class-based bond rest length (Å), e.g. 3.8 for Cα traces.
+    pub bond_equilibrium: Option<f64>,
+    /// Distance beyond which non-bonded pairs are ignored; `None` sums all pairs.
+    /// Lennard-Jones and Coulomb terms are switched off over the last
+    /// [`NONBONDED_SWITCH_WIDTH`] Å, so energy and forces stay continuous.
+    pub nonbonded_cutoff: Option<f64>,
+    /// GB/SA solvation; the engine enables it at `PhysicsLevel::Gb`.
+    pub implicit_solvent: Option<ImplicitSolvent>,
+    pub aggregation_shields: Vec<AggregationShield>,
+    pub restraints: Vec<Restraint>,
+    /// Multi-bead parameters. When set, van der Waals, electrostatic and
+    /// hydrogen-bond terms run over [`BeadModel`] beads; bonded, solvation,
+    /// aggregation and restraint terms stay on the Cα trace.
+    pub force_field: Option<ForceField>,
 }
 
 impl Default for EnergyModel {
@@ -117,6 +259,12 @@ impl Default for EnergyModel {
             dielectric: env.dielectric,
             hydrogen_strength: env.hydrogen_strength,
             environment: env,
+            bond_equilibrium: None,
+            nonbonded_cutoff: Some(DEFAULT_NONBONDED_CUTOFF),
+            implicit_solvent: None,
+            aggregation_shields: Vec::new(),
+            restraints: Vec::new(),
+            force_field: None,
         }
     }
 }
@@ -129,6 +277,15 @@ impl EnergyModel {
         }
     }
 
+    /// Model for chains whose positions are real Cα atoms (structures and
+    /// sequences built with ideal geometry) rather than toy coordinates.
+    pub fn alpha_carbon_trace() -> Self {
+        Self {
+            bond_equilibrium: Some(3.8),
+            ..Self::default()
+        }
+    }
+
     pub fn with_environment(mut self, environment: EnvironmentPreset) -> Self {
         self.dielectric = environment.dielectric;
         self.hydrogen_strength = environment.hydrogen_strength;
@@ -136,11 +293,56 @@ impl EnergyModel {
         self
     }
 
+    pub fn with_nonbonded_cutoff(mut self, cutoff: Option<f64>) -> Self {
+        self.nonbonded_cutoff = cutoff;
+        self
+    }
+
+    pub fn with_implicit_solvent(mut self, solvent: Option<ImplicitSolvent>) -> Self {
+        self.implicit_solvent = solvent;
+        self
+    }
+
+    pub fn with_aggregation_shield(mut self, shield: AggregationShield) -> Self {
+        self.aggregation_shields.push(shield);
+        self
+    }
+
+    pub fn with_restraint(mut self, restraint: Restraint) -> Self {
+        self.restraints.push(restraint);
+        self
+    }
+
+    pub fn with_force_field(mut self, force_field: Option<ForceField>) -> Self {
+        self.force_field = force_field;
+        self
+    }
+
+    /// Current value, violation and energy of every restraint on `chain`.
+    pub fn restraint_statuses(&self, chain: &PeptideChain) -> Vec<RestraintStatus> {
+        let positions = chain.positions();
+        self.restraints
+            .iter()
+            .map(|restraint| restraint.status(&positions))
+            .collect()
+    }
+
     pub fn environment(&self) -> EnvironmentPreset {
         self.environment
     }
 
+    /// Cell list over `chain` at this model's non-bonded cutoff, for callers
+    /// that keep it up to date with [`CellList::refresh`] between evaluations.
+    pub fn cell_list(&self, chain: &PeptideChain) -> CellList {
+        CellList::from_chain(chain, self.nonbonded_cutoff.unwrap_or(f64::INFINITY))
+    }
+
     pub fn energy_summary(&self, chain: &PeptideChain) -> EnergySummary {
+        self.energy_summary_with(chain, &self.cell_list(chain))
+    }
+
+    /// [`Self::energy_summary`] using a caller-maintained cell list.
+    pub fn energy_summary_with(&self, chain: &PeptideChain, cells: &CellList) -> EnergySummary {
         let residues = chain.residues();
         if residues.len() < 2 {
             return EnergySummary::default();
@@ -153,7 +355,7 @@ impl EnergyModel {
             if let [left, right] = window {
                 let class_left = parameters::classify(left.amino_acid);
                 let class_right = parameters::classify(right.amino_acid);
-                let equilibrium = parameters::bond_equilibrium_distance(class_left, class_right);
+                let equilibrium = self.bond_equilibrium(class_left, class_right);
                 let distance = distance(left.position(), right.position());
                 let diff = distance - equilibrium;
                 summary.bond += 0.5 * self.bond_k * diff * diff;
@@ -190,29 +392,33 @@ impl EnergyModel {
         let mut vdw = 0.0;
         let mut electro = 0.0;
         let mut hydrogen = 0.0;
-        for (i, left) in residues.iter().enumerate() {
-            let class_left = parameters::classify(left.amino_acid);
-            for right in residues.iter().skip(i + 1) {
-                let class_right = parameters::classify(right.amino_acid);
-                let distance = distance(left.position(), right.position()).max(0.1);
-
-                let (sigma, epsilon) = parameters::lennard_jones_params(class_left, class_right);
-                let sr = sigma / distance;
-                let sr6 = sr.powi(6);
-                vdw += 4.0 * epsilon * (sr6 * sr6 - sr6);
-
-                let charge_product =
-                    left.amino_acid.partial_charge() * right.amino_acid.partial_charge();
-                if charge_product.abs() > f64::EPSILON {
-                    electro += 332.0636 * charge_product / (self.dielectric * distance);
-                }
+        let pairs = match &self.force_field {
+            Some(field) => self.bead_terms(&BeadModel::build(chain, field)),
+            None => self.nonbonded_terms(chain, cells),
+        };
+        for (_, terms) in pairs {
+            vdw += terms.van_der_waals.0;
+            electro += terms.electrostatic.0;
+            hydrogen += terms.hydrogen_bond.0;
+        }
 
-                if is_hbond_candidate(class_left, class_right) && distance < 3.5 {
-                    let r10 = distance.powi(10);
-                    let r12 = distance.powi(12);
-                    hydrogen += -self.hydrogen_strength * ((1.0 / r12) - (1.0 / r10));
-                }
-            }
+        if let Some(solvation) = self.solvation_terms(chain, cells, false) {
+            summary.polar_solvation = solvation.polar * self.scaling_factor;
+            summary.nonpolar_solvation = solvation.nonpolar * self.scaling_factor;
+        }
+        summary.aggregation = self
+            .aggregation_terms(chain, cells)
+            .iter()
+            .map(|(_, (energy, _))| energy)
+            .sum::<f64>()
+            * self.scaling_factor;
+        if !self.restraints.is_empty() {
+            let positions = chain.positions();
+            summary.restraint = self
+                .restraints
+                .iter()
+                .map(|restraint| restraint.energy(&positions))
+                .sum();
         }
 
         summary.bond *= self.scaling_factor;
@@ -224,6 +430,383 @@ impl EnergyModel {
         summary
     }
 
+    /// Analytic gradient of [`Self::energy_summary`] with respect to every
+    /// residue position, split by term.
+    pub fn gradient(&self, chain: &PeptideChain) -> EnergyGradient {
+        self.gradient_with(chain, &self.cell_list(chain))
+    }
+
+    /// [`Self::gradient`] using a caller-maintained cell list.
+    pub fn gradient_with(&self, chain: &PeptideChain, cells: &CellList) -> EnergyGradient {
+        let residues = chain.residues();
+        let mut gradient = EnergyGradient::zeros(residues.len());
+        if residues.len() < 2 {
+            return gradient;
+        }
+        let scale = self.scaling_factor;
+        let positions: Vec<[f64; 3]> = residues.iter().map(|res| res.position()).collect();
+
+        // Bonds: E = ½k(r - r₀)²
+        for i in 0..residues.len() - 1 {
+            let class_left = parameters::classify(residues[i].amino_acid);
+            let class_right = parameters::classify(residues[i + 1].amino_acid);
+            let equilibrium = self.bond_equilibrium(class_left, class_right);
+            let delta = subtract(positions[i + 1], positions[i]);
+            let length = norm(delta);
+            if length < 1e-8 {
+                continue;
+            }
+            let de_dr = scale * self.bond_k * (length - equilibrium);
+            add_scaled(&mut gradient.bond[i + 1], delta, de_dr / length);
+            add_scaled(&mut gradient.bond[i], delta, -de_dr / length);
+        }
+
+        // Angles: E = ½k(θ - θ₀)²
+        for i in 0..residues.len().saturating_sub(2) {
+            let class_middle = parameters::classify(residues[i + 1].amino_acid);
+            let equilibrium = parameters::angle_equilibrium(class_middle);
+            let a = subtract(positions[i], positions[i + 1]);
+            let c = subtract(positions[i + 2], positions[i + 1]);
+            let (length_a, length_c) = (norm(a), norm(c));
+            if length_a < 1e-8 || length_c < 1e-8 {
+                continue;
+            }
+            let unit_a = scaled(a, 1.0 / length_a);
+            let unit_c = scaled(c, 1.0 / length_c);
+            let cos_theta = dot(unit_a, unit_c).clamp(-1.0, 1.0);
+            let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+            if sin_theta < 1e-8 {
+                continue;
+            }
+            let de_dtheta = scale * self.angle_k * (cos_theta.acos() - equilibrium);
+            let factor = -de_dtheta / sin_theta;
+            let g1 = scaled(
+                subtract(unit_c, scaled(unit_a, cos_theta)),
+                factor / length_a,
+            );
+            let g3 = scaled(
+                subtract(unit_a, scaled(unit_c, cos_theta)),
+                factor / length_c,
+            );
+            add_scaled(&mut gradient.angle[i], g1, 1.0);
+            add_scaled(&mut gradient.angle[i + 2], g3, 1.0);
+            add_scaled(&mut gradient.angle[i + 1], g1, -1.0);
+            add_scaled(&mut gradient.angle[i + 1], g3, -1.0);
+        }
+
+        // Dihedrals: E = k(1 + cos(nφ - δ)), dφ/dx after Blondel & Karplus (1996).
+        for i in 0..residues.len().saturating_sub(3) {
+            let [p1, p2, p3, p4] = [
+                positions[i],
+                positions[i + 1],
+                positions[i + 2],
+                positions[i + 3],
+            ];
+            let Some(derivatives) = dihedral_derivatives(p1, p2, p3, p4) else {
+                continue;
+            };
+            let phi = dihedral_angle(p1, p2, p3, p4);
+            let de_dphi = -scale
+                * self.dihedral_k
+                * self.dihedral_multiplicity
+                * (self.dihedral_multiplicity * phi - self.dihedral_phase).sin();
+            for (offset, d) in derivatives.into_iter().enumerate() {
+                add_scaled(&mut gradient.dihedral[i + offset], d, de_dphi);
+            }
+        }
+
+        // Pairwise interactions
+        if let Some(field) = &self.force_field {
+            let beads = BeadModel::build(chain, field);
+            let bead_positions = beads.positions();
+            let mut bead_gradient = EnergyGradient::zeros(beads.len());
+            self.add_pair_gradient(&mut bead_gradient, &bead_positions, self.bead_terms(&beads));
+            for (target, source) in [
+                (&mut gradient.van_der_waals, &bead_gradient.van_der_waals),
+                (&mut gradient.electrostatic, &bead_gradient.electrostatic),
+                (&mut gradient.hydrogen_bond, &bead_gradient.hydrogen_bond),
+            ] {
+                for (entry, value) in target.iter_mut().zip(beads.project_gradient(source)) {
+                    add_scaled(entry, value, 1.0);
+                }
+            }
+        } else {
+            self.add_pair_gradient(
+                &mut gradient,
+                &positions,
+                self.nonbonded_terms(chain, cells),
+            );
+        }
+
+        if let Some(solvation) = self.solvation_terms(chain, cells, true) {
+            for (target, source) in [
+                (&mut gradient.polar_solvation, solvation.polar_gradient),
+                (
+                    &mut gradient.nonpolar_solvation,
+                    solvation.nonpolar_gradient,
+                ),
+            ] {
+                for (entry, value) in target.iter_mut().zip(source) {
+                    add_scaled(entry, value, scale);
+                }
+            }
+        }
+
+        for (pair, (_, de_dr)) in self.aggregation_terms(chain, cells) {
+            let delta = subtract(positions[pair.right], positions[pair.left]);
+            add_scaled(
+                &mut gradient.aggregation[pair.right],
+                delta,
+                scale * de_dr / pair.distance,
+            );
+            add_scaled(
+                &mut gradient.aggregation[pair.left],
+                delta,
+                -scale * de_dr / pair.distance,
+            );
+        }
+
+        for restraint in &self.restraints {
+            restraint.add_gradient(&positions, &mut gradient.restraint);
+        }
+
+        gradient
+    }
+
+    /// Adds the scaled non-bonded forces of `pairs` (indices into `positions`).
+    fn add_pair_gradient(
+        &self,
+        gradient: &mut EnergyGradient,
+        positions: &[[f64; 3]],
+        pairs: Vec<(NeighborPair, PairTerms)>,
+    ) {
+        let scale = self.scaling_factor;
+        for (pair, terms) in pairs {
+            let delta = subtract(positions[pair.right], positions[pair.left]);
+            for (term, de_dr) in [
+                (&mut gradient.van_der_waals, terms.van_der_waals.1),
+                (&mut gradient.electrostatic, terms.electrostatic.1),
+                (&mut gradient.hydrogen_bond, terms.hydrogen_bond.1),
+            ] {
+                if de_dr != 0.0 {
+                    add_scaled(&mut term[pair.right], delta, scale * de_dr / pair.distance);
+                    add_scaled(&mut term[pair.left], delta, -scale * de_dr / pair.distance);
+                }
+            }
+        }
+    }
+
+    /// Unscaled `(energy, dE/dr)` of every shielded hydrophobic contact.
+    fn aggregation_terms(
+        &self,
+        chain: &PeptideChain,
+        cells: &CellList,
+    ) -> Vec<(NeighborPair, (f64, f64))> {
+        if self.aggregation_shields.is_empty() {
+            return Vec::new();
+        }
+        let residues = chain.residues();
+        let hydrophobic = |index: usize| {
+            parameters::classify(residues[index].amino_acid) == ResidueClass::Hydrophobic
+        };
+        cells
+            .pairs(NONBONDED_MIN_SEPARATION)
+            .into_iter()
+            .filter(|pair| hydrophobic(pair.left) && hydrophobic(pair.right))
+            .filter_map(|pair| {
+                let strength: f64 = self
+                    .aggregation_shields
+                    .iter()
+                    .filter(|shield| pair.left >= shield.start && pair.right <= shield.end)
+                    .map(|shield| shield.strength)
+                    .sum();
+                if strength == 0.0 {
+                    return None;
+                }
+                let switch =
+                    1.0 / (1.0 + ((pair.distance - CONTACT_MIDPOINT) / CONTACT_WIDTH).exp());
+                let derivative = -switch * (1.0 - switch) / CONTACT_WIDTH;
+                Some((pair, (strength * switch, strength * derivative)))
+            })
+            .collect()
+    }
+
+    fn solvation_terms(
+        &self,
+        chain: &PeptideChain,
+        cells: &CellList,
+        with_gradient: bool,
+    ) -> Option<crate::implicit_solvent::SolvationTerms> {
+        let solvent = self.implicit_solvent.as_ref()?;
+        Some(solvent.evaluate(
+            chain,
+            &cells.pairs(1),
+            self.dielectric,
+            self.environment.surface_tension,
+            with_gradient,
+        ))
+    }
+
+    /// Non-bonded pairs from `cells` with their unscaled terms, in pair order.
+    fn nonbonded_terms(
+        &self,
+        chain: &PeptideChain,
+        cells: &CellList,
+    ) -> Vec<(NeighborPair, PairTerms)> {
+        let residues = chain.residues();
+        let pairs = cells.pairs(NONBONDED_MIN_SEPARATION);
+        let evaluate = |pair: &NeighborPair| {
+            let terms = self.pair_terms(&residues[pair.left], &residues[pair.right], pair.distance);
+            (*pair, terms)
+        };
+        if pairs.len() >= PARALLEL_MIN_PAIRS {
+            pairs.par_iter().map(evaluate).collect()
+        } else {
+            pairs.iter().map(evaluate).collect()
+        }
+    }
+
+    /// Bead pairs (indices into `beads`) whose residues are at least
+    /// [`NONBONDED_MIN_SEPARATION`] apart, with their unscaled terms.
+    fn bead_terms(&self, beads: &BeadModel) -> Vec<(NeighborPair, PairTerms)> {
+        let cells = CellList::new(
+            &beads.positions(),
+            self.nonbonded_cutoff.unwrap_or(f64::INFINITY),
+        );
+        let sites = beads.beads();
+        let pairs: Vec<NeighborPair> = cells
+            .pairs(1)
+            .into_iter()
+            .filter(|pair| {
+                sites[pair.right].residue - sites[pair.left].residue >= NONBONDED_MIN_SEPARATION
+            })
+            .collect();
+        let evaluate = |pair: &NeighborPair| {
+            let terms = self.bead_pair_terms(
+                &sites[pair.left].parameters,
+                &sites[pair.right].parameters,
+                pair.distance,
+            );
+            (*pair, terms)
+        };
+        if pairs.len() >= PARALLEL_MIN_PAIRS {
+            pairs.par_iter().map(evaluate).collect()
+        } else {
+            pairs.iter().map(evaluate).collect()
+        }
+    }
+
+    fn bond_equilibrium(&self, left: ResidueClass, right: ResidueClass) -> f64 {
+        self.bond_equilibrium
+            .unwrap_or_else(|| parameters::bond_equilibrium_distance(left, right))
+    }
+
+    /// Unscaled `(energy, dE/dr)` of the non-bonded terms for one pair.
+    fn pair_terms(&self, left: &Residue, right: &Residue, distance: f64) -> PairTerms {
+        let hbond_candidate = is_hbond_candidate(
+            parameters::classify(left.amino_acid),
+            parameters::classify(right.amino_acid),
+        );
+        let lennard_jones = parameters::combine_lennard_jones(
+            parameters::residue_lennard_jones(left.amino_acid, left.modification),
+            parameters::residue_lennard_jones(right.amino_acid, right.modification),
+        );
+        self.combined_pair_terms(
+            distance,
+            lennard_jones,
+            left.charge() * right.charge(),
+            |distance| {
+                if !hbond_candidate || distance >= 3.5 {
+                    return (0.0, 0.0);
+                }
+                let r10 = distance.powi(10);
+                let r12 = distance.powi(12);
+                (
+                    -self.hydrogen_strength * ((1.0 / r12) - (1.0 / r10)),
+                    -self.hydrogen_strength * (10.0 / (r10 * distance) - 12.0 / (r12 * distance)),
+                )
+            },
+        )
+    }
+
+    /// [`Self::pair_terms`] for two force-field beads. Donor–acceptor pairs
+    /// get a 10-12 well of depth `hydrogen_strength` at
+    /// [`BEAD_HBOND_DISTANCE`].
+    fn bead_pair_terms(
+        &self,
+        left: &BeadParameters,
+        right: &BeadParameters,
+        distance: f64,
+    ) -> PairTerms {
+        let hbond_candidate = left.hydrogen_bond.pairs_with(right.hydrogen_bond);
+        self.combined_pair_terms(
+            distance,
+            parameters::combine_lennard_jones(
+                (left.sigma, left.epsilon),
+                (right.sigma, right.epsilon),
+            ),
+            left.charge * right.charge,
+            |distance| {
+                if !hbond_candidate || distance >= BEAD_HBOND_RANGE {
+                    return (0.0, 0.0);
+                }
+                let ratio = BEAD_HBOND_DISTANCE / distance;
+                let (r10, r12) = (ratio.powi(10), ratio.powi(12));
+                (
+                    self.hydrogen_strength * (5.0 * r12 - 6.0 * r10),
+                    60.0 * self.hydrogen_strength * (r10 - r12) / distance,
+                )
+            },
+        )
+    }
+
+    /// Lennard-Jones and Coulomb terms, switched off towards the cutoff, plus
+    /// `hydrogen_bond(distance)`. Distances below 0.1 Å are clamped, so the
+    /// derivative vanishes there.
+    fn combined_pair_terms(
+        &self,
+        distance: f64,
+        (sigma, epsilon): (f64, f64),
+        charge_product: f64,
+        hydrogen_bond: impl Fn(f64) -> (f64, f64),
+    ) -> PairTerms {
+        let clamped = distance < 0.1;
+        let distance = distance.max(0.1);
+        let mut terms = PairTerms::default();
+
+        let sr = sigma / distance;
+        let sr6 = sr.powi(6);
+        terms.van_der_waals = (
+            4.0 * epsilon * (sr6 * sr6 - sr6),
+            4.0 * epsilon * (6.0 * sr6 - 12.0 * sr6 * sr6) / distance,
+        );
+
+        if charge_product.abs() > f64::EPSILON {
+            // With GB/SA active the solvent screening comes from the GB term.
+            let dielectric = self
+                .implicit_solvent
+                .map_or(self.dielectric, |solvent| solvent.solute_dielectric);
+            let energy = 332.0636 * charge_product / (dielectric * distance);
+            terms.electrostatic = (energy, -energy / distance);
+        }
+
+        if let Some(cutoff) = self.nonbonded_cutoff {
+            let (switch, derivative) = cutoff_switch(distance, cutoff);
+            for term in [&mut terms.van_der_waals, &mut terms.electrostatic] {
+                *term = (term.0 * switch, term.1 * switch + term.0 * derivative);
+            }
+        }
+
+        terms.hydrogen_bond = hydrogen_bond(distance);
+
+        if clamped {
+            terms.van_der_waals.1 = 0.0;
+            terms.electrostatic.1 = 0.0;
+            terms.hydrogen_bond.1 = 0.0;
+        }
+        terms
+    }
+
     pub fn total_energy(&self, chain: &PeptideChain) -> f64 {
         self.energy_summary(chain).total()
     }
@@ -248,6 +831,13 @@ impl EnergyModel {
     }
 }
 
+#[derive(Clone, Copy, Debug, Default)]
+struct PairTerms {
+    van_der_waals: (f64, f64),
+    electrostatic: (f64, f64),
+    hydrogen_bond: (f64, f64),
+}
+
 fn distance(left: [f64; 3], right: [f64; 3]) -> f64 {
     ((left[0] - right[0]).powi(2) + (left[1] - right[1]).powi(2) + (left[2] - right[2]).powi(2))
         .sqrt()
@@ -266,6 +856,20 @@ fn subtract(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
     [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
 }
 
+fn scaled(v: [f64; 3], factor: f64) -> [f64; 3] {
+    [v[0] * factor, v[1] * factor, v[2] * factor]
+}
+
+fn add_scaled(target: &mut [f64; 3], v: [f64; 3], factor: f64) {
+    target[0] += v[0] * factor;
+    target[1] += v[1] * factor;
+    target[2] += v[2] * factor;
+}
+
+fn norm(v: [f64; 3]) -> f64 {
+    dot(v, v).sqrt()
+}
+
 fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
     a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
 }
@@ -285,7 +889,41 @@ fn bond_angle(a: [f64; 3], b: [f64; 3], c: [f64; 3]) -> f64 {
     dot.acos()
 }
 
-fn dihedral_angle(p0: [f64; 3], p1: [f64; 3], p2: [f64; 3], p3: [f64; 3]) -> f64 {
+/// dφ/dx of [`dihedral_angle`] for each of the four points, after Blondel &
+/// Karplus (1996); `None` when three of the points are collinear.
+pub(crate) fn dihedral_derivatives(
+    p1: [f64; 3],
+    p2: [f64; 3],
+    p3: [f64; 3],
+    p4: [f64; 3],
+) -> Option<[[f64; 3]; 4]> {
+    let f = subtract(p1, p2);
+    let g = subtract(p2, p3);
+    let h = subtract(p4, p3);
+    let a = cross(f, g);
+    let b = cross(h, g);
+    let (a2, b2, g_len) = (dot(a, a), dot(b, b), norm(g));
+    if a2 < 1e-12 || b2 < 1e-12 || g_len < 1e-8 {
+        return None;
+    }
+    let d1 = scaled(a, g_len / a2);
+    let d4 = scaled(b, -g_len / b2);
+    let fg = dot(f, g) / (a2 * g_len);
+    let hg = dot(h, g) / (b2 * g_len);
+    let d2 = [
+        -d1[0] - fg * a[0] + hg * b[0],
+        -d1[1] - fg * a[1] + hg * b[1],
+        -d1[2] - fg * a[2] + hg * b[2],
+    ];
+    let d3 = [
+        -d4[0] + fg * a[0] - hg * b[0],
+        -d4[1] + fg * a[1] - hg * b[1],
+        -d4[2] + fg * a[2] - hg * b[2],
+    ];
+    Some([d1, d2, d3, d4])
+}
+
+pub(crate) fn dihedral_angle(p0: [f64; 3], p1: [f64; 3], p2: [f64; 3], p3: [f64; 3]) -> f64 {
     let b0 = normalize(subtract(p1, p0));
     let b1 = normalize(subtract(p2, p1));
     let b2 = normalize(subtract(p3, p2));
@@ -299,6 +937,24 @@ fn dihedral_angle(p0: [f64; 3], p1: [f64; 3], p2: [f64; 3], p3: [f64; 3]) -> f64
     y.atan2(x)
 }
 
+/// CHARMM-style switch `(S, dS/dr)`: one below `cutoff -`
+/// [`NONBONDED_SWITCH_WIDTH`], zero at the cutoff, smooth in between.
+fn cutoff_switch(distance: f64, cutoff: f64) -> (f64, f64) {
+    let on = (cutoff - NONBONDED_SWITCH_WIDTH).max(0.0);
+    if distance <= on {
+        return (1.0, 0.0);
+    }
+    if distance >= cutoff {
+        return (0.0, 0.0);
+    }
+    let (r2, on2, off2) = (distance * distance, on * on, cutoff * cutoff);
+    let denominator = (off2 - on2).powi(3);
+    (
+        (off2 - r2).powi(2) * (off2 + 2.0 * r2 - 3.0 * on2) / denominator,
+        12.0 * distance * (off2 - r2) * (on2 - r2) / denominator,
+    )
+}
+
 fn is_hbond_candidate(left: ResidueClass, right: ResidueClass) -> bool {
     matches!(
         (left, right),
@@ -313,7 +969,7 @@ fn is_hbond_candidate(left: ResidueClass, right: ResidueClass) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{AminoAcid, PeptideChain, Residue, ResidueId};
+    use crate::{AminoAcid, Modification, PeptideChain, Residue, ResidueId};
 
     #[test]
     fn environment_changes_dielectric() {
@@ -340,4 +996,307 @@ mod tests {
         let expected = 0.5 * model.bond_k * (1.3 - eq).powi(2);
         assert!((summary.bond - expected).abs() < 1e-6);
     }
+
+    #[test]
+    fn cell_list_energy_matches_all_pairs() {
+        let sequence = [
+            AminoAcid::Lysine,
+            AminoAcid::Serine,
+            AminoAcid::Glutamate,
+            AminoAcid::Threonine,
+            AminoAcid::Alanine,
+        ];
+        let sequence: Vec<AminoAcid> = sequence.iter().cycle().take(60).copied().collect();
+        let chain = PeptideChain::from_sequence(&sequence, crate::StartingConformation::Helix);
+        let exact = EnergyModel::alpha_carbon_trace().with_nonbonded_cutoff(None);
+        let wide = EnergyModel::alpha_carbon_trace().with_nonbonded_cutoff(Some(500.0));
+        let cut = EnergyModel::alpha_carbon_trace();
+
+        let exact_summary = exact.energy_summary(&chain);
+        assert!((wide.energy_summary(&chain).total() - exact_summary.total()).abs() < 1e-9);
+        let truncated = cut.energy_summary(&chain);
+        assert!((truncated.van_der_waals - exact_summary.van_der_waals).abs() < 0.05);
+        assert_eq!(truncated.bond, exact_summary.bond);
+
+        let mut cells = cut.cell_list(&chain);
+        let mut moved = chain.clone();
+        let mut positions = moved.positions();
+        positions[30][2] += 1.5;
+        moved.set_positions(&positions);
+        assert_eq!(cells.refresh(&positions), 1);
+        let incremental = cut.energy_summary_with(&moved, &cells);
+        assert_eq!(incremental.total(), cut.energy_summary(&moved).total());
+    }
+
+    #[test]
+    fn cutoff_switches_electrostatics_off_smoothly() {
+        // Lys and Glu four residues apart; only their Coulomb term reaches
+        // the switching band below the 12 Å cutoff.
+        let pair_at = |separation: f64| {
+            PeptideChain::new(vec![
+                Residue::new(ResidueId(1), AminoAcid::Lysine).with_position([0.0, 0.0, 0.0]),
+                Residue::new(ResidueId(2), AminoAcid::Alanine).with_position([0.0, 3.8, 0.0]),
+                Residue::new(ResidueId(3), AminoAcid::Alanine).with_position([0.0, 7.6, 0.0]),
+                Residue::new(ResidueId(4), AminoAcid::Glutamate)
+                    .with_position([separation, 0.0, 0.0]),
+            ])
+        };
+        let cut = EnergyModel::default();
+        let exact = EnergyModel::default().with_nonbonded_cutoff(None);
+
+        let inside = pair_at(9.0);
+        let unswitched = exact.energy_summary(&inside).electrostatic;
+        assert!(unswitched < 0.0);
+        assert_eq!(cut.energy_summary(&inside).electrostatic, unswitched);
+
+        let mut previous = unswitched;
+        for step in 1..=20 {
+            let separation = 10.0 + 0.1 * step as f64;
+            let chain = pair_at(separation);
+            let energy = cut.energy_summary(&chain).electrostatic;
+            assert!(energy >= previous - 1e-12 && energy <= 0.0);
+            previous = energy;
+
+            let gradient = cut.gradient(&chain).electrostatic[3][0];
+            let h = 1e-6;
+            let numeric = (cut.energy_summary(&pair_at(separation + h)).electrostatic
+                - cut.energy_summary(&pair_at(separation - h)).electrostatic)
+                / (2.0 * h);
+            assert!(
+                (gradient - numeric).abs() < 1e-6,
+                "{separation}: {gradient} vs {numeric}"
+            );
+        }
+        assert!(previous.abs() < 1e-12);
+        assert!(cut.energy_summary(&pair_at(11.999)).electrostatic.abs() < 1e-6);
+    }
+
+    fn sample_chain() -> PeptideChain {
+        let residues = [
+            (AminoAcid::Serine, [0.0, 0.0, 0.0]),
+            (AminoAcid::Lysine, [1.5, 0.2, 0.1]),
+            (AminoAcid::Glutamate, [2.1, 1.5, -0.3]),
+            (AminoAcid::Threonine, [1.0, 2.4, 0.4]),
+            (AminoAcid::Lysine, [-0.3, 1.9, 1.2]),
+        ];
+        PeptideChain::new(
+            residues
+                .iter()
+                .enumerate()
+                .map(|(index, (amino_acid, position))| {
+                    Residue::new(ResidueId(index), *amino_acid).with_position(*position)
+                })
+                .collect(),
+        )
+    }
+
+    /// [`sample_chain`] with a phosphoserine, for the modified charge and
+    /// Lennard-Jones path.
+    fn phosphoserine_chain() -> PeptideChain {
+        let mut chain = sample_chain();
+        chain
+            .residue_mut(ResidueId(0))
+            .expect("serine")
+            .modification = Some(Modification::Phosphorylation);
+        chain
+    }
+
+    #[test]
+    fn analytic_gradient_matches_finite_differences() {
+        let chain = sample_chain();
+        let model = EnergyModel::default();
+        let gradient = model.gradient(&chain);
+        assert_ne!(gradient.electrostatic[4], [0.0; 3]);
+        assert_ne!(gradient.hydrogen_bond[0], [0.0; 3]);
+        let step = 1e-6;
+        type Term<'a> = (fn(&EnergySummary) -> f64, &'a Vec<[f64; 3]>);
+        let terms: [Term; 6] = [
+            (|s| s.bond, &gradient.bond),
+            (|s| s.angle, &gradient.angle),
+            (|s| s.dihedral, &gradient.dihedral),
+            (|s| s.van_der_waals, &gradient.van_der_waals),
+            (|s| s.electrostatic, &gradient.electrostatic),
+            (|s| s.hydrogen_bond, &gradient.hydrogen_bond),
+        ];
+        for index in 0..chain.len() {
+            for axis in 0..3 {
+                let displaced = |offset: f64| {
+                    let mut positions = chain.positions();
+                    positions[index][axis] += offset;
+                    let mut moved = chain.clone();
+                    moved.set_positions(&positions);
+                    model.energy_summary(&moved)
+                };
+                let (plus, minus) = (displaced(step), displaced(-step));
+                for (energy, analytic) in &terms {
+                    let numeric = (energy(&plus) - energy(&minus)) / (2.0 * step);
+                    let analytic = analytic[index][axis];
+                    assert!(
+                        (numeric - analytic).abs() < 1e-4 * (1.0 + numeric.abs()),
+                        "residue {index} axis {axis}: numeric {numeric}, analytic {analytic}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn modified_residue_gradient_matches_finite_differences() {
+        let chain = phosphoserine_chain();
+        let model = EnergyModel::default();
+        let gradient = model.gradient(&chain);
+        assert_ne!(gradient.electrostatic[0], [0.0; 3]);
+        let step = 1e-6;
+        for index in 0..chain.len() {
+            for axis in 0..3 {
+                let displaced = |offset: f64| {
+                    let mut positions = chain.positions();
+                    positions[index][axis] += offset;
+                    let mut moved = chain.clone();
+                    moved.set_positions(&positions);
+                    model.energy_summary(&moved)
+                };
+                let (plus, minus) = (displaced(step), displaced(-step));
+                for (numeric, analytic) in [
+                    (
+                        (plus.van_der_waals - minus.van_der_waals) / (2.0 * step),
+                        gradient.van_der_waals[index][axis],
+                    ),
+                    (
+                        (plus.electrostatic - minus.electrostatic) / (2.0 * step),
+                        gradient.electrostatic[index][axis],
+                    ),
+                ] {
+                    assert!(
+                        (numeric - analytic).abs() < 1e-4 * (1.0 + numeric.abs()),
+                        "residue {index} axis {axis}: numeric {numeric}, analytic {analytic}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn force_field_beads_replace_residue_pairs() {
+        let sequence = [
+            AminoAcid::Lysine,
+            AminoAcid::Serine,
+            AminoAcid::Glutamate,
+            AminoAcid::Threonine,
+            AminoAcid::Alanine,
+            AminoAcid::Aspartate,
+            AminoAcid::Glutamine,
+            AminoAcid::Leucine,
+        ];
+        let mut chain = PeptideChain::from_sequence(&sequence, crate::StartingConformation::Helix);
+        // Pull the trace off the ideal helix so every bead frame turns.
+        let positions: Vec<[f64; 3]> = chain
+            .positions()
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut position)| {
+                position[index % 3] += 0.3 * (index as f64).sin();
+                position
+            })
+            .collect();
+        chain.set_positions(&positions);
+
+        let plain = EnergyModel::alpha_carbon_trace();
+        let model = plain
+            .clone()
+            .with_force_field(Some(crate::ForceField::builtin()));
+        let summary = model.energy_summary(&chain);
+        let baseline = plain.energy_summary(&chain);
+        assert_eq!(summary.bond, baseline.bond);
+        assert_eq!(summary.dihedral, baseline.dihedral);
+        assert_ne!(summary.van_der_waals, baseline.van_der_waals);
+        // Backbone C=O(i)···N(i+4) contacts of the helix.
+        assert!(summary.hydrogen_bond < 0.0);
+
+        let gradient = model.gradient(&chain);
+        let step = 1e-6;
+        type Term<'a> = (fn(&EnergySummary) -> f64, &'a Vec<[f64; 3]>);
+        let terms: [Term; 3] = [
+            (|s| s.van_der_waals, &gradient.van_der_waals),
+            (|s| s.electrostatic, &gradient.electrostatic),
+            (|s| s.hydrogen_bond, &gradient.hydrogen_bond),
+        ];
+        for index in 0..chain.len() {
+            for axis in 0..3 {
+                let displaced = |offset: f64| {
+                    let mut positions = chain.positions();
+                    positions[index][axis] += offset;
+                    let mut moved = chain.clone();
+                    moved.set_positions(&positions);
+                    model.energy_summary(&moved)
+                };
+                let (plus, minus) = (displaced(step), displaced(-step));
+                for (energy, analytic) in &terms {
+                    let numeric = (energy(&plus) - energy(&minus)) / (2.0 * step);
+                    let analytic = analytic[index][axis];
+                    assert!(
+                        (numeric - analytic).abs() < 1e-4 * (1.0 + numeric.abs()),
+                        "residue {index} axis {axis}: numeric {numeric}, analytic {analytic}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn modifications_and_shields_change_energy() {
+        let sequence = [
+            AminoAcid::Leucine,
+            AminoAcid::Serine,
+            AminoAcid::Isoleucine,
+            AminoAcid::Valine,
+            AminoAcid::Lysine,
+            AminoAcid::Leucine,
+            AminoAcid::Alanine,
+            AminoAcid::Phenylalanine,
+            AminoAcid::Valine,
+        ];
+        let chain = PeptideChain::from_sequence(&sequence, crate::StartingConformation::Helix);
+        let model = EnergyModel::alpha_carbon_trace();
+        let plain = model.energy_summary(&chain);
+        assert_eq!(plain.aggregation, 0.0);
+
+        let mut phospho = chain.clone();
+        phospho
+            .residue_mut(ResidueId(2))
+            .expect("serine")
+            .modification = Some(Modification::Phosphorylation);
+        let modified = model.energy_summary(&phospho);
+        assert!(modified.electrostatic < plain.electrostatic);
+        assert_ne!(modified.van_der_waals, plain.van_der_waals);
+
+        let shielded = model.clone().with_aggregation_shield(AggregationShield {
+            start: 0,
+            end: chain.len() - 1,
+            strength: 1.0,
+        });
+        let summary = shielded.energy_summary(&chain);
+        assert!(summary.aggregation > 0.0);
+        assert!((summary.total() - plain.total() - summary.aggregation).abs() < 1e-9);
+
+        let gradient = shielded.gradient(&chain);
+        let step = 1e-6;
+        for index in 0..chain.len() {
+            for axis in 0..3 {
+                let displaced = |offset: f64| {
+                    let mut positions = chain.positions();
+                    positions[index][axis] += offset;
+                    let mut moved = chain.clone();
+                    moved.set_positions(&positions);
+                    shielded.energy_summary(&moved).aggregation
+                };
+                let numeric = (displaced(step) - displaced(-step)) / (2.0 * step);
+                let analytic = gradient.aggregation[index][axis];
+                assert!(
+                    (numeric - analytic).abs() < 1e-6,
+                    "residue {index} axis {axis}"
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,177 @@
+//! FASTA sequence loading and ideal starting conformations.
+
+use std::fs;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::aminoacid::AminoAcid;
+use crate::chain::PeptideChain;
+
+#[derive(Debug, Error)]
+pub enum FastaError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("line {line}, column {column}: unsupported residue symbol '{symbol}'")]
+    InvalidResidue {
+        line: usize,
+        column: usize,
+        symbol: char,
+    },
+    #[error("no sequence records found")]
+    Empty,
+}
+
+/// One `>header` record of a FASTA file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FastaRecord {
+    pub header: String,
+    pub sequence: Vec<AminoAcid>,
+}
+
+impl FastaRecord {
+    /// First word of the header, e.g. `sp|P04578|ENV_HV1H2` for UniProt entries.
+    pub fn identifier(&self) -> &str {
+        self.header.split_whitespace().next().unwrap_or("")
+    }
+
+    pub fn to_chain(&self, conformation: StartingConformation) -> PeptideChain {
+        PeptideChain::from_sequence(&self.sequence, conformation)
+    }
+}
+
+/// Backbone torsions used to lay out a chain built from sequence alone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StartingConformation {
+    /// β-strand torsions (φ = -139°, ψ = 135°), ~3.3 Å rise per residue.
+    Extended,
+    /// Right-handed α-helix torsions (φ = -57°, ψ = -47°).
+    Helix,
+}
+
+impl StartingConformation {
+    pub fn torsions(self) -> (f64, f64) {
+        match self {
+            Self::Extended => (-139.0, 135.0),
+            Self::Helix => (-57.0, -47.0),
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "extended" | "strand" | "beta" => Some(Self::Extended),
+            "helix" | "helical" | "alpha" => Some(Self::Helix),
+            _ => None,
+        }
+    }
+}
+
+/// Parses FASTA text. Text before the first header is treated as an
+/// anonymous record so bare one-letter sequences are accepted as well.
+pub fn parse_fasta(text: &str) -> Result<Vec<FastaRecord>, FastaError> {
+    let mut records: Vec<FastaRecord> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let trimmed = line.trim_end();
+        if let Some(header) = trimmed.strip_prefix('>') {
+            records.push(FastaRecord {
+                header: header.trim().to_string(),
+                sequence: Vec::new(),
+            });
+            continue;
+        }
+        if trimmed.trim_start().starts_with(';') {
+            continue;
+        }
+        for (column, symbol) in trimmed.chars().enumerate() {
+            if symbol.is_whitespace() || symbol == '*' || symbol == '-' {
+                continue;
+            }
+            let amino_acid = AminoAcid::from_char(symbol).ok_or(FastaError::InvalidResidue {
+                line: index + 1,
+                column: column + 1,
+                symbol,
+            })?;
+            if records.is_empty() {
+                records.push(FastaRecord {
+                    header: String::new(),
+                    sequence: Vec::new(),
+                });
+            }
+            records
+                .last_mut()
+                .expect("record pushed above")
+                .sequence
+                .push(amino_acid);
+        }
+    }
+    records.retain(|record| !record.sequence.is_empty());
+    if records.is_empty() {
+        return Err(FastaError::Empty);
+    }
+    Ok(records)
+}
+
+pub fn read_fasta(path: &Path) -> Result<Vec<FastaRecord>, FastaError> {
+    let text = fs::read_to_string(path)?;
+    parse_fasta(&text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aminoacid::ResidueId;
+
+    fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+        ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn parses_multi_record_fasta() {
+        let text = "\
+>gp41_fp HIV-1 fusion peptide
+AVGIGALFLG
+FLGAAGSTMG*
+; comment line
+>chignolin
+gyd pet gtw
+";
+        let records = parse_fasta(text).expect("fasta parses");
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].identifier(), "gp41_fp");
+        assert_eq!(records[0].sequence.len(), 20);
+        assert_eq!(records[1].sequence[1], AminoAcid::Tyrosine);
+        assert_eq!(records[1].sequence.len(), 9);
+
+        let err = parse_fasta(">bad\nACDX\n").expect_err("X is not supported");
+        assert!(matches!(
+            err,
+            FastaError::InvalidResidue {
+                line: 2,
+                column: 4,
+                symbol: 'X'
+            }
+        ));
+    }
+
+    #[test]
+    fn builds_extended_and_helical_chains() {
+        let records = parse_fasta("GYDPETGTWG").expect("bare sequence parses");
+        let extended = records[0].to_chain(StartingConformation::Extended);
+        let helix = records[0].to_chain(StartingConformation::Helix);
+        assert_eq!(extended.len(), 10);
+        assert_eq!(extended.residues()[0].id, ResidueId(1));
+
+        let span = |chain: &PeptideChain| {
+            distance(
+                chain.residues()[0].position(),
+                chain.residues()[9].position(),
+            )
+        };
+        assert!(span(&extended) > 25.0);
+        assert!(span(&helix) < 16.0);
+        for window in helix.residues().windows(2) {
+            let ca_ca = distance(window[0].position(), window[1].position());
+            assert!((ca_ca - 3.8).abs() < 0.05);
+        }
+    }
+}
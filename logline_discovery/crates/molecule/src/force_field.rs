@@ -0,0 +1,245 @@
+//! Per-bead parameters for multi-bead residue templates.
+//!
+//! A force field is a whitespace-separated table with one bead per line:
+//!
+//! ```text
+//! # bead  residue  sigma  epsilon  charge  hbond     centroid
+//! N       *        3.25   0.17     -0.31   donor     -
+//! N       PRO      3.25   0.17     -0.10   none      -
+//! SC      ALA      3.70   0.15      0.00   none      1.53
+//! ```
+//!
+//! Backbone rows (`N`, `CA`, `C`, `O`) with residue `*` apply to every
+//! residue and must all be present; a row naming a residue overrides them.
+//! `SC` rows give the side-chain centroid of one residue and its distance
+//! from Cα. Sigma and centroid are in Å, epsilon in kcal/mol, charge in e;
+//! `hbond` is `none`, `donor`, `acceptor` or `both`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::aminoacid::AminoAcid;
+use crate::residue_template::BeadKind;
+
+const BUILTIN: &str = include_str!("../data/backbone_centroid.ff");
+
+#[derive(Debug, Error)]
+pub enum ForceFieldError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("line {line}: {message}")]
+    Malformed { line: usize, message: String },
+    #[error("missing wildcard `{kind} *` backbone row")]
+    MissingBackbone { kind: &'static str },
+}
+
+/// Hydrogen-bonding role of a bead; donors pair with acceptors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HydrogenBondRole {
+    None,
+    Donor,
+    Acceptor,
+    Both,
+}
+
+impl HydrogenBondRole {
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" | "-" => Some(Self::None),
+            "donor" => Some(Self::Donor),
+            "acceptor" => Some(Self::Acceptor),
+            "both" => Some(Self::Both),
+            _ => None,
+        }
+    }
+
+    pub fn donates(self) -> bool {
+        matches!(self, Self::Donor | Self::Both)
+    }
+
+    pub fn accepts(self) -> bool {
+        matches!(self, Self::Acceptor | Self::Both)
+    }
+
+    /// Whether one of the two beads can donate to the other.
+    pub fn pairs_with(self, other: Self) -> bool {
+        (self.donates() && other.accepts()) || (self.accepts() && other.donates())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BeadParameters {
+    pub sigma: f64,
+    pub epsilon: f64,
+    pub charge: f64,
+    pub hydrogen_bond: HydrogenBondRole,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SideChainParameters {
+    pub bead: BeadParameters,
+    /// Cα–centroid distance (Å) along the ideal Cβ direction.
+    pub centroid_distance: f64,
+}
+
+/// Parsed force-field table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForceField {
+    backbone: HashMap<(BeadKind, Option<AminoAcid>), BeadParameters>,
+    side_chains: HashMap<AminoAcid, SideChainParameters>,
+}
+
+impl ForceField {
+    /// The bundled backbone + side-chain centroid table.
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN).expect("bundled force field parses")
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self, ForceFieldError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ForceFieldError> {
+        let mut field = Self {
+            backbone: HashMap::new(),
+            side_chains: HashMap::new(),
+        };
+        for (index, raw) in text.lines().enumerate() {
+            let line = index + 1;
+            let content = raw.split('#').next().unwrap_or("").trim();
+            if content.is_empty() {
+                continue;
+            }
+            let malformed = |message: String| ForceFieldError::Malformed { line, message };
+            let columns: Vec<&str> = content.split_whitespace().collect();
+            let [kind, residue, sigma, epsilon, charge, hbond, centroid] = columns[..] else {
+                return Err(malformed(format!(
+                    "expected 7 columns, found {}",
+                    columns.len()
+                )));
+            };
+
+            let kind = BeadKind::by_name(kind)
+                .ok_or_else(|| malformed(format!("unknown bead '{kind}'")))?;
+            let residue = match residue {
+                "*" => None,
+                code => Some(
+                    AminoAcid::from_three_letter(code)
+                        .ok_or_else(|| malformed(format!("unknown residue '{code}'")))?,
+                ),
+            };
+            let number = |name: &str, value: &str| {
+                value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|value| value.is_finite())
+                    .ok_or_else(|| malformed(format!("invalid {name} '{value}'")))
+            };
+            let bead = BeadParameters {
+                sigma: number("sigma", sigma)?,
+                epsilon: number("epsilon", epsilon)?,
+                charge: number("charge", charge)?,
+                hydrogen_bond: HydrogenBondRole::by_name(hbond)
+                    .ok_or_else(|| malformed(format!("unknown hbond role '{hbond}'")))?,
+            };
+            if bead.sigma <= 0.0 || bead.epsilon < 0.0 {
+                return Err(malformed(
+                    "sigma must be positive and epsilon non-negative".to_string(),
+                ));
+            }
+
+            let duplicate = if kind == BeadKind::SideChain {
+                let residue =
+                    residue.ok_or_else(|| malformed("SC rows need a residue".to_string()))?;
+                let centroid_distance = number("centroid", centroid)?;
+                if centroid_distance <= 0.0 {
+                    return Err(malformed("centroid must be positive".to_string()));
+                }
+                field
+                    .side_chains
+                    .insert(
+                        residue,
+                        SideChainParameters {
+                            bead,
+                            centroid_distance,
+                        },
+                    )
+                    .is_some()
+            } else {
+                if centroid != "-" {
+                    return Err(malformed(format!(
+                        "{} rows take `-` as centroid",
+                        kind.name()
+                    )));
+                }
+                field.backbone.insert((kind, residue), bead).is_some()
+            };
+            if duplicate {
+                return Err(malformed("duplicate row".to_string()));
+            }
+        }
+
+        for kind in BeadKind::BACKBONE {
+            if !field.backbone.contains_key(&(kind, None)) {
+                return Err(ForceFieldError::MissingBackbone { kind: kind.name() });
+            }
+        }
+        Ok(field)
+    }
+
+    /// Backbone bead parameters for `amino_acid`, falling back to the
+    /// wildcard row. `kind` must be a backbone bead.
+    pub fn backbone(&self, kind: BeadKind, amino_acid: AminoAcid) -> BeadParameters {
+        self.backbone
+            .get(&(kind, Some(amino_acid)))
+            .or_else(|| self.backbone.get(&(kind, None)))
+            .copied()
+            .expect("parse checks every backbone wildcard row")
+    }
+
+    pub fn side_chain(&self, amino_acid: AminoAcid) -> Option<SideChainParameters> {
+        self.side_chains.get(&amino_acid).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_table_covers_backbone_and_side_chains() {
+        let field = ForceField::builtin();
+        assert!(field
+            .backbone(BeadKind::N, AminoAcid::Alanine)
+            .hydrogen_bond
+            .donates());
+        assert_eq!(
+            field
+                .backbone(BeadKind::N, AminoAcid::Proline)
+                .hydrogen_bond,
+            HydrogenBondRole::None
+        );
+        assert!(field.side_chain(AminoAcid::Glycine).is_none());
+        let lysine = field.side_chain(AminoAcid::Lysine).unwrap();
+        assert_eq!(lysine.bead.charge, 1.0);
+        assert!(lysine.centroid_distance > 3.0);
+    }
+
+    #[test]
+    fn parse_reports_line_and_missing_rows() {
+        let error = ForceField::parse("N * 3.2 0.1 0.0 donor -\nXX * 1 1 1 none -").unwrap_err();
+        assert!(matches!(error, ForceFieldError::Malformed { line: 2, .. }));
+
+        let error = ForceField::parse("SC ALA 3.7 0.15 0.0 none 0").unwrap_err();
+        assert!(matches!(error, ForceFieldError::Malformed { line: 1, .. }));
+
+        let error = ForceField::parse("N * 3.2 0.1 0.0 donor -").unwrap_err();
+        assert!(matches!(
+            error,
+            ForceFieldError::MissingBackbone { kind: "CA" }
+        ));
+    }
+}
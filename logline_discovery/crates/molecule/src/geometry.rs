@@ -0,0 +1,425 @@
+//! Internal-coordinate geometry for peptide backbones.
+//!
+//! Positions are rebuilt with the Natural Extension Reference Frame (NeRF)
+//! construction: every atom is placed from the three atoms before it using a
+//! bond length, a bond angle and a torsion. Changing one torsion therefore
+//! swings the whole downstream chain rigidly around the bond axis while all
+//! bond lengths and angles stay untouched.
+
+/// Ideal N–Cα bond length in ångström.
+pub const N_CA_LENGTH: f64 = 1.458;
+/// Ideal Cα–C bond length in ångström.
+pub const CA_C_LENGTH: f64 = 1.525;
+/// Ideal C–N peptide bond length in ångström.
+pub const C_N_LENGTH: f64 = 1.329;
+/// Ideal N–Cα–C angle in degrees.
+pub const N_CA_C_ANGLE: f64 = 111.2;
+/// Ideal Cα–C–N angle in degrees.
+pub const CA_C_N_ANGLE: f64 = 116.2;
+/// Ideal C–N–Cα angle in degrees.
+pub const C_N_CA_ANGLE: f64 = 121.7;
+
+/// Backbone heavy atoms of a single residue.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BackboneAtoms {
+    pub n: [f64; 3],
+    pub ca: [f64; 3],
+    pub c: [f64; 3],
+}
+
+/// Backbone torsions of a single residue, in degrees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BackboneTorsions {
+    pub phi: f64,
+    pub psi: f64,
+    pub omega: f64,
+}
+
+/// Builds an ideal N/Cα/C backbone from per-residue phi/psi/omega torsions.
+///
+/// The first residue is anchored with N at the origin and Cα on the +x axis;
+/// its phi is undefined and ignored. `omega` of residue *i* is the peptide
+/// bond torsion between residue *i* and *i + 1*.
+pub fn build_backbone(torsions: &[BackboneTorsions]) -> Vec<BackboneAtoms> {
+    let mut atoms = Vec::with_capacity(torsions.len());
+    let Some(first) = torsions.first() else {
+        return atoms;
+    };
+
+    let n = [0.0, 0.0, 0.0];
+    let ca = [N_CA_LENGTH, 0.0, 0.0];
+    let angle = N_CA_C_ANGLE.to_radians();
+    let c = [
+        ca[0] - CA_C_LENGTH * angle.cos(),
+        CA_C_LENGTH * angle.sin(),
+        0.0,
+    ];
+    atoms.push(BackboneAtoms { n, ca, c });
+
+    let mut previous = (*first, BackboneAtoms { n, ca, c });
+    for current in torsions.iter().skip(1) {
+        let (prev_torsions, prev_atoms) = previous;
+        let n = place_atom(
+            prev_atoms.n,
+            prev_atoms.ca,
+            prev_atoms.c,
+            C_N_LENGTH,
+            CA_C_N_ANGLE.to_radians(),
+            prev_torsions.psi.to_radians(),
+        );
+        let ca = place_atom(
+            prev_atoms.ca,
+            prev_atoms.c,
+            n,
+            N_CA_LENGTH,
+            C_N_CA_ANGLE.to_radians(),
+            prev_torsions.omega.to_radians(),
+        );
+        let c = place_atom(
+            prev_atoms.c,
+            n,
+            ca,
+            CA_C_LENGTH,
+            N_CA_C_ANGLE.to_radians(),
+            current.phi.to_radians(),
+        );
+        let residue = BackboneAtoms { n, ca, c };
+        atoms.push(residue);
+        previous = (*current, residue);
+    }
+
+    atoms
+}
+
+/// Places atom `d` so that |cd| = `bond_length`, ∠bcd = `bond_angle` and the
+/// torsion a–b–c–d equals `torsion` (both angles in radians).
+pub fn place_atom(
+    a: [f64; 3],
+    b: [f64; 3],
+    c: [f64; 3],
+    bond_length: f64,
+    bond_angle: f64,
+    torsion: f64,
+) -> [f64; 3] {
+    let (bc, m, n) = reference_frame(a, b, c);
+    let x = -bond_length * bond_angle.cos();
+    let y = bond_length * bond_angle.sin() * torsion.cos();
+    let z = bond_length * bond_angle.sin() * torsion.sin();
+    [
+        c[0] + bc[0] * x + m[0] * y + n[0] * z,
+        c[1] + bc[1] * x + m[1] * y + n[1] * z,
+        c[2] + bc[2] * x + m[2] * y + n[2] * z,
+    ]
+}
+
+/// Bond length, bond angle and torsion (radians) of `d` relative to `a`, `b`, `c`.
+///
+/// This is the exact inverse of [`place_atom`], including for degenerate
+/// (collinear) reference frames.
+pub fn internal_coordinate(
+    a: [f64; 3],
+    b: [f64; 3],
+    c: [f64; 3],
+    d: [f64; 3],
+) -> InternalCoordinate {
+    let (bc, m, n) = reference_frame(a, b, c);
+    let v = subtract(d, c);
+    let bond_length = norm(v);
+    let x = dot(v, bc);
+    let y = dot(v, m);
+    let z = dot(v, n);
+    let bond_angle = (y * y + z * z).sqrt().atan2(-x);
+    let torsion = z.atan2(y);
+    InternalCoordinate {
+        bond_length,
+        bond_angle,
+        torsion,
+    }
+}
+
+/// Torsion a–b–c–d in radians, in the IUPAC sign convention.
+pub fn dihedral(a: [f64; 3], b: [f64; 3], c: [f64; 3], d: [f64; 3]) -> f64 {
+    internal_coordinate(a, b, c, d).torsion
+}
+
+/// Bond length, bond angle and torsion (radians) locating one atom.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InternalCoordinate {
+    pub bond_length: f64,
+    pub bond_angle: f64,
+    pub torsion: f64,
+}
+
+/// Internal-coordinate view of a linear chain of points (e.g. a Cα trace).
+///
+/// The first two points are kept verbatim as anchors; a virtual reference
+/// point perpendicular to the first bond gives the third point a torsion, so
+/// every bond from index 1 onwards can be rotated.
+#[derive(Clone, Debug)]
+pub struct InternalCoordinates {
+    anchors: Vec<[f64; 3]>,
+    reference: [f64; 3],
+    entries: Vec<InternalCoordinate>,
+}
+
+impl InternalCoordinates {
+    pub fn from_positions(positions: &[[f64; 3]]) -> Self {
+        let anchors: Vec<[f64; 3]> = positions.iter().take(2).copied().collect();
+        let reference = match anchors.as_slice() {
+            [first, second] => virtual_reference(*first, *second),
+            _ => [0.0, 0.0, 0.0],
+        };
+        let mut entries = Vec::with_capacity(positions.len().saturating_sub(2));
+        for index in 2..positions.len() {
+            let a = if index == 2 {
+                reference
+            } else {
+                positions[index - 3]
+            };
+            entries.push(internal_coordinate(
+                a,
+                positions[index - 2],
+                positions[index - 1],
+                positions[index],
+            ));
+        }
+        Self {
+            anchors,
+            reference,
+            entries,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.anchors.len() + self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.anchors.is_empty()
+    }
+
+    /// Torsion (radians) about the bond (`pivot - 1`, `pivot`), the one
+    /// [`Self::rotate_bond`] changes; `None` when no point lies past it.
+    pub fn torsion(&self, pivot: usize) -> Option<f64> {
+        pivot
+            .checked_sub(1)
+            .and_then(|entry| self.entries.get(entry))
+            .map(|entry| entry.torsion)
+    }
+
+    /// Rotates every point after `pivot` around the bond (`pivot - 1`, `pivot`)
+    /// by `delta` radians. Returns `false` when the bond does not exist or no
+    /// point lies downstream of it.
+    pub fn rotate_bond(&mut self, pivot: usize, delta: f64) -> bool {
+        if pivot == 0 {
+            return false;
+        }
+        match self.entries.get_mut(pivot - 1) {
+            Some(entry) => {
+                entry.torsion += delta;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Rebuilds Cartesian positions with NeRF from the stored internal coordinates.
+    pub fn to_positions(&self) -> Vec<[f64; 3]> {
+        let mut positions = self.anchors.clone();
+        positions.reserve(self.entries.len());
+        for (offset, entry) in self.entries.iter().enumerate() {
+            let index = offset + 2;
+            let a = if index == 2 {
+                self.reference
+            } else {
+                positions[index - 3]
+            };
+            let placed = place_atom(
+                a,
+                positions[index - 2],
+                positions[index - 1],
+                entry.bond_length,
+                entry.bond_angle,
+                entry.torsion,
+            );
+            positions.push(placed);
+        }
+        positions
+    }
+}
+
+fn reference_frame(a: [f64; 3], b: [f64; 3], c: [f64; 3]) -> ([f64; 3], [f64; 3], [f64; 3]) {
+    let bc = normalize(subtract(c, b));
+    let ab = subtract(b, a);
+    let mut n = cross(ab, bc);
+    if norm(n) < 1e-8 {
+        n = perpendicular(bc);
+    }
+    let n = normalize(n);
+    let m = cross(n, bc);
+    (bc, m, n)
+}
+
+fn virtual_reference(first: [f64; 3], second: [f64; 3]) -> [f64; 3] {
+    let offset = perpendicular(normalize(subtract(second, first)));
+    [
+        first[0] + offset[0],
+        first[1] + offset[1],
+        first[2] + offset[2],
+    ]
+}
+
+fn perpendicular(v: [f64; 3]) -> [f64; 3] {
+    let helper = if v[0].abs() < 0.9 {
+        [1.0, 0.0, 0.0]
+    } else {
+        [0.0, 1.0, 0.0]
+    };
+    let p = cross(v, helper);
+    if norm(p) < 1e-12 {
+        [0.0, 0.0, 1.0]
+    } else {
+        normalize(p)
+    }
+}
+
+fn subtract(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn norm(v: [f64; 3]) -> f64 {
+    dot(v, v).sqrt()
+}
+
+fn normalize(v: [f64; 3]) -> [f64; 3] {
+    let length = norm(v);
+    if length < 1e-12 {
+        [0.0, 0.0, 0.0]
+    } else {
+        [v[0] / length, v[1] / length, v[2] / length]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+        norm(subtract(a, b))
+    }
+
+    fn trace() -> Vec<[f64; 3]> {
+        vec![
+            [0.0, 0.0, 0.0],
+            [1.6, 0.0, 0.1],
+            [3.2, 0.5, -0.1],
+            [4.1, 1.8, 0.4],
+            [5.5, 2.0, 1.3],
+        ]
+    }
+
+    #[test]
+    fn round_trips_positions_including_collinear_segments() {
+        let mut positions = trace();
+        positions.push([7.0, 2.0, 1.3]);
+        positions.push([8.5, 2.0, 1.3]);
+        let rebuilt = InternalCoordinates::from_positions(&positions).to_positions();
+        for (original, rebuilt) in positions.iter().zip(&rebuilt) {
+            assert!(distance(*original, *rebuilt) < 1e-9);
+        }
+    }
+
+    #[test]
+    fn rotating_a_bond_moves_only_downstream_points_rigidly() {
+        let positions = trace();
+        let mut internal = InternalCoordinates::from_positions(&positions);
+        assert!(internal.rotate_bond(2, 60f64.to_radians()));
+        let rotated = internal.to_positions();
+
+        for index in 0..=2 {
+            assert!(distance(positions[index], rotated[index]) < 1e-9);
+        }
+        assert!(distance(positions[3], rotated[3]) > 0.1);
+        // Points 1 and 2 lie on the rotation axis, so every pair that does not
+        // involve point 0 must keep its distance.
+        for i in 1..positions.len() {
+            for j in (i + 1)..positions.len() {
+                let before = distance(positions[i], positions[j]);
+                let after = distance(rotated[i], rotated[j]);
+                assert!((before - after).abs() < 1e-9, "pair ({i}, {j}) deformed");
+            }
+        }
+    }
+
+    #[test]
+    fn dihedral_matches_torsions_used_to_build_the_backbone() {
+        let torsions = [
+            BackboneTorsions {
+                phi: -120.0,
+                psi: 130.0,
+                omega: 175.0,
+            },
+            BackboneTorsions {
+                phi: -65.0,
+                psi: -40.0,
+                omega: -178.0,
+            },
+        ];
+        let atoms = build_backbone(&torsions);
+        let psi = dihedral(atoms[0].n, atoms[0].ca, atoms[0].c, atoms[1].n).to_degrees();
+        let omega = dihedral(atoms[0].ca, atoms[0].c, atoms[1].n, atoms[1].ca).to_degrees();
+        let phi = dihedral(atoms[0].c, atoms[1].n, atoms[1].ca, atoms[1].c).to_degrees();
+        assert!((psi - 130.0).abs() < 1e-6);
+        assert!((omega - 175.0).abs() < 1e-6);
+        assert!((phi + 65.0).abs() < 1e-6);
+
+        // Cross-check the sign convention against the textbook atan2 formula.
+        let (p0, p1, p2, p3) = (atoms[0].c, atoms[1].n, atoms[1].ca, atoms[1].c);
+        let b0 = subtract(p0, p1);
+        let b1 = normalize(subtract(p2, p1));
+        let b2 = subtract(p3, p2);
+        let v = subtract(b0, scale(b1, dot(b0, b1)));
+        let w = subtract(b2, scale(b1, dot(b2, b1)));
+        let reference = dot(cross(b1, v), w).atan2(dot(v, w)).to_degrees();
+        assert!((reference - phi).abs() < 1e-6);
+    }
+
+    fn scale(v: [f64; 3], factor: f64) -> [f64; 3] {
+        [v[0] * factor, v[1] * factor, v[2] * factor]
+    }
+
+    #[test]
+    fn ideal_backbone_has_expected_bond_lengths() {
+        let torsions = vec![
+            BackboneTorsions {
+                phi: -57.0,
+                psi: -47.0,
+                omega: 180.0,
+            };
+            4
+        ];
+        let atoms = build_backbone(&torsions);
+        assert_eq!(atoms.len(), 4);
+        for window in atoms.windows(2) {
+            assert!((distance(window[0].c, window[1].n) - C_N_LENGTH).abs() < 1e-9);
+            let ca_ca = distance(window[0].ca, window[1].ca);
+            assert!((ca_ca - 3.8).abs() < 0.05, "Cα–Cα distance {ca_ca}");
+        }
+        for residue in &atoms {
+            assert!((distance(residue.n, residue.ca) - N_CA_LENGTH).abs() < 1e-9);
+            assert!((distance(residue.ca, residue.c) - CA_C_LENGTH).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,318 @@
+//! Generalized Born / surface-area (GB/SA) implicit solvent for one-bead
+//! residues.
+//!
+//! Born radii follow Still's pairwise r⁻⁴ descreening, the polar term uses
+//! the Still GB interaction function, and the nonpolar term is a surface
+//! tension times the Hasel pairwise approximation of each bead's
+//! solvent-accessible area.
+
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::aminoacid::AminoAcid;
+use crate::chain::{PeptideChain, Residue};
+use crate::neighbor_list::NeighborPair;
+use crate::parameters;
+
+const COULOMB: f64 = 332.0636;
+/// Hasel overlap parameters for sequence neighbours and for every other pair.
+const HASEL_BONDED: f64 = 0.8875;
+const HASEL_NONBONDED: f64 = 0.3516;
+
+/// GB/SA settings. The solvent dielectric and surface tension come from the
+/// model's [`crate::EnvironmentPreset`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImplicitSolvent {
+    /// Dielectric inside the solute. Coulomb uses it in place of the solvent
+    /// dielectric while GB/SA is active, since the GB term adds the screening.
+    pub solute_dielectric: f64,
+    pub probe_radius: f64,
+    /// Born radius assigned to fully buried beads (Å).
+    pub max_born_radius: f64,
+}
+
+impl Default for ImplicitSolvent {
+    fn default() -> Self {
+        Self {
+            solute_dielectric: 4.0,
+            probe_radius: 1.4,
+            max_born_radius: 25.0,
+        }
+    }
+}
+
+/// Unscaled solvation energies and, when requested, their gradients.
+pub(crate) struct SolvationTerms {
+    pub polar: f64,
+    pub nonpolar: f64,
+    pub polar_gradient: Vec<[f64; 3]>,
+    pub nonpolar_gradient: Vec<[f64; 3]>,
+}
+
+impl ImplicitSolvent {
+    /// Born radius of every bead.
+    pub fn born_radii(&self, residues: &[AminoAcid], pairs: &[NeighborPair]) -> Vec<f64> {
+        let radii = bead_radii(residues);
+        self.born_radii_with(&radii, pairs)
+            .into_iter()
+            .map(|(alpha, _)| alpha)
+            .collect()
+    }
+
+    /// `pairs` must hold every pair within the model cutoff at sequence
+    /// separation one or more, in `(left, right)` order.
+    pub(crate) fn evaluate(
+        &self,
+        chain: &PeptideChain,
+        pairs: &[NeighborPair],
+        solvent_dielectric: f64,
+        surface_tension: f64,
+        with_gradient: bool,
+    ) -> SolvationTerms {
+        let residues: Vec<AminoAcid> = chain.residues().iter().map(|r| r.amino_acid).collect();
+        let charges: Vec<f64> = chain.residues().iter().map(Residue::charge).collect();
+        let positions = chain.positions();
+        let n = residues.len();
+        let mut terms = SolvationTerms {
+            polar: 0.0,
+            nonpolar: 0.0,
+            polar_gradient: vec![[0.0; 3]; if with_gradient { n } else { 0 }],
+            nonpolar_gradient: vec![[0.0; 3]; if with_gradient { n } else { 0 }],
+        };
+        let radii = bead_radii(&residues);
+        let unit = |pair: &NeighborPair| {
+            let l = positions[pair.left];
+            let r = positions[pair.right];
+            let d = pair.distance.max(1e-8);
+            [(r[0] - l[0]) / d, (r[1] - l[1]) / d, (r[2] - l[2]) / d]
+        };
+
+        // Polar solvation: -½·332·(1/ε_in - 1/ε_w)·ΣΣ qᵢqⱼ / f_GB.
+        let tau = 0.5 * COULOMB * (1.0 / self.solute_dielectric - 1.0 / solvent_dielectric);
+        if tau > 0.0 {
+            let born = self.born_radii_with(&radii, pairs);
+            let mut de_dalpha = vec![0.0; n];
+            for i in 0..n {
+                let (alpha, _) = born[i];
+                terms.polar -= tau * charges[i] * charges[i] / alpha;
+                de_dalpha[i] += tau * charges[i] * charges[i] / (alpha * alpha);
+            }
+            for pair in pairs {
+                let qq = charges[pair.left] * charges[pair.right];
+                if qq == 0.0 {
+                    continue;
+                }
+                let (alpha_l, alpha_r) = (born[pair.left].0, born[pair.right].0);
+                let r2 = pair.distance * pair.distance;
+                let a = alpha_l * alpha_r;
+                let damping = (-r2 / (4.0 * a)).exp();
+                let f = (r2 + a * damping).sqrt();
+                terms.polar -= 2.0 * tau * qq / f;
+                if with_gradient {
+                    let de_df = 2.0 * tau * qq / (f * f);
+                    let df_dr = pair.distance * (1.0 - 0.25 * damping) / f;
+                    let df_da = damping * (1.0 + r2 / (4.0 * a)) / (2.0 * f);
+                    de_dalpha[pair.left] += de_df * df_da * alpha_r;
+                    de_dalpha[pair.right] += de_df * df_da * alpha_l;
+                    push_pair(&mut terms.polar_gradient, pair, unit(pair), de_df * df_dr);
+                }
+            }
+            if with_gradient {
+                // Chain rule through the Born radii: dα/dr = -α²·(4/3)·ρ³/r⁵.
+                for pair in pairs {
+                    let r5 = pair.distance.powi(5);
+                    let mut de_dr = 0.0;
+                    let (alpha_l, clamped_l) = born[pair.left];
+                    if !clamped_l {
+                        let dalpha =
+                            -alpha_l * alpha_l * (4.0 / 3.0) * radii[pair.right].powi(3) / r5;
+                        de_dr += de_dalpha[pair.left] * dalpha;
+                    }
+                    let (alpha_r, clamped_r) = born[pair.right];
+                    if !clamped_r {
+                        let dalpha =
+                            -alpha_r * alpha_r * (4.0 / 3.0) * radii[pair.left].powi(3) / r5;
+                        de_dr += de_dalpha[pair.right] * dalpha;
+                    }
+                    push_pair(&mut terms.polar_gradient, pair, unit(pair), de_dr);
+                }
+            }
+        }
+
+        // Nonpolar solvation: γ·Σ Aᵢ with Hasel's pairwise area estimate.
+        if surface_tension != 0.0 {
+            let probe = self.probe_radius;
+            let spheres: Vec<f64> = radii
+                .iter()
+                .map(|r| 4.0 * PI * (r + probe).powi(2))
+                .collect();
+            // Overlap factor of `j` on `i` and its derivative in r.
+            let overlap = |i: usize, j: usize, d: f64, bonded: bool| -> Option<(f64, f64)> {
+                let (ri, rj) = (radii[i], radii[j]);
+                let reach = ri + rj + 2.0 * probe;
+                if d >= reach {
+                    return None;
+                }
+                let p = if bonded {
+                    HASEL_BONDED
+                } else {
+                    HASEL_NONBONDED
+                };
+                let lead = PI * (ri + probe);
+                let shape = 1.0 + (rj - ri) / d;
+                let b = lead * (reach - d) * shape;
+                let db = lead * (-shape - (reach - d) * (rj - ri) / (d * d));
+                Some((1.0 - p * b / spheres[i], -p * db / spheres[i]))
+            };
+            let mut areas = spheres.clone();
+            let mut factors = Vec::with_capacity(pairs.len());
+            for pair in pairs {
+                let bonded = pair.right == pair.left + 1;
+                let forward = overlap(pair.left, pair.right, pair.distance, bonded);
+                let backward = overlap(pair.right, pair.left, pair.distance, bonded);
+                if let Some((factor, _)) = forward {
+                    areas[pair.left] *= factor.max(0.0);
+                }
+                if let Some((factor, _)) = backward {
+                    areas[pair.right] *= factor.max(0.0);
+                }
+                factors.push((forward, backward));
+            }
+            terms.nonpolar = surface_tension * areas.iter().sum::<f64>();
+            if with_gradient {
+                for (pair, (forward, backward)) in pairs.iter().zip(factors) {
+                    let mut de_dr = 0.0;
+                    for (owner, entry) in [(pair.left, forward), (pair.right, backward)] {
+                        if let Some((factor, dfactor)) = entry {
+                            if factor > 0.0 {
+                                de_dr += surface_tension * areas[owner] * dfactor / factor;
+                            }
+                        }
+                    }
+                    push_pair(&mut terms.nonpolar_gradient, pair, unit(pair), de_dr);
+                }
+            }
+        }
+
+        terms
+    }
+
+    /// `(α, clamped)` per bead; clamped radii do not respond to neighbours.
+    fn born_radii_with(&self, radii: &[f64], pairs: &[NeighborPair]) -> Vec<(f64, bool)> {
+        let mut inverse: Vec<f64> = radii.iter().map(|r| 1.0 / r).collect();
+        for pair in pairs {
+            let r4 = pair.distance.powi(4);
+            inverse[pair.left] -= radii[pair.right].powi(3) / (3.0 * r4);
+            inverse[pair.right] -= radii[pair.left].powi(3) / (3.0 * r4);
+        }
+        let floor = 1.0 / self.max_born_radius;
+        inverse
+            .into_iter()
+            .map(|value| {
+                if value > floor {
+                    (1.0 / value, false)
+                } else {
+                    (self.max_born_radius, true)
+                }
+            })
+            .collect()
+    }
+}
+
+fn bead_radii(residues: &[AminoAcid]) -> Vec<f64> {
+    residues
+        .iter()
+        .map(|aa| parameters::solvation_radius(parameters::classify(*aa)))
+        .collect()
+}
+
+/// Adds `de_dr` along the pair axis: `+u` on the right bead, `-u` on the left.
+fn push_pair(gradient: &mut [[f64; 3]], pair: &NeighborPair, unit: [f64; 3], de_dr: f64) {
+    if de_dr == 0.0 {
+        return;
+    }
+    for axis in 0..3 {
+        gradient[pair.right][axis] += de_dr * unit[axis];
+        gradient[pair.left][axis] -= de_dr * unit[axis];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EnergyModel, EnergySummary, PeptideChain, StartingConformation};
+
+    fn charged_helix() -> PeptideChain {
+        let sequence = [
+            AminoAcid::Lysine,
+            AminoAcid::Leucine,
+            AminoAcid::Glutamate,
+            AminoAcid::Serine,
+            AminoAcid::Arginine,
+            AminoAcid::Alanine,
+            AminoAcid::Aspartate,
+            AminoAcid::Valine,
+        ];
+        PeptideChain::from_sequence(&sequence, StartingConformation::Helix)
+    }
+
+    fn solvated() -> EnergyModel {
+        EnergyModel::alpha_carbon_trace().with_implicit_solvent(Some(ImplicitSolvent::default()))
+    }
+
+    #[test]
+    fn born_radii_grow_with_burial() {
+        let chain = charged_helix();
+        let model = solvated();
+        let residues: Vec<AminoAcid> = chain.residues().iter().map(|r| r.amino_acid).collect();
+        let pairs = model.cell_list(&chain).pairs(1);
+        let born = ImplicitSolvent::default().born_radii(&residues, &pairs);
+        let radii = bead_radii(&residues);
+        for (alpha, radius) in born.iter().zip(&radii) {
+            assert!(alpha > radius && *alpha <= 25.0, "born radius {alpha}");
+        }
+        // Terminal beads are more exposed than the middle of the helix.
+        assert!(born[0] < born[4] && born[7] < born[4]);
+
+        let summary = model.energy_summary(&chain);
+        assert!(summary.polar_solvation < 0.0);
+        assert!(summary.nonpolar_solvation > 0.0);
+        let plain = EnergyModel::alpha_carbon_trace().energy_summary(&chain);
+        assert_eq!(plain.polar_solvation, 0.0);
+    }
+
+    #[test]
+    fn solvation_gradient_matches_finite_differences() {
+        let chain = charged_helix();
+        let model = solvated();
+        let gradient = model.gradient(&chain);
+        let step = 1e-6;
+        type Term<'a> = (fn(&EnergySummary) -> f64, &'a Vec<[f64; 3]>);
+        let terms: [Term; 3] = [
+            (|s| s.polar_solvation, &gradient.polar_solvation),
+            (|s| s.nonpolar_solvation, &gradient.nonpolar_solvation),
+            (|s| s.electrostatic, &gradient.electrostatic),
+        ];
+        for index in 0..chain.len() {
+            for axis in 0..3 {
+                let displaced = |offset: f64| {
+                    let mut positions = chain.positions();
+                    positions[index][axis] += offset;
+                    let mut moved = chain.clone();
+                    moved.set_positions(&positions);
+                    model.energy_summary(&moved)
+                };
+                let (plus, minus) = (displaced(step), displaced(-step));
+                for (energy, analytic) in &terms {
+                    let numeric = (energy(&plus) - energy(&minus)) / (2.0 * step);
+                    let analytic = analytic[index][axis];
+                    assert!(
+                        (numeric - analytic).abs() < 1e-4 * (1.0 + numeric.abs()),
+                        "residue {index} axis {axis}: numeric {numeric}, analytic {analytic}"
+                    );
+                }
+            }
+        }
+    }
+}
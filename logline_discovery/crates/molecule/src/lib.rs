@@ -2,12 +2,46 @@ pub mod aminoacid;
 pub mod bond_constraints;
 pub mod chain;
 pub mod dynamic_energy;
+pub mod fasta;
 pub mod foldable_graph;
+pub mod force_field;
+pub mod geometry;
+pub mod implicit_solvent;
+pub mod minimizer;
+pub mod neighbor_list;
 pub mod parameters;
+pub mod residue_template;
+pub mod restraints;
+pub mod secondary_structure;
+pub mod structure_io;
+pub mod structure_metrics;
+pub mod trajectory_io;
 
 pub use aminoacid::{AminoAcid, ResidueId};
 pub use bond_constraints::{BondConstraint, BondConstraintSet};
 pub use chain::{PeptideChain, Residue};
-pub use dynamic_energy::{EnergyModel, EnergySample, EnergySummary, EnvironmentPreset};
+pub use dynamic_energy::{
+    AggregationShield, EnergyGradient, EnergyModel, EnergySample, EnergySummary, EnvironmentPreset,
+};
+pub use fasta::{parse_fasta, read_fasta, FastaError, FastaRecord, StartingConformation};
 pub use foldable_graph::FoldableGraph;
-pub use parameters::{classify, lennard_jones_params, ResidueClass};
+pub use force_field::{
+    BeadParameters, ForceField, ForceFieldError, HydrogenBondRole, SideChainParameters,
+};
+pub use geometry::{BackboneAtoms, BackboneTorsions, InternalCoordinates};
+pub use implicit_solvent::ImplicitSolvent;
+pub use minimizer::{MinimizationReport, Minimizer, MinimizerAlgorithm};
+pub use neighbor_list::{CellList, NeighborPair};
+pub use parameters::{classify, lennard_jones_params, Modification, ResidueClass};
+pub use residue_template::{Bead, BeadKind, BeadModel};
+pub use restraints::{Restraint, RestraintStatus};
+pub use secondary_structure::{
+    assign_from_backbone, assign_from_chain_backbone, assign_from_torsions, assignment_codes,
+    ramachandran_region, SecondaryStructure, SecondaryStructureFractions,
+};
+pub use structure_io::{read_structure, write_structure, StructureError, StructureFormat};
+pub use structure_metrics::{
+    contact_map, end_to_end_distance, kabsch_rmsd, kabsch_superposition, radius_of_gyration,
+    StructureMetrics, StructureReference,
+};
+pub use trajectory_io::{write_trajectory, TrajectoryFormat, TrajectoryFrame};
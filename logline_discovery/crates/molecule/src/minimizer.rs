@@ -0,0 +1,267 @@
+//! Local energy minimisation of residue positions.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chain::PeptideChain;
+use crate::dynamic_energy::EnergyModel;
+
+/// Backtracking halvings tried before a step is abandoned.
+const MAX_BACKTRACKS: usize = 30;
+/// Armijo sufficient-decrease constant.
+const ARMIJO: f64 = 1e-4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MinimizerAlgorithm {
+    SteepestDescent,
+    Lbfgs,
+}
+
+/// Gradient-based minimiser over Cartesian residue positions.
+#[derive(Clone, Debug)]
+pub struct Minimizer {
+    pub algorithm: MinimizerAlgorithm,
+    pub max_steps: usize,
+    /// Convergence threshold on the RMS gradient (kcal·mol⁻¹·Å⁻¹).
+    pub tolerance: f64,
+    /// Largest distance any residue may move in a single step (Å).
+    pub max_displacement: f64,
+    /// Correction pairs kept by L-BFGS.
+    pub history: usize,
+}
+
+/// Outcome of a [`Minimizer::minimize`] call.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MinimizationReport {
+    pub algorithm: MinimizerAlgorithm,
+    pub steps: usize,
+    pub initial_energy: f64,
+    pub final_energy: f64,
+    pub rms_gradient: f64,
+    pub converged: bool,
+}
+
+impl MinimizationReport {
+    pub fn delta_energy(&self) -> f64 {
+        self.final_energy - self.initial_energy
+    }
+}
+
+impl Default for Minimizer {
+    fn default() -> Self {
+        Self {
+            algorithm: MinimizerAlgorithm::Lbfgs,
+            max_steps: 200,
+            tolerance: 1e-2,
+            max_displacement: 0.2,
+            history: 7,
+        }
+    }
+}
+
+impl Minimizer {
+    pub fn new(max_steps: usize, tolerance: f64) -> Self {
+        Self {
+            max_steps,
+            tolerance,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_algorithm(mut self, algorithm: MinimizerAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Relaxes `chain` in place until the RMS gradient drops below the
+    /// tolerance, `max_steps` iterations have run, or no downhill step is
+    /// found. Each accepted step satisfies the Armijo condition, so the
+    /// energy never increases. Residue `phi` torsions are updated to follow
+    /// the final positions.
+    pub fn minimize(&self, model: &EnergyModel, chain: &mut PeptideChain) -> MinimizationReport {
+        let start = chain.positions();
+        let mut positions = start.clone();
+        let mut energy = model.total_energy(chain);
+        let mut gradient = model.gradient(chain).total();
+        let initial_energy = energy;
+        let mut history: VecDeque<Correction> = VecDeque::with_capacity(self.history);
+        let mut steps = 0;
+        let mut converged = rms(&gradient) <= self.tolerance;
+
+        while !converged && steps < self.max_steps {
+            let mut direction = match self.algorithm {
+                MinimizerAlgorithm::SteepestDescent => scaled(&gradient, -1.0),
+                MinimizerAlgorithm::Lbfgs => lbfgs_direction(&gradient, &history),
+            };
+            let mut slope = dot(&direction, &gradient);
+            if slope >= 0.0 {
+                direction = scaled(&gradient, -1.0);
+                slope = -dot(&gradient, &gradient);
+                history.clear();
+            }
+
+            let largest = direction
+                .iter()
+                .map(|d| (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt())
+                .fold(0.0, f64::max);
+            let mut alpha = if largest > self.max_displacement {
+                self.max_displacement / largest
+            } else {
+                1.0
+            };
+
+            let mut accepted = None;
+            for _ in 0..MAX_BACKTRACKS {
+                let trial = axpy(&positions, &direction, alpha);
+                chain.set_positions(&trial);
+                let trial_energy = model.total_energy(chain);
+                if trial_energy <= energy + ARMIJO * alpha * slope {
+                    accepted = Some((trial, trial_energy));
+                    break;
+                }
+                alpha *= 0.5;
+            }
+            let Some((trial, trial_energy)) = accepted else {
+                chain.set_positions(&positions);
+                break;
+            };
+
+            let trial_gradient = model.gradient(chain).total();
+            if self.algorithm == MinimizerAlgorithm::Lbfgs && self.history > 0 {
+                let s = axpy(&trial, &positions, -1.0);
+                let y = axpy(&trial_gradient, &gradient, -1.0);
+                let sy = dot(&s, &y);
+                if sy > 1e-10 {
+                    if history.len() == self.history {
+                        history.pop_front();
+                    }
+                    history.push_back(Correction {
+                        s,
+                        y,
+                        rho: 1.0 / sy,
+                    });
+                }
+            }
+
+            positions = trial;
+            energy = trial_energy;
+            gradient = trial_gradient;
+            steps += 1;
+            converged = rms(&gradient) <= self.tolerance;
+        }
+
+        chain.set_positions(&positions);
+        chain.sync_torsions(&start);
+        MinimizationReport {
+            algorithm: self.algorithm,
+            steps,
+            initial_energy,
+            final_energy: energy,
+            rms_gradient: rms(&gradient),
+            converged,
+        }
+    }
+}
+
+struct Correction {
+    s: Vec<[f64; 3]>,
+    y: Vec<[f64; 3]>,
+    rho: f64,
+}
+
+/// L-BFGS two-loop recursion: approximates `-H⁻¹g` from recent corrections.
+fn lbfgs_direction(gradient: &[[f64; 3]], history: &VecDeque<Correction>) -> Vec<[f64; 3]> {
+    let mut q = gradient.to_vec();
+    let mut alphas = Vec::with_capacity(history.len());
+    for correction in history.iter().rev() {
+        let alpha = correction.rho * dot(&correction.s, &q);
+        q = axpy(&q, &correction.y, -alpha);
+        alphas.push(alpha);
+    }
+    let gamma = history
+        .back()
+        .map(|last| dot(&last.s, &last.y) / dot(&last.y, &last.y))
+        .unwrap_or(1.0);
+    let mut r = scaled(&q, gamma);
+    for (correction, alpha) in history.iter().zip(alphas.iter().rev()) {
+        let beta = correction.rho * dot(&correction.y, &r);
+        r = axpy(&r, &correction.s, alpha - beta);
+    }
+    scaled(&r, -1.0)
+}
+
+fn dot(a: &[[f64; 3]], b: &[[f64; 3]]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| x[0] * y[0] + x[1] * y[1] + x[2] * y[2])
+        .sum()
+}
+
+fn scaled(v: &[[f64; 3]], factor: f64) -> Vec<[f64; 3]> {
+    v.iter()
+        .map(|x| [x[0] * factor, x[1] * factor, x[2] * factor])
+        .collect()
+}
+
+/// `x + factor·y`, element-wise.
+fn axpy(x: &[[f64; 3]], y: &[[f64; 3]], factor: f64) -> Vec<[f64; 3]> {
+    x.iter()
+        .zip(y)
+        .map(|(a, b)| {
+            [
+                a[0] + factor * b[0],
+                a[1] + factor * b[1],
+                a[2] + factor * b[2],
+            ]
+        })
+        .collect()
+}
+
+fn rms(gradient: &[[f64; 3]]) -> f64 {
+    if gradient.is_empty() {
+        return 0.0;
+    }
+    (dot(gradient, gradient) / (3 * gradient.len()) as f64).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AminoAcid, Residue, ResidueId};
+
+    fn strained_chain() -> PeptideChain {
+        PeptideChain::new(vec![
+            Residue::new(ResidueId(1), AminoAcid::Alanine).with_position([0.0, 0.0, 0.0]),
+            Residue::new(ResidueId(2), AminoAcid::Serine).with_position([1.9, 0.3, 0.0]),
+            Residue::new(ResidueId(3), AminoAcid::Valine).with_position([2.6, 1.8, 0.4]),
+            Residue::new(ResidueId(4), AminoAcid::Leucine).with_position([4.2, 2.0, -0.2]),
+        ])
+    }
+
+    #[test]
+    fn both_algorithms_lower_the_energy() {
+        let model = EnergyModel::default();
+        for algorithm in [
+            MinimizerAlgorithm::SteepestDescent,
+            MinimizerAlgorithm::Lbfgs,
+        ] {
+            let mut chain = strained_chain();
+            let report = Minimizer::new(500, 1e-3)
+                .with_algorithm(algorithm)
+                .minimize(&model, &mut chain);
+            assert!(report.steps > 0);
+            assert!(report.final_energy < report.initial_energy - 1.0);
+            assert!((model.total_energy(&chain) - report.final_energy).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn lbfgs_converges_on_bond_stretch() {
+        let model = EnergyModel::default();
+        let mut chain = strained_chain();
+        let report = Minimizer::new(1_000, 1e-4).minimize(&model, &mut chain);
+        assert!(report.converged, "{report:?}");
+        assert!(report.rms_gradient <= 1e-4);
+    }
+}
@@ -0,0 +1,244 @@
+//! Cutoff-based cell list for pairwise residue interactions.
+//!
+//! Positions are binned into cubic cells one cutoff wide, so every pair
+//! closer than the cutoff sits in the same or an adjacent cell. Pair
+//! enumeration is O(N) for a fixed density instead of the O(N²) double loop,
+//! and is spread over the rayon pool for large chains. An infinite cutoff
+//! puts every residue in one cell and degenerates to the exact all-pairs loop.
+
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use crate::chain::PeptideChain;
+
+/// Chains shorter than this are scanned on the calling thread.
+const PARALLEL_MIN_RESIDUES: usize = 256;
+
+type CellKey = [i64; 3];
+
+/// Pair of residue indices (`left < right`) closer than the cutoff.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NeighborPair {
+    pub left: usize,
+    pub right: usize,
+    pub distance: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct CellList {
+    cutoff: f64,
+    positions: Vec<[f64; 3]>,
+    cell_of: Vec<CellKey>,
+    cells: HashMap<CellKey, Vec<usize>>,
+}
+
+impl CellList {
+    /// Bins `positions` with the given cutoff (Å). `f64::INFINITY` keeps
+    /// every pair.
+    pub fn new(positions: &[[f64; 3]], cutoff: f64) -> Self {
+        assert!(cutoff > 0.0, "cell list cutoff must be positive");
+        let mut list = Self {
+            cutoff,
+            positions: positions.to_vec(),
+            cell_of: Vec::with_capacity(positions.len()),
+            cells: HashMap::new(),
+        };
+        for (index, position) in positions.iter().enumerate() {
+            let key = list.key(*position);
+            list.cell_of.push(key);
+            list.cells.entry(key).or_default().push(index);
+        }
+        list
+    }
+
+    pub fn from_chain(chain: &PeptideChain, cutoff: f64) -> Self {
+        Self::new(&chain.positions(), cutoff)
+    }
+
+    pub fn cutoff(&self) -> f64 {
+        self.cutoff
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Moves one residue, re-binning it only if it crossed a cell boundary.
+    pub fn update(&mut self, index: usize, position: [f64; 3]) {
+        self.positions[index] = position;
+        let key = self.key(position);
+        let old = self.cell_of[index];
+        if key == old {
+            return;
+        }
+        if let Some(members) = self.cells.get_mut(&old) {
+            members.retain(|&member| member != index);
+            if members.is_empty() {
+                self.cells.remove(&old);
+            }
+        }
+        self.cells.entry(key).or_default().push(index);
+        self.cell_of[index] = key;
+    }
+
+    /// Applies a new set of positions, touching only residues that moved.
+    /// Returns how many residues were updated.
+    pub fn refresh(&mut self, positions: &[[f64; 3]]) -> usize {
+        assert_eq!(positions.len(), self.len(), "cell list size mismatch");
+        let mut moved = 0;
+        for (index, position) in positions.iter().enumerate() {
+            if *position != self.positions[index] {
+                self.update(index, *position);
+                moved += 1;
+            }
+        }
+        moved
+    }
+
+    /// Residues within the cutoff of `index`, in ascending index order.
+    pub fn neighbors(&self, index: usize) -> Vec<(usize, f64)> {
+        let mut found = Vec::new();
+        self.visit_neighbors(index, |other, distance| {
+            if other != index {
+                found.push((other, distance));
+            }
+        });
+        found.sort_unstable_by_key(|(other, _)| *other);
+        found
+    }
+
+    /// Every pair closer than the cutoff whose sequence separation is at
+    /// least `min_separation`, ordered by `(left, right)` so sums over the
+    /// result are reproducible regardless of thread count.
+    pub fn pairs(&self, min_separation: usize) -> Vec<NeighborPair> {
+        let min_separation = min_separation.max(1);
+        let collect = |left: usize| {
+            let mut found = Vec::new();
+            self.visit_neighbors(left, |right, distance| {
+                if right >= left + min_separation {
+                    found.push(NeighborPair {
+                        left,
+                        right,
+                        distance,
+                    });
+                }
+            });
+            found.sort_unstable_by_key(|pair| pair.right);
+            found
+        };
+        if self.len() >= PARALLEL_MIN_RESIDUES {
+            (0..self.len())
+                .into_par_iter()
+                .flat_map_iter(collect)
+                .collect()
+        } else {
+            (0..self.len()).flat_map(collect).collect()
+        }
+    }
+
+    fn visit_neighbors(&self, index: usize, mut visit: impl FnMut(usize, f64)) {
+        let origin = self.positions[index];
+        let center = self.cell_of[index];
+        // An infinite cutoff keeps every residue in the single origin cell.
+        let reach = if self.cutoff.is_finite() { 1 } else { 0 };
+        for dx in -reach..=reach {
+            for dy in -reach..=reach {
+                for dz in -reach..=reach {
+                    let key = [center[0] + dx, center[1] + dy, center[2] + dz];
+                    let Some(members) = self.cells.get(&key) else {
+                        continue;
+                    };
+                    for &other in members {
+                        let distance = distance(origin, self.positions[other]);
+                        if distance < self.cutoff {
+                            visit(other, distance);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn key(&self, position: [f64; 3]) -> CellKey {
+        if !self.cutoff.is_finite() {
+            return [0, 0, 0];
+        }
+        [
+            (position[0] / self.cutoff).floor() as i64,
+            (position[1] / self.cutoff).floor() as i64,
+            (position[2] / self.cutoff).floor() as i64,
+        ]
+    }
+}
+
+fn distance(left: [f64; 3], right: [f64; 3]) -> f64 {
+    ((left[0] - right[0]).powi(2) + (left[1] - right[1]).powi(2) + (left[2] - right[2]).powi(2))
+        .sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lattice(count: usize) -> Vec<[f64; 3]> {
+        (0..count)
+            .map(|i| {
+                let f = i as f64;
+                [
+                    (f * 1.7).sin() * 9.0 + f * 0.3,
+                    (f * 0.9).cos() * 7.0,
+                    (f * 2.3).sin() * 5.0,
+                ]
+            })
+            .collect()
+    }
+
+    fn brute_force(
+        positions: &[[f64; 3]],
+        cutoff: f64,
+        min_separation: usize,
+    ) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for i in 0..positions.len() {
+            for j in i + min_separation..positions.len() {
+                if distance(positions[i], positions[j]) < cutoff {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs
+    }
+
+    #[test]
+    fn matches_brute_force_pairs() {
+        for count in [12, 300] {
+            let positions = lattice(count);
+            for cutoff in [4.0, 6.5, f64::INFINITY] {
+                let cells = CellList::new(&positions, cutoff);
+                let found: Vec<_> = cells
+                    .pairs(3)
+                    .iter()
+                    .map(|pair| (pair.left, pair.right))
+                    .collect();
+                assert_eq!(found, brute_force(&positions, cutoff, 3));
+            }
+        }
+    }
+
+    #[test]
+    fn incremental_update_matches_rebuild() {
+        let mut positions = lattice(40);
+        let mut cells = CellList::new(&positions, 5.0);
+        positions[7] = [30.0, -12.0, 4.0];
+        positions[8][0] += 0.01;
+        assert_eq!(cells.refresh(&positions), 2);
+        let rebuilt = CellList::new(&positions, 5.0);
+        assert_eq!(cells.pairs(1), rebuilt.pairs(1));
+        assert_eq!(cells.neighbors(7), rebuilt.neighbors(7));
+    }
+}
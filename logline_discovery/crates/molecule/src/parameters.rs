@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use crate::AminoAcid;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -32,31 +34,92 @@ pub fn classify(amino_acid: AminoAcid) -> ResidueClass {
 }
 
 pub fn lennard_jones_params(a: ResidueClass, b: ResidueClass) -> (f64, f64) {
-    let sigma_a = match a {
-        ResidueClass::Hydrophobic => 3.8,
-        ResidueClass::Polar => 3.6,
-        ResidueClass::Positive | ResidueClass::Negative => 3.4,
-    };
-    let sigma_b = match b {
-        ResidueClass::Hydrophobic => 3.8,
-        ResidueClass::Polar => 3.6,
-        ResidueClass::Positive | ResidueClass::Negative => 3.4,
-    };
-    let epsilon_a: f64 = match a {
-        ResidueClass::Hydrophobic => 0.20_f64,
-        ResidueClass::Polar => 0.15_f64,
-        ResidueClass::Positive | ResidueClass::Negative => 0.12_f64,
-    };
-    let epsilon_b: f64 = match b {
-        ResidueClass::Hydrophobic => 0.20_f64,
-        ResidueClass::Polar => 0.15_f64,
-        ResidueClass::Positive | ResidueClass::Negative => 0.12_f64,
-    };
-    let sigma = (sigma_a + sigma_b) * 0.5;
-    let epsilon = (epsilon_a * epsilon_b).sqrt();
+    combine_lennard_jones(bead_lennard_jones(a), bead_lennard_jones(b))
+}
+
+/// Lorentz–Berthelot mixing of two `(sigma, epsilon)` beads.
+pub fn combine_lennard_jones(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    let sigma = (a.0 + b.0) * 0.5;
+    let epsilon = (a.1 * b.1).sqrt();
     (sigma, epsilon)
 }
 
+fn bead_lennard_jones(class: ResidueClass) -> (f64, f64) {
+    match class {
+        ResidueClass::Hydrophobic => (3.8, 0.20),
+        ResidueClass::Polar => (3.6, 0.15),
+        ResidueClass::Positive | ResidueClass::Negative => (3.4, 0.12),
+    }
+}
+
+/// Post-translational modifications that change a bead's physics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Modification {
+    Phosphorylation,
+    Glycosylation,
+    Acetylation,
+    Methylation,
+}
+
+impl Modification {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "phosphorylation" | "phospho" | "phosphate" => Some(Self::Phosphorylation),
+            "glycosylation" | "glycan" | "n-glycan" | "o-glycan" => Some(Self::Glycosylation),
+            "acetylation" | "acetyl" => Some(Self::Acetylation),
+            "methylation" | "methyl" => Some(Self::Methylation),
+            _ => None,
+        }
+    }
+
+    /// Added mass (amu): HPO₃, one GlcNAc, acetyl and methyl groups.
+    pub fn mass_delta(self) -> f64 {
+        match self {
+            Self::Phosphorylation => 79.97,
+            Self::Glycosylation => 203.19,
+            Self::Acetylation => 42.04,
+            Self::Methylation => 14.03,
+        }
+    }
+}
+
+/// Net charge of a residue bead after an optional modification.
+pub fn residue_charge(amino_acid: AminoAcid, modification: Option<Modification>) -> f64 {
+    let base = amino_acid.partial_charge();
+    match modification {
+        // A phosphate carries about -2 at physiological pH.
+        Some(Modification::Phosphorylation) => base - 2.0,
+        // Acetyl-lysine loses the ε-amino charge.
+        Some(Modification::Acetylation) if amino_acid == AminoAcid::Lysine => 0.0,
+        _ => base,
+    }
+}
+
+/// `(sigma, epsilon)` of a residue bead after an optional modification.
+/// Bulky adducts widen the bead; the glycan's hydration shell also weakens
+/// its dispersion contacts.
+pub fn residue_lennard_jones(
+    amino_acid: AminoAcid,
+    modification: Option<Modification>,
+) -> (f64, f64) {
+    modified_lennard_jones(bead_lennard_jones(classify(amino_acid)), modification)
+}
+
+/// Applies the [`residue_lennard_jones`] adjustment for `modification` to any
+/// `(sigma, epsilon)` pair, e.g. a side-chain bead from a force field.
+pub fn modified_lennard_jones(
+    (sigma, epsilon): (f64, f64),
+    modification: Option<Modification>,
+) -> (f64, f64) {
+    match modification {
+        None => (sigma, epsilon),
+        Some(Modification::Phosphorylation) => (sigma + 0.4, epsilon),
+        Some(Modification::Glycosylation) => (sigma + 1.2, epsilon * 0.5),
+        Some(Modification::Acetylation) => (sigma + 0.3, epsilon * 1.1),
+        Some(Modification::Methylation) => (sigma + 0.2, epsilon * 1.1),
+    }
+}
+
 pub fn bond_equilibrium_distance(a: ResidueClass, b: ResidueClass) -> f64 {
     let base = 1.45;
     match (a, b) {
@@ -75,3 +138,12 @@ pub fn angle_equilibrium(class_middle: ResidueClass) -> f64 {
         ResidueClass::Positive | ResidueClass::Negative => 1.90, // ~109°
     }
 }
+
+/// Bead radius (Å) used for Born radii and solvent-accessible area.
+pub fn solvation_radius(class: ResidueClass) -> f64 {
+    match class {
+        ResidueClass::Hydrophobic => 3.4,
+        ResidueClass::Polar => 3.2,
+        ResidueClass::Positive | ResidueClass::Negative => 3.6,
+    }
+}
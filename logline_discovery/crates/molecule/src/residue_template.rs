@@ -0,0 +1,356 @@
+//! Multi-bead residue templates built on top of the Cα trace.
+//!
+//! Each residue expands to backbone N, Cα, C and O beads plus a side-chain
+//! centroid (none for glycine). Local geometry comes from the ideal backbone
+//! for the residue's phi/psi/omega. That fragment is rotated onto the frame
+//! of the Cα trace around the residue (residues *i - 1*, *i*, *i + 1*) and
+//! translated onto its Cα. Chain positions stay the single source of truth,
+//! so moves keep acting on Cα atoms. [`BeadModel::project_gradient`] folds
+//! forces on beads back onto them.
+
+use std::ops::Range;
+
+use crate::chain::PeptideChain;
+use crate::force_field::{BeadParameters, ForceField};
+use crate::geometry;
+use crate::parameters;
+
+/// Ideal C=O bond length in ångström.
+const CARBONYL_LENGTH: f64 = 1.231;
+/// Ideal Cα–C=O angle in degrees.
+const CA_C_O_ANGLE: f64 = 120.5;
+/// Trace displacement (Å) for the central differences of the frame rotation.
+const FRAME_STEP: f64 = 1e-6;
+
+type Frame = [[f64; 3]; 3];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BeadKind {
+    N,
+    CA,
+    C,
+    O,
+    /// Side-chain centroid.
+    SideChain,
+}
+
+impl BeadKind {
+    pub const BACKBONE: [BeadKind; 4] = [Self::N, Self::CA, Self::C, Self::O];
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "N" => Some(Self::N),
+            "CA" => Some(Self::CA),
+            "C" => Some(Self::C),
+            "O" => Some(Self::O),
+            "SC" => Some(Self::SideChain),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::N => "N",
+            Self::CA => "CA",
+            Self::C => "C",
+            Self::O => "O",
+            Self::SideChain => "SC",
+        }
+    }
+}
+
+/// One interaction site of a residue.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bead {
+    /// Chain index of the owning residue.
+    pub residue: usize,
+    pub kind: BeadKind,
+    pub position: [f64; 3],
+    pub parameters: BeadParameters,
+}
+
+/// Beads of a whole chain, with what is needed to map bead forces back
+/// onto the Cα positions they were built from.
+#[derive(Clone, Debug)]
+pub struct BeadModel {
+    beads: Vec<Bead>,
+    /// Bead offsets from their residue's Cα in the ideal backbone.
+    offsets: Vec<[f64; 3]>,
+    residue_beads: Vec<Range<usize>>,
+    trace: Vec<[f64; 3]>,
+    ideal_frames: Vec<Option<Frame>>,
+}
+
+impl BeadModel {
+    pub fn build(chain: &PeptideChain, field: &ForceField) -> Self {
+        let trace = chain.positions();
+        let ideal = chain.backbone();
+        let ideal_trace: Vec<[f64; 3]> = ideal.iter().map(|atoms| atoms.ca).collect();
+        let ideal_frames: Vec<Option<Frame>> = (0..ideal_trace.len())
+            .map(|index| trace_frame(&ideal_trace, index))
+            .collect();
+
+        let mut beads = Vec::new();
+        let mut offsets = Vec::new();
+        let mut residue_beads = Vec::with_capacity(trace.len());
+        for (index, (residue, atoms)) in chain.residues().iter().zip(&ideal).enumerate() {
+            let oxygen = geometry::place_atom(
+                atoms.n,
+                atoms.ca,
+                atoms.c,
+                CARBONYL_LENGTH,
+                CA_C_O_ANGLE.to_radians(),
+                (residue.psi + 180.0).to_radians(),
+            );
+            let mut sites: Vec<(BeadKind, [f64; 3], BeadParameters)> = [
+                (BeadKind::N, atoms.n),
+                (BeadKind::CA, atoms.ca),
+                (BeadKind::C, atoms.c),
+                (BeadKind::O, oxygen),
+            ]
+            .into_iter()
+            .map(|(kind, position)| (kind, position, field.backbone(kind, residue.amino_acid)))
+            .collect();
+
+            // Adducts sit on the side chain, or on Cα for glycine.
+            let charge_shift = parameters::residue_charge(residue.amino_acid, residue.modification)
+                - parameters::residue_charge(residue.amino_acid, None);
+            match field.side_chain(residue.amino_acid) {
+                Some(side_chain) => {
+                    let mut bead = side_chain.bead;
+                    (bead.sigma, bead.epsilon) = parameters::modified_lennard_jones(
+                        (bead.sigma, bead.epsilon),
+                        residue.modification,
+                    );
+                    bead.charge += charge_shift;
+                    let direction = ideal_beta_direction(atoms.n, atoms.ca, atoms.c);
+                    let centroid = add(atoms.ca, scaled(direction, side_chain.centroid_distance));
+                    sites.push((BeadKind::SideChain, centroid, bead));
+                }
+                None => sites[1].2.charge += charge_shift,
+            }
+
+            let start = beads.len();
+            let rotation = (ideal_frames[index], trace_frame(&trace, index));
+            for (kind, position, parameters) in sites {
+                let offset = subtract(position, atoms.ca);
+                beads.push(Bead {
+                    residue: index,
+                    kind,
+                    position: add(trace[index], rotate(rotation, offset)),
+                    parameters,
+                });
+                offsets.push(offset);
+            }
+            residue_beads.push(start..beads.len());
+        }
+
+        Self {
+            beads,
+            offsets,
+            residue_beads,
+            trace,
+            ideal_frames,
+        }
+    }
+
+    pub fn beads(&self) -> &[Bead] {
+        &self.beads
+    }
+
+    pub fn len(&self) -> usize {
+        self.beads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.beads.is_empty()
+    }
+
+    pub fn positions(&self) -> Vec<[f64; 3]> {
+        self.beads.iter().map(|bead| bead.position).collect()
+    }
+
+    /// Chain-rule projection of a per-bead gradient onto the Cα positions
+    /// the beads were built from.
+    ///
+    /// A bead moves rigidly with its own Cα and turns with the frame of the
+    /// three trace positions around it; the frame's derivative is taken by
+    /// central differences.
+    pub fn project_gradient(&self, bead_gradient: &[[f64; 3]]) -> Vec<[f64; 3]> {
+        let mut gradient = vec![[0.0; 3]; self.trace.len()];
+        for (residue, range) in self.residue_beads.iter().enumerate() {
+            for bead in range.clone() {
+                gradient[residue] = add(gradient[residue], bead_gradient[bead]);
+            }
+            let (Some(ideal), Some(start)) = (
+                self.ideal_frames[residue],
+                frame_start(residue, self.trace.len()),
+            ) else {
+                continue;
+            };
+            let triple = [
+                self.trace[start],
+                self.trace[start + 1],
+                self.trace[start + 2],
+            ];
+            for (member, target) in gradient[start..start + 3].iter_mut().enumerate() {
+                for axis in 0..3 {
+                    let mut forward = triple;
+                    let mut backward = triple;
+                    forward[member][axis] += FRAME_STEP;
+                    backward[member][axis] -= FRAME_STEP;
+                    let forward = (Some(ideal), frame(forward));
+                    let backward = (Some(ideal), frame(backward));
+                    target[axis] += range
+                        .clone()
+                        .map(|bead| {
+                            let offset = self.offsets[bead];
+                            let moved = subtract(rotate(forward, offset), rotate(backward, offset));
+                            dot(bead_gradient[bead], moved) / (2.0 * FRAME_STEP)
+                        })
+                        .sum::<f64>();
+                }
+            }
+        }
+        gradient
+    }
+}
+
+/// First of the three consecutive trace positions that orient residue `index`.
+fn frame_start(index: usize, len: usize) -> Option<usize> {
+    (len >= 3).then(|| index.saturating_sub(1).min(len - 3))
+}
+
+fn trace_frame(trace: &[[f64; 3]], index: usize) -> Option<Frame> {
+    let start = frame_start(index, trace.len())?;
+    frame([trace[start], trace[start + 1], trace[start + 2]])
+}
+
+/// Orthonormal frame of three points: along `a → c`, towards `b`, and
+/// their normal. `None` when the points are (nearly) collinear.
+fn frame([a, b, c]: [[f64; 3]; 3]) -> Option<Frame> {
+    let span = subtract(c, a);
+    let length = norm(span);
+    if length < 1e-8 {
+        return None;
+    }
+    let first = scaled(span, 1.0 / length);
+    let towards = subtract(b, a);
+    let perpendicular = subtract(towards, scaled(first, dot(towards, first)));
+    let height = norm(perpendicular);
+    if height < 1e-8 {
+        return None;
+    }
+    let second = scaled(perpendicular, 1.0 / height);
+    Some([first, second, cross(first, second)])
+}
+
+/// Rotates `v` from the first frame into the second; without both frames
+/// the offset is kept as is.
+fn rotate((from, to): (Option<Frame>, Option<Frame>), v: [f64; 3]) -> [f64; 3] {
+    match (from, to) {
+        (Some(from), Some(to)) => (0..3).fold([0.0; 3], |acc, axis| {
+            add(acc, scaled(to[axis], dot(from[axis], v)))
+        }),
+        _ => v,
+    }
+}
+
+/// Unit vector from Cα towards an ideal L-amino-acid Cβ.
+fn ideal_beta_direction(n: [f64; 3], ca: [f64; 3], c: [f64; 3]) -> [f64; 3] {
+    let b = subtract(ca, n);
+    let c = subtract(c, ca);
+    let a = cross(b, c);
+    let direction = add(
+        add(scaled(a, -0.582_734_31), scaled(b, 0.568_028_27)),
+        scaled(c, -0.540_674_66),
+    );
+    scaled(direction, 1.0 / norm(direction))
+}
+
+fn add(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn subtract(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scaled(v: [f64; 3], factor: f64) -> [f64; 3] {
+    [v[0] * factor, v[1] * factor, v[2] * factor]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn norm(v: [f64; 3]) -> f64 {
+    dot(v, v).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aminoacid::AminoAcid;
+    use crate::fasta::StartingConformation;
+
+    fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+        norm(subtract(a, b))
+    }
+
+    #[test]
+    fn beads_follow_rigid_motion_of_the_ideal_backbone() {
+        let sequence: Vec<AminoAcid> = "AKGLPDW"
+            .chars()
+            .map(|symbol| AminoAcid::from_char(symbol).unwrap())
+            .collect();
+        let mut chain = PeptideChain::from_sequence(&sequence, StartingConformation::Helix);
+        let backbone = chain.backbone();
+        // 90° about z, then a shift: the beads must move with the trace.
+        let moved = |p: [f64; 3]| [-p[1] + 4.0, p[0] - 2.0, p[2] + 7.5];
+        let trace: Vec<[f64; 3]> = chain.positions().into_iter().map(moved).collect();
+        chain.set_positions(&trace);
+
+        let field = ForceField::builtin();
+        let model = BeadModel::build(&chain, &field);
+        // Glycine carries no side-chain centroid.
+        assert_eq!(model.len(), 5 * sequence.len() - 1);
+        for bead in model.beads() {
+            let atoms = backbone[bead.residue];
+            let expected = match bead.kind {
+                BeadKind::N => Some(atoms.n),
+                BeadKind::CA => Some(atoms.ca),
+                BeadKind::C => Some(atoms.c),
+                BeadKind::O | BeadKind::SideChain => None,
+            };
+            if let Some(expected) = expected {
+                assert!(distance(bead.position, moved(expected)) < 1e-9);
+            }
+        }
+
+        let beads = model.beads();
+        for (residue, amino_acid) in sequence.iter().enumerate() {
+            let site = |kind| {
+                beads
+                    .iter()
+                    .find(|bead| bead.residue == residue && bead.kind == kind)
+                    .map(|bead| bead.position)
+            };
+            let (c, o, ca) = (site(BeadKind::C), site(BeadKind::O), site(BeadKind::CA));
+            assert!((distance(c.unwrap(), o.unwrap()) - CARBONYL_LENGTH).abs() < 1e-9);
+            if let Some(centroid) = site(BeadKind::SideChain) {
+                let expected = field.side_chain(*amino_acid).unwrap();
+                let reach = distance(ca.unwrap(), centroid);
+                assert!((reach - expected.centroid_distance).abs() < 1e-9);
+            }
+        }
+    }
+}
@@ -0,0 +1,360 @@
+//! Harmonic restraints toward experimental or reference geometry, such as NMR
+//! distance restraints or a target structure.
+//!
+//! Residues are chain indices, as in [`PeptideChain::residues`]. Force
+//! constants are absolute (kcal·mol⁻¹·Å⁻² or kcal·mol⁻¹·rad⁻²), so
+//! restraint energies are not multiplied by
+//! [`EnergyModel::scaling_factor`](crate::EnergyModel).
+
+use serde::{Deserialize, Serialize};
+
+use crate::chain::PeptideChain;
+use crate::dynamic_energy::{dihedral_angle, dihedral_derivatives};
+use crate::structure_metrics::kabsch_superposition;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Restraint {
+    /// E = ½k(d − d₀)² on the distance between two residues (Å).
+    Distance {
+        left: usize,
+        right: usize,
+        target: f64,
+        force_constant: f64,
+    },
+    /// E = ½k(φ − φ₀)² on the dihedral through four residues, with the
+    /// difference wrapped to (−π, π]. `target` is in radians.
+    Dihedral {
+        residues: [usize; 4],
+        target: f64,
+        force_constant: f64,
+    },
+    /// Flat-bottom E = ½k·max(0, rmsd − target)² against `reference`
+    /// positions of `residues`, after optimal (Kabsch) superposition.
+    Rmsd {
+        residues: Vec<usize>,
+        reference: Vec<[f64; 3]>,
+        target: f64,
+        force_constant: f64,
+    },
+}
+
+/// How far a restraint is from being satisfied. Dihedral values are reported
+/// in degrees; distances and RMSDs in Å.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RestraintStatus {
+    pub kind: &'static str,
+    pub residues: Vec<usize>,
+    pub value: f64,
+    pub target: f64,
+    /// Absolute deviation outside the allowed value, in the units of `value`.
+    pub violation: f64,
+    pub energy: f64,
+}
+
+impl Restraint {
+    pub fn distance(left: usize, right: usize, target: f64, force_constant: f64) -> Self {
+        Self::Distance {
+            left,
+            right,
+            target,
+            force_constant,
+        }
+    }
+
+    pub fn dihedral(residues: [usize; 4], target_degrees: f64, force_constant: f64) -> Self {
+        Self::Dihedral {
+            residues,
+            target: target_degrees.to_radians(),
+            force_constant,
+        }
+    }
+
+    /// Restrains `chain`'s residues to the positions of `reference`.
+    pub fn rmsd_to(reference: &PeptideChain, target: f64, force_constant: f64) -> Self {
+        Self::Rmsd {
+            residues: (0..reference.len()).collect(),
+            reference: reference.positions(),
+            target,
+            force_constant,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Restraint::Distance { .. } => "distance",
+            Restraint::Dihedral { .. } => "dihedral",
+            Restraint::Rmsd { .. } => "rmsd",
+        }
+    }
+
+    pub fn residues(&self) -> Vec<usize> {
+        match self {
+            Restraint::Distance { left, right, .. } => vec![*left, *right],
+            Restraint::Dihedral { residues, .. } => residues.to_vec(),
+            Restraint::Rmsd { residues, .. } => residues.clone(),
+        }
+    }
+
+    fn force_constant(&self) -> f64 {
+        match self {
+            Restraint::Distance { force_constant, .. }
+            | Restraint::Dihedral { force_constant, .. }
+            | Restraint::Rmsd { force_constant, .. } => *force_constant,
+        }
+    }
+
+    fn target(&self) -> f64 {
+        match self {
+            Restraint::Distance { target, .. }
+            | Restraint::Dihedral { target, .. }
+            | Restraint::Rmsd { target, .. } => *target,
+        }
+    }
+
+    /// Current distance, dihedral (radians) or RMSD; `None` when a residue
+    /// index is outside `positions`.
+    fn measure(&self, positions: &[[f64; 3]]) -> Option<f64> {
+        let at = |index: usize| positions.get(index).copied();
+        match self {
+            Restraint::Distance { left, right, .. } => {
+                Some(norm(subtract(at(*right)?, at(*left)?)))
+            }
+            Restraint::Dihedral { residues, .. } => {
+                let [a, b, c, d] = residues.map(at);
+                Some(dihedral_angle(a?, b?, c?, d?))
+            }
+            Restraint::Rmsd {
+                residues,
+                reference,
+                ..
+            } => {
+                let offsets = rmsd_offsets(positions, residues, reference)?;
+                let sum: f64 = offsets.iter().map(|offset| dot(*offset, *offset)).sum();
+                Some((sum / offsets.len() as f64).sqrt())
+            }
+        }
+    }
+
+    /// Signed deviation penalised by the harmonic term.
+    fn deviation(&self, value: f64) -> f64 {
+        let target = self.target();
+        match self {
+            Restraint::Distance { .. } => value - target,
+            Restraint::Dihedral { .. } => wrap_angle(value - target),
+            Restraint::Rmsd { .. } => (value - target).max(0.0),
+        }
+    }
+
+    pub fn energy(&self, positions: &[[f64; 3]]) -> f64 {
+        self.measure(positions).map_or(0.0, |value| {
+            let deviation = self.deviation(value);
+            0.5 * self.force_constant() * deviation * deviation
+        })
+    }
+
+    /// Adds dE/dx of this restraint to `gradient`, indexed like `positions`.
+    pub fn add_gradient(&self, positions: &[[f64; 3]], gradient: &mut [[f64; 3]]) {
+        let Some(value) = self.measure(positions) else {
+            return;
+        };
+        let de_dvalue = self.force_constant() * self.deviation(value);
+        if de_dvalue == 0.0 {
+            return;
+        }
+        match self {
+            Restraint::Distance { left, right, .. } => {
+                if value < 1e-8 {
+                    return;
+                }
+                let delta = subtract(positions[*right], positions[*left]);
+                add_scaled(&mut gradient[*right], delta, de_dvalue / value);
+                add_scaled(&mut gradient[*left], delta, -de_dvalue / value);
+            }
+            Restraint::Dihedral { residues, .. } => {
+                let [a, b, c, d] = residues.map(|index| positions[index]);
+                if let Some(derivatives) = dihedral_derivatives(a, b, c, d) {
+                    for (index, derivative) in residues.iter().zip(derivatives) {
+                        add_scaled(&mut gradient[*index], derivative, de_dvalue);
+                    }
+                }
+            }
+            Restraint::Rmsd {
+                residues,
+                reference,
+                ..
+            } => {
+                // The fitted rotation is stationary and the offsets sum to
+                // zero, so neither the rotation nor the centroids contribute.
+                let Some(offsets) = rmsd_offsets(positions, residues, reference) else {
+                    return;
+                };
+                let factor = de_dvalue / (offsets.len() as f64 * value);
+                for (index, offset) in residues.iter().zip(offsets) {
+                    add_scaled(&mut gradient[*index], offset, factor);
+                }
+            }
+        }
+    }
+
+    pub fn status(&self, positions: &[[f64; 3]]) -> RestraintStatus {
+        let value = self.measure(positions).unwrap_or(f64::NAN);
+        let (value, target, violation) = match self {
+            Restraint::Dihedral { target, .. } => (
+                value.to_degrees(),
+                target.to_degrees(),
+                self.deviation(value).abs().to_degrees(),
+            ),
+            _ => (value, self.target(), self.deviation(value).abs()),
+        };
+        RestraintStatus {
+            kind: self.kind(),
+            residues: self.residues(),
+            value,
+            target,
+            violation,
+            energy: self.energy(positions),
+        }
+    }
+}
+
+/// Offsets of `residues` from the matching `reference` positions once both
+/// are centred and the reference is rotated onto them; `None` when nothing
+/// can be compared.
+fn rmsd_offsets(
+    positions: &[[f64; 3]],
+    residues: &[usize],
+    reference: &[[f64; 3]],
+) -> Option<Vec<[f64; 3]>> {
+    if residues.is_empty() || residues.len() != reference.len() {
+        return None;
+    }
+    let current = residues
+        .iter()
+        .map(|index| positions.get(*index).copied())
+        .collect::<Option<Vec<_>>>()?;
+    let (rotation, _) = kabsch_superposition(reference, &current);
+    let (current_centroid, reference_centroid) = (centroid(&current), centroid(reference));
+    Some(
+        current
+            .iter()
+            .zip(reference)
+            .map(|(x, y)| {
+                let y = subtract(*y, reference_centroid);
+                let fitted = [
+                    dot(rotation[0], y),
+                    dot(rotation[1], y),
+                    dot(rotation[2], y),
+                ];
+                subtract(subtract(*x, current_centroid), fitted)
+            })
+            .collect(),
+    )
+}
+
+fn centroid(points: &[[f64; 3]]) -> [f64; 3] {
+    let mut sum = [0.0; 3];
+    for point in points {
+        add_scaled(&mut sum, *point, 1.0 / points.len() as f64);
+    }
+    sum
+}
+
+fn wrap_angle(angle: f64) -> f64 {
+    let wrapped = angle.rem_euclid(std::f64::consts::TAU);
+    if wrapped > std::f64::consts::PI {
+        wrapped - std::f64::consts::TAU
+    } else {
+        wrapped
+    }
+}
+
+fn subtract(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn add_scaled(target: &mut [f64; 3], v: [f64; 3], factor: f64) {
+    target[0] += v[0] * factor;
+    target[1] += v[1] * factor;
+    target[2] += v[2] * factor;
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn norm(v: [f64; 3]) -> f64 {
+    dot(v, v).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AminoAcid, EnergyModel, StartingConformation};
+
+    fn chain() -> PeptideChain {
+        PeptideChain::from_sequence(&[AminoAcid::Alanine; 8], StartingConformation::Helix)
+    }
+
+    #[test]
+    fn gradients_match_finite_differences() {
+        let chain = chain();
+        let mut reference = chain.clone();
+        let shifted: Vec<_> = chain
+            .positions()
+            .iter()
+            .map(|p| [p[1] + 1.5, -p[0] - 0.5, p[2]])
+            .collect();
+        reference.set_positions(&shifted);
+        let mut positions = chain.positions();
+        positions[6][1] += 2.0;
+        let restraints = [
+            Restraint::distance(0, 7, 6.0, 5.0),
+            Restraint::dihedral([1, 2, 3, 4], 170.0, 2.0),
+            Restraint::rmsd_to(&reference, 0.1, 3.0),
+        ];
+        let step = 1e-6;
+        for restraint in &restraints {
+            let mut gradient = vec![[0.0; 3]; positions.len()];
+            restraint.add_gradient(&positions, &mut gradient);
+            for index in 0..positions.len() {
+                for axis in 0..3 {
+                    let mut plus = positions.clone();
+                    let mut minus = positions.clone();
+                    plus[index][axis] += step;
+                    minus[index][axis] -= step;
+                    let numeric =
+                        (restraint.energy(&plus) - restraint.energy(&minus)) / (2.0 * step);
+                    assert!(
+                        (numeric - gradient[index][axis]).abs() < 1e-5,
+                        "{} residue {index} axis {axis}: {numeric} vs {}",
+                        restraint.kind(),
+                        gradient[index][axis]
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reports_violations_and_adds_energy_to_the_model() {
+        let chain = chain();
+        let positions = chain.positions();
+        let current = norm(subtract(positions[5], positions[0]));
+        let restraint = Restraint::distance(0, 5, current - 1.0, 4.0);
+        let status = restraint.status(&positions);
+        assert_eq!(status.kind, "distance");
+        assert!((status.violation - 1.0).abs() < 1e-9);
+        assert!((status.energy - 2.0).abs() < 1e-9);
+
+        // Within the flat bottom the RMSD restraint is satisfied.
+        let rmsd = Restraint::rmsd_to(&chain, 0.5, 10.0).status(&positions);
+        assert_eq!((rmsd.value, rmsd.violation, rmsd.energy), (0.0, 0.0, 0.0));
+
+        let plain = EnergyModel::default();
+        let restrained = plain.clone().with_restraint(restraint);
+        let summary = restrained.energy_summary(&chain);
+        assert!((summary.restraint - 2.0).abs() < 1e-9);
+        assert!((summary.total() - plain.total_energy(&chain) - 2.0).abs() < 1e-9);
+        assert_eq!(restrained.restraint_statuses(&chain), [status]);
+    }
+}
@@ -0,0 +1,332 @@
+//! Secondary-structure assignment: helix, strand, turn or coil per residue.
+//!
+//! Two classifiers are offered. [`assign_from_torsions`] reads the
+//! Ramachandran region of each residue's phi/psi and only needs the torsions
+//! the chain already tracks. [`assign_from_backbone`] follows DSSP (Kabsch &
+//! Sander, 1983): it places carbonyl O and amide H on an N/Cα/C backbone,
+//! scores backbone hydrogen bonds electrostatically and reads helices,
+//! bridges and turns from the bond pattern, so it is only meaningful once the
+//! backbone coordinates are realistic.
+
+use serde::{Deserialize, Serialize};
+
+use crate::chain::{PeptideChain, Residue};
+use crate::geometry::BackboneAtoms;
+
+/// Shortest run of helical residues reported as a helix; shorter runs are
+/// single turns.
+pub const MIN_HELIX_LENGTH: usize = 4;
+/// Shortest run of extended residues reported as a strand.
+pub const MIN_STRAND_LENGTH: usize = 2;
+/// DSSP hydrogen-bond energy threshold in kcal/mol.
+pub const HBOND_ENERGY_CUTOFF: f64 = -0.5;
+/// DSSP partial-charge product q₁q₂·f in kcal·mol⁻¹·Å.
+const HBOND_COUPLING: f64 = 0.084 * 332.0;
+/// C=O bond length in ångström.
+const C_O_LENGTH: f64 = 1.231;
+/// N–H bond length in ångström.
+const N_H_LENGTH: f64 = 1.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SecondaryStructure {
+    Helix,
+    Strand,
+    Turn,
+    Coil,
+}
+
+impl SecondaryStructure {
+    /// One-letter DSSP-style code: `H`, `E`, `T` or `C`.
+    pub fn code(self) -> char {
+        match self {
+            Self::Helix => 'H',
+            Self::Strand => 'E',
+            Self::Turn => 'T',
+            Self::Coil => 'C',
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Helix => "helix",
+            Self::Strand => "strand",
+            Self::Turn => "turn",
+            Self::Coil => "coil",
+        }
+    }
+}
+
+/// Fraction of residues in each class; all zero for an empty assignment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SecondaryStructureFractions {
+    pub helix: f64,
+    pub strand: f64,
+    pub turn: f64,
+    pub coil: f64,
+}
+
+impl SecondaryStructureFractions {
+    pub fn from_assignment(assignment: &[SecondaryStructure]) -> Self {
+        let mut fractions = Self::default();
+        if assignment.is_empty() {
+            return fractions;
+        }
+        let share = 1.0 / assignment.len() as f64;
+        for class in assignment {
+            match class {
+                SecondaryStructure::Helix => fractions.helix += share,
+                SecondaryStructure::Strand => fractions.strand += share,
+                SecondaryStructure::Turn => fractions.turn += share,
+                SecondaryStructure::Coil => fractions.coil += share,
+            }
+        }
+        fractions
+    }
+}
+
+/// Assignment as a string of one-letter codes, one per residue.
+pub fn assignment_codes(assignment: &[SecondaryStructure]) -> String {
+    assignment.iter().map(|class| class.code()).collect()
+}
+
+/// Ramachandran region of a single residue, with torsions in degrees.
+///
+/// The right-handed helical basin (φ ∈ [−160, −20], ψ ∈ [−120, 50]) is
+/// `Helix`, the β basin (φ ∈ [−180, −45], ψ ≥ 90 or ψ ≤ −150) is `Strand`
+/// and positive φ (left-handed helix, glycine turns) is `Turn`.
+pub fn ramachandran_region(phi: f64, psi: f64) -> SecondaryStructure {
+    let (phi, psi) = (wrap_degrees(phi), wrap_degrees(psi));
+    if (-160.0..=-20.0).contains(&phi) && (-120.0..=50.0).contains(&psi) {
+        SecondaryStructure::Helix
+    } else if phi <= -45.0 && (psi >= 90.0 || psi <= -150.0) {
+        SecondaryStructure::Strand
+    } else if phi > 0.0 {
+        SecondaryStructure::Turn
+    } else {
+        SecondaryStructure::Coil
+    }
+}
+
+/// Classifies residues by Ramachandran region, then demotes helical runs
+/// shorter than [`MIN_HELIX_LENGTH`] to turns and extended runs shorter than
+/// [`MIN_STRAND_LENGTH`] to coil. Strand pairing is not checked, so a lone
+/// extended segment counts as strand.
+pub fn assign_from_torsions(residues: &[Residue]) -> Vec<SecondaryStructure> {
+    let mut assignment: Vec<_> = residues
+        .iter()
+        .map(|residue| ramachandran_region(residue.phi, residue.psi))
+        .collect();
+    let mut start = 0;
+    while start < assignment.len() {
+        let class = assignment[start];
+        let end = assignment[start..]
+            .iter()
+            .position(|other| *other != class)
+            .map_or(assignment.len(), |offset| start + offset);
+        let demoted = match class {
+            SecondaryStructure::Helix if end - start < MIN_HELIX_LENGTH => {
+                Some(SecondaryStructure::Turn)
+            }
+            SecondaryStructure::Strand if end - start < MIN_STRAND_LENGTH => {
+                Some(SecondaryStructure::Coil)
+            }
+            _ => None,
+        };
+        if let Some(demoted) = demoted {
+            assignment[start..end].fill(demoted);
+        }
+        start = end;
+    }
+    assignment
+}
+
+/// DSSP-like assignment from backbone hydrogen bonds.
+///
+/// Two consecutive i→i+4 turns start an α-helix (`Helix`), residues in a
+/// parallel or antiparallel bridge are `Strand`, and residues inside any
+/// other i→i+3, i+4 or i+5 turn are `Turn`. 3₁₀ and π helices are reported as
+/// turns.
+pub fn assign_from_backbone(backbone: &[BackboneAtoms]) -> Vec<SecondaryStructure> {
+    let len = backbone.len();
+    let hbonds = HydrogenBonds::new(backbone);
+    let mut assignment = vec![SecondaryStructure::Coil; len];
+
+    for n in 3..=5 {
+        for i in 0..len.saturating_sub(n) {
+            if hbonds.bonded(i, i + n) {
+                for class in &mut assignment[i + 1..i + n] {
+                    *class = SecondaryStructure::Turn;
+                }
+            }
+        }
+    }
+    for i in 0..len {
+        for j in i + 3..len {
+            if hbonds.bridged(i, j) {
+                assignment[i] = SecondaryStructure::Strand;
+                assignment[j] = SecondaryStructure::Strand;
+            }
+        }
+    }
+    for i in 1..len.saturating_sub(4) {
+        if hbonds.bonded(i - 1, i + 3) && hbonds.bonded(i, i + 4) {
+            assignment[i..i + 4].fill(SecondaryStructure::Helix);
+        }
+    }
+    assignment
+}
+
+/// Backbone hydrogen-bond classification of `chain` built from its torsions;
+/// see [`assign_from_backbone`].
+pub fn assign_from_chain_backbone(chain: &PeptideChain) -> Vec<SecondaryStructure> {
+    assign_from_backbone(&chain.backbone())
+}
+
+/// Hydrogen bonds from the C=O of one residue to the N–H of another.
+struct HydrogenBonds {
+    atoms: Vec<BackboneAtoms>,
+    oxygens: Vec<Option<[f64; 3]>>,
+    hydrogens: Vec<Option<[f64; 3]>>,
+}
+
+impl HydrogenBonds {
+    fn new(backbone: &[BackboneAtoms]) -> Self {
+        // The carbonyl O lies in the peptide plane, bisecting the external
+        // angle at C; DSSP places the amide H opposite the previous C=O.
+        let oxygens: Vec<_> = (0..backbone.len())
+            .map(|i| {
+                let next = backbone.get(i + 1)?;
+                let atoms = backbone[i];
+                let direction = add(
+                    unit(subtract(atoms.c, atoms.ca))?,
+                    unit(subtract(atoms.c, next.n))?,
+                );
+                Some(add(atoms.c, scale(unit(direction)?, C_O_LENGTH)))
+            })
+            .collect();
+        let hydrogens = (0..backbone.len())
+            .map(|i| {
+                let previous = backbone.get(i.checked_sub(1)?)?;
+                let carbonyl = unit(subtract(previous.c, oxygens[i - 1]?))?;
+                Some(add(backbone[i].n, scale(carbonyl, N_H_LENGTH)))
+            })
+            .collect();
+        Self {
+            atoms: backbone.to_vec(),
+            oxygens,
+            hydrogens,
+        }
+    }
+
+    /// Electrostatic DSSP energy of the bond C=O(`carbonyl`)···H–N(`amide`).
+    fn energy(&self, carbonyl: usize, amide: usize) -> Option<f64> {
+        let (c, o) = (self.atoms[carbonyl].c, self.oxygens[carbonyl]?);
+        let (n, h) = (self.atoms[amide].n, self.hydrogens[amide]?);
+        let inverse = |a, b| 1.0 / norm(subtract(a, b)).max(0.5);
+        Some(HBOND_COUPLING * (inverse(o, n) + inverse(c, h) - inverse(o, h) - inverse(c, n)))
+    }
+
+    /// Whether residue `carbonyl`'s C=O accepts a hydrogen bond from residue
+    /// `amide`'s N–H.
+    fn bonded(&self, carbonyl: usize, amide: usize) -> bool {
+        carbonyl < self.atoms.len()
+            && amide < self.atoms.len()
+            && carbonyl.abs_diff(amide) > 1
+            && self
+                .energy(carbonyl, amide)
+                .is_some_and(|energy| energy < HBOND_ENERGY_CUTOFF)
+    }
+
+    /// Parallel or antiparallel bridge between residues `i` and `j`.
+    fn bridged(&self, i: usize, j: usize) -> bool {
+        let bond = |a: Option<usize>, b: Option<usize>| match (a, b) {
+            (Some(a), Some(b)) => self.bonded(a, b),
+            _ => false,
+        };
+        let (before, after) = (|k: usize| k.checked_sub(1), |k: usize| Some(k + 1));
+        let parallel = (bond(before(i), Some(j)) && bond(Some(j), after(i)))
+            || (bond(before(j), Some(i)) && bond(Some(i), after(j)));
+        let antiparallel = (bond(Some(i), Some(j)) && bond(Some(j), Some(i)))
+            || (bond(before(i), after(j)) && bond(before(j), after(i)));
+        parallel || antiparallel
+    }
+}
+
+fn wrap_degrees(angle: f64) -> f64 {
+    let wrapped = angle.rem_euclid(360.0);
+    if wrapped > 180.0 {
+        wrapped - 360.0
+    } else {
+        wrapped
+    }
+}
+
+fn subtract(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn add(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn scale(v: [f64; 3], factor: f64) -> [f64; 3] {
+    [v[0] * factor, v[1] * factor, v[2] * factor]
+}
+
+fn norm(v: [f64; 3]) -> f64 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
+fn unit(v: [f64; 3]) -> Option<[f64; 3]> {
+    let length = norm(v);
+    (length > 1e-9).then(|| scale(v, 1.0 / length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AminoAcid, StartingConformation};
+
+    fn chain(conformation: StartingConformation) -> PeptideChain {
+        PeptideChain::from_sequence(&[AminoAcid::Alanine; 12], conformation)
+    }
+
+    #[test]
+    fn torsion_classifier_reads_ramachandran_regions() {
+        let helix = assign_from_torsions(chain(StartingConformation::Helix).residues());
+        assert_eq!(assignment_codes(&helix), "HHHHHHHHHHHH");
+        let strand = assign_from_torsions(chain(StartingConformation::Extended).residues());
+        assert_eq!(assignment_codes(&strand), "EEEEEEEEEEEE");
+        assert_eq!(ramachandran_region(60.0, 40.0), SecondaryStructure::Turn);
+        assert_eq!(ramachandran_region(-30.0, 480.0), SecondaryStructure::Coil);
+
+        // A helix broken by an extended residue keeps only the long segment;
+        // the three-residue tail becomes a turn and the lone strand is coil.
+        let mut residues = chain(StartingConformation::Helix).residues().to_vec();
+        residues[8] = residues[8].clone().with_torsions(-139.0, 135.0, 180.0);
+        let broken = assign_from_torsions(&residues);
+        assert_eq!(assignment_codes(&broken), "HHHHHHHHCTTT");
+        let fractions = SecondaryStructureFractions::from_assignment(&broken);
+        assert!((fractions.helix - 8.0 / 12.0).abs() < 1e-12);
+        assert!((fractions.turn - 0.25).abs() < 1e-12);
+        let total = fractions.helix + fractions.strand + fractions.turn + fractions.coil;
+        assert!((total - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn hydrogen_bond_classifier_finds_helix_but_no_lone_strand() {
+        let helix = assign_from_chain_backbone(&chain(StartingConformation::Helix));
+        let codes = assignment_codes(&helix);
+        // The first residue and the last turn lack i→i+4 partners.
+        assert!(codes.starts_with('C'), "{codes}");
+        assert_eq!(&codes[1..8], "HHHHHHH", "{codes}");
+        assert!(
+            SecondaryStructureFractions::from_assignment(&helix).helix > 0.6,
+            "{codes}"
+        );
+
+        // An isolated extended chain has no partner to pair with.
+        let extended = assign_from_chain_backbone(&chain(StartingConformation::Extended));
+        assert_eq!(assignment_codes(&extended), "CCCCCCCCCCCC");
+    }
+}
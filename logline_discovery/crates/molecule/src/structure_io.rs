@@ -0,0 +1,582 @@
+//! PDB and mmCIF import/export for [`PeptideChain`].
+//!
+//! Import keeps one bead per residue at the Cα atom. When the N and C
+//! backbone atoms are present as well, phi/psi/omega are measured from them
+//! so torsion-based moves start from the deposited conformation.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::aminoacid::{AminoAcid, ResidueId};
+use crate::chain::{PeptideChain, Residue};
+use crate::geometry;
+
+#[derive(Debug, Error)]
+pub enum StructureError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("line {line}: {message}")]
+    Malformed { line: usize, message: String },
+    #[error("line {line}: unknown residue name {name}")]
+    UnknownResidue { line: usize, name: String },
+    #[error("no Cα atoms found{}", .chain.as_ref().map(|id| format!(" for chain {id}")).unwrap_or_default())]
+    NoResidues { chain: Option<String> },
+    #[error("unsupported structure format: {0}")]
+    UnsupportedFormat(String),
+    #[error("trajectory frame {frame}: {message}")]
+    Frame { frame: usize, message: String },
+}
+
+/// Supported coordinate file formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StructureFormat {
+    Pdb,
+    MmCif,
+}
+
+impl StructureFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "pdb" | "ent" => Some(Self::Pdb),
+            "cif" | "mmcif" => Some(Self::MmCif),
+            _ => None,
+        }
+    }
+}
+
+/// Reads a chain from a `.pdb` or `.cif` file. `chain_id` selects a chain;
+/// the first protein chain in the file is used otherwise.
+pub fn read_structure(path: &Path, chain_id: Option<&str>) -> Result<PeptideChain, StructureError> {
+    let format = StructureFormat::from_path(path)
+        .ok_or_else(|| StructureError::UnsupportedFormat(path.display().to_string()))?;
+    let text = fs::read_to_string(path)?;
+    match format {
+        StructureFormat::Pdb => parse_pdb(&text, chain_id),
+        StructureFormat::MmCif => parse_mmcif(&text, chain_id),
+    }
+}
+
+/// Writes a chain as `.pdb` or `.cif`, chosen by the file extension.
+pub fn write_structure(path: &Path, chain: &PeptideChain) -> Result<(), StructureError> {
+    let format = StructureFormat::from_path(path)
+        .ok_or_else(|| StructureError::UnsupportedFormat(path.display().to_string()))?;
+    let text = match format {
+        StructureFormat::Pdb => write_pdb(chain),
+        StructureFormat::MmCif => write_mmcif(chain),
+    };
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Parses ATOM/HETATM records from the first model of a PDB file.
+pub fn parse_pdb(text: &str, chain_id: Option<&str>) -> Result<PeptideChain, StructureError> {
+    let mut atoms = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line_no = index + 1;
+        let record = line.get(..6).unwrap_or(line).trim_end();
+        match record {
+            "ENDMDL" => break,
+            "ATOM" | "HETATM" => {}
+            _ => continue,
+        }
+        let field = |start: usize, end: usize| line.get(start..end.min(line.len())).unwrap_or("");
+        let alt_loc = field(16, 17).trim();
+        if !alt_loc.is_empty() && alt_loc != "A" {
+            continue;
+        }
+        let text = |start: usize, end: usize| field(start, end).trim().to_string();
+        atoms.push(AtomRecord {
+            line: line_no,
+            hetero: record == "HETATM",
+            atom_name: text(12, 16),
+            residue_name: text(17, 20),
+            chain_id: text(21, 22),
+            sequence: text(22, 26),
+            insertion: text(26, 27),
+            coordinates: [text(30, 38), text(38, 46), text(46, 54)],
+        });
+    }
+    build_chain(atoms, chain_id)
+}
+
+/// Parses the `_atom_site` loop from the first model of an mmCIF file.
+pub fn parse_mmcif(text: &str, chain_id: Option<&str>) -> Result<PeptideChain, StructureError> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut cursor = 0;
+    while cursor < lines.len() {
+        if lines[cursor].trim() == "loop_"
+            && lines
+                .get(cursor + 1)
+                .is_some_and(|next| next.trim_start().starts_with("_atom_site."))
+        {
+            break;
+        }
+        cursor += 1;
+    }
+    if cursor >= lines.len() {
+        return Err(StructureError::NoResidues {
+            chain: chain_id.map(str::to_string),
+        });
+    }
+    cursor += 1;
+
+    let mut columns = Vec::new();
+    while let Some(line) = lines.get(cursor) {
+        let trimmed = line.trim();
+        match trimmed.strip_prefix("_atom_site.") {
+            Some(name) => columns.push(name.to_string()),
+            None => break,
+        }
+        cursor += 1;
+    }
+    let column = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| columns.iter().position(|column| column == name))
+    };
+    let missing = |name: &str| StructureError::Malformed {
+        line: cursor,
+        message: format!("_atom_site loop lacks {name}"),
+    };
+    let group_col = column(&["group_PDB"]);
+    let atom_col = column(&["auth_atom_id", "label_atom_id"]).ok_or_else(|| missing("atom_id"))?;
+    let residue_col =
+        column(&["auth_comp_id", "label_comp_id"]).ok_or_else(|| missing("comp_id"))?;
+    let chain_col = column(&["auth_asym_id", "label_asym_id"]).ok_or_else(|| missing("asym_id"))?;
+    let seq_col = column(&["auth_seq_id", "label_seq_id"]).ok_or_else(|| missing("seq_id"))?;
+    let x_col = column(&["Cartn_x"]).ok_or_else(|| missing("Cartn_x"))?;
+    let y_col = column(&["Cartn_y"]).ok_or_else(|| missing("Cartn_y"))?;
+    let z_col = column(&["Cartn_z"]).ok_or_else(|| missing("Cartn_z"))?;
+    let alt_col = column(&["label_alt_id"]);
+    let insertion_col = column(&["pdbx_PDB_ins_code"]);
+    let model_col = column(&["pdbx_PDB_model_num"]);
+
+    let mut atoms = Vec::new();
+    let mut first_model: Option<String> = None;
+    for (offset, line) in lines[cursor..].iter().enumerate() {
+        let line_no = cursor + offset + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed == "#" || trimmed == "loop_" || trimmed.starts_with('_') {
+            break;
+        }
+        let values = tokenize_cif_row(trimmed);
+        if values.len() < columns.len() {
+            return Err(StructureError::Malformed {
+                line: line_no,
+                message: format!("expected {} values, found {}", columns.len(), values.len()),
+            });
+        }
+        let value = |col: usize| cif_value(&values[col]);
+        if let Some(col) = model_col {
+            let model = value(col).to_string();
+            match &first_model {
+                Some(first) if *first != model => break,
+                Some(_) => {}
+                None => first_model = Some(model),
+            }
+        }
+        if let Some(col) = alt_col {
+            let alt = value(col);
+            if !alt.is_empty() && alt != "A" {
+                continue;
+            }
+        }
+        atoms.push(AtomRecord {
+            line: line_no,
+            hetero: group_col.is_some_and(|col| value(col) == "HETATM"),
+            atom_name: value(atom_col).to_string(),
+            residue_name: value(residue_col).to_string(),
+            chain_id: value(chain_col).to_string(),
+            sequence: value(seq_col).to_string(),
+            insertion: insertion_col
+                .map(|col| value(col).to_string())
+                .unwrap_or_default(),
+            coordinates: [x_col, y_col, z_col].map(|col| value(col).to_string()),
+        });
+    }
+    build_chain(atoms, chain_id)
+}
+
+/// Renders the chain as a Cα-only PDB file.
+pub fn write_pdb(chain: &PeptideChain) -> String {
+    let mut out = String::new();
+    out.push_str("REMARK   1 GENERATED BY LOGLINE FOLDING ENGINE (CA TRACE)\n");
+    push_pdb_atoms(&mut out, chain, &chain.positions());
+    out.push_str("END\n");
+    out
+}
+
+/// Appends Cα ATOM records for `chain`'s residues at `positions`, followed by
+/// a TER record.
+pub(crate) fn push_pdb_atoms(out: &mut String, chain: &PeptideChain, positions: &[[f64; 3]]) {
+    for (serial, (residue, [x, y, z])) in chain.residues().iter().zip(positions).enumerate() {
+        out.push_str(&format!(
+            "ATOM  {:>5}  CA  {:>3} A{:>4}    {:>8.3}{:>8.3}{:>8.3}{:>6.2}{:>6.2}           C\n",
+            (serial + 1) % 100_000,
+            residue.amino_acid.three_letter(),
+            residue.id.0 % 10_000,
+            x,
+            y,
+            z,
+            1.0,
+            0.0,
+        ));
+    }
+    if let Some(last) = chain.residues().last() {
+        out.push_str(&format!(
+            "TER   {:>5}      {:>3} A{:>4}\n",
+            (chain.len() + 1) % 100_000,
+            last.amino_acid.three_letter(),
+            last.id.0 % 10_000,
+        ));
+    }
+}
+
+/// Renders the chain as a Cα-only mmCIF file.
+pub fn write_mmcif(chain: &PeptideChain) -> String {
+    let mut out = String::new();
+    out.push_str("data_logline_fold\n#\nloop_\n");
+    for column in [
+        "group_PDB",
+        "id",
+        "type_symbol",
+        "label_atom_id",
+        "label_comp_id",
+        "label_asym_id",
+        "label_seq_id",
+        "Cartn_x",
+        "Cartn_y",
+        "Cartn_z",
+        "occupancy",
+        "B_iso_or_equiv",
+        "auth_seq_id",
+        "auth_asym_id",
+        "pdbx_PDB_model_num",
+    ] {
+        out.push_str("_atom_site.");
+        out.push_str(column);
+        out.push('\n');
+    }
+    for (index, residue) in chain.residues().iter().enumerate() {
+        let [x, y, z] = residue.position();
+        out.push_str(&format!(
+            "ATOM {} C CA {} A {} {:.3} {:.3} {:.3} 1.00 0.00 {} A 1\n",
+            index + 1,
+            residue.amino_acid.three_letter(),
+            index + 1,
+            x,
+            y,
+            z,
+            residue.id.0,
+        ));
+    }
+    out.push_str("#\n");
+    out
+}
+
+/// An atom record as read. Numbers stay text until [`build_chain`] keeps
+/// the atom, so a malformed record in an unselected chain is never parsed.
+struct AtomRecord {
+    line: usize,
+    hetero: bool,
+    atom_name: String,
+    residue_name: String,
+    chain_id: String,
+    sequence: String,
+    insertion: String,
+    coordinates: [String; 3],
+}
+
+impl AtomRecord {
+    fn sequence(&self) -> Result<i64, StructureError> {
+        self.sequence
+            .parse()
+            .map_err(|_| StructureError::Malformed {
+                line: self.line,
+                message: format!("invalid residue number '{}'", self.sequence),
+            })
+    }
+
+    fn position(&self) -> Result<[f64; 3], StructureError> {
+        let mut position = [0.0; 3];
+        for ((value, text), axis) in position
+            .iter_mut()
+            .zip(&self.coordinates)
+            .zip(["x", "y", "z"])
+        {
+            *value = text.parse().map_err(|_| StructureError::Malformed {
+                line: self.line,
+                message: format!("invalid {axis} coordinate '{text}'"),
+            })?;
+        }
+        Ok(position)
+    }
+}
+
+#[derive(Default)]
+struct ResidueAtoms {
+    amino_acid: Option<AminoAcid>,
+    sequence: i64,
+    n: Option<[f64; 3]>,
+    ca: Option<[f64; 3]>,
+    c: Option<[f64; 3]>,
+}
+
+fn build_chain(
+    atoms: Vec<AtomRecord>,
+    chain_id: Option<&str>,
+) -> Result<PeptideChain, StructureError> {
+    let selected_chain = match chain_id {
+        Some(id) => Some(id.to_string()),
+        None => atoms
+            .iter()
+            .find(|atom| atom.atom_name == "CA" && !atom.hetero)
+            .map(|atom| atom.chain_id.clone()),
+    };
+
+    let mut residues: Vec<ResidueAtoms> = Vec::new();
+    let mut current_key: Option<(i64, String)> = None;
+    for atom in atoms {
+        if Some(&atom.chain_id) != selected_chain.as_ref() {
+            continue;
+        }
+        let amino_acid = AminoAcid::from_three_letter(&atom.residue_name);
+        // Ligands, waters and ions share the chain identifier; in mmCIF
+        // files without auth columns they also lack a sequence number.
+        if (atom.hetero && amino_acid.is_none()) || atom.sequence.is_empty() {
+            continue;
+        }
+        let sequence = atom.sequence()?;
+        let key = (sequence, atom.insertion.clone());
+        if current_key.as_ref() != Some(&key) {
+            current_key = Some(key);
+            residues.push(ResidueAtoms {
+                sequence,
+                ..ResidueAtoms::default()
+            });
+        }
+        let entry = residues.last_mut().expect("residue pushed above");
+        match atom.atom_name.as_str() {
+            "N" => entry.n = Some(atom.position()?),
+            "CA" => {
+                entry.amino_acid =
+                    Some(amino_acid.ok_or_else(|| StructureError::UnknownResidue {
+                        line: atom.line,
+                        name: atom.residue_name.clone(),
+                    })?);
+                entry.ca = Some(atom.position()?);
+            }
+            "C" => entry.c = Some(atom.position()?),
+            _ => {}
+        }
+    }
+    residues.retain(|residue| residue.ca.is_some());
+    if residues.is_empty() {
+        return Err(StructureError::NoResidues {
+            chain: selected_chain,
+        });
+    }
+
+    // Keep author numbering so contracts can address residues as published;
+    // fall back to 1-based numbering when it is negative or not unique.
+    let mut seen = HashSet::new();
+    let keep_numbering = residues
+        .iter()
+        .all(|residue| residue.sequence >= 0 && seen.insert(residue.sequence));
+
+    let mut chain_residues = Vec::with_capacity(residues.len());
+    for (index, entry) in residues.iter().enumerate() {
+        let id = if keep_numbering {
+            entry.sequence as usize
+        } else {
+            index + 1
+        };
+        let mut residue = Residue::new(
+            ResidueId(id),
+            entry.amino_acid.expect("Cα residues carry an amino acid"),
+        )
+        .with_position(entry.ca.expect("retained residues have Cα"));
+        let previous = index.checked_sub(1).and_then(|i| residues.get(i));
+        let next = residues.get(index + 1);
+        if let (Some(prev), Some(n), Some(ca), Some(c)) =
+            (previous.and_then(|prev| prev.c), entry.n, entry.ca, entry.c)
+        {
+            residue.phi = geometry::dihedral(prev, n, ca, c).to_degrees();
+        }
+        if let (Some(n), Some(ca), Some(c), Some(next_n)) =
+            (entry.n, entry.ca, entry.c, next.and_then(|next| next.n))
+        {
+            residue.psi = geometry::dihedral(n, ca, c, next_n).to_degrees();
+        }
+        if let (Some(ca), Some(c), Some(next_n), Some(next_ca)) = (
+            entry.ca,
+            entry.c,
+            next.and_then(|next| next.n),
+            next.and_then(|next| next.ca),
+        ) {
+            residue.omega = geometry::dihedral(ca, c, next_n, next_ca).to_degrees();
+        }
+        chain_residues.push(residue);
+    }
+    Ok(PeptideChain::new(chain_residues))
+}
+
+fn tokenize_cif_row(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&ch) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut token = String::new();
+        if ch == '\'' || ch == '"' {
+            chars.next();
+            token.push(ch);
+            for next in chars.by_ref() {
+                token.push(next);
+                if next == ch {
+                    break;
+                }
+            }
+        } else {
+            while let Some(&next) = chars.peek() {
+                if next.is_whitespace() {
+                    break;
+                }
+                token.push(next);
+                chars.next();
+            }
+        }
+        tokens.push(token);
+    }
+    tokens
+}
+
+fn cif_value(raw: &str) -> &str {
+    let unquoted = raw.trim_matches(|c| c == '\'' || c == '"');
+    match unquoted {
+        "." | "?" => "",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHIGNOLIN_FRAGMENT: &str = "\
+HEADER    DE NOVO PROTEIN
+ATOM      1  N   GLY A   1      -7.252   2.397   2.012  1.00  0.00           N
+ATOM      2  CA  GLY A   1      -6.148   3.263   1.638  1.00  0.00           C
+ATOM      3  C   GLY A   1      -4.832   2.525   1.550  1.00  0.00           C
+ATOM      4  N   TYR A   2      -4.841   1.202   1.591  1.00  0.00           N
+ATOM      5  CA  TYR A   2      -3.653   0.394   1.528  1.00  0.00           C
+ATOM      6  CB  TYR A   2      -3.804  -0.856   2.402  1.00  0.00           C
+ATOM      7  C   TYR A   2      -3.327   0.011   0.093  1.00  0.00           C
+ATOM      8  N   ASP A   3      -2.054  -0.202  -0.205  1.00  0.00           N
+ATOM      9  CA  ASP A   3      -1.603  -0.563  -1.541  1.00  0.00           C
+ATOM     10  C   ASP A   3      -0.115  -0.845  -1.569  1.00  0.00           C
+HETATM   11  O   HOH A 101      10.000  10.000  10.000  1.00  0.00           O
+ENDMDL
+ATOM     12  CA  GLY A   1      99.000  99.000  99.000  1.00  0.00           C
+";
+
+    #[test]
+    fn parses_pdb_backbone_and_torsions() {
+        let chain = parse_pdb(CHIGNOLIN_FRAGMENT, None).expect("pdb parses");
+        assert_eq!(chain.len(), 3);
+        let residues = chain.residues();
+        assert_eq!(residues[1].id, ResidueId(2));
+        assert_eq!(residues[1].amino_acid, AminoAcid::Tyrosine);
+        assert_eq!(residues[2].position(), [-1.603, -0.563, -1.541]);
+        assert!(residues[1].phi.abs() > 1.0);
+        assert!(residues[1].psi.abs() > 1.0);
+        assert!(residues[0].omega.abs() > 90.0);
+    }
+
+    #[test]
+    fn pdb_round_trip_preserves_sequence_and_positions() {
+        let chain = parse_pdb(CHIGNOLIN_FRAGMENT, None).expect("pdb parses");
+        let reparsed = parse_pdb(&write_pdb(&chain), None).expect("written pdb parses");
+        assert_eq!(reparsed.len(), chain.len());
+        for (left, right) in chain.residues().iter().zip(reparsed.residues()) {
+            assert_eq!(left.id, right.id);
+            assert_eq!(left.amino_acid, right.amino_acid);
+            assert_eq!(left.position(), right.position());
+        }
+
+        let from_cif = parse_mmcif(&write_mmcif(&chain), None).expect("written cif parses");
+        assert_eq!(from_cif.len(), chain.len());
+        assert_eq!(from_cif.residues()[2].id, ResidueId(3));
+        assert_eq!(from_cif.residues()[2].position(), [-1.603, -0.563, -1.541]);
+    }
+
+    #[test]
+    fn parses_mmcif_atom_site_loop() {
+        let text = "\
+data_test
+#
+loop_
+_atom_site.group_PDB
+_atom_site.id
+_atom_site.label_atom_id
+_atom_site.label_alt_id
+_atom_site.label_comp_id
+_atom_site.label_asym_id
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+_atom_site.auth_seq_id
+_atom_site.auth_asym_id
+_atom_site.pdbx_PDB_model_num
+ATOM 1 CA . LEU A 1.0 2.0 3.0 512 B 1
+ATOM 2 CA B LEU A 9.0 9.0 9.0 512 B 1
+ATOM 3 CA . ILE A 4.0 5.0 6.0 513 B 1
+ATOM 4 CA . LYS A 7.0 8.0 9.0 514 C 1
+ATOM 5 CA . ILE A 0.0 0.0 0.0 513 B 2
+#
+";
+        let chain = parse_mmcif(text, Some("B")).expect("cif parses");
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain.residues()[0].id, ResidueId(512));
+        assert_eq!(chain.residues()[0].position(), [1.0, 2.0, 3.0]);
+        assert_eq!(chain.residues()[1].amino_acid, AminoAcid::Isoleucine);
+
+        let err = parse_mmcif(text, Some("Z")).expect_err("missing chain");
+        assert!(matches!(err, StructureError::NoResidues { .. }));
+    }
+
+    #[test]
+    fn mmcif_skips_waters_and_unselected_chains() {
+        let text = "\
+data_test
+loop_
+_atom_site.group_PDB
+_atom_site.label_atom_id
+_atom_site.label_comp_id
+_atom_site.label_asym_id
+_atom_site.label_seq_id
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+ATOM CA GLY A 1 1.0 2.0 3.0
+ATOM CA ALA A 2 4.0 5.0 6.0
+HETATM O HOH A . 7.0 8.0 9.0
+ATOM CA GLY B 1 bad 0.0 0.0
+#
+";
+        let chain = parse_mmcif(text, Some("A")).expect("waters and chain B are skipped");
+        assert_eq!(chain.len(), 2);
+
+        let err = parse_mmcif(text, Some("B")).expect_err("chain B has a bad coordinate");
+        assert!(err.to_string().contains("invalid x coordinate 'bad'"));
+    }
+}
@@ -0,0 +1,396 @@
+//! Structural descriptors of a bead chain: Kabsch-aligned RMSD, radius of
+//! gyration, end-to-end distance, contact maps and the native-contact
+//! fraction Q.
+//!
+//! Superposition uses Horn's quaternion method: the optimal rotation is the
+//! eigenvector of the largest eigenvalue of a symmetric 4×4 key matrix,
+//! which avoids the reflection handling an SVD-based Kabsch fit needs.
+
+use serde::{Deserialize, Serialize};
+
+use crate::chain::PeptideChain;
+use crate::secondary_structure::{assign_from_torsions, SecondaryStructureFractions};
+
+/// Cα–Cα distance (Å) within which two residues are in contact.
+pub const CONTACT_CUTOFF: f64 = 8.0;
+/// Smallest sequence separation counted as a contact; closer pairs touch
+/// through the backbone alone.
+pub const CONTACT_MIN_SEPARATION: usize = 3;
+/// Sharpness (Å⁻¹) of the switch deciding whether a native contact is formed.
+const Q_BETA: f64 = 5.0;
+/// A native contact counts as formed up to this multiple of its native length.
+const Q_TOLERANCE: f64 = 1.2;
+
+/// Residue pairs `(i, j)`, `i < j`, within [`CONTACT_CUTOFF`] and at least
+/// [`CONTACT_MIN_SEPARATION`] apart in sequence.
+pub fn contact_map(positions: &[[f64; 3]]) -> Vec<(usize, usize)> {
+    let mut contacts = Vec::new();
+    for i in 0..positions.len() {
+        for j in i + CONTACT_MIN_SEPARATION..positions.len() {
+            if distance(positions[i], positions[j]) <= CONTACT_CUTOFF {
+                contacts.push((i, j));
+            }
+        }
+    }
+    contacts
+}
+
+pub fn radius_of_gyration(positions: &[[f64; 3]]) -> f64 {
+    if positions.is_empty() {
+        return 0.0;
+    }
+    let center = centroid(positions);
+    let sum: f64 = positions
+        .iter()
+        .map(|p| {
+            let d = subtract(*p, center);
+            dot(d, d)
+        })
+        .sum();
+    (sum / positions.len() as f64).sqrt()
+}
+
+pub fn end_to_end_distance(positions: &[[f64; 3]]) -> f64 {
+    match (positions.first(), positions.last()) {
+        (Some(first), Some(last)) => distance(*first, *last),
+        _ => 0.0,
+    }
+}
+
+/// Rotation `R` minimising `Σ|(x − x̄) − R(y − ȳ)|²` for `current` x and
+/// `reference` y, with the RMSD it leaves. Both slices must be the same length.
+pub fn kabsch_superposition(reference: &[[f64; 3]], current: &[[f64; 3]]) -> ([[f64; 3]; 3], f64) {
+    let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    if reference.is_empty() || reference.len() != current.len() {
+        return (identity, 0.0);
+    }
+    let (c_ref, c_cur) = (centroid(reference), centroid(current));
+    let mut s = [[0.0; 3]; 3];
+    let mut squares = 0.0;
+    for (y, x) in reference.iter().zip(current) {
+        let (y, x) = (subtract(*y, c_ref), subtract(*x, c_cur));
+        squares += dot(x, x) + dot(y, y);
+        for a in 0..3 {
+            for b in 0..3 {
+                s[a][b] += y[a] * x[b];
+            }
+        }
+    }
+    let key = [
+        [
+            s[0][0] + s[1][1] + s[2][2],
+            s[1][2] - s[2][1],
+            s[2][0] - s[0][2],
+            s[0][1] - s[1][0],
+        ],
+        [
+            s[1][2] - s[2][1],
+            s[0][0] - s[1][1] - s[2][2],
+            s[0][1] + s[1][0],
+            s[2][0] + s[0][2],
+        ],
+        [
+            s[2][0] - s[0][2],
+            s[0][1] + s[1][0],
+            -s[0][0] + s[1][1] - s[2][2],
+            s[1][2] + s[2][1],
+        ],
+        [
+            s[0][1] - s[1][0],
+            s[2][0] + s[0][2],
+            s[1][2] + s[2][1],
+            -s[0][0] - s[1][1] + s[2][2],
+        ],
+    ];
+    let (eigenvalue, [q0, q1, q2, q3]) = largest_eigenpair(key);
+    let rotation = [
+        [
+            q0 * q0 + q1 * q1 - q2 * q2 - q3 * q3,
+            2.0 * (q1 * q2 - q0 * q3),
+            2.0 * (q1 * q3 + q0 * q2),
+        ],
+        [
+            2.0 * (q1 * q2 + q0 * q3),
+            q0 * q0 - q1 * q1 + q2 * q2 - q3 * q3,
+            2.0 * (q2 * q3 - q0 * q1),
+        ],
+        [
+            2.0 * (q1 * q3 - q0 * q2),
+            2.0 * (q2 * q3 + q0 * q1),
+            q0 * q0 - q1 * q1 - q2 * q2 + q3 * q3,
+        ],
+    ];
+    let msd = (squares - 2.0 * eigenvalue).max(0.0) / reference.len() as f64;
+    (rotation, msd.sqrt())
+}
+
+/// RMSD between `reference` and `current` after optimal superposition.
+pub fn kabsch_rmsd(reference: &[[f64; 3]], current: &[[f64; 3]]) -> f64 {
+    kabsch_superposition(reference, current).1
+}
+
+/// Snapshot of the structural descriptors at one step.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StructureMetrics {
+    /// Kabsch-aligned RMSD (Å) to the reference.
+    pub rmsd: f64,
+    pub radius_of_gyration: f64,
+    pub end_to_end_distance: f64,
+    /// Fraction of the reference's contacts formed, in `[0, 1]`; 1 when the
+    /// reference has none.
+    pub native_contact_fraction: f64,
+    /// Ramachandran secondary-structure content; see
+    /// [`assign_from_torsions`].
+    pub secondary_structure: SecondaryStructureFractions,
+}
+
+/// Reference conformation that [`StructureMetrics`] are measured against.
+/// Residues are matched to the measured chain by index.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StructureReference {
+    /// Chain indices the reference covers.
+    residues: Vec<usize>,
+    positions: Vec<[f64; 3]>,
+    /// Native contacts as chain-index pairs with their native length.
+    native_contacts: Vec<(usize, usize, f64)>,
+}
+
+impl StructureReference {
+    /// Reference covering every residue of `chain`.
+    pub fn new(chain: &PeptideChain) -> Self {
+        let positions = chain.positions();
+        Self::from_positions((0..positions.len()).collect(), positions)
+    }
+
+    /// Reference for `chain` taken from `structure`, matching residues by
+    /// number; `None` when they share no residues.
+    pub fn matching(chain: &PeptideChain, structure: &PeptideChain) -> Option<Self> {
+        let (residues, positions): (Vec<usize>, Vec<[f64; 3]>) = structure
+            .residues()
+            .iter()
+            .filter_map(|residue| Some((chain.index_of(residue.id)?, residue.position())))
+            .unzip();
+        (!residues.is_empty()).then(|| Self::from_positions(residues, positions))
+    }
+
+    fn from_positions(residues: Vec<usize>, positions: Vec<[f64; 3]>) -> Self {
+        let native_contacts = contact_map(&positions)
+            .into_iter()
+            .map(|(i, j)| {
+                (
+                    residues[i],
+                    residues[j],
+                    distance(positions[i], positions[j]),
+                )
+            })
+            .collect();
+        Self {
+            residues,
+            positions,
+            native_contacts,
+        }
+    }
+
+    pub fn residues(&self) -> &[usize] {
+        &self.residues
+    }
+
+    pub fn positions(&self) -> &[[f64; 3]] {
+        &self.positions
+    }
+
+    /// Smooth Q of Best, Hummer & Eaton (2013): each native contact of
+    /// length r₀ counts `1/(1 + e^{β(r − 1.2·r₀)})`.
+    pub fn native_contact_fraction(&self, positions: &[[f64; 3]]) -> f64 {
+        if self.native_contacts.is_empty() {
+            return 1.0;
+        }
+        let formed: f64 = self
+            .native_contacts
+            .iter()
+            .filter_map(|(i, j, native)| {
+                let r = distance(*positions.get(*i)?, *positions.get(*j)?);
+                Some(1.0 / (1.0 + (Q_BETA * (r - Q_TOLERANCE * native)).exp()))
+            })
+            .sum();
+        formed / self.native_contacts.len() as f64
+    }
+
+    pub fn measure(&self, chain: &PeptideChain) -> StructureMetrics {
+        let positions = chain.positions();
+        let matched: Vec<[f64; 3]> = self
+            .residues
+            .iter()
+            .filter_map(|index| positions.get(*index).copied())
+            .collect();
+        StructureMetrics {
+            rmsd: kabsch_rmsd(&self.positions, &matched),
+            radius_of_gyration: radius_of_gyration(&positions),
+            end_to_end_distance: end_to_end_distance(&positions),
+            native_contact_fraction: self.native_contact_fraction(&positions),
+            secondary_structure: SecondaryStructureFractions::from_assignment(
+                &assign_from_torsions(chain.residues()),
+            ),
+        }
+    }
+}
+
+/// Largest eigenvalue of a symmetric 4×4 matrix and its unit eigenvector,
+/// by cyclic Jacobi rotations.
+fn largest_eigenpair(mut a: [[f64; 4]; 4]) -> (f64, [f64; 4]) {
+    let mut v = [[0.0; 4]; 4];
+    for (i, row) in v.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+    for _ in 0..50 {
+        let off: f64 = (0..4)
+            .flat_map(|p| (p + 1..4).map(move |q| (p, q)))
+            .map(|(p, q)| a[p][q] * a[p][q])
+            .sum();
+        if off < 1e-24 {
+            break;
+        }
+        for p in 0..3 {
+            for q in p + 1..4 {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (row_p, row_q) = (a[p], a[q]);
+                a[p] = std::array::from_fn(|k| c * row_p[k] - s * row_q[k]);
+                a[q] = std::array::from_fn(|k| s * row_p[k] + c * row_q[k]);
+                for row in v.iter_mut() {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    let best = (0..4)
+        .max_by(|i, j| a[*i][*i].total_cmp(&a[*j][*j]))
+        .unwrap_or(0);
+    (
+        a[best][best],
+        [v[0][best], v[1][best], v[2][best], v[3][best]],
+    )
+}
+
+fn centroid(points: &[[f64; 3]]) -> [f64; 3] {
+    let n = points.len().max(1) as f64;
+    let mut sum = [0.0; 3];
+    for p in points {
+        for axis in 0..3 {
+            sum[axis] += p[axis] / n;
+        }
+    }
+    sum
+}
+
+fn subtract(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+    let d = subtract(a, b);
+    dot(d, d).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AminoAcid, StartingConformation};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn rotate(points: &[[f64; 3]], axis: [f64; 3], angle: f64, shift: [f64; 3]) -> Vec<[f64; 3]> {
+        let norm = dot(axis, axis).sqrt();
+        let k = [axis[0] / norm, axis[1] / norm, axis[2] / norm];
+        let (sin, cos) = angle.sin_cos();
+        points
+            .iter()
+            .map(|v| {
+                let cross = [
+                    k[1] * v[2] - k[2] * v[1],
+                    k[2] * v[0] - k[0] * v[2],
+                    k[0] * v[1] - k[1] * v[0],
+                ];
+                let along = dot(k, *v) * (1.0 - cos);
+                [0, 1, 2].map(|a| v[a] * cos + cross[a] * sin + k[a] * along + shift[a])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn kabsch_removes_rigid_motion() {
+        let mut rng = StdRng::seed_from_u64(16);
+        for _ in 0..50 {
+            let reference: Vec<[f64; 3]> = (0..12)
+                .map(|_| [(); 3].map(|_| rng.gen_range(-10.0..10.0)))
+                .collect();
+            let axis = [(); 3].map(|_| rng.gen_range(-1.0..1.0));
+            let angle = rng.gen_range(-3.1..3.1);
+            let moved = rotate(&reference, axis, angle, [4.0, -2.0, 7.5]);
+            let (rotation, rmsd) = kabsch_superposition(&reference, &moved);
+            assert!(rmsd < 1e-6, "{rmsd}");
+            // The rotation maps the centred reference onto the centred copy.
+            let (c_ref, c_cur) = (centroid(&reference), centroid(&moved));
+            for (y, x) in reference.iter().zip(&moved) {
+                let y = subtract(*y, c_ref);
+                let fitted = [0, 1, 2].map(|a| dot(rotation[a], y));
+                assert!(distance(fitted, subtract(*x, c_cur)) < 1e-6);
+            }
+
+            // With noise, the fit can only beat the centroid-only RMSD.
+            let noisy: Vec<[f64; 3]> = moved
+                .iter()
+                .map(|p| [0, 1, 2].map(|a| p[a] + rng.gen_range(-0.2..0.2)))
+                .collect();
+            let fitted = kabsch_rmsd(&reference, &noisy);
+            let centred = {
+                let (c_ref, c_cur) = (centroid(&reference), centroid(&noisy));
+                let sum: f64 = reference
+                    .iter()
+                    .zip(&noisy)
+                    .map(|(y, x)| distance(subtract(*x, c_cur), subtract(*y, c_ref)).powi(2))
+                    .sum();
+                (sum / reference.len() as f64).sqrt()
+            };
+            assert!(fitted > 0.0 && fitted <= centred + 1e-9);
+            assert!(fitted < 0.3);
+        }
+    }
+
+    #[test]
+    fn helix_metrics_against_extended_reference() {
+        let sequence = [AminoAcid::Alanine; 12];
+        let helix = PeptideChain::from_sequence(&sequence, StartingConformation::Helix);
+        let extended = PeptideChain::from_sequence(&sequence, StartingConformation::Extended);
+
+        let native = StructureReference::new(&helix).measure(&helix);
+        assert!(native.rmsd < 1e-6);
+        assert!((native.native_contact_fraction - 1.0).abs() < 0.01);
+        let contacts = contact_map(&helix.positions());
+        assert!(!contacts.is_empty());
+        assert!(contacts
+            .iter()
+            .all(|(i, j)| j - i >= CONTACT_MIN_SEPARATION));
+
+        let unfolded = StructureReference::new(&helix).measure(&extended);
+        assert!(unfolded.rmsd > 1.0);
+        assert!(unfolded.native_contact_fraction < 0.5);
+        assert!(unfolded.radius_of_gyration > native.radius_of_gyration);
+        assert!(unfolded.end_to_end_distance > native.end_to_end_distance);
+    }
+}
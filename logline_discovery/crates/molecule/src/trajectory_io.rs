@@ -0,0 +1,686 @@
+//! Multi-frame coordinate export for replaying folds in VMD, PyMOL or
+//! MDAnalysis.
+//!
+//! Text formats (multi-model PDB, XYZ) keep residue names and frame labels;
+//! the binary formats are the CHARMM/NAMD DCD layout (little-endian Fortran
+//! records, Å) and GROMACS XTC (big-endian XDR, nm, lossy compression to
+//! [`XTC_PRECISION`]). The XTC encoder follows the reference `xdrfile`
+//! implementation, so any reader of that library can decode it.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chain::PeptideChain;
+use crate::structure_io::{push_pdb_atoms, StructureError};
+
+/// XTC precision: coordinates are stored to 1/1000 nm.
+pub const XTC_PRECISION: f32 = 1000.0;
+const XTC_MAGIC: i32 = 1995;
+/// XTC frames with at most this many atoms are stored uncompressed.
+const XTC_MAX_UNCOMPRESSED: usize = 9;
+/// Largest scaled coordinate XTC can store.
+const XTC_MAX_ABS: f32 = (i32::MAX - 2) as f32;
+/// Bit budget `i` lets three integers below `XTC_MAGIC_INTS[i]` share `i` bits.
+const XTC_MAGIC_INTS: [i32; 73] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 8, 10, 12, 16, 20, 25, 32, 40, 50, 64, 80, 101, 128, 161, 203, 256,
+    322, 406, 512, 645, 812, 1024, 1290, 1625, 2048, 2580, 3250, 4096, 5060, 6501, 8192, 10321,
+    13003, 16384, 20642, 26007, 32768, 41285, 52015, 65536, 82570, 104031, 131072, 165140, 208063,
+    262144, 330280, 416127, 524287, 660561, 832255, 1048576, 1321122, 1664510, 2097152, 2642245,
+    3329021, 4194304, 5284491, 6658042, 8388607, 10568983, 13316085, 16777216,
+];
+const XTC_FIRST_INDEX: usize = 9;
+
+/// One recorded conformation of a chain.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrajectoryFrame {
+    /// Engine step the frame was taken at.
+    pub step: usize,
+    /// Span committed at this step, or a marker such as `start`.
+    pub label: String,
+    /// Bead positions in residue order, in Å.
+    pub positions: Vec<[f64; 3]>,
+}
+
+/// Supported trajectory file formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrajectoryFormat {
+    /// One `MODEL`/`ENDMDL` block per frame.
+    Pdb,
+    Xyz,
+    Dcd,
+    Xtc,
+}
+
+impl TrajectoryFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "pdb" => Some(Self::Pdb),
+            "xyz" => Some(Self::Xyz),
+            "dcd" => Some(Self::Dcd),
+            "xtc" => Some(Self::Xtc),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pdb => "pdb",
+            Self::Xyz => "xyz",
+            Self::Dcd => "dcd",
+            Self::Xtc => "xtc",
+        }
+    }
+}
+
+/// Writes `frames` of `chain` in the format chosen by the file extension.
+pub fn write_trajectory(
+    path: &Path,
+    chain: &PeptideChain,
+    frames: &[TrajectoryFrame],
+) -> Result<TrajectoryFormat, StructureError> {
+    let format = TrajectoryFormat::from_path(path)
+        .ok_or_else(|| StructureError::UnsupportedFormat(path.display().to_string()))?;
+    check_frames(chain, frames)?;
+    match format {
+        TrajectoryFormat::Pdb => fs::write(path, write_multi_model_pdb(chain, frames))?,
+        TrajectoryFormat::Xyz => fs::write(path, write_xyz(chain, frames))?,
+        TrajectoryFormat::Dcd => fs::write(path, write_dcd(frames))?,
+        TrajectoryFormat::Xtc => fs::write(path, write_xtc(frames)?)?,
+    }
+    Ok(format)
+}
+
+fn check_frames(chain: &PeptideChain, frames: &[TrajectoryFrame]) -> Result<(), StructureError> {
+    for (index, frame) in frames.iter().enumerate() {
+        if frame.positions.len() != chain.len() {
+            return Err(StructureError::Frame {
+                frame: index,
+                message: format!(
+                    "{} positions for a chain of {} residues",
+                    frame.positions.len(),
+                    chain.len()
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Renders the frames as a multi-model Cα PDB file.
+pub fn write_multi_model_pdb(chain: &PeptideChain, frames: &[TrajectoryFrame]) -> String {
+    let mut out = String::new();
+    out.push_str("REMARK   1 GENERATED BY LOGLINE FOLDING ENGINE (CA TRACE)\n");
+    for (index, frame) in frames.iter().enumerate() {
+        out.push_str(&format!(
+            "REMARK   2 MODEL {} STEP {} {}\n",
+            index + 1,
+            frame.step,
+            frame.label
+        ));
+        out.push_str(&format!("MODEL     {:>4}\n", (index + 1) % 10_000));
+        push_pdb_atoms(&mut out, chain, &frame.positions);
+        out.push_str("ENDMDL\n");
+    }
+    out.push_str("END\n");
+    out
+}
+
+/// Renders the frames as concatenated XYZ blocks with one `CA` bead per
+/// residue; the comment line carries the step and label.
+pub fn write_xyz(chain: &PeptideChain, frames: &[TrajectoryFrame]) -> String {
+    let mut out = String::new();
+    for frame in frames {
+        out.push_str(&format!("{}\n", frame.positions.len()));
+        out.push_str(&format!("step={} label={}\n", frame.step, frame.label));
+        for (residue, [x, y, z]) in chain.residues().iter().zip(&frame.positions) {
+            out.push_str(&format!(
+                "CA {:>12.5} {:>12.5} {:>12.5} {}\n",
+                x,
+                y,
+                z,
+                residue.amino_acid.three_letter()
+            ));
+        }
+    }
+    out
+}
+
+/// Encodes the frames as a CHARMM-format DCD file without unit cell.
+pub fn write_dcd(frames: &[TrajectoryFrame]) -> Vec<u8> {
+    let atoms = frames.first().map_or(0, |frame| frame.positions.len());
+    let first_step = frames.first().map_or(0, |frame| frame.step);
+    let last_step = frames.last().map_or(0, |frame| frame.step);
+    let interval = match frames {
+        [first, second, ..] => second.step.saturating_sub(first.step).max(1),
+        _ => 1,
+    };
+    let mut out = Vec::new();
+
+    let mut header = Vec::with_capacity(84);
+    header.extend_from_slice(b"CORD");
+    for value in [frames.len(), first_step, interval, last_step] {
+        header.extend_from_slice(&(value as i32).to_le_bytes());
+    }
+    header.extend_from_slice(&[0; 5 * 4]);
+    header.extend_from_slice(&1.0f32.to_le_bytes()); // timestep per engine step
+    header.extend_from_slice(&[0; 9 * 4]); // no unit cell, reserved fields
+    header.extend_from_slice(&24i32.to_le_bytes()); // CHARMM version
+    fortran_record(&mut out, &header);
+
+    let mut titles = Vec::with_capacity(4 + 2 * 80);
+    titles.extend_from_slice(&2i32.to_le_bytes());
+    for title in [
+        "REMARKS GENERATED BY LOGLINE FOLDING ENGINE (CA TRACE)",
+        "REMARKS ONE BEAD PER RESIDUE",
+    ] {
+        let mut line = [b' '; 80];
+        line[..title.len()].copy_from_slice(title.as_bytes());
+        titles.extend_from_slice(&line);
+    }
+    fortran_record(&mut out, &titles);
+    fortran_record(&mut out, &(atoms as i32).to_le_bytes());
+
+    for frame in frames {
+        for axis in 0..3 {
+            let coordinates: Vec<u8> = frame
+                .positions
+                .iter()
+                .flat_map(|position| (position[axis] as f32).to_le_bytes())
+                .collect();
+            fortran_record(&mut out, &coordinates);
+        }
+    }
+    out
+}
+
+fn fortran_record(out: &mut Vec<u8>, record: &[u8]) {
+    let marker = (record.len() as i32).to_le_bytes();
+    out.extend_from_slice(&marker);
+    out.extend_from_slice(record);
+    out.extend_from_slice(&marker);
+}
+
+/// Encodes the frames as a GROMACS XTC file, converting Å to nm. The step is
+/// stored as both the frame step and its time in ps; the box is left empty.
+pub fn write_xtc(frames: &[TrajectoryFrame]) -> Result<Vec<u8>, StructureError> {
+    let mut out = Vec::new();
+    for (index, frame) in frames.iter().enumerate() {
+        let coordinates: Vec<[f32; 3]> = frame
+            .positions
+            .iter()
+            .map(|position| position.map(|value| (value / 10.0) as f32))
+            .collect();
+        push_i32(&mut out, XTC_MAGIC);
+        push_i32(&mut out, coordinates.len() as i32);
+        push_i32(&mut out, frame.step as i32);
+        push_f32(&mut out, frame.step as f32);
+        for _ in 0..9 {
+            push_f32(&mut out, 0.0);
+        }
+        push_xtc_coordinates(&mut out, &coordinates, XTC_PRECISION).map_err(|message| {
+            StructureError::Frame {
+                frame: index,
+                message,
+            }
+        })?;
+    }
+    Ok(out)
+}
+
+/// `xdr3dfcoord`: integer coordinates relative to the frame minimum, with
+/// runs of close neighbours sent as small differences whose bit budget
+/// adapts from atom to atom.
+fn push_xtc_coordinates(
+    out: &mut Vec<u8>,
+    coordinates: &[[f32; 3]],
+    precision: f32,
+) -> Result<(), String> {
+    push_i32(out, coordinates.len() as i32);
+    if coordinates.len() <= XTC_MAX_UNCOMPRESSED {
+        for value in coordinates.iter().flatten() {
+            push_f32(out, *value);
+        }
+        return Ok(());
+    }
+    push_f32(out, precision);
+
+    let mut ints = Vec::with_capacity(coordinates.len());
+    let (mut minint, mut maxint) = ([i32::MAX; 3], [i32::MIN; 3]);
+    let mut mindiff = i64::MAX;
+    let mut previous = [0i32; 3];
+    for (index, coordinate) in coordinates.iter().enumerate() {
+        let mut scaled = [0i32; 3];
+        for axis in 0..3 {
+            let value = coordinate[axis] * precision;
+            let rounded = if value >= 0.0 {
+                (f64::from(value) + 0.5) as f32
+            } else {
+                (f64::from(value) - 0.5) as f32
+            };
+            if rounded.abs() > XTC_MAX_ABS {
+                return Err(format!("coordinate {value} too large for XTC"));
+            }
+            scaled[axis] = rounded as i32;
+            minint[axis] = minint[axis].min(scaled[axis]);
+            maxint[axis] = maxint[axis].max(scaled[axis]);
+        }
+        let diff: i64 = (0..3)
+            .map(|axis| (i64::from(previous[axis]) - i64::from(scaled[axis])).abs())
+            .sum();
+        if index > 0 {
+            mindiff = mindiff.min(diff);
+        }
+        previous = scaled;
+        ints.push(scaled);
+    }
+    for value in minint.into_iter().chain(maxint) {
+        push_i32(out, value);
+    }
+
+    let mut sizes = [0u32; 3];
+    for axis in 0..3 {
+        let size = i64::from(maxint[axis]) - i64::from(minint[axis]) + 1;
+        if size as f32 >= XTC_MAX_ABS {
+            return Err("coordinate range too large for XTC".to_string());
+        }
+        sizes[axis] = size as u32;
+    }
+    // Each size fits 24 bits, so the three can be packed into one integer.
+    let packed = (sizes[0] | sizes[1] | sizes[2]) <= 0xff_ffff;
+    let sizes_bits = bit_length(sizes.iter().map(|size| u128::from(*size)).product());
+
+    // The decoder only reads `small_index`; capping it and `max_index` to
+    // the table keeps the encoder in bounds for absurd spacings.
+    let last_index = XTC_MAGIC_INTS.len() - 1;
+    let mut small_index = XTC_FIRST_INDEX;
+    while small_index < last_index && i64::from(XTC_MAGIC_INTS[small_index]) < mindiff {
+        small_index += 1;
+    }
+    push_i32(out, small_index as i32);
+    let max_index = (small_index + 8).min(last_index);
+    let min_index = max_index - 8;
+    let larger = XTC_MAGIC_INTS[max_index] / 2;
+    let mut smaller = XTC_MAGIC_INTS[XTC_FIRST_INDEX.max(small_index - 1)] / 2;
+    let mut small_num = XTC_MAGIC_INTS[small_index] / 2;
+
+    let mut bits = BitWriter::default();
+    let mut previous_run: i32 = -1;
+    let mut previous = [0i32; 3];
+    let within = |a: [i32; 3], b: [i32; 3], limit: i32| (0..3).all(|k| (a[k] - b[k]).abs() < limit);
+    let mut index = 0;
+    while index < ints.len() {
+        let mut is_smaller: i32 =
+            if small_index < max_index && index >= 1 && within(ints[index], previous, larger) {
+                1
+            } else if small_index > min_index {
+                -1
+            } else {
+                0
+            };
+        // Sending the second of two close atoms first lets the pair share
+        // one small-difference run (water molecules in GROMACS).
+        let mut is_small = false;
+        if index + 1 < ints.len() && within(ints[index], ints[index + 1], small_num) {
+            ints.swap(index, index + 1);
+            is_small = true;
+        }
+        let current = ints[index];
+        let offsets = [0, 1, 2].map(|k| (current[k] - minint[k]) as u32);
+        if packed {
+            bits.write_ints(sizes_bits, sizes, offsets);
+        } else {
+            for (size, offset) in sizes.iter().zip(offsets) {
+                bits.write(bit_length(u128::from(*size)), u128::from(offset));
+            }
+        }
+        previous = current;
+        index += 1;
+
+        if !is_small && is_smaller == -1 {
+            is_smaller = 0;
+        }
+        let mut run = Vec::new();
+        while is_small && run.len() < 8 {
+            let current = ints[index];
+            let squared: i64 = (0..3)
+                .map(|k| i64::from(current[k] - previous[k]).pow(2))
+                .sum();
+            if is_smaller == -1 && squared >= i64::from(smaller).pow(2) {
+                is_smaller = 0;
+            }
+            run.push([0, 1, 2].map(|k| (current[k] - previous[k] + small_num) as u32));
+            previous = current;
+            index += 1;
+            is_small = index < ints.len() && within(ints[index], previous, small_num);
+        }
+        let run_length = 3 * run.len() as i32;
+        if run_length != previous_run || is_smaller != 0 {
+            previous_run = run_length;
+            bits.write(1, 1);
+            bits.write(5, (run_length + is_smaller + 1) as u128);
+        } else {
+            bits.write(1, 0);
+        }
+        let small_size = XTC_MAGIC_INTS[small_index] as u32;
+        for offsets in run {
+            bits.write_ints(small_index as u32, [small_size; 3], offsets);
+        }
+        if is_smaller != 0 {
+            small_index = (small_index as i32 + is_smaller) as usize;
+            if is_smaller < 0 {
+                small_num = smaller;
+                smaller = if small_index > XTC_FIRST_INDEX {
+                    XTC_MAGIC_INTS[small_index - 1] / 2
+                } else {
+                    0
+                };
+            } else {
+                smaller = small_num;
+                small_num = XTC_MAGIC_INTS[small_index] / 2;
+            }
+        }
+    }
+
+    let bytes = bits.into_bytes();
+    push_i32(out, bytes.len() as i32);
+    out.extend_from_slice(&bytes);
+    out.resize(out.len().next_multiple_of(4), 0);
+    Ok(())
+}
+
+/// Most-significant-bit-first bit stream, zero-padded to whole bytes.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    used: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, count: u32, value: u128) {
+        for bit in (0..count).rev() {
+            if self.used.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if (value >> bit) & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> (self.used % 8);
+            }
+            self.used += 1;
+        }
+    }
+
+    /// Packs `values` as one mixed-radix integer over `sizes` and sends it in
+    /// `count` bits, least significant byte first.
+    fn write_ints(&mut self, count: u32, sizes: [u32; 3], values: [u32; 3]) {
+        let mut packed = u128::from(values[0]);
+        for (size, value) in sizes.iter().zip(values).skip(1) {
+            packed = packed * u128::from(*size) + u128::from(value);
+        }
+        let mut remaining = count;
+        while remaining > 8 {
+            self.write(8, packed & 0xff);
+            packed >>= 8;
+            remaining -= 8;
+        }
+        self.write(remaining, packed);
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+fn bit_length(value: u128) -> u32 {
+    128 - value.leading_zeros()
+}
+
+fn push_i32(out: &mut Vec<u8>, value: i32) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+fn push_f32(out: &mut Vec<u8>, value: f32) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structure_io::parse_pdb;
+    use crate::{AminoAcid, StartingConformation};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn frames(chain: &PeptideChain, count: usize) -> Vec<TrajectoryFrame> {
+        (0..count)
+            .map(|index| TrajectoryFrame {
+                step: index * 5,
+                label: format!("residue-{index}"),
+                positions: chain
+                    .positions()
+                    .iter()
+                    .map(|p| [p[0] + index as f64, p[1], p[2] - 0.5 * index as f64])
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Decoder ported from `xdrfile`'s read path.
+    fn read_xtc(bytes: &[u8]) -> Vec<(i32, Vec<[f32; 3]>)> {
+        let int = |cursor: &mut usize| {
+            let value = i32::from_be_bytes(bytes[*cursor..*cursor + 4].try_into().unwrap());
+            *cursor += 4;
+            value
+        };
+        let mut cursor = 0;
+        let mut frames = Vec::new();
+        while cursor < bytes.len() {
+            assert_eq!(int(&mut cursor), XTC_MAGIC);
+            let atoms = int(&mut cursor) as usize;
+            let step = int(&mut cursor);
+            let _time = f32::from_bits(int(&mut cursor) as u32);
+            let _box: Vec<i32> = (0..9).map(|_| int(&mut cursor)).collect();
+            assert_eq!(int(&mut cursor) as usize, atoms);
+            if atoms <= XTC_MAX_UNCOMPRESSED {
+                let coordinates = (0..atoms)
+                    .map(|_| [(); 3].map(|_| f32::from_bits(int(&mut cursor) as u32)))
+                    .collect();
+                frames.push((step, coordinates));
+                continue;
+            }
+            let precision = f32::from_bits(int(&mut cursor) as u32);
+            let minint = [(); 3].map(|_| int(&mut cursor));
+            let maxint = [(); 3].map(|_| int(&mut cursor));
+            let mut small_index = int(&mut cursor) as usize;
+            let length = int(&mut cursor) as usize;
+            let data = &bytes[cursor..cursor + length];
+            cursor += length.next_multiple_of(4);
+
+            let sizes = [0, 1, 2].map(|k| (maxint[k] - minint[k] + 1) as u32);
+            let packed = (sizes[0] | sizes[1] | sizes[2]) <= 0xff_ffff;
+            let sizes_bits = bit_length(sizes.iter().map(|s| u128::from(*s)).product());
+            let mut smaller = XTC_MAGIC_INTS[XTC_FIRST_INDEX.max(small_index - 1)] / 2;
+            let mut small_num = XTC_MAGIC_INTS[small_index] / 2;
+            let mut reader = BitReader { data, position: 0 };
+            let mut coordinates = Vec::with_capacity(atoms);
+            let mut run = 0;
+            while coordinates.len() < atoms {
+                let mut current = if packed {
+                    reader.read_ints(sizes_bits, sizes)
+                } else {
+                    sizes.map(|size| reader.read(bit_length(u128::from(size))) as i32)
+                };
+                for k in 0..3 {
+                    current[k] += minint[k];
+                }
+                let mut previous = current;
+                let mut is_smaller = 0;
+                if reader.read(1) == 1 {
+                    run = reader.read(5) as i32;
+                    is_smaller = run % 3;
+                    run -= is_smaller;
+                    is_smaller -= 1;
+                }
+                if run > 0 {
+                    let small_size = XTC_MAGIC_INTS[small_index] as u32;
+                    for k in (0..run).step_by(3) {
+                        let mut next = reader.read_ints(small_index as u32, [small_size; 3]);
+                        for axis in 0..3 {
+                            next[axis] += previous[axis] - small_num;
+                        }
+                        if k == 0 {
+                            std::mem::swap(&mut next, &mut previous);
+                            coordinates.push(previous);
+                        } else {
+                            previous = next;
+                        }
+                        coordinates.push(next);
+                    }
+                } else {
+                    coordinates.push(current);
+                }
+                small_index = (small_index as i32 + is_smaller) as usize;
+                if is_smaller < 0 {
+                    small_num = smaller;
+                    smaller = if small_index > XTC_FIRST_INDEX {
+                        XTC_MAGIC_INTS[small_index - 1] / 2
+                    } else {
+                        0
+                    };
+                } else if is_smaller > 0 {
+                    smaller = small_num;
+                    small_num = XTC_MAGIC_INTS[small_index] / 2;
+                }
+            }
+            let scaled = coordinates
+                .iter()
+                .map(|c| c.map(|value| value as f32 / precision))
+                .collect();
+            frames.push((step, scaled));
+        }
+        frames
+    }
+
+    struct BitReader<'a> {
+        data: &'a [u8],
+        position: usize,
+    }
+
+    impl BitReader<'_> {
+        fn read(&mut self, count: u32) -> u128 {
+            let mut value = 0;
+            for _ in 0..count {
+                let bit = (self.data[self.position / 8] >> (7 - self.position % 8)) & 1;
+                value = (value << 1) | u128::from(bit);
+                self.position += 1;
+            }
+            value
+        }
+
+        fn read_ints(&mut self, count: u32, sizes: [u32; 3]) -> [i32; 3] {
+            let (mut packed, mut shift, mut remaining) = (0u128, 0, count);
+            while remaining > 8 {
+                packed |= self.read(8) << shift;
+                shift += 8;
+                remaining -= 8;
+            }
+            packed |= self.read(remaining) << shift;
+            let mut values = [0i32; 3];
+            for k in (1..3).rev() {
+                values[k] = (packed % u128::from(sizes[k])) as i32;
+                packed /= u128::from(sizes[k]);
+            }
+            values[0] = packed as i32;
+            values
+        }
+    }
+
+    #[test]
+    fn xtc_round_trips_within_precision() {
+        let mut rng = StdRng::seed_from_u64(18);
+        let mut cases = Vec::new();
+        // Bead traces, tight clusters that trigger small-difference runs,
+        // sparse jumps that push the bit budget around, and a frame short
+        // enough to be stored uncompressed.
+        let mut walk = vec![[0.0f64; 3]];
+        for _ in 0..60 {
+            let last = *walk.last().unwrap();
+            let step = if rng.gen_bool(0.3) { 0.4 } else { 3.8 };
+            walk.push(last.map(|v| v + rng.gen_range(-step..step)));
+        }
+        cases.push(walk);
+        cases.push(
+            (0..40)
+                .map(|i| {
+                    let jump = if i % 7 == 0 { 400.0 } else { 0.0 };
+                    [(); 3].map(|_| rng.gen_range(-2.0..2.0) + jump)
+                })
+                .collect(),
+        );
+        cases.push(vec![[1.0, -2.0, 3.5]; 5]);
+
+        for positions in cases {
+            let frames = vec![
+                TrajectoryFrame {
+                    step: 3,
+                    label: "start".into(),
+                    positions: positions.clone(),
+                },
+                TrajectoryFrame {
+                    step: 8,
+                    label: "minimize".into(),
+                    positions: positions.iter().map(|p| p.map(|v| -v)).collect(),
+                },
+            ];
+            let decoded = read_xtc(&write_xtc(&frames).unwrap());
+            assert_eq!(decoded.len(), 2);
+            for (frame, (step, coordinates)) in frames.iter().zip(decoded) {
+                assert_eq!(step as usize, frame.step);
+                assert_eq!(coordinates.len(), frame.positions.len());
+                for (expected, actual) in frame.positions.iter().zip(coordinates) {
+                    for axis in 0..3 {
+                        let angstrom = f64::from(actual[axis]) * 10.0;
+                        assert!((angstrom - expected[axis]).abs() < 0.006, "{expected:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn text_and_dcd_layouts() {
+        let chain =
+            PeptideChain::from_sequence(&[AminoAcid::Glycine; 4], StartingConformation::Helix);
+        let frames = frames(&chain, 3);
+
+        let pdb = write_multi_model_pdb(&chain, &frames);
+        assert_eq!(pdb.matches("ENDMDL").count(), 3);
+        let first = parse_pdb(&pdb, None).unwrap();
+        assert_eq!(first.len(), 4);
+        assert!((first.positions()[2][0] - chain.positions()[2][0]).abs() < 1e-3);
+
+        let xyz = write_xyz(&chain, &frames);
+        assert_eq!(xyz.lines().count(), 3 * (2 + 4));
+        assert!(xyz.contains("step=10 label=residue-2"));
+
+        let dcd = write_dcd(&frames);
+        let int = |offset: usize| i32::from_le_bytes(dcd[offset..offset + 4].try_into().unwrap());
+        assert_eq!((int(0), &dcd[4..8], int(88)), (84, &b"CORD"[..], 84));
+        assert_eq!((int(8), int(12), int(16)), (3, 0, 5));
+        assert_eq!((int(92), int(96 + 164), int(96 + 168)), (164, 164, 4));
+        assert_eq!(int(96 + 172), 4);
+        let frames_start = 96 + 180;
+        assert_eq!(dcd.len(), frames_start + 3 * 3 * (8 + 4 * 4));
+        let x = f32::from_le_bytes(dcd[frames_start + 8..frames_start + 12].try_into().unwrap());
+        assert!((f64::from(x) - frames[0].positions[1][0]).abs() < 1e-5);
+
+        let mismatched = TrajectoryFrame {
+            positions: vec![[0.0; 3]],
+            ..frames[0].clone()
+        };
+        assert!(matches!(
+            check_frames(&chain, &[mismatched]),
+            Err(StructureError::Frame { frame: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,200 @@
+//! AlphaFold confidence outputs.
+//!
+//! [`AlphaFoldRankingAdapter`] reads `ranking_debug.json` (`plddts` for
+//! monomer runs, `iptm+ptm` for multimer runs, plus the `order` of models).
+//! [`PlddtAdapter`] reads per-residue pLDDT: the AlphaFold DB confidence
+//! JSON (`residueNumber`/`confidenceScore`) or ColabFold score files
+//! (`plddt`, optionally `ptm`, `iptm` and `max_pae`).
+
+use serde_json::{json, Map, Value};
+use spans_core::UniversalSpan;
+
+use crate::registry::{summary_span, IngestAdapter};
+use crate::{IngestError, IngestOptions};
+
+/// pLDDT bands used by AlphaFold DB.
+const VERY_HIGH_PLDDT: f64 = 90.0;
+const CONFIDENT_PLDDT: f64 = 70.0;
+const LOW_PLDDT: f64 = 50.0;
+
+pub struct AlphaFoldRankingAdapter;
+
+impl AlphaFoldRankingAdapter {
+    fn scores(value: &Value) -> Option<(&'static str, &Map<String, Value>)> {
+        ["iptm+ptm", "plddts"]
+            .into_iter()
+            .find_map(|key| Some((key, value.get(key)?.as_object()?)))
+    }
+}
+
+impl IngestAdapter for AlphaFoldRankingAdapter {
+    fn name(&self) -> &'static str {
+        "alphafold_ranking"
+    }
+
+    fn detect(&self, value: &Value) -> f64 {
+        let ordered = value.get("order").is_some_and(Value::is_array);
+        if ordered && Self::scores(value).is_some() {
+            0.95
+        } else {
+            0.0
+        }
+    }
+
+    fn convert(&self, value: Value, opts: &IngestOptions) -> Result<UniversalSpan, IngestError> {
+        let (kind, scores) = Self::scores(&value).ok_or(IngestError::MissingField("plddts"))?;
+        let order: Vec<&str> = value["order"]
+            .as_array()
+            .ok_or(IngestError::MissingField("order"))?
+            .iter()
+            .filter_map(Value::as_str)
+            .collect();
+        let top_model = order.first().ok_or_else(|| IngestError::Malformed {
+            adapter: self.name(),
+            message: "empty model order".to_string(),
+        })?;
+        let metrics = json!({
+            "score": kind,
+            "order": order,
+            "top_model": top_model,
+            "top_score": scores.get(*top_model),
+            "scores": scores,
+        });
+        summary_span(self, "AlphaFold ranking", metrics, opts)
+    }
+
+    fn default_flow(&self) -> Option<&'static str> {
+        Some("structure_prediction")
+    }
+
+    fn default_workflow(&self) -> Option<&'static str> {
+        Some("alphafold")
+    }
+}
+
+pub struct PlddtAdapter;
+
+impl PlddtAdapter {
+    /// The object carrying the scores; AlphaFold DB wraps it in an array.
+    fn record(value: &Value) -> Option<&Value> {
+        let record = match value {
+            Value::Array(items) if items.len() == 1 => &items[0],
+            other => other,
+        };
+        ["confidenceScore", "plddt"]
+            .iter()
+            .any(|key| record.get(key).is_some_and(Value::is_array))
+            .then_some(record)
+    }
+}
+
+impl IngestAdapter for PlddtAdapter {
+    fn name(&self) -> &'static str {
+        "alphafold_plddt"
+    }
+
+    fn detect(&self, value: &Value) -> f64 {
+        if Self::record(value).is_some() {
+            0.9
+        } else {
+            0.0
+        }
+    }
+
+    fn convert(&self, value: Value, opts: &IngestOptions) -> Result<UniversalSpan, IngestError> {
+        let record = Self::record(&value).ok_or(IngestError::MissingField("plddt"))?;
+        let scores: Vec<f64> = record
+            .get("confidenceScore")
+            .or_else(|| record.get("plddt"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_f64)
+            .collect();
+        if scores.is_empty() {
+            return Err(IngestError::Malformed {
+                adapter: self.name(),
+                message: "no pLDDT scores".to_string(),
+            });
+        }
+        let fraction = |keep: fn(f64) -> bool| {
+            scores.iter().filter(|score| keep(**score)).count() as f64 / scores.len() as f64
+        };
+        let mut metrics = json!({
+            "residues": scores.len(),
+            "mean_plddt": scores.iter().sum::<f64>() / scores.len() as f64,
+            "min_plddt": scores.iter().copied().fold(f64::INFINITY, f64::min),
+            "very_high_fraction": fraction(|score| score > VERY_HIGH_PLDDT),
+            "confident_fraction": fraction(|score| score > CONFIDENT_PLDDT),
+            "low_fraction": fraction(|score| score < LOW_PLDDT),
+        });
+        for key in ["ptm", "iptm", "max_pae"] {
+            if let Some(score) = record.get(key).filter(|score| score.is_number()) {
+                metrics[key] = score.clone();
+            }
+        }
+        summary_span(self, "pLDDT", metrics, opts)
+    }
+
+    fn default_flow(&self) -> Option<&'static str> {
+        Some("structure_prediction")
+    }
+
+    fn default_workflow(&self) -> Option<&'static str> {
+        Some("alphafold")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::AdapterRegistry;
+    use chrono::Utc;
+
+    #[test]
+    fn ranks_monomer_and_multimer_models() {
+        let registry = AdapterRegistry::default();
+        let opts = IngestOptions::new("prediction", "alphafold", Utc::now())
+            .with_source("gp41/ranking_debug.json");
+        let monomer = json!({
+            "plddts": { "model_1_pred_0": 81.5, "model_2_pred_0": 88.25 },
+            "order": ["model_2_pred_0", "model_1_pred_0"]
+        });
+        let adapter = registry.detect(&monomer).unwrap();
+        assert_eq!(adapter.name(), "alphafold_ranking");
+        let span = adapter.convert(monomer, &opts).unwrap();
+        assert_eq!(span.payload["metrics"]["top_model"], "model_2_pred_0");
+        assert_eq!(span.payload["metrics"]["top_score"], 88.25);
+
+        let multimer = json!({
+            "iptm+ptm": { "model_1_multimer_v3_pred_0": 0.82 },
+            "order": ["model_1_multimer_v3_pred_0"]
+        });
+        let span = registry.ingest(multimer, &opts).unwrap();
+        assert_eq!(span.payload["metrics"]["score"], "iptm+ptm");
+    }
+
+    #[test]
+    fn bands_alphafold_db_and_colabfold_plddt() {
+        let registry = AdapterRegistry::default();
+        let opts = IngestOptions::new("prediction", "alphafold", Utc::now());
+        let database = json!([{
+            "residueNumber": [1, 2, 3, 4],
+            "confidenceScore": [95.0, 80.0, 60.0, 40.0],
+            "confidenceCategory": ["H", "M", "L", "D"]
+        }]);
+        assert_eq!(
+            registry.detect(&database).unwrap().name(),
+            "alphafold_plddt"
+        );
+        let metrics = registry.ingest(database, &opts).unwrap().payload["metrics"].clone();
+        assert_eq!(metrics["mean_plddt"], 68.75);
+        assert_eq!(metrics["confident_fraction"], 0.5);
+        assert_eq!(metrics["low_fraction"], 0.25);
+
+        let colabfold = json!({ "plddt": [70.5, 90.5], "ptm": 0.61, "max_pae": 31.75 });
+        let metrics = registry.ingest(colabfold, &opts).unwrap().payload["metrics"].clone();
+        assert_eq!(metrics["ptm"], 0.61);
+        assert_eq!(metrics["very_high_fraction"], 0.5);
+    }
+}
@@ -0,0 +1,475 @@
+//! GROMACS binary energy files (`.edr`).
+//!
+//! An `.edr` file is XDR (big-endian) data: a header naming each energy
+//! term and its unit, then one frame per `nstenergy` steps holding the
+//! time, step and the instantaneous value of every term, followed by
+//! optional data blocks. Reals are floats or doubles depending on the
+//! precision `mdrun` was built with; [`read_edr`] detects which from the
+//! first frame. Files from before GROMACS 4.0 (format version 1) are not
+//! read.
+
+use serde_json::{json, Map, Value};
+use spans_core::UniversalSpan;
+
+use crate::metric_table::series_summary;
+use crate::registry::{summary_span, IngestAdapter};
+use crate::{IngestError, IngestOptions};
+
+/// Key of the object [`read_edr`] decodes an energy file into.
+const EDR_FRAMES: &str = "gromacs_edr";
+/// Newest energy file format this reader understands.
+const ENX_VERSION: i32 = 5;
+const NAMES_MAGIC: i32 = -55555;
+const FRAME_MAGIC: i32 = -7777777;
+/// Every frame opens with this real so old readers can spot the format.
+const FRAME_MARKER: f64 = -2e10;
+
+/// Block data types (`xdr_datatype` in GROMACS).
+const TYPE_INT: i32 = 0;
+const TYPE_FLOAT: i32 = 1;
+const TYPE_DOUBLE: i32 = 2;
+const TYPE_INT64: i32 = 3;
+const TYPE_CHAR: i32 = 4;
+const TYPE_STRING: i32 = 5;
+
+/// Decodes an `.edr` file into
+/// `{"gromacs_edr": {"version", "precision", "times", "steps", "terms"}}`,
+/// where each term holds its `name`, `unit` and per-frame `values`.
+pub fn read_edr(bytes: &[u8]) -> Result<Value, IngestError> {
+    decode(bytes).map_err(|message| IngestError::Malformed {
+        adapter: "gromacs_edr",
+        message,
+    })
+}
+
+fn decode(bytes: &[u8]) -> Result<Value, String> {
+    let mut xdr = Xdr::new(bytes);
+    if xdr.int()? != NAMES_MAGIC {
+        return Err("not a GROMACS energy file, or one from before GROMACS 4.0".to_string());
+    }
+    let version = xdr.int()?;
+    if !(2..=ENX_VERSION).contains(&version) {
+        return Err(format!("unsupported energy file version {version}"));
+    }
+    let count = xdr.count()?;
+    let mut names = Vec::with_capacity(count);
+    for _ in 0..count {
+        names.push((xdr.gmx_string()?, xdr.gmx_string()?));
+    }
+
+    xdr.double = match (xdr.peek_f32(), xdr.peek_f64()) {
+        _ if xdr.is_empty() => false,
+        (Some(marker), _) if marker < -1e10 => false,
+        (_, Some(marker)) if marker < -1e10 => true,
+        _ => return Err("first energy frame has no header marker".to_string()),
+    };
+
+    let mut times = Vec::new();
+    let mut steps = Vec::new();
+    let mut values = vec![Vec::new(); count];
+    while !xdr.is_empty() {
+        let frame = times.len();
+        let context = |message: String| format!("frame {frame}: {message}");
+        let (time, step, energies) = read_frame(&mut xdr, count).map_err(context)?;
+        // Frames with no energies carry only data blocks.
+        if let Some(energies) = energies {
+            times.push(time);
+            steps.push(step);
+            for (series, value) in values.iter_mut().zip(energies) {
+                series.push(value);
+            }
+        }
+    }
+
+    let terms: Vec<Value> = names
+        .into_iter()
+        .zip(values)
+        .map(|((name, unit), values)| json!({ "name": name, "unit": unit, "values": values }))
+        .collect();
+    Ok(json!({
+        EDR_FRAMES: {
+            "version": version,
+            "precision": if xdr.double { "double" } else { "single" },
+            "times": times,
+            "steps": steps,
+            "terms": terms,
+        }
+    }))
+}
+
+/// Time, step and energies of the next frame; the energies are `None` when
+/// the frame stores none.
+fn read_frame(xdr: &mut Xdr<'_>, terms: usize) -> Result<(f64, i64, Option<Vec<f64>>), String> {
+    if xdr.real()? != FRAME_MARKER || xdr.int()? != FRAME_MAGIC {
+        return Err("bad frame header".to_string());
+    }
+    let version = xdr.int()?;
+    if !(2..=ENX_VERSION).contains(&version) {
+        return Err(format!("unsupported frame version {version}"));
+    }
+    let time = xdr.double()?;
+    let step = xdr.int64()?;
+    let nsum = xdr.int()?;
+    if version >= 3 {
+        xdr.int64()?; // steps covered by the sums
+    }
+    if version >= 5 {
+        xdr.double()?; // time covered by the sums
+    }
+    let energies = xdr.count()?;
+    if energies != 0 && energies != terms {
+        return Err(format!("{energies} energies for {terms} terms"));
+    }
+    // Distance restraints have their own block in older frames.
+    let restraints = xdr.count()?;
+    let mut blocks: Vec<Vec<(i32, usize)>> = Vec::new();
+    if version < 4 && restraints > 0 {
+        let real = if xdr.double { TYPE_DOUBLE } else { TYPE_FLOAT };
+        blocks.push(vec![(real, restraints), (real, restraints)]);
+    }
+    for _ in 0..xdr.count()? {
+        if version < 4 {
+            let real = if xdr.double { TYPE_DOUBLE } else { TYPE_FLOAT };
+            blocks.push(vec![(real, xdr.count()?)]);
+        } else {
+            xdr.int()?; // block id
+            let subblocks = xdr.count()?;
+            blocks.push(
+                (0..subblocks)
+                    .map(|_| Ok((xdr.int()?, xdr.count()?)))
+                    .collect::<Result<_, String>>()?,
+            );
+        }
+    }
+    xdr.int()?; // energy size
+    xdr.int()?; // reserved
+    xdr.int()?; // reserved
+
+    let mut values = Vec::with_capacity(energies);
+    for _ in 0..energies {
+        values.push(xdr.real()?);
+        if nsum > 0 {
+            xdr.real()?; // running average
+            xdr.real()?; // running sum of squares
+        }
+    }
+    for (kind, count) in blocks.into_iter().flatten() {
+        match kind {
+            TYPE_INT | TYPE_FLOAT | TYPE_CHAR => xdr.skip(4 * count)?,
+            TYPE_DOUBLE | TYPE_INT64 => xdr.skip(8 * count)?,
+            TYPE_STRING => {
+                for _ in 0..count {
+                    xdr.gmx_string()?;
+                }
+            }
+            _ => return Err(format!("unknown block data type {kind}")),
+        }
+    }
+    Ok((time, step, (energies > 0).then_some(values)))
+}
+
+/// Big-endian XDR reader.
+struct Xdr<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    /// Whether reals are doubles.
+    double: bool,
+}
+
+impl<'a> Xdr<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+            double: false,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.offset >= self.bytes.len()
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let bytes = self
+            .bytes
+            .get(self.offset..self.offset + N)
+            .ok_or_else(|| format!("truncated at byte {}", self.offset))?;
+        self.offset += N;
+        Ok(bytes.try_into().expect("slice of length N"))
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), String> {
+        if count > self.bytes.len() - self.offset {
+            return Err(format!("truncated at byte {}", self.offset));
+        }
+        self.offset += count;
+        Ok(())
+    }
+
+    fn int(&mut self) -> Result<i32, String> {
+        self.take().map(i32::from_be_bytes)
+    }
+
+    /// A non-negative count, bounded by the bytes left so corrupt sizes
+    /// fail instead of allocating.
+    fn count(&mut self) -> Result<usize, String> {
+        let value = self.int()?;
+        usize::try_from(value)
+            .ok()
+            .filter(|count| *count <= self.bytes.len() - self.offset)
+            .ok_or_else(|| format!("invalid count {value} at byte {}", self.offset - 4))
+    }
+
+    fn int64(&mut self) -> Result<i64, String> {
+        self.take().map(i64::from_be_bytes)
+    }
+
+    fn double(&mut self) -> Result<f64, String> {
+        self.take().map(f64::from_be_bytes)
+    }
+
+    fn real(&mut self) -> Result<f64, String> {
+        if self.double {
+            self.double()
+        } else {
+            self.take()
+                .map(|bytes| f64::from(f32::from_be_bytes(bytes)))
+        }
+    }
+
+    /// A string as `gmx_fio_do_string` writes it: the length counting the
+    /// terminating NUL, then an XDR string of length-prefixed bytes padded
+    /// to a multiple of four.
+    fn gmx_string(&mut self) -> Result<String, String> {
+        self.int()?;
+        let length = self.count()?;
+        let start = self.offset;
+        self.skip(length.next_multiple_of(4))?;
+        Ok(String::from_utf8_lossy(&self.bytes[start..start + length]).into_owned())
+    }
+
+    fn peek_f32(&self) -> Option<f64> {
+        let bytes = self.bytes.get(self.offset..self.offset + 4)?;
+        Some(f64::from(f32::from_be_bytes(bytes.try_into().ok()?)))
+    }
+
+    fn peek_f64(&self) -> Option<f64> {
+        let bytes = self.bytes.get(self.offset..self.offset + 8)?;
+        Some(f64::from_be_bytes(bytes.try_into().ok()?))
+    }
+}
+
+/// Energy files decoded by [`read_edr`]: the frame count, time range and
+/// last step, with each term summarised like a `gmx energy` series.
+pub struct GromacsEdrAdapter;
+
+impl IngestAdapter for GromacsEdrAdapter {
+    fn name(&self) -> &'static str {
+        "gromacs_edr"
+    }
+
+    fn detect(&self, value: &Value) -> f64 {
+        if value.get(EDR_FRAMES).is_some() {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn convert(&self, value: Value, opts: &IngestOptions) -> Result<UniversalSpan, IngestError> {
+        let frames = value
+            .get(EDR_FRAMES)
+            .ok_or(IngestError::MissingField(EDR_FRAMES))?;
+        let times: Vec<f64> = serde_json::from_value(frames["times"].clone())
+            .map_err(|_| IngestError::MissingField("times"))?;
+        let (Some(first), Some(last)) = (times.first(), times.last()) else {
+            return Err(IngestError::Malformed {
+                adapter: self.name(),
+                message: "no energy frames found".to_string(),
+            });
+        };
+        let mut terms = Map::new();
+        for term in frames["terms"].as_array().into_iter().flatten() {
+            let (Some(name), Ok(values)) = (
+                term["name"].as_str(),
+                serde_json::from_value::<Vec<f64>>(term["values"].clone()),
+            ) else {
+                continue;
+            };
+            let mut summary = series_summary(&values);
+            summary["unit"] = term["unit"].clone();
+            terms.insert(name.to_string(), summary);
+        }
+        let metrics = json!({
+            "version": frames["version"],
+            "precision": frames["precision"],
+            "frames": times.len(),
+            "step": frames["steps"].as_array().and_then(|steps| steps.last()),
+            "time_range_ps": [first, last],
+            "terms": terms,
+        });
+        summary_span(self, "GROMACS energies", metrics, opts)
+    }
+
+    fn default_flow(&self) -> Option<&'static str> {
+        Some("molecular_dynamics")
+    }
+
+    fn default_workflow(&self) -> Option<&'static str> {
+        Some("gromacs")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{lift_file, AdapterRegistry};
+    use chrono::Utc;
+
+    /// XDR writer for building energy files in the layout `mdrun` writes.
+    struct Writer {
+        bytes: Vec<u8>,
+        double: bool,
+    }
+
+    impl Writer {
+        fn int(&mut self, value: i32) {
+            self.bytes.extend(value.to_be_bytes());
+        }
+
+        fn int64(&mut self, value: i64) {
+            self.bytes.extend(value.to_be_bytes());
+        }
+
+        fn double(&mut self, value: f64) {
+            self.bytes.extend(value.to_be_bytes());
+        }
+
+        fn real(&mut self, value: f64) {
+            if self.double {
+                self.double(value);
+            } else {
+                self.bytes.extend((value as f32).to_be_bytes());
+            }
+        }
+
+        fn gmx_string(&mut self, value: &str) {
+            self.int(value.len() as i32 + 1);
+            self.int(value.len() as i32);
+            self.bytes.extend(value.as_bytes());
+            self.bytes.resize(self.bytes.len().next_multiple_of(4), 0);
+        }
+    }
+
+    /// Two terms over three frames; the second frame carries a block of
+    /// ints, doubles and strings, the third only running sums.
+    fn energy_file(double: bool) -> Vec<u8> {
+        let mut w = Writer {
+            bytes: Vec::new(),
+            double,
+        };
+        w.int(NAMES_MAGIC);
+        w.int(ENX_VERSION);
+        w.int(2);
+        for (name, unit) in [("Potential", "kJ/mol"), ("Temperature", "K")] {
+            w.gmx_string(name);
+            w.gmx_string(unit);
+        }
+        let frames: [(f64, i64, i32, [f64; 2], bool); 3] = [
+            (0.0, 0, 0, [-1000.0, 298.0], false),
+            (10.0, 5000, 0, [-1010.0, 302.0], true),
+            (20.0, 10000, 2, [-1020.0, 300.0], false),
+        ];
+        for (time, step, nsum, energies, block) in frames {
+            w.real(FRAME_MARKER);
+            w.int(FRAME_MAGIC);
+            w.int(ENX_VERSION);
+            w.double(time);
+            w.int64(step);
+            w.int(nsum);
+            w.int64(step);
+            w.double(time);
+            w.int(2);
+            w.int(0);
+            w.int(i32::from(block));
+            if block {
+                w.int(7);
+                w.int(3);
+                for (kind, count) in [(TYPE_INT, 3), (TYPE_DOUBLE, 2), (TYPE_STRING, 1)] {
+                    w.int(kind);
+                    w.int(count);
+                }
+            }
+            w.int(0);
+            w.int(0);
+            w.int(0);
+            for energy in energies {
+                w.real(energy);
+                if nsum > 0 {
+                    w.real(energy);
+                    w.real(0.0);
+                }
+            }
+            if block {
+                (0..3).for_each(|value| w.int(value));
+                (0..2).for_each(|value| w.double(value.into()));
+                w.gmx_string("pull");
+            }
+        }
+        w.bytes
+    }
+
+    #[test]
+    fn reads_single_and_double_precision_energy_files() {
+        let registry = AdapterRegistry::default();
+        for (double, precision) in [(false, "single"), (true, "double")] {
+            let value = lift_file(Some("ener.edr"), &energy_file(double)).unwrap();
+            let adapter = registry.detect(&value).unwrap();
+            assert_eq!(adapter.name(), "gromacs_edr");
+
+            let opts = IngestOptions::new("md", "gromacs", Utc::now()).with_source("ener.edr");
+            let span = adapter.convert(value, &opts).unwrap();
+            let metrics = &span.payload["metrics"];
+            assert!(span.id.0.starts_with("span::gromacs_edr::ener::"));
+            assert_eq!(metrics["precision"], precision);
+            assert_eq!(metrics["frames"], 3);
+            assert_eq!(metrics["step"], 10000);
+            assert_eq!(metrics["time_range_ps"], json!([0.0, 20.0]));
+            assert_eq!(metrics["terms"]["Potential"]["mean"], -1010.0);
+            assert_eq!(metrics["terms"]["Temperature"]["final"], 300.0);
+            assert_eq!(metrics["terms"]["Temperature"]["unit"], "K");
+        }
+    }
+
+    #[test]
+    fn header_strings_carry_their_length_with_the_nul() {
+        // One term as `gmx_fio_do_string` lays it out: 5 for "Pres" plus
+        // NUL, then the XDR string; likewise 4 and "bar" for the unit.
+        let mut bytes = Vec::new();
+        for value in [NAMES_MAGIC, ENX_VERSION, 1, 5, 4] {
+            bytes.extend(value.to_be_bytes());
+        }
+        bytes.extend(b"Pres");
+        for value in [4_i32, 3] {
+            bytes.extend(value.to_be_bytes());
+        }
+        bytes.extend(b"bar\0");
+        let value = read_edr(&bytes).unwrap();
+        assert_eq!(
+            value[EDR_FRAMES]["terms"],
+            json!([{ "name": "Pres", "unit": "bar", "values": [] }])
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_and_foreign_files() {
+        let bytes = energy_file(false);
+        let truncated = read_edr(&bytes[..bytes.len() - 6]).unwrap_err();
+        assert!(
+            truncated.to_string().contains("frame 2: truncated"),
+            "{truncated}"
+        );
+        let foreign = read_edr(b"GROMACS?").unwrap_err();
+        assert!(foreign.to_string().contains("not a GROMACS energy file"));
+    }
+}
@@ -0,0 +1,320 @@
+//! GROMACS run summaries.
+//!
+//! [`GromacsLogAdapter`] reads `mdrun` `.log` files: the last energy frame,
+//! the `A V E R A G E S` block and the step/time reached. `gmx energy`
+//! exports, `.xvg` tables of the selected terms against time, are read by
+//! [`GromacsEnergyAdapter`]; binary `.edr` files are decoded in
+//! [`crate::edr`].
+
+use serde_json::{json, Map, Value};
+use spans_core::UniversalSpan;
+
+use crate::metric_table::series_summary;
+use crate::registry::{has_extension, source_text, summary_span, IngestAdapter};
+use crate::{IngestError, IngestOptions};
+
+/// Width of one column in `mdrun` energy blocks (`%15s` / `%15.5e`).
+const LOG_COLUMN_WIDTH: usize = 15;
+const ENERGY_HEADER: &str = "Energies (kJ/mol)";
+const AVERAGES_MARKER: &str = "A V E R A G E S";
+
+pub struct GromacsLogAdapter;
+
+impl IngestAdapter for GromacsLogAdapter {
+    fn name(&self) -> &'static str {
+        "gromacs_log"
+    }
+
+    fn detect(&self, value: &Value) -> f64 {
+        let Some((file_name, content)) = source_text(value) else {
+            return 0.0;
+        };
+        if !content.contains(ENERGY_HEADER) {
+            0.0
+        } else if content.contains("GROMACS") || has_extension(file_name, "log") {
+            0.95
+        } else {
+            0.6
+        }
+    }
+
+    fn convert(&self, value: Value, opts: &IngestOptions) -> Result<UniversalSpan, IngestError> {
+        let (_, content) = source_text(&value).ok_or(IngestError::MissingField("source_text"))?;
+        let summary = parse_log(content);
+        if summary.frames == 0 && summary.averages.is_none() {
+            return Err(IngestError::Malformed {
+                adapter: self.name(),
+                message: "no energy frames found".to_string(),
+            });
+        }
+        let metrics = json!({
+            "version": summary.version,
+            "step": summary.step,
+            "time_ps": summary.time,
+            "energy_frames": summary.frames,
+            "final": summary.last,
+            "averages": summary.averages,
+        });
+        summary_span(self, "GROMACS run", metrics, opts)
+    }
+
+    fn default_flow(&self) -> Option<&'static str> {
+        Some("molecular_dynamics")
+    }
+
+    fn default_workflow(&self) -> Option<&'static str> {
+        Some("gromacs")
+    }
+}
+
+#[derive(Default)]
+struct LogSummary {
+    version: Option<String>,
+    step: Option<f64>,
+    time: Option<f64>,
+    frames: usize,
+    last: Map<String, Value>,
+    averages: Option<Map<String, Value>>,
+}
+
+fn parse_log(content: &str) -> LogSummary {
+    let mut summary = LogSummary::default();
+    let mut averaging = false;
+    let mut lines = content.lines().peekable();
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        if let Some(version) = trimmed
+            .strip_prefix("GROMACS version:")
+            .or_else(|| trimmed.split_once(", version ").map(|(_, version)| version))
+        {
+            summary
+                .version
+                .get_or_insert_with(|| version.trim().to_string());
+        } else if trimmed.contains(AVERAGES_MARKER) {
+            averaging = true;
+        } else if !averaging && trimmed.split_whitespace().eq(["Step", "Time"]) {
+            let values: Vec<f64> = lines
+                .next()
+                .unwrap_or("")
+                .split_whitespace()
+                .filter_map(|value| value.parse().ok())
+                .collect();
+            if let [step, time] = values[..] {
+                summary.step = Some(step);
+                summary.time = Some(time);
+            }
+        } else if trimmed == ENERGY_HEADER {
+            let mut block = Map::new();
+            while let (Some(header), Some(values)) = (lines.next(), lines.peek()) {
+                if header.trim().is_empty() {
+                    break;
+                }
+                let names: Vec<String> = header
+                    .chars()
+                    .collect::<Vec<_>>()
+                    .chunks(LOG_COLUMN_WIDTH)
+                    .map(|chunk| chunk.iter().collect::<String>().trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .collect();
+                let numbers: Option<Vec<f64>> = values
+                    .split_whitespace()
+                    .map(|value| value.parse().ok())
+                    .collect();
+                match numbers {
+                    Some(numbers) if !names.is_empty() && numbers.len() == names.len() => {
+                        lines.next();
+                        block.extend(names.into_iter().zip(numbers.into_iter().map(Value::from)));
+                    }
+                    _ => break,
+                }
+            }
+            if averaging {
+                summary.averages = Some(block);
+            } else {
+                summary.last = block;
+                summary.frames += 1;
+            }
+        }
+    }
+    summary
+}
+
+/// `gmx energy` `.xvg` output: `@ sN legend` names the series, data rows
+/// hold time followed by one value per series.
+pub struct GromacsEnergyAdapter;
+
+impl IngestAdapter for GromacsEnergyAdapter {
+    fn name(&self) -> &'static str {
+        "gromacs_energy"
+    }
+
+    fn detect(&self, value: &Value) -> f64 {
+        let Some((file_name, content)) = source_text(value) else {
+            return 0.0;
+        };
+        if has_extension(file_name, "xvg") {
+            0.9
+        } else if content
+            .lines()
+            .any(|line| line.starts_with('@') && line.contains("legend"))
+        {
+            0.8
+        } else {
+            0.0
+        }
+    }
+
+    fn convert(&self, value: Value, opts: &IngestOptions) -> Result<UniversalSpan, IngestError> {
+        let (_, content) = source_text(&value).ok_or(IngestError::MissingField("source_text"))?;
+        let malformed = |message: String| IngestError::Malformed {
+            adapter: "gromacs_energy",
+            message,
+        };
+
+        let mut title = None;
+        let mut x_label = None;
+        let mut legends: Vec<(usize, String)> = Vec::new();
+        let mut rows: Vec<Vec<f64>> = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('&') {
+                continue;
+            }
+            if let Some(directive) = trimmed.strip_prefix('@') {
+                let quoted = directive
+                    .split_once('"')
+                    .map(|(_, rest)| rest.trim_end_matches('"').to_string());
+                let words: Vec<&str> = directive.split_whitespace().collect();
+                match words[..] {
+                    ["title", ..] => title = quoted,
+                    ["xaxis", "label", ..] => x_label = quoted,
+                    [series, "legend", ..] => {
+                        if let (Some(number), Some(name)) = (
+                            series.strip_prefix('s').and_then(|n| n.parse().ok()),
+                            quoted,
+                        ) {
+                            legends.push((number, name));
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            let row: Option<Vec<f64>> = trimmed
+                .split_whitespace()
+                .map(|value| value.parse().ok())
+                .collect();
+            let row =
+                row.ok_or_else(|| malformed(format!("line {}: non-numeric row", index + 1)))?;
+            rows.push(row);
+        }
+
+        let width = rows.iter().map(Vec::len).min().unwrap_or(0);
+        if width < 2 {
+            return Err(malformed(
+                "no data rows with a time and a value".to_string(),
+            ));
+        }
+        let series: Map<String, Value> = (1..width)
+            .map(|column| {
+                let name = legends
+                    .iter()
+                    .find(|(number, _)| *number == column - 1)
+                    .map_or_else(|| format!("s{}", column - 1), |(_, name)| name.clone());
+                let values: Vec<f64> = rows.iter().map(|row| row[column]).collect();
+                (name, series_summary(&values))
+            })
+            .collect();
+        let metrics = json!({
+            "title": title,
+            "x_label": x_label,
+            "frames": rows.len(),
+            "x_range": [rows[0][0], rows[rows.len() - 1][0]],
+            "series": series,
+        });
+        summary_span(self, "GROMACS energies", metrics, opts)
+    }
+
+    fn default_flow(&self) -> Option<&'static str> {
+        Some("molecular_dynamics")
+    }
+
+    fn default_workflow(&self) -> Option<&'static str> {
+        Some("gromacs")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{lift_text, AdapterRegistry};
+    use chrono::Utc;
+
+    const MD_LOG: &str = "\
+                      :-) GROMACS - gmx mdrun, 2023.1 (-:
+
+GROMACS:      gmx mdrun, version 2023.1
+
+           Step           Time
+          50000      100.00000
+
+   Energies (kJ/mol)
+          Angle    Proper Dih.        LJ (SR)   Coulomb (SR)      Potential
+    9.74139e+03    4.34956e+02    1.29844e+04   -4.29637e+05   -3.46487e+05
+    Kinetic En.   Total Energy    Temperature Pressure (bar)
+    6.45960e+04   -2.81891e+05    3.00416e+02   -7.72003e+01
+
+	<======  ###############  ==>
+	<====  A V E R A G E S  ====>
+	<==  ###############  ======>
+
+   Energies (kJ/mol)
+          Angle    Proper Dih.        LJ (SR)   Coulomb (SR)      Potential
+    9.70000e+03    4.30000e+02    1.30000e+04   -4.30000e+05   -3.47000e+05
+";
+
+    #[test]
+    fn reads_last_frame_and_averages_from_mdrun_log() {
+        let value = lift_text(Some("md.log"), MD_LOG);
+        let registry = AdapterRegistry::default();
+        let adapter = registry.detect(&value).unwrap();
+        assert_eq!(adapter.name(), "gromacs_log");
+
+        let opts = IngestOptions::new("md", "gromacs", Utc::now()).with_source("md.log");
+        let span = adapter.convert(value, &opts).unwrap();
+        let metrics = &span.payload["metrics"];
+        assert!(span.id.0.starts_with("span::gromacs_log::md::"));
+        assert_eq!(metrics["version"], "2023.1");
+        assert_eq!(metrics["step"], 50000.0);
+        assert_eq!(metrics["time_ps"], 100.0);
+        assert_eq!(metrics["energy_frames"], 1);
+        assert_eq!(metrics["final"]["Temperature"], 300.416);
+        assert_eq!(metrics["final"]["Pressure (bar)"], -77.2003);
+        assert_eq!(metrics["averages"]["Potential"], -347000.0);
+    }
+
+    #[test]
+    fn summarises_energy_xvg_series() {
+        let xvg = "\
+# This file was created by gmx energy
+@    title \"GROMACS Energies\"
+@    xaxis  label \"Time (ps)\"
+@ s0 legend \"Potential\"
+@ s1 legend \"Temperature\"
+    0.000000  -1000.0  298.0
+   10.000000  -1010.0  302.0
+";
+        let value = lift_text(Some("energy.xvg"), xvg);
+        assert_eq!(
+            AdapterRegistry::default().detect(&value).unwrap().name(),
+            "gromacs_energy"
+        );
+        let opts = IngestOptions::new("md", "gromacs", Utc::now());
+        let span = GromacsEnergyAdapter.convert(value, &opts).unwrap();
+        let metrics = &span.payload["metrics"];
+        assert_eq!(metrics["title"], "GROMACS Energies");
+        assert_eq!(metrics["x_range"], json!([0.0, 10.0]));
+        assert_eq!(metrics["series"]["Potential"]["mean"], -1005.0);
+        assert_eq!(metrics["series"]["Temperature"]["final"], 302.0);
+    }
+}
@@ -1,7 +1,18 @@
+pub mod alphafold;
+pub mod edr;
+pub mod gromacs;
+pub mod metric_table;
+pub mod openmm;
+pub mod registry;
+
+pub use registry::{lift_file, lift_text, source_text, AdapterRegistry, IngestAdapter};
+
 use chrono::{DateTime, Utc};
 use serde_json::Value;
+use spans_core::schema::{validate_span, SchemaMode, SchemaViolation};
 use spans_core::{span_from_json, UniversalSpan};
 use thiserror::Error;
+use tracing::warn;
 
 #[derive(Debug, Error)]
 pub enum IngestError {
@@ -9,6 +20,30 @@ pub enum IngestError {
     Parse(#[from] anyhow::Error),
     #[error("required field missing: {0}")]
     MissingField(&'static str),
+    #[error("no ingest adapter recognises the payload")]
+    NoAdapter,
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("not UTF-8 text: {0}")]
+    NotText(#[from] std::str::Utf8Error),
+    #[error("{adapter}: {message}")]
+    Malformed {
+        adapter: &'static str,
+        message: String,
+    },
+    #[error("span {span} violates its payload schema: {}", join_violations(.violations))]
+    Schema {
+        span: String,
+        violations: Vec<SchemaViolation>,
+    },
+}
+
+fn join_violations(violations: &[SchemaViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 #[derive(Debug, Clone)]
@@ -16,6 +51,10 @@ pub struct IngestOptions {
     pub flow: String,
     pub workflow: String,
     pub default_start: DateTime<Utc>,
+    /// Path or name of the ingested file, recorded on converted spans.
+    pub source: Option<String>,
+    /// What to do with spans whose payload violates its schema.
+    pub schema_mode: SchemaMode,
 }
 
 impl IngestOptions {
@@ -28,8 +67,20 @@ impl IngestOptions {
             flow: flow.into(),
             workflow: workflow.into(),
             default_start,
+            source: None,
+            schema_mode: SchemaMode::default(),
         }
     }
+
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    pub fn with_schema_mode(mut self, mode: SchemaMode) -> Self {
+        self.schema_mode = mode;
+        self
+    }
 }
 
 /// Ingest a payload into a UniversalSpan, applying LogLine Discovery conventions.
@@ -55,7 +106,28 @@ pub fn ingest_json(mut value: Value, opts: &IngestOptions) -> Result<UniversalSp
         );
     }
 
-    Ok(span_from_json(value)?)
+    check_schema(span_from_json(value)?, opts.schema_mode)
+}
+
+/// Applies `mode` to the span's payload schema violations.
+pub fn check_schema(span: UniversalSpan, mode: SchemaMode) -> Result<UniversalSpan, IngestError> {
+    if mode == SchemaMode::Off {
+        return Ok(span);
+    }
+    let violations = validate_span(&span);
+    if violations.is_empty() {
+        return Ok(span);
+    }
+    if mode == SchemaMode::Strict {
+        return Err(IngestError::Schema {
+            span: span.id.0,
+            violations,
+        });
+    }
+    for violation in &violations {
+        warn!(span = %span.id.0, path = %violation.path, "{}", violation.message);
+    }
+    Ok(span)
 }
 
 /// Specialized adapter for LogLine Fold span payloads.
@@ -122,4 +194,21 @@ mod tests {
         assert_eq!(span.workflow, "fold_pipeline");
         assert_eq!(span.started_at.to_rfc3339(), "2025-09-27T19:51:10+00:00");
     }
+
+    #[test]
+    fn schema_mode_decides_the_fate_of_malformed_payloads() {
+        let raw = json!({ "span_id": "m1", "metadata": { "value": "high" } });
+        let opts = IngestOptions::new("metric", "qc", Utc::now());
+
+        let strict = opts.clone().with_schema_mode(SchemaMode::Strict);
+        match ingest_json(raw.clone(), &strict) {
+            Err(IngestError::Schema { span, violations }) => {
+                assert_eq!(span, "m1");
+                assert_eq!(violations.len(), 2);
+            }
+            other => panic!("expected a schema error, got {other:?}"),
+        }
+        assert!(ingest_json(raw.clone(), &opts).is_ok());
+        assert!(ingest_json(raw, &opts.with_schema_mode(SchemaMode::Off)).is_ok());
+    }
 }
@@ -0,0 +1,124 @@
+//! Plain CSV metric tables: a header row naming the columns, then one row
+//! per sample. Numeric columns are summarised; text columns are skipped.
+
+use serde_json::{json, Map, Value};
+use spans_core::UniversalSpan;
+
+use crate::registry::{has_extension, source_text, summary_span, IngestAdapter};
+use crate::{IngestError, IngestOptions};
+
+/// Parsed table with every cell kept as text.
+pub(crate) struct MetricTable {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl MetricTable {
+    pub(crate) fn parse(text: &str) -> Result<Self, csv::Error> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(text.as_bytes());
+        let columns = reader.headers()?.iter().map(str::to_string).collect();
+        let rows = reader
+            .records()
+            .map(|record| record.map(|record| record.iter().map(str::to_string).collect()))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { columns, rows })
+    }
+
+    /// `(column, values)` of every column whose non-empty cells all parse
+    /// as finite numbers.
+    fn numeric_columns(&self) -> Vec<(&str, Vec<f64>)> {
+        self.columns
+            .iter()
+            .enumerate()
+            .filter_map(|(index, column)| {
+                let values: Option<Vec<f64>> = self
+                    .rows
+                    .iter()
+                    .map(|row| row[index].as_str())
+                    .filter(|cell| !cell.is_empty())
+                    .map(|cell| cell.parse::<f64>().ok().filter(|value| value.is_finite()))
+                    .collect();
+                values
+                    .filter(|values| !values.is_empty())
+                    .map(|values| (column.as_str(), values))
+            })
+            .collect()
+    }
+
+    /// Row count and `mean`/`min`/`max`/`final` of each numeric column.
+    pub(crate) fn summary(&self) -> Value {
+        let columns: Map<String, Value> = self
+            .numeric_columns()
+            .into_iter()
+            .map(|(column, values)| (column.to_string(), series_summary(&values)))
+            .collect();
+        json!({ "rows": self.rows.len(), "columns": columns })
+    }
+}
+
+/// `mean`/`min`/`max`/`final` of a non-empty series.
+pub(crate) fn series_summary(values: &[f64]) -> Value {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    json!({ "mean": mean, "min": min, "max": max, "final": values.last() })
+}
+
+/// Any CSV file with a header and at least one numeric column.
+pub struct CsvTableAdapter;
+
+impl IngestAdapter for CsvTableAdapter {
+    fn name(&self) -> &'static str {
+        "csv_table"
+    }
+
+    fn detect(&self, value: &Value) -> f64 {
+        let Some((file_name, content)) = source_text(value) else {
+            return 0.0;
+        };
+        match MetricTable::parse(content) {
+            Ok(table) if !table.rows.is_empty() && !table.numeric_columns().is_empty() => {
+                if has_extension(file_name, "csv") {
+                    0.5
+                } else {
+                    0.3
+                }
+            }
+            _ => 0.0,
+        }
+    }
+
+    fn convert(&self, value: Value, opts: &IngestOptions) -> Result<UniversalSpan, IngestError> {
+        let (_, content) = source_text(&value).ok_or(IngestError::MissingField("source_text"))?;
+        let table = MetricTable::parse(content).map_err(|err| IngestError::Malformed {
+            adapter: self.name(),
+            message: err.to_string(),
+        })?;
+        summary_span(self, "metric table", table.summary(), opts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::lift_text;
+    use chrono::Utc;
+
+    #[test]
+    fn summarises_numeric_columns() {
+        let text = "sample,rmsd,label\n1,2.5,a\n2,1.5,b\n3,,c\n";
+        let value = lift_text(Some("scores.csv"), text);
+        assert_eq!(CsvTableAdapter.detect(&value), 0.5);
+        let opts = IngestOptions::new("metrics", "import", Utc::now()).with_source("scores.csv");
+        let span = CsvTableAdapter.convert(value, &opts).unwrap();
+        let metrics = &span.payload["metrics"];
+        assert_eq!(metrics["rows"], 3);
+        assert_eq!(metrics["columns"]["rmsd"]["mean"], 2.0);
+        assert_eq!(metrics["columns"]["sample"]["final"], 3.0);
+        assert!(metrics["columns"].get("label").is_none());
+
+        assert_eq!(CsvTableAdapter.detect(&lift_text(None, "a,b\nx,y\n")), 0.0);
+    }
+}
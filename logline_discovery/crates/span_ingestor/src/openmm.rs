@@ -0,0 +1,83 @@
+//! OpenMM `StateDataReporter` CSV output.
+//!
+//! The reporter writes a `#`-prefixed, quoted header such as
+//! `#"Step","Time (ps)","Potential Energy (kJ/mole)","Temperature (K)"`
+//! followed by one comma-separated row per report interval.
+
+use serde_json::Value;
+use spans_core::UniversalSpan;
+
+use crate::metric_table::MetricTable;
+use crate::registry::{source_text, summary_span, IngestAdapter};
+use crate::{IngestError, IngestOptions};
+
+/// Columns only the state reporter writes.
+const REPORTER_COLUMNS: [&str; 4] = [
+    "\"Step\"",
+    "\"Time (ps)\"",
+    "(kJ/mole)",
+    "\"Temperature (K)\"",
+];
+
+pub struct OpenMmReporterAdapter;
+
+impl IngestAdapter for OpenMmReporterAdapter {
+    fn name(&self) -> &'static str {
+        "openmm_state"
+    }
+
+    fn detect(&self, value: &Value) -> f64 {
+        let Some((_, content)) = source_text(value) else {
+            return 0.0;
+        };
+        let header = content.lines().next().unwrap_or("");
+        if header.starts_with("#\"") && REPORTER_COLUMNS.iter().any(|c| header.contains(c)) {
+            0.9
+        } else {
+            0.0
+        }
+    }
+
+    fn convert(&self, value: Value, opts: &IngestOptions) -> Result<UniversalSpan, IngestError> {
+        let (_, content) = source_text(&value).ok_or(IngestError::MissingField("source_text"))?;
+        let content = content.strip_prefix('#').unwrap_or(content);
+        let table = MetricTable::parse(content).map_err(|err| IngestError::Malformed {
+            adapter: self.name(),
+            message: err.to_string(),
+        })?;
+        summary_span(self, "OpenMM run", table.summary(), opts)
+    }
+
+    fn default_flow(&self) -> Option<&'static str> {
+        Some("molecular_dynamics")
+    }
+
+    fn default_workflow(&self) -> Option<&'static str> {
+        Some("openmm")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{lift_text, AdapterRegistry};
+    use chrono::Utc;
+
+    #[test]
+    fn summarises_reporter_columns() {
+        let text = "#\"Step\",\"Time (ps)\",\"Potential Energy (kJ/mole)\",\"Temperature (K)\"\n\
+                    1000,2.0,-37890.5,298.0\n\
+                    2000,4.0,-37910.5,302.0\n";
+        let value = lift_text(Some("state.csv"), text);
+        let registry = AdapterRegistry::default();
+        let adapter = registry.detect(&value).unwrap();
+        assert_eq!(adapter.name(), "openmm_state");
+
+        let opts = IngestOptions::new("md", "openmm", Utc::now()).with_source("state.csv");
+        let span = adapter.convert(value, &opts).unwrap();
+        let columns = &span.payload["metrics"]["columns"];
+        assert_eq!(columns["Step"]["final"], 2000.0);
+        assert_eq!(columns["Temperature (K)"]["mean"], 300.0);
+        assert_eq!(columns["Potential Energy (kJ/mole)"]["min"], -37910.5);
+    }
+}
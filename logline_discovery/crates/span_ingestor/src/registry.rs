@@ -0,0 +1,296 @@
+//! Adapter trait and registry for picking how a payload becomes a span.
+//!
+//! Every input is seen as a [`serde_json::Value`]: JSON documents as parsed,
+//! `.edr` energy files as decoded by [`read_edr`], anything else wrapped by
+//! [`lift_text`]. Each registered adapter scores how
+//! sure it is that it understands the value, and the most confident one
+//! converts it.
+
+use std::path::Path;
+
+use serde_json::{json, Value};
+use spans_core::identity::content_hash_of;
+use spans_core::UniversalSpan;
+
+use crate::alphafold::{AlphaFoldRankingAdapter, PlddtAdapter};
+use crate::edr::{read_edr, GromacsEdrAdapter};
+use crate::gromacs::{GromacsEnergyAdapter, GromacsLogAdapter};
+use crate::metric_table::CsvTableAdapter;
+use crate::openmm::OpenMmReporterAdapter;
+use crate::{ingest_fold_json, ingest_json, IngestError, IngestOptions};
+
+/// Key of the object [`lift_text`] wraps non-JSON input in.
+const SOURCE_TEXT: &str = "source_text";
+
+/// Translates one kind of instrument output into a [`UniversalSpan`].
+pub trait IngestAdapter: Send + Sync {
+    /// Stable name recorded on converted spans, e.g. `gromacs_log`.
+    fn name(&self) -> &'static str;
+
+    /// Confidence in `[0, 1]` that this adapter understands `value`;
+    /// `0.0` declines it.
+    fn detect(&self, value: &Value) -> f64;
+
+    fn convert(&self, value: Value, opts: &IngestOptions) -> Result<UniversalSpan, IngestError>;
+
+    /// Flow used when neither the caller nor the payload names one.
+    fn default_flow(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn default_workflow(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+/// Adapters tried in registration order; ties go to the earlier one.
+pub struct AdapterRegistry {
+    adapters: Vec<Box<dyn IngestAdapter>>,
+}
+
+impl Default for AdapterRegistry {
+    /// Registry with every built-in adapter.
+    fn default() -> Self {
+        Self::empty()
+            .with_adapter(FoldJsonAdapter)
+            .with_adapter(SpanJsonAdapter)
+            .with_adapter(GromacsLogAdapter)
+            .with_adapter(GromacsEnergyAdapter)
+            .with_adapter(GromacsEdrAdapter)
+            .with_adapter(OpenMmReporterAdapter)
+            .with_adapter(AlphaFoldRankingAdapter)
+            .with_adapter(PlddtAdapter)
+            .with_adapter(CsvTableAdapter)
+    }
+}
+
+impl AdapterRegistry {
+    pub fn empty() -> Self {
+        Self {
+            adapters: Vec::new(),
+        }
+    }
+
+    pub fn with_adapter(mut self, adapter: impl IngestAdapter + 'static) -> Self {
+        self.register(adapter);
+        self
+    }
+
+    pub fn register(&mut self, adapter: impl IngestAdapter + 'static) {
+        self.adapters.push(Box::new(adapter));
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.adapters.iter().map(|adapter| adapter.name()).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn IngestAdapter> {
+        self.adapters
+            .iter()
+            .find(|adapter| adapter.name() == name)
+            .map(|adapter| adapter.as_ref())
+    }
+
+    /// Most confident adapter for `value`, if any scores above zero.
+    pub fn detect(&self, value: &Value) -> Option<&dyn IngestAdapter> {
+        let mut best: Option<(&dyn IngestAdapter, f64)> = None;
+        for adapter in &self.adapters {
+            let confidence = adapter.detect(value);
+            if confidence > best.map_or(0.0, |(_, score)| score) {
+                best = Some((adapter.as_ref(), confidence));
+            }
+        }
+        best.map(|(adapter, _)| adapter)
+    }
+
+    /// Converts `value` with the most confident adapter.
+    pub fn ingest(&self, value: Value, opts: &IngestOptions) -> Result<UniversalSpan, IngestError> {
+        self.detect(&value)
+            .ok_or(IngestError::NoAdapter)?
+            .convert(value, opts)
+    }
+}
+
+/// Wraps file contents for detection: JSON text is parsed as is, anything
+/// else becomes `{"source_text": {"file_name": ..., "content": ...}}`.
+pub fn lift_text(file_name: Option<&str>, text: &str) -> Value {
+    serde_json::from_str(text)
+        .unwrap_or_else(|_| json!({ SOURCE_TEXT: { "file_name": file_name, "content": text } }))
+}
+
+/// Reads a file's bytes for detection. `.edr` files are decoded with
+/// [`read_edr`], a `.json` file must parse, and other text goes through
+/// [`lift_text`].
+pub fn lift_file(file_name: Option<&str>, bytes: &[u8]) -> Result<Value, IngestError> {
+    if has_extension(file_name, "edr") {
+        return read_edr(bytes);
+    }
+    let text = std::str::from_utf8(bytes)?;
+    if has_extension(file_name, "json") {
+        Ok(serde_json::from_str(text)?)
+    } else {
+        Ok(lift_text(file_name, text))
+    }
+}
+
+/// File name and content of a value built by [`lift_text`].
+pub fn source_text(value: &Value) -> Option<(Option<&str>, &str)> {
+    let source = value.get(SOURCE_TEXT)?;
+    let content = source.get("content")?.as_str()?;
+    Some((source.get("file_name").and_then(Value::as_str), content))
+}
+
+/// Whether a lifted file name has `extension` (case-insensitive).
+pub(crate) fn has_extension(file_name: Option<&str>, extension: &str) -> bool {
+    file_name
+        .and_then(|name| Path::new(name).extension())
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
+
+/// Span payload for a converted instrument output. The name comes from the
+/// source file stem, or the start time when there is none; the id adds the
+/// content hash of the payload, so outputs sharing a file name (every
+/// `md.log`) stay apart while re-ingesting one file reproduces its id.
+pub(crate) fn summary_span(
+    adapter: &dyn IngestAdapter,
+    label: &str,
+    metrics: Value,
+    opts: &IngestOptions,
+) -> Result<UniversalSpan, IngestError> {
+    let stem = opts
+        .source
+        .as_deref()
+        .and_then(|source| Path::new(source).file_stem())
+        .and_then(|stem| stem.to_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| opts.default_start.format("%Y%m%dT%H%M%SZ").to_string());
+    let mut payload = json!({
+        "source": { "adapter": adapter.name(), "file": opts.source },
+        "metrics": metrics,
+    });
+    let digest = content_hash_of(&payload);
+    let id = format!("span::{}::{stem}::{}", adapter.name(), &digest[..16]);
+    payload["span_id"] = json!(id);
+    payload["name"] = json!(format!("{stem} {label}"));
+    ingest_json(payload, opts)
+}
+
+/// Payloads that already follow the span schema (`span_id`/`id` plus a
+/// timestamp, or any object as a last resort).
+pub struct SpanJsonAdapter;
+
+impl IngestAdapter for SpanJsonAdapter {
+    fn name(&self) -> &'static str {
+        "span_json"
+    }
+
+    fn detect(&self, value: &Value) -> f64 {
+        if source_text(value).is_some() {
+            return 0.0;
+        }
+        match value.as_object() {
+            Some(object) if object.contains_key("span_id") || object.contains_key("id") => 0.6,
+            Some(_) => 0.1,
+            None => 0.0,
+        }
+    }
+
+    fn convert(&self, value: Value, opts: &IngestOptions) -> Result<UniversalSpan, IngestError> {
+        ingest_json(value, opts)
+    }
+}
+
+/// LogLine Fold reports, recognised by their `protein_metadata` block.
+pub struct FoldJsonAdapter;
+
+impl IngestAdapter for FoldJsonAdapter {
+    fn name(&self) -> &'static str {
+        "fold_json"
+    }
+
+    fn detect(&self, value: &Value) -> f64 {
+        if value.get("protein_metadata").is_some() {
+            0.9
+        } else {
+            0.0
+        }
+    }
+
+    fn convert(&self, value: Value, opts: &IngestOptions) -> Result<UniversalSpan, IngestError> {
+        ingest_fold_json(value, opts)
+    }
+
+    fn default_flow(&self) -> Option<&'static str> {
+        Some("protein_folding")
+    }
+
+    fn default_workflow(&self) -> Option<&'static str> {
+        Some("fold_pipeline")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    struct Fixed(&'static str, f64);
+
+    impl IngestAdapter for Fixed {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn detect(&self, _: &Value) -> f64 {
+            self.1
+        }
+
+        fn convert(&self, _: Value, opts: &IngestOptions) -> Result<UniversalSpan, IngestError> {
+            summary_span(self, "fixed", json!({}), opts)
+        }
+    }
+
+    #[test]
+    fn registry_picks_the_most_confident_adapter() {
+        let registry = AdapterRegistry::empty()
+            .with_adapter(Fixed("low", 0.2))
+            .with_adapter(Fixed("high", 0.7))
+            .with_adapter(Fixed("tie", 0.7));
+        assert_eq!(registry.detect(&json!({})).unwrap().name(), "high");
+        assert!(AdapterRegistry::empty().detect(&json!({})).is_none());
+
+        let opts = IngestOptions::new("flow", "workflow", Utc::now()).with_source("runs/md.log");
+        let span = registry.ingest(json!({}), &opts).unwrap();
+        assert!(span.id.0.starts_with("span::high::md::"), "{}", span.id.0);
+        assert_eq!(span.payload["source"]["file"], "runs/md.log");
+        let again = registry.ingest(json!({}), &opts).unwrap();
+        assert_eq!(again.id, span.id);
+        let other_run = opts.clone().with_source("other/md.log");
+        assert_ne!(registry.ingest(json!({}), &other_run).unwrap().id, span.id);
+    }
+
+    #[test]
+    fn builtin_registry_keeps_span_and_fold_payloads() {
+        let registry = AdapterRegistry::default();
+        let fold = json!({ "span_id": "f", "protein_metadata": { "target": "gp41" } });
+        assert_eq!(registry.detect(&fold).unwrap().name(), "fold_json");
+        let span = json!({ "span_id": "s", "timestamp": "2025-09-29T10:00:00Z" });
+        assert_eq!(registry.detect(&span).unwrap().name(), "span_json");
+        let text = lift_text(Some("notes.txt"), "free-form notes");
+        assert!(registry.detect(&text).is_none());
+        assert!(matches!(
+            registry.ingest(text, &IngestOptions::new("f", "w", Utc::now())),
+            Err(IngestError::NoAdapter)
+        ));
+    }
+
+    #[test]
+    fn malformed_json_files_report_the_parse_error() {
+        let error = lift_file(Some("run.json"), b"{\"span_id\": ").unwrap_err();
+        assert!(matches!(error, IngestError::Json(_)), "{error}");
+        assert!(error.to_string().contains("EOF"), "{error}");
+        let text = lift_file(Some("run.txt"), b"{\"span_id\": ").unwrap();
+        assert!(source_text(&text).is_some());
+    }
+}
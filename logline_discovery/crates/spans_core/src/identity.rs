@@ -0,0 +1,116 @@
+//! Content-addressed span identity.
+//!
+//! A span's content hash is the BLAKE3 digest of its canonical JSON: the
+//! serialized span with object keys sorted, no insignificant whitespace
+//! and the `content_hash` field itself left out. Two spans with the same
+//! hash are byte-identical records whatever their producer's key order.
+
+use serde_json::Value;
+
+/// Key under which the hash is stored next to the span id.
+pub const CONTENT_HASH_KEY: &str = "content_hash";
+
+/// Compact JSON with object keys sorted at every level.
+pub fn canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(fields) => {
+            let mut keys: Vec<&String> = fields.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (index, key) in keys.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::from(key.as_str()).to_string());
+                out.push(':');
+                write_canonical(&fields[key], out);
+            }
+            out.push('}');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+/// Hex BLAKE3 digest of a serialized span record, ignoring any top-level
+/// `content_hash` it already carries.
+pub fn content_hash_of(record: &Value) -> String {
+    let digest = match record {
+        Value::Object(fields) if fields.contains_key(CONTENT_HASH_KEY) => {
+            let mut fields = fields.clone();
+            fields.remove(CONTENT_HASH_KEY);
+            blake3::hash(canonical_json(&Value::Object(fields)).as_bytes())
+        }
+        other => blake3::hash(canonical_json(other).as_bytes()),
+    };
+    digest.to_hex().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UniversalSpan;
+    use chrono::Utc;
+    use serde_json::json;
+
+    #[test]
+    fn canonical_json_sorts_keys_at_every_level() {
+        let value: Value =
+            serde_json::from_str(r#"{ "b": [ {"z": 1, "a": "x\"y"} ], "a": null }"#).unwrap();
+        assert_eq!(
+            canonical_json(&value),
+            r#"{"a":null,"b":[{"a":"x\"y","z":1}]}"#
+        );
+    }
+
+    #[test]
+    fn content_hash_ignores_the_stored_hash_and_tracks_content() {
+        let span = UniversalSpan::new("s1", "scan", "metric", "qc", Utc::now(), json!({ "v": 1 }));
+        let hashed = span.clone().with_content_hash();
+        let hash = hashed.content_hash.clone().unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hashed.compute_content_hash(), hash);
+        assert_eq!(
+            content_hash_of(&serde_json::to_value(&hashed).unwrap()),
+            hash
+        );
+
+        let mut changed = span;
+        changed.payload["v"] = json!(2);
+        assert_ne!(changed.compute_content_hash(), hash);
+
+        // A stale stored hash is replaced and handed back.
+        let mut stale = hashed.clone();
+        stale.payload["v"] = json!(2);
+        assert_eq!(stale.rehash(), Some(hash.clone()));
+        assert_eq!(stale.content_hash, Some(changed.compute_content_hash()));
+        assert_eq!(stale.rehash(), None);
+    }
+
+    #[test]
+    fn span_from_json_rejects_a_stale_supplied_hash() {
+        let mut record = json!({ "span_id": "s1", "timestamp": "2025-09-29T10:00:00Z", "v": 1 });
+        record[CONTENT_HASH_KEY] = json!(content_hash_of(&record));
+        let span = crate::span_from_json(record.clone()).unwrap();
+        assert_eq!(span.content_hash, None);
+
+        record["v"] = json!(2);
+        let err = crate::span_from_json(record).unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{err}");
+    }
+}
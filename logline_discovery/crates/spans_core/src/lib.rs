@@ -1,3 +1,7 @@
+pub mod identity;
+pub mod otlp;
+pub mod schema;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -24,6 +28,9 @@ pub struct CausalLinks {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UniversalSpan {
     pub id: SpanId,
+    /// BLAKE3 digest of the span's canonical JSON; see [`identity`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
     pub name: String,
     pub flow: String,
     pub workflow: String,
@@ -44,6 +51,7 @@ impl UniversalSpan {
     ) -> Self {
         Self {
             id: SpanId::new(id),
+            content_hash: None,
             name: name.into(),
             flow: flow.into(),
             workflow: workflow.into(),
@@ -68,6 +76,27 @@ impl UniversalSpan {
         self.causal.related_ids.push(related);
         self
     }
+
+    /// Content hash of the span as it stands, ignoring any stored one.
+    pub fn compute_content_hash(&self) -> String {
+        let record = serde_json::to_value(self).expect("spans serialize to JSON");
+        identity::content_hash_of(&record)
+    }
+
+    /// Stores the current content hash next to the span id.
+    pub fn with_content_hash(mut self) -> Self {
+        self.content_hash = Some(self.compute_content_hash());
+        self
+    }
+
+    /// Replaces the stored content hash with one computed from the span as
+    /// it stands. Returns the stored hash when it did not match.
+    pub fn rehash(&mut self) -> Option<String> {
+        let hash = self.compute_content_hash();
+        self.content_hash
+            .replace(hash.clone())
+            .filter(|stored| *stored != hash)
+    }
 }
 
 /// Helper to construct spans that wrap external systems (e.g., LogLine Warp spans).
@@ -123,6 +152,19 @@ pub fn span_from_json(value: serde_json::Value) -> anyhow::Result<UniversalSpan>
         })
         .unwrap_or_default();
 
+    // A supplied hash covers the record as sent, not the span wrapping it,
+    // so it is checked here rather than carried over.
+    if let Some(supplied) = value
+        .get(identity::CONTENT_HASH_KEY)
+        .and_then(|v| v.as_str())
+    {
+        let computed = identity::content_hash_of(&value);
+        anyhow::ensure!(
+            supplied == computed,
+            "span {id}: content_hash {supplied} does not match its content ({computed})"
+        );
+    }
+
     let mut span = UniversalSpan::new(id, name, flow, workflow, started_at, value.clone());
 
     if let Some(ts) = finished_at {
@@ -0,0 +1,625 @@
+//! OpenTelemetry (OTLP) import and export for [`UniversalSpan`].
+//!
+//! Each field of a span has a natural OTLP home: `flow` is the resource's
+//! `service.name`, `workflow` the instrumentation scope name, the parent
+//! becomes `parentSpanId`, related spans become links and the top-level
+//! payload keys become attributes. Ids that are not already 16-digit hex
+//! are hashed into OTLP ids and the original kept in a `logline.*`
+//! attribute, which import reads back.
+//!
+//! OTLP fields a [`UniversalSpan`] has no home for (trace state, kind,
+//! status, events, foreign resources and so on) are kept under the
+//! payload's `otel` key, recording only what differs from what export
+//! would derive, so both directions round-trip.
+
+mod proto;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+pub use proto::*;
+
+use crate::{SpanId, UniversalSpan};
+
+/// Payload key holding the OTLP fields a span cannot express itself.
+pub const OTEL_PAYLOAD_KEY: &str = "otel";
+
+const SERVICE_NAME: &str = "service.name";
+const SPAN_ID_ATTRIBUTE: &str = "logline.span_id";
+const PARENT_ID_ATTRIBUTE: &str = "logline.parent_id";
+const RELATED_IDS_ATTRIBUTE: &str = "logline.related_ids";
+const FLOW_ATTRIBUTE: &str = "logline.flow";
+const WORKFLOW_ATTRIBUTE: &str = "logline.workflow";
+const CONTENT_HASH_ATTRIBUTE: &str = "logline.content_hash";
+/// Carries payloads that are not JSON objects.
+const PAYLOAD_ATTRIBUTE: &str = "logline.payload";
+const LOGLINE_PREFIX: &str = "logline.";
+
+const SPAN_KIND_INTERNAL: i32 = 1;
+
+#[derive(Debug, Error)]
+pub enum OtlpError {
+    #[error("invalid OTLP protobuf: {0}")]
+    Protobuf(#[from] prost::DecodeError),
+    #[error("invalid OTLP JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("span {span}: timestamp {nanos}ns is out of range")]
+    Timestamp { span: String, nanos: u64 },
+    #[error("span {span}: invalid `otel` payload: {source}")]
+    Payload {
+        span: String,
+        source: serde_json::Error,
+    },
+}
+
+/// Wire encodings of OTLP/HTTP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtlpEncoding {
+    Protobuf,
+    Json,
+}
+
+impl OtlpEncoding {
+    /// Encoding named by a `Content-Type` header, ignoring parameters.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let media_type = content_type.split(';').next().unwrap_or("").trim();
+        match media_type.to_ascii_lowercase().as_str() {
+            "application/x-protobuf" | "application/protobuf" => Some(Self::Protobuf),
+            "application/json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Protobuf => "application/x-protobuf",
+            Self::Json => "application/json",
+        }
+    }
+
+    pub fn encode<M: prost::Message + Serialize>(self, message: &M) -> Vec<u8> {
+        match self {
+            Self::Protobuf => message.encode_to_vec(),
+            Self::Json => serde_json::to_vec(message).expect("OTLP messages serialize to JSON"),
+        }
+    }
+
+    pub fn decode<M: prost::Message + Default + DeserializeOwned>(
+        self,
+        bytes: &[u8],
+    ) -> Result<M, OtlpError> {
+        match self {
+            Self::Protobuf => Ok(M::decode(bytes)?),
+            Self::Json => Ok(serde_json::from_slice(bytes)?),
+        }
+    }
+}
+
+/// A span together with the resource and scope it is reported under.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Placement {
+    resource: Resource,
+    resource_schema_url: String,
+    scope: InstrumentationScope,
+    scope_schema_url: String,
+    span: Span,
+}
+
+/// Exports spans as one request, grouped by resource and scope in order of
+/// first appearance.
+pub fn to_otlp(spans: &[UniversalSpan]) -> Result<ExportTraceServiceRequest, OtlpError> {
+    let mut request = ExportTraceServiceRequest::default();
+    for span in spans {
+        let placement = export_span(span)?;
+        let resource = Some(placement.resource);
+        let index = match request.resource_spans.iter().position(|group| {
+            group.resource == resource && group.schema_url == placement.resource_schema_url
+        }) {
+            Some(index) => index,
+            None => {
+                request.resource_spans.push(ResourceSpans {
+                    resource,
+                    scope_spans: Vec::new(),
+                    schema_url: placement.resource_schema_url,
+                });
+                request.resource_spans.len() - 1
+            }
+        };
+        let scopes = &mut request.resource_spans[index].scope_spans;
+        let scope = Some(placement.scope);
+        match scopes
+            .iter_mut()
+            .find(|group| group.scope == scope && group.schema_url == placement.scope_schema_url)
+        {
+            Some(group) => group.spans.push(placement.span),
+            None => scopes.push(ScopeSpans {
+                scope,
+                spans: vec![placement.span],
+                schema_url: placement.scope_schema_url,
+            }),
+        }
+    }
+    Ok(request)
+}
+
+/// Imports every span of a request, in request order.
+pub fn from_otlp(request: &ExportTraceServiceRequest) -> Result<Vec<UniversalSpan>, OtlpError> {
+    let mut spans = Vec::new();
+    for resource_spans in &request.resource_spans {
+        for scope_spans in &resource_spans.scope_spans {
+            for span in &scope_spans.spans {
+                spans.push(import_span(Placement {
+                    resource: resource_spans.resource.clone().unwrap_or_default(),
+                    resource_schema_url: resource_spans.schema_url.clone(),
+                    scope: scope_spans.scope.clone().unwrap_or_default(),
+                    scope_schema_url: scope_spans.schema_url.clone(),
+                    span: span.clone(),
+                })?);
+            }
+        }
+    }
+    Ok(spans)
+}
+
+fn export_span(span: &UniversalSpan) -> Result<Placement, OtlpError> {
+    let mut placement = derive_placement(span);
+    let overrides = span
+        .payload
+        .get(OTEL_PAYLOAD_KEY)
+        .filter(|overrides| overrides.is_object());
+    if let Some(overrides) = overrides {
+        let mut merged = serde_json::to_value(&placement)?;
+        merge(&mut merged, overrides);
+        placement = serde_json::from_value(merged).map_err(|source| OtlpError::Payload {
+            span: span.id.0.clone(),
+            source,
+        })?;
+    }
+    if overrides.is_none_or(|overrides| overrides["span"].get("attributes").is_none()) {
+        placement.span.attributes = derive_attributes(span, &placement);
+    }
+    Ok(placement)
+}
+
+/// The OTLP form of `span` from its own fields, ignoring `otel` overrides
+/// and attributes.
+fn derive_placement(span: &UniversalSpan) -> Placement {
+    let trace_id = md5::compute(&span.workflow).0.to_vec();
+    let links = span
+        .causal
+        .related_ids
+        .iter()
+        .map(|related| Link {
+            trace_id: trace_id.clone(),
+            span_id: span_id_bytes(&related.0),
+            ..Link::default()
+        })
+        .collect();
+    Placement {
+        resource: Resource {
+            attributes: vec![key_value(SERVICE_NAME, Value::from(span.flow.as_str()))],
+            ..Resource::default()
+        },
+        scope: InstrumentationScope {
+            name: span.workflow.clone(),
+            ..InstrumentationScope::default()
+        },
+        span: Span {
+            trace_id,
+            span_id: span_id_bytes(&span.id.0),
+            parent_span_id: span
+                .causal
+                .parent_id
+                .as_ref()
+                .map(|parent| span_id_bytes(&parent.0))
+                .unwrap_or_default(),
+            name: span.name.clone(),
+            kind: SPAN_KIND_INTERNAL,
+            start_time_unix_nano: unix_nanos(span.started_at),
+            end_time_unix_nano: span.finished_at.map_or(0, unix_nanos),
+            links,
+            ..Span::default()
+        },
+        ..Placement::default()
+    }
+}
+
+/// Payload attributes, plus `logline.*` attributes for whatever import
+/// could not recover from `placement` alone; sorted by key.
+fn derive_attributes(span: &UniversalSpan, placement: &Placement) -> Vec<KeyValue> {
+    let imported = imported_fields(placement);
+    let mut attributes = Vec::new();
+    if imported.id != span.id.0 {
+        attributes.push(key_value(
+            SPAN_ID_ATTRIBUTE,
+            Value::from(span.id.0.as_str()),
+        ));
+    }
+    let parent = span
+        .causal
+        .parent_id
+        .as_ref()
+        .map(|parent| parent.0.clone());
+    if imported.parent_id != parent {
+        attributes.push(key_value(PARENT_ID_ATTRIBUTE, Value::from(parent)));
+    }
+    let related: Vec<String> = span
+        .causal
+        .related_ids
+        .iter()
+        .map(|related| related.0.clone())
+        .collect();
+    if imported.related_ids != related {
+        attributes.push(key_value(RELATED_IDS_ATTRIBUTE, Value::from(related)));
+    }
+    if imported.flow != span.flow {
+        attributes.push(key_value(FLOW_ATTRIBUTE, Value::from(span.flow.as_str())));
+    }
+    if imported.workflow != span.workflow {
+        attributes.push(key_value(
+            WORKFLOW_ATTRIBUTE,
+            Value::from(span.workflow.as_str()),
+        ));
+    }
+    if let Some(hash) = &span.content_hash {
+        attributes.push(key_value(
+            CONTENT_HASH_ATTRIBUTE,
+            Value::from(hash.as_str()),
+        ));
+    }
+    match &span.payload {
+        Value::Object(payload) => attributes.extend(
+            payload
+                .iter()
+                .filter(|(key, _)| key.as_str() != OTEL_PAYLOAD_KEY)
+                .map(|(key, value)| key_value(key, value.clone())),
+        ),
+        other => attributes.push(key_value(PAYLOAD_ATTRIBUTE, other.clone())),
+    }
+    attributes.sort_by(|a, b| a.key.cmp(&b.key));
+    attributes
+}
+
+/// Identity fields import reads from the OTLP structure, before any
+/// `logline.*` attribute overrides them.
+struct ImportedFields {
+    id: String,
+    parent_id: Option<String>,
+    related_ids: Vec<String>,
+    flow: String,
+    workflow: String,
+}
+
+fn imported_fields(placement: &Placement) -> ImportedFields {
+    let span = &placement.span;
+    let flow = placement
+        .resource
+        .attributes
+        .iter()
+        .find(|attribute| attribute.key == SERVICE_NAME)
+        .and_then(|attribute| match &attribute.value.as_ref()?.value {
+            Some(any_value::Value::StringValue(name)) => Some(name.clone()),
+            _ => None,
+        });
+    ImportedFields {
+        id: hex::encode(&span.span_id),
+        parent_id: (!span.parent_span_id.is_empty()).then(|| hex::encode(&span.parent_span_id)),
+        related_ids: span
+            .links
+            .iter()
+            .map(|link| hex::encode(&link.span_id))
+            .collect(),
+        flow: flow.unwrap_or_else(|| "unknown_flow".to_string()),
+        workflow: Some(placement.scope.name.clone())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "unknown_workflow".to_string()),
+    }
+}
+
+fn import_span(mut placement: Placement) -> Result<UniversalSpan, OtlpError> {
+    placement.span.attributes.sort_by(|a, b| a.key.cmp(&b.key));
+    let fields = imported_fields(&placement);
+    let otlp = &placement.span;
+
+    let mut payload = Map::new();
+    let mut id = fields.id;
+    let mut parent_id = fields.parent_id;
+    let mut related_ids = fields.related_ids;
+    let mut flow = fields.flow;
+    let mut workflow = fields.workflow;
+    let mut scalar_payload = None;
+    let mut content_hash = None;
+    for attribute in &otlp.attributes {
+        let value = attribute.value.as_ref().map_or(Value::Null, any_to_json);
+        match attribute.key.as_str() {
+            SPAN_ID_ATTRIBUTE => id = value.as_str().map_or(id, str::to_string),
+            PARENT_ID_ATTRIBUTE => parent_id = value.as_str().map(str::to_string),
+            RELATED_IDS_ATTRIBUTE => {
+                related_ids = value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            }
+            FLOW_ATTRIBUTE => flow = value.as_str().map_or(flow, str::to_string),
+            WORKFLOW_ATTRIBUTE => workflow = value.as_str().map_or(workflow, str::to_string),
+            PAYLOAD_ATTRIBUTE => scalar_payload = Some(value),
+            CONTENT_HASH_ATTRIBUTE => content_hash = value.as_str().map(str::to_string),
+            key if key.starts_with(LOGLINE_PREFIX) => {}
+            key => {
+                payload.insert(key.to_string(), value);
+            }
+        }
+    }
+
+    let started_at = from_unix_nanos(&id, otlp.start_time_unix_nano)?;
+    let mut span = UniversalSpan::new(
+        id,
+        otlp.name.clone(),
+        flow,
+        workflow,
+        started_at,
+        scalar_payload.unwrap_or(Value::Object(payload)),
+    );
+    if otlp.end_time_unix_nano != 0 {
+        let finished_at = from_unix_nanos(&span.id.0, otlp.end_time_unix_nano)?;
+        span = span.with_finish_time(finished_at);
+    }
+    span.causal.parent_id = parent_id.map(SpanId);
+    span.causal.related_ids = related_ids.into_iter().map(SpanId).collect();
+    span.content_hash = content_hash;
+
+    let mut derived = derive_placement(&span);
+    derived.span.attributes = derive_attributes(&span, &placement);
+    let overrides = diff(
+        &serde_json::to_value(&placement)?,
+        &serde_json::to_value(&derived)?,
+    );
+    if let Some(overrides) = overrides {
+        if let Value::Object(payload) = &mut span.payload {
+            payload.insert(OTEL_PAYLOAD_KEY.to_string(), overrides);
+        }
+    }
+    Ok(span)
+}
+
+/// The parts of `actual` that differ from `derived`, descending into
+/// objects; `None` when they are equal.
+fn diff(actual: &Value, derived: &Value) -> Option<Value> {
+    match (actual, derived) {
+        (Value::Object(actual), Value::Object(derived)) => {
+            let changed: Map<String, Value> = actual
+                .iter()
+                .filter_map(|(key, value)| {
+                    let derived = derived.get(key).unwrap_or(&Value::Null);
+                    Some((key.clone(), diff(value, derived)?))
+                })
+                .collect();
+            (!changed.is_empty()).then_some(Value::Object(changed))
+        }
+        _ => (actual != derived).then(|| actual.clone()),
+    }
+}
+
+/// Inverse of [`diff`]: writes `overrides` over `target`.
+fn merge(target: &mut Value, overrides: &Value) {
+    match (target, overrides) {
+        (Value::Object(target), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                merge(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (target, overrides) => *target = overrides.clone(),
+    }
+}
+
+/// 8-byte OTLP span id: the id itself when it is 16-digit lowercase hex,
+/// otherwise the head of its MD5 digest.
+fn span_id_bytes(id: &str) -> Vec<u8> {
+    match hex::decode(id) {
+        Ok(bytes) if bytes.len() == 8 && hex::encode(&bytes) == id => bytes,
+        _ => md5::compute(id).0[..8].to_vec(),
+    }
+}
+
+fn unix_nanos(ts: DateTime<Utc>) -> u64 {
+    ts.timestamp_nanos_opt()
+        .and_then(|nanos| u64::try_from(nanos).ok())
+        .unwrap_or(0)
+}
+
+fn from_unix_nanos(span: &str, nanos: u64) -> Result<DateTime<Utc>, OtlpError> {
+    i64::try_from(nanos)
+        .map(DateTime::from_timestamp_nanos)
+        .map_err(|_| OtlpError::Timestamp {
+            span: span.to_string(),
+            nanos,
+        })
+}
+
+fn key_value(key: &str, value: Value) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: Some(json_to_any(value)),
+    }
+}
+
+fn json_to_any(value: Value) -> AnyValue {
+    use any_value::Value as Any;
+    let value = match value {
+        Value::Null => None,
+        Value::Bool(flag) => Some(Any::BoolValue(flag)),
+        Value::Number(number) => Some(match number.as_i64() {
+            Some(int) => Any::IntValue(int),
+            None => Any::DoubleValue(number.as_f64().unwrap_or(f64::NAN)),
+        }),
+        Value::String(text) => Some(Any::StringValue(text)),
+        Value::Array(items) => Some(Any::ArrayValue(ArrayValue {
+            values: items.into_iter().map(json_to_any).collect(),
+        })),
+        Value::Object(fields) => Some(Any::KvlistValue(KeyValueList {
+            values: fields
+                .into_iter()
+                .map(|(key, value)| key_value(&key, value))
+                .collect(),
+        })),
+    };
+    AnyValue { value }
+}
+
+/// JSON form of an attribute value. Bytes become base64 strings; spans
+/// whose attributes do not survive this keep the originals under `otel`.
+fn any_to_json(value: &AnyValue) -> Value {
+    use any_value::Value as Any;
+    match &value.value {
+        None => Value::Null,
+        Some(Any::StringValue(text)) => Value::from(text.as_str()),
+        Some(Any::BoolValue(flag)) => Value::from(*flag),
+        Some(Any::IntValue(int)) => Value::from(*int),
+        Some(Any::DoubleValue(double)) => Value::from(*double),
+        Some(Any::ArrayValue(array)) => array.values.iter().map(any_to_json).collect(),
+        Some(Any::KvlistValue(list)) => Value::Object(
+            list.values
+                .iter()
+                .map(|kv| {
+                    (
+                        kv.key.clone(),
+                        kv.value.as_ref().map_or(Value::Null, any_to_json),
+                    )
+                })
+                .collect(),
+        ),
+        Some(Any::BytesValue(bytes)) => {
+            use base64::Engine as _;
+            Value::from(base64::engine::general_purpose::STANDARD.encode(bytes))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_spans() -> Vec<UniversalSpan> {
+        let start = "2025-09-29T10:00:00.123456789Z".parse().unwrap();
+        let end = "2025-09-29T10:05:00Z".parse().unwrap();
+        let root = UniversalSpan::new(
+            "run::gp41",
+            "fold gp41",
+            "protein_folding",
+            "fold_pipeline",
+            start,
+            json!({ "rmsd": 1.25, "steps": 500, "tags": ["hiv", null], "target": { "chain": "A" } }),
+        )
+        .with_finish_time(end)
+        .with_content_hash();
+        let child = UniversalSpan::new(
+            "00f067aa0ba902b7",
+            "analysis",
+            "protein_folding",
+            "fold_pipeline",
+            end,
+            json!("scalar payload"),
+        )
+        .with_parent(SpanId::new("run::gp41"))
+        .add_related(SpanId::new("twin::7"));
+        let other = UniversalSpan::new("twin::7", "twin", "twin", "bridge", start, Value::Null);
+        vec![root, child, other]
+    }
+
+    fn assert_same(a: &[UniversalSpan], b: &[UniversalSpan]) {
+        assert_eq!(
+            serde_json::to_value(a).unwrap(),
+            serde_json::to_value(b).unwrap()
+        );
+    }
+
+    #[test]
+    fn spans_round_trip_through_protobuf_and_json() {
+        let spans = sample_spans();
+        let request = to_otlp(&spans).unwrap();
+        assert_eq!(request.resource_spans.len(), 2);
+        let root = &request.resource_spans[0].scope_spans[0].spans[0];
+        let child = &request.resource_spans[0].scope_spans[0].spans[1];
+        assert_eq!(child.span_id, hex::decode("00f067aa0ba902b7").unwrap());
+        assert_eq!(child.parent_span_id, root.span_id);
+        assert_eq!(child.links[0].span_id, span_id_bytes("twin::7"));
+
+        for encoding in [OtlpEncoding::Protobuf, OtlpEncoding::Json] {
+            let bytes = encoding.encode(&request);
+            let decoded: ExportTraceServiceRequest = encoding.decode(&bytes).unwrap();
+            assert_eq!(decoded, request);
+            assert_same(&from_otlp(&decoded).unwrap(), &spans);
+        }
+    }
+
+    #[test]
+    fn foreign_otlp_json_round_trips() {
+        let body = json!({ "resourceSpans": [{
+            "resource": { "attributes": [
+                { "key": "service.name", "value": { "stringValue": "spectrometer" } },
+                { "key": "host.name", "value": { "stringValue": "lab-3" } }
+            ] },
+            "scopeSpans": [{
+                "scope": { "name": "acquisition", "version": "1.2" },
+                "spans": [{
+                    "traceId": "4bf92f3577b34da6a3ce929d0e0e4736",
+                    "spanId": "00f067aa0ba902b7",
+                    "parentSpanId": "53995c3f42cd8ad8",
+                    "name": "scan",
+                    "kind": 2,
+                    "startTimeUnixNano": "1727604000000000000",
+                    "endTimeUnixNano": 1727604001000000000u64,
+                    "attributes": [
+                        { "key": "wavelength_nm", "value": { "doubleValue": 532.0 } },
+                        { "key": "detector", "value": { "intValue": "4" } },
+                        { "key": "raw", "value": { "bytesValue": "AAE=" } }
+                    ],
+                    "events": [{ "timeUnixNano": "1727604000500000000", "name": "peak" }],
+                    "links": [{ "traceId": "4bf92f3577b34da6a3ce929d0e0e4736", "spanId": "a2fb4a1d1a96d312" }],
+                    "status": { "code": 1 }
+                }]
+            }]
+        }]});
+        let request: ExportTraceServiceRequest = serde_json::from_value(body).unwrap();
+        let spans = from_otlp(&request).unwrap();
+        let span = &spans[0];
+        assert_eq!(span.id.0, "00f067aa0ba902b7");
+        assert_eq!(span.flow, "spectrometer");
+        assert_eq!(span.workflow, "acquisition");
+        assert_eq!(span.causal.parent_id, Some(SpanId::new("53995c3f42cd8ad8")));
+        assert_eq!(
+            span.causal.related_ids,
+            vec![SpanId::new("a2fb4a1d1a96d312")]
+        );
+        assert_eq!(span.payload["wavelength_nm"], 532.0);
+        assert_eq!(span.payload["detector"], 4);
+        assert_eq!(span.payload["otel"]["span"]["kind"], 2);
+
+        let mut expected = request.clone();
+        expected.resource_spans[0].scope_spans[0].spans[0]
+            .attributes
+            .sort_by(|a, b| a.key.cmp(&b.key));
+        assert_eq!(to_otlp(&spans).unwrap(), expected);
+    }
+
+    #[test]
+    fn content_types_pick_the_encoding() {
+        assert_eq!(
+            OtlpEncoding::from_content_type("application/x-protobuf"),
+            Some(OtlpEncoding::Protobuf)
+        );
+        assert_eq!(
+            OtlpEncoding::from_content_type("Application/JSON; charset=utf-8"),
+            Some(OtlpEncoding::Json)
+        );
+        assert_eq!(OtlpEncoding::from_content_type("text/plain"), None);
+    }
+}
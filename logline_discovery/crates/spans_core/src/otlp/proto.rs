@@ -0,0 +1,367 @@
+//! The subset of `opentelemetry.proto.collector.trace.v1` needed to carry
+//! traces, declared by hand so the crate needs no protoc build step.
+//!
+//! Every message encodes as protobuf through `prost` and as OTLP/JSON
+//! through `serde`: camelCase field names, trace and span ids as hex,
+//! 64-bit integers as decimal strings (numbers are accepted on input) and
+//! `bytesValue` as base64.
+
+use base64::Engine as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportTraceServiceRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub resource_spans: Vec<ResourceSpans>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportTraceServiceResponse {
+    #[prost(message, optional, tag = "1")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial_success: Option<ExportTracePartialSuccess>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportTracePartialSuccess {
+    #[prost(int64, tag = "1")]
+    #[serde(with = "decimal")]
+    pub rejected_spans: i64,
+    #[prost(string, tag = "2")]
+    pub error_message: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ResourceSpans {
+    #[prost(message, optional, tag = "1")]
+    pub resource: Option<Resource>,
+    #[prost(message, repeated, tag = "2")]
+    pub scope_spans: Vec<ScopeSpans>,
+    #[prost(string, tag = "3")]
+    pub schema_url: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScopeSpans {
+    #[prost(message, optional, tag = "1")]
+    pub scope: Option<InstrumentationScope>,
+    #[prost(message, repeated, tag = "2")]
+    pub spans: Vec<Span>,
+    #[prost(string, tag = "3")]
+    pub schema_url: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Resource {
+    #[prost(message, repeated, tag = "1")]
+    pub attributes: Vec<KeyValue>,
+    #[prost(uint32, tag = "2")]
+    pub dropped_attributes_count: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InstrumentationScope {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub version: String,
+    #[prost(message, repeated, tag = "3")]
+    pub attributes: Vec<KeyValue>,
+    #[prost(uint32, tag = "4")]
+    pub dropped_attributes_count: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Span {
+    #[prost(bytes = "vec", tag = "1")]
+    #[serde(with = "hex_id")]
+    pub trace_id: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    #[serde(with = "hex_id")]
+    pub span_id: Vec<u8>,
+    #[prost(string, tag = "3")]
+    pub trace_state: String,
+    #[prost(bytes = "vec", tag = "4")]
+    #[serde(with = "hex_id")]
+    pub parent_span_id: Vec<u8>,
+    #[prost(fixed32, tag = "16")]
+    pub flags: u32,
+    #[prost(string, tag = "5")]
+    pub name: String,
+    /// `SpanKind`: 0 unspecified, 1 internal, 2 server, 3 client,
+    /// 4 producer, 5 consumer.
+    #[prost(int32, tag = "6")]
+    pub kind: i32,
+    #[prost(fixed64, tag = "7")]
+    #[serde(with = "decimal")]
+    pub start_time_unix_nano: u64,
+    #[prost(fixed64, tag = "8")]
+    #[serde(with = "decimal")]
+    pub end_time_unix_nano: u64,
+    #[prost(message, repeated, tag = "9")]
+    pub attributes: Vec<KeyValue>,
+    #[prost(uint32, tag = "10")]
+    pub dropped_attributes_count: u32,
+    #[prost(message, repeated, tag = "11")]
+    pub events: Vec<Event>,
+    #[prost(uint32, tag = "12")]
+    pub dropped_events_count: u32,
+    #[prost(message, repeated, tag = "13")]
+    pub links: Vec<Link>,
+    #[prost(uint32, tag = "14")]
+    pub dropped_links_count: u32,
+    #[prost(message, optional, tag = "15")]
+    pub status: Option<Status>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Event {
+    #[prost(fixed64, tag = "1")]
+    #[serde(with = "decimal")]
+    pub time_unix_nano: u64,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(message, repeated, tag = "3")]
+    pub attributes: Vec<KeyValue>,
+    #[prost(uint32, tag = "4")]
+    pub dropped_attributes_count: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Link {
+    #[prost(bytes = "vec", tag = "1")]
+    #[serde(with = "hex_id")]
+    pub trace_id: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    #[serde(with = "hex_id")]
+    pub span_id: Vec<u8>,
+    #[prost(string, tag = "3")]
+    pub trace_state: String,
+    #[prost(message, repeated, tag = "4")]
+    pub attributes: Vec<KeyValue>,
+    #[prost(uint32, tag = "5")]
+    pub dropped_attributes_count: u32,
+    #[prost(fixed32, tag = "6")]
+    pub flags: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Status {
+    #[prost(string, tag = "2")]
+    pub message: String,
+    /// `StatusCode`: 0 unset, 1 ok, 2 error.
+    #[prost(int32, tag = "3")]
+    pub code: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KeyValue {
+    #[prost(string, tag = "1")]
+    pub key: String,
+    #[prost(message, optional, tag = "2")]
+    pub value: Option<AnyValue>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ArrayValue {
+    #[prost(message, repeated, tag = "1")]
+    pub values: Vec<AnyValue>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KeyValueList {
+    #[prost(message, repeated, tag = "1")]
+    pub values: Vec<KeyValue>,
+}
+
+/// Attribute value; an unset `value` is OTLP's empty value.
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+#[serde(try_from = "AnyValueJson", into = "AnyValueJson")]
+pub struct AnyValue {
+    #[prost(oneof = "any_value::Value", tags = "1, 2, 3, 4, 5, 6, 7")]
+    pub value: Option<any_value::Value>,
+}
+
+pub mod any_value {
+    use super::{ArrayValue, KeyValueList};
+
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Value {
+        #[prost(string, tag = "1")]
+        StringValue(String),
+        #[prost(bool, tag = "2")]
+        BoolValue(bool),
+        #[prost(int64, tag = "3")]
+        IntValue(i64),
+        #[prost(double, tag = "4")]
+        DoubleValue(f64),
+        #[prost(message, tag = "5")]
+        ArrayValue(ArrayValue),
+        #[prost(message, tag = "6")]
+        KvlistValue(KeyValueList),
+        #[prost(bytes = "vec", tag = "7")]
+        BytesValue(Vec<u8>),
+    }
+}
+
+/// JSON shape of [`AnyValue`]: an object with at most one of these keys.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct AnyValueJson {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    string_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bool_value: Option<bool>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "decimal::serialize_option",
+        deserialize_with = "decimal::deserialize_option"
+    )]
+    int_value: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    double_value: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    array_value: Option<ArrayValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kvlist_value: Option<KeyValueList>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes_value: Option<String>,
+}
+
+impl TryFrom<AnyValueJson> for AnyValue {
+    type Error = String;
+
+    fn try_from(json: AnyValueJson) -> Result<Self, Self::Error> {
+        use any_value::Value;
+        let bytes = json
+            .bytes_value
+            .map(|encoded| base64::engine::general_purpose::STANDARD.decode(encoded))
+            .transpose()
+            .map_err(|err| format!("invalid bytesValue: {err}"))?;
+        let mut values = [
+            json.string_value.map(Value::StringValue),
+            json.bool_value.map(Value::BoolValue),
+            json.int_value.map(Value::IntValue),
+            json.double_value.map(Value::DoubleValue),
+            json.array_value.map(Value::ArrayValue),
+            json.kvlist_value.map(Value::KvlistValue),
+            bytes.map(Value::BytesValue),
+        ]
+        .into_iter()
+        .flatten();
+        let value = values.next();
+        if values.next().is_some() {
+            return Err("AnyValue sets more than one value".to_string());
+        }
+        Ok(AnyValue { value })
+    }
+}
+
+impl From<AnyValue> for AnyValueJson {
+    fn from(value: AnyValue) -> Self {
+        use any_value::Value;
+        let mut json = AnyValueJson::default();
+        match value.value {
+            Some(Value::StringValue(value)) => json.string_value = Some(value),
+            Some(Value::BoolValue(value)) => json.bool_value = Some(value),
+            Some(Value::IntValue(value)) => json.int_value = Some(value),
+            Some(Value::DoubleValue(value)) => json.double_value = Some(value),
+            Some(Value::ArrayValue(value)) => json.array_value = Some(value),
+            Some(Value::KvlistValue(value)) => json.kvlist_value = Some(value),
+            Some(Value::BytesValue(value)) => {
+                json.bytes_value = Some(base64::engine::general_purpose::STANDARD.encode(value))
+            }
+            None => {}
+        }
+        json
+    }
+}
+
+/// Trace and span ids as lowercase hex; empty ids as `""`.
+mod hex_id {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(id: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(id))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        hex::decode(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+/// 64-bit integers as decimal strings, read back from strings or numbers.
+mod decimal {
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr<T> {
+        Number(T),
+        Text(String),
+    }
+
+    impl<T: FromStr> Repr<T>
+    where
+        T::Err: Display,
+    {
+        fn parse<E: serde::de::Error>(self) -> Result<T, E> {
+            match self {
+                Repr::Number(value) => Ok(value),
+                Repr::Text(text) => text.parse().map_err(E::custom),
+            }
+        }
+    }
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr + Deserialize<'de>,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        Repr::deserialize(deserializer)?.parse()
+    }
+
+    pub fn serialize_option<S: Serializer>(
+        value: &Option<i64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize_option<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<i64>, D::Error> {
+        Option::<Repr<i64>>::deserialize(deserializer)?
+            .map(Repr::parse)
+            .transpose()
+    }
+}
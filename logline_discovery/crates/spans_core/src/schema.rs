@@ -0,0 +1,310 @@
+//! Versioned JSON Schemas for span payloads.
+//!
+//! Each span kind the runner maps into the lab database (subject,
+//! protocol, execution, frame, metric, twin observation, ...) has a JSON
+//! Schema per payload version, built in from `schemas/v<N>/<kind>.json`.
+//! The kind follows from the span's flow; a payload picks its version with
+//! a top-level `schema_version` and otherwise gets the latest one. Spans
+//! whose flow has no kind are not checked.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::UniversalSpan;
+
+/// Payload key selecting the schema version.
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// `(flow, kind)` for every flow with a payload schema.
+const FLOW_KINDS: [(&str, &str); 12] = [
+    ("subject_init", "subject"),
+    ("protocol_config", "protocol"),
+    ("execution_run", "execution"),
+    ("acquisition", "acquisition"),
+    ("frame", "frame"),
+    ("metric", "metric"),
+    ("analysis", "analysis"),
+    ("review", "review"),
+    ("manuscript", "manuscript"),
+    ("artifact_manifest", "artifact"),
+    ("twin_observation", "twin_observation"),
+    ("twin_divergence", "twin_divergence"),
+];
+
+/// `(kind, version, schema)` of the built-in schemas.
+const BUILTIN_SCHEMAS: [(&str, u32, &str); 12] = [
+    ("subject", 1, include_str!("../schemas/v1/subject.json")),
+    ("protocol", 1, include_str!("../schemas/v1/protocol.json")),
+    ("execution", 1, include_str!("../schemas/v1/execution.json")),
+    (
+        "acquisition",
+        1,
+        include_str!("../schemas/v1/acquisition.json"),
+    ),
+    ("frame", 1, include_str!("../schemas/v1/frame.json")),
+    ("metric", 1, include_str!("../schemas/v1/metric.json")),
+    ("analysis", 1, include_str!("../schemas/v1/analysis.json")),
+    ("review", 1, include_str!("../schemas/v1/review.json")),
+    (
+        "manuscript",
+        1,
+        include_str!("../schemas/v1/manuscript.json"),
+    ),
+    ("artifact", 1, include_str!("../schemas/v1/artifact.json")),
+    (
+        "twin_observation",
+        1,
+        include_str!("../schemas/v1/twin_observation.json"),
+    ),
+    (
+        "twin_divergence",
+        1,
+        include_str!("../schemas/v1/twin_divergence.json"),
+    ),
+];
+
+/// How ingest treats payloads that violate their schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaMode {
+    /// Reject the span.
+    Strict,
+    /// Log the violations and keep the span.
+    #[default]
+    Warn,
+    /// Skip validation.
+    Off,
+}
+
+#[derive(Debug, Error)]
+#[error("unknown schema mode `{0}` (expected strict, warn or off)")]
+pub struct UnknownSchemaMode(String);
+
+impl FromStr for SchemaMode {
+    type Err = UnknownSchemaMode;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode.to_ascii_lowercase().as_str() {
+            "strict" => Ok(Self::Strict),
+            "warn" => Ok(Self::Warn),
+            "off" => Ok(Self::Off),
+            _ => Err(UnknownSchemaMode(mode.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for SchemaMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Strict => "strict",
+            Self::Warn => "warn",
+            Self::Off => "off",
+        })
+    }
+}
+
+/// One schema violation; `path` is a JSON pointer into the span.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaViolation {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+struct SpanSchema {
+    kind: &'static str,
+    version: u32,
+    document: Value,
+    validator: jsonschema::Validator,
+}
+
+pub struct SchemaRegistry {
+    schemas: Vec<SpanSchema>,
+}
+
+impl SchemaRegistry {
+    /// The built-in schemas, compiled once.
+    pub fn builtin() -> &'static Self {
+        static REGISTRY: OnceLock<SchemaRegistry> = OnceLock::new();
+        REGISTRY.get_or_init(|| {
+            let schemas = BUILTIN_SCHEMAS
+                .iter()
+                .map(|&(kind, version, text)| {
+                    let document: Value =
+                        serde_json::from_str(text).expect("built-in span schema is valid JSON");
+                    let validator = jsonschema::options()
+                        .should_validate_formats(true)
+                        .build(&document)
+                        .expect("built-in span schema compiles");
+                    SpanSchema {
+                        kind,
+                        version,
+                        document,
+                        validator,
+                    }
+                })
+                .collect();
+            SchemaRegistry { schemas }
+        })
+    }
+
+    /// Span kind whose schema covers `flow`.
+    pub fn kind_for_flow(flow: &str) -> Option<&'static str> {
+        FLOW_KINDS
+            .iter()
+            .find(|(candidate, _)| *candidate == flow)
+            .map(|(_, kind)| *kind)
+    }
+
+    /// `(kind, version)` of every schema, in registration order.
+    pub fn versions(&self) -> Vec<(&'static str, u32)> {
+        self.schemas
+            .iter()
+            .map(|schema| (schema.kind, schema.version))
+            .collect()
+    }
+
+    pub fn latest_version(&self, kind: &str) -> Option<u32> {
+        self.schemas
+            .iter()
+            .filter(|schema| schema.kind == kind)
+            .map(|schema| schema.version)
+            .max()
+    }
+
+    /// The JSON Schema document for `kind` at `version`.
+    pub fn schema(&self, kind: &str, version: u32) -> Option<&Value> {
+        self.find(kind, version).map(|schema| &schema.document)
+    }
+
+    fn find(&self, kind: &str, version: u32) -> Option<&SpanSchema> {
+        self.schemas
+            .iter()
+            .find(|schema| schema.kind == kind && schema.version == version)
+    }
+
+    /// Every violation of the span's payload schema; empty when the payload
+    /// conforms or the flow has no schema.
+    pub fn validate(&self, span: &UniversalSpan) -> Vec<SchemaViolation> {
+        let Some(kind) = Self::kind_for_flow(&span.flow) else {
+            return Vec::new();
+        };
+        let version = match span.payload.get(SCHEMA_VERSION_KEY) {
+            None => self.latest_version(kind),
+            Some(version) => version
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok()),
+        };
+        let Some(schema) = version.and_then(|version| self.find(kind, version)) else {
+            let known: Vec<String> = self
+                .schemas
+                .iter()
+                .filter(|schema| schema.kind == kind)
+                .map(|schema| schema.version.to_string())
+                .collect();
+            return vec![SchemaViolation {
+                path: format!("/payload/{SCHEMA_VERSION_KEY}"),
+                message: format!(
+                    "unsupported {kind} schema version {} (known: {})",
+                    span.payload[SCHEMA_VERSION_KEY],
+                    known.join(", ")
+                ),
+            }];
+        };
+        schema
+            .validator
+            .iter_errors(&span.payload)
+            .map(|error| SchemaViolation {
+                path: format!("/payload{}", error.instance_path),
+                message: error.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// Validates `span` against the built-in schemas.
+pub fn validate_span(span: &UniversalSpan) -> Vec<SchemaViolation> {
+    SchemaRegistry::builtin().validate(span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn span(flow: &str, payload: Value) -> UniversalSpan {
+        UniversalSpan::new("span::test", "test", flow, "workflow", Utc::now(), payload)
+    }
+
+    #[test]
+    fn builtin_schemas_cover_every_mapped_flow() {
+        let registry = SchemaRegistry::builtin();
+        for (flow, kind) in FLOW_KINDS {
+            assert_eq!(SchemaRegistry::kind_for_flow(flow), Some(kind));
+            assert_eq!(registry.latest_version(kind), Some(1));
+            assert!(registry.schema(kind, 1).is_some());
+        }
+        assert!(validate_span(&span("protein_folding", json!(null))).is_empty());
+    }
+
+    #[test]
+    fn reports_every_violation_with_its_path() {
+        let metric = span(
+            "metric",
+            json!({ "metadata": { "metric_name": "rmsd", "value": 1.5, "unit": "Å" } }),
+        );
+        assert!(validate_span(&metric).is_empty());
+
+        let frame = span(
+            "frame",
+            json!({ "metadata": { "frame_index": "3", "recorded_at": "yesterday" } }),
+        );
+        let mut paths: Vec<String> = validate_span(&frame)
+            .into_iter()
+            .map(|violation| violation.path)
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                "/payload/metadata/frame_index",
+                "/payload/metadata/recorded_at"
+            ]
+        );
+
+        let missing = validate_span(&span(
+            "review",
+            json!({ "metadata": { "reviewer": "ana" } }),
+        ));
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].path, "/payload/metadata");
+        assert!(missing[0].message.contains("verdict"));
+    }
+
+    #[test]
+    fn payloads_select_their_schema_version() {
+        let twin = json!({ "twin": { "cycle_id": "c1", "side": "physical", "metrics": {} } });
+        let mut payload = twin.clone();
+        payload[SCHEMA_VERSION_KEY] = json!(1);
+        assert!(validate_span(&span("twin_observation", payload)).is_empty());
+
+        payload = twin;
+        payload[SCHEMA_VERSION_KEY] = json!(7);
+        let violations = validate_span(&span("twin_observation", payload));
+        assert_eq!(violations[0].path, "/payload/schema_version");
+        assert!(violations[0].message.contains("known: 1"));
+
+        assert_eq!("STRICT".parse::<SchemaMode>().unwrap(), SchemaMode::Strict);
+        assert!("loose".parse::<SchemaMode>().is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use std::time::{Duration, Instant};
 
 /// Monotonic clock that paces rotation execution in millisecond granularity.
+#[derive(Clone, Debug)]
 pub struct RotationClock {
     start: Instant,
     base_interval: Duration,
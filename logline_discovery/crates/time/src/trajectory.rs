@@ -1,7 +1,10 @@
 use std::time::Duration;
 
+use serde::{Deserialize, Serialize};
+
 /// Unique identifier for a rotation span in the LogLine timeline.
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct SpanId(pub String);
 
 impl SpanId {
@@ -11,7 +14,7 @@ impl SpanId {
 }
 
 /// Record describing a single rotation span and its informational footprint.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SpanRecord {
     pub id: SpanId,
     pub delta_entropy: f64,
@@ -42,7 +45,7 @@ impl SpanRecord {
 }
 
 /// Aggregate trajectory of rotations executed by the folding engine.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Trajectory {
     spans: Vec<SpanRecord>,
     total_entropy: f64,
@@ -1,7 +1,7 @@
 use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use std::fs::{self, File};
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
 use std::path::{Path, PathBuf};
 use warp_common::{new_audit_id, ContractEnvelope, ContractVersion, SpanRecord};
 
@@ -57,27 +57,29 @@ impl SpanLog {
     }
 
     pub fn load(&self) -> Result<Vec<LogEntry>> {
+        self.entries()?.collect()
+    }
+
+    /// Entries in log order, read one line at a time.
+    pub fn entries(&self) -> Result<LogEntries> {
         let f = File::open(&self.path)?;
-        let reader = BufReader::new(f);
-        let mut v = Vec::new();
-        for line in reader.lines() {
-            let line = line?;
-            if line.trim().is_empty() {
-                continue;
-            }
-            let entry: LogEntry = serde_json::from_str(&line)?;
-            v.push(entry);
-        }
-        Ok(v)
+        Ok(LogEntries {
+            lines: BufReader::new(f).lines(),
+        })
     }
 
     pub fn count(&self) -> Result<usize> {
-        Ok(self.load()?.len())
+        let mut count = 0;
+        for entry in self.entries()? {
+            entry?;
+            count += 1;
+        }
+        Ok(count)
     }
 
     pub fn find_span(&self, id: &str) -> Result<Option<SpanRecord>> {
-        for e in self.load()? {
-            if let LogEntry::Span { span, .. } = e {
+        for e in self.entries()? {
+            if let LogEntry::Span { span, .. } = e? {
                 if span.id == id {
                     return Ok(Some(span));
                 }
@@ -87,10 +89,9 @@ impl SpanLog {
     }
 
     pub fn rollback_to(&self, target_span_id: &str) -> Result<(usize, ContractEnvelope)> {
-        let mut entries = self.load()?;
         let mut target_idx: Option<usize> = None;
-        for (i, e) in entries.iter().enumerate() {
-            if let LogEntry::Span { span, .. } = e {
+        for (i, e) in self.entries()?.enumerate() {
+            if let LogEntry::Span { span, .. } = e? {
                 if span.id == target_span_id {
                     target_idx = Some(i);
                 }
@@ -100,26 +101,7 @@ impl SpanLog {
             Some(i) => i,
             None => bail!("span id not found: {}", target_span_id),
         };
-
-        let mut reversed_count = 0usize;
-        for i in (idx + 1)..entries.len() {
-            if let LogEntry::Span { reversed, .. } = &mut entries[i] {
-                if !*reversed {
-                    *reversed = true;
-                    reversed_count += 1;
-                }
-            }
-        }
-
-        let tmp = self.path.with_extension("tmp");
-        {
-            let mut f = File::create(&tmp)?;
-            for e in &entries {
-                let s = serde_json::to_string(e)?;
-                writeln!(f, "{}", s)?;
-            }
-        }
-        fs::rename(&tmp, &self.path)?;
+        let reversed_count = self.reverse_after(idx)?;
 
         let contract = ContractEnvelope {
             version: ContractVersion("1.x".into()),
@@ -135,6 +117,30 @@ impl SpanLog {
         Ok((reversed_count, contract))
     }
 
+    /// Rewrites the log with every span after entry `idx` marked reversed,
+    /// streaming through a temporary file. Returns how many spans flipped.
+    fn reverse_after(&self, idx: usize) -> Result<usize> {
+        let mut reversed_count = 0usize;
+        let tmp = self.path.with_extension("tmp");
+        {
+            let mut f = BufWriter::new(File::create(&tmp)?);
+            for (i, e) in self.entries()?.enumerate() {
+                let mut e = e?;
+                if let LogEntry::Span { reversed, .. } = &mut e {
+                    if i > idx && !*reversed {
+                        *reversed = true;
+                        reversed_count += 1;
+                    }
+                }
+                let s = serde_json::to_string(&e)?;
+                writeln!(f, "{}", s)?;
+            }
+            f.flush()?;
+        }
+        fs::rename(&tmp, &self.path)?;
+        Ok(reversed_count)
+    }
+
     pub fn checkpoint_create(&self, label: &str) -> Result<ContractEnvelope> {
         let ck = Checkpoint {
             id: warp_common::new_audit_id().0,
@@ -152,10 +158,9 @@ impl SpanLog {
     }
 
     pub fn rollback_to_checkpoint(&self, label: &str) -> Result<(usize, ContractEnvelope)> {
-        let mut entries = self.load()?;
         let mut ck_idx: Option<usize> = None;
-        for (i, e) in entries.iter().enumerate() {
-            if let LogEntry::Contract { contract } = e {
+        for (i, e) in self.entries()?.enumerate() {
+            if let LogEntry::Contract { contract } = e? {
                 if contract.kind == "checkpoint" {
                     if let Some(lab) = contract.payload.get("label").and_then(|x| x.as_str()) {
                         if lab == label {
@@ -169,26 +174,7 @@ impl SpanLog {
             Some(i) => i,
             None => bail!("checkpoint not found: {}", label),
         };
-
-        let mut reversed_count = 0usize;
-        for i in (idx + 1)..entries.len() {
-            if let LogEntry::Span { reversed, .. } = &mut entries[i] {
-                if !*reversed {
-                    *reversed = true;
-                    reversed_count += 1;
-                }
-            }
-        }
-
-        let tmp = self.path.with_extension("tmp");
-        {
-            let mut f = File::create(&tmp)?;
-            for e in &entries {
-                let s = serde_json::to_string(e)?;
-                writeln!(f, "{}", s)?;
-            }
-        }
-        fs::rename(&tmp, &self.path)?;
+        let reversed_count = self.reverse_after(idx)?;
 
         let contract = ContractEnvelope {
             version: ContractVersion("1.x".into()),
@@ -205,6 +191,28 @@ impl SpanLog {
     }
 }
 
+/// Iterator over the entries of a [`SpanLog`]; blank lines are skipped.
+pub struct LogEntries {
+    lines: Lines<BufReader<File>>,
+}
+
+impl Iterator for LogEntries {
+    type Item = Result<LogEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.into())),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(serde_json::from_str(&line).map_err(Into::into));
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Checkpoint {
     pub id: String,
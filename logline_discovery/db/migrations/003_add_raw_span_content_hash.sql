@@ -0,0 +1 @@
+ALTER TABLE discovery.raw_spans ADD COLUMN IF NOT EXISTS content_hash TEXT;